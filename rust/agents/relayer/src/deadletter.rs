@@ -0,0 +1,133 @@
+//! Tooling for cleaning up messages the relayer has failed to deliver.
+//!
+//! `relayer deadletter export --file <path>` dumps every undelivered message
+//! that has had at least one failed attempt, along with its retry count and
+//! the last error, to a JSON file.
+//!
+//! `relayer deadletter replay --file <path> [--message-id <id>]...` resets the
+//! retry count of the entries in the file (or only the selected ones) so that
+//! the relayer retries them immediately the next time it starts.
+//!
+//! Both commands use the relayer's configuration and open its database, so the
+//! relayer must be stopped while they are run.
+
+use std::{collections::HashSet, fs::File, path::PathBuf, str::FromStr};
+
+use ethers::utils::hex;
+use eyre::{bail, eyre, Context, Result};
+use hyperlane_base::{
+    db::{HyperlaneRocksDB, DB},
+    LoadableFromSettings,
+};
+use hyperlane_core::H256;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::RelayerSettings;
+
+/// A message which has failed to be delivered.
+#[derive(Debug, Serialize, Deserialize)]
+struct DeadletterEntry {
+    message_id: H256,
+    origin: u32,
+    nonce: u32,
+    destination: u32,
+    sender: H256,
+    recipient: H256,
+    body: String,
+    retries: u32,
+    last_error: Option<String>,
+}
+
+/// Run a deadletter command with the arguments following `deadletter`.
+pub fn run(args: &[String]) -> Result<()> {
+    let mut args = args.iter();
+    let command = args
+        .next()
+        .ok_or_else(|| eyre!("Expected `export` or `replay`"))?;
+    let mut file = None;
+    let mut message_ids = HashSet::new();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| eyre!("Missing value for {arg}"));
+        match arg.as_str() {
+            "--file" => file = Some(PathBuf::from(value()?)),
+            "--message-id" => {
+                message_ids.insert(H256::from_str(value()?).context("Invalid message id")?);
+            }
+            _ => bail!("Unexpected argument {arg}"),
+        }
+    }
+    let file = file.ok_or_else(|| eyre!("Missing --file"))?;
+
+    let settings = RelayerSettings::load()?;
+    let db = DB::from_path(&settings.db)?;
+    let dbs = settings
+        .origin_chains
+        .iter()
+        .map(|origin| HyperlaneRocksDB::new(origin, db.clone()))
+        .collect::<Vec<_>>();
+
+    match command.as_str() {
+        "export" => export(&dbs, file),
+        "replay" => replay(&dbs, file, &message_ids),
+        _ => bail!("Unknown deadletter command {command}"),
+    }
+}
+
+fn export(dbs: &[HyperlaneRocksDB], file: PathBuf) -> Result<()> {
+    let mut entries = vec![];
+    for db in dbs {
+        let mut nonce = 0;
+        while let Some(message) = db.retrieve_message_by_nonce(nonce)? {
+            nonce += 1;
+            if db.retrieve_processed_by_nonce(&message.nonce)? == Some(true) {
+                continue;
+            }
+            let id = message.id();
+            let retries = db
+                .retrieve_pending_message_retry_count_by_message_id(&id)?
+                .unwrap_or_default();
+            if retries == 0 {
+                continue;
+            }
+            entries.push(DeadletterEntry {
+                message_id: id,
+                origin: message.origin,
+                nonce: message.nonce,
+                destination: message.destination,
+                sender: message.sender,
+                recipient: message.recipient,
+                body: format!("0x{}", hex::encode(&message.body)),
+                retries,
+                last_error: db.retrieve_pending_message_last_error_by_message_id(&id)?,
+            });
+        }
+    }
+
+    serde_json::to_writer_pretty(File::create(&file)?, &entries)?;
+    println!("Exported {} messages to {}", entries.len(), file.display());
+    Ok(())
+}
+
+fn replay(dbs: &[HyperlaneRocksDB], file: PathBuf, message_ids: &HashSet<H256>) -> Result<()> {
+    let entries: Vec<DeadletterEntry> = serde_json::from_reader(File::open(&file)?)?;
+    let mut replayed = 0;
+    for entry in entries
+        .iter()
+        .filter(|entry| message_ids.is_empty() || message_ids.contains(&entry.message_id))
+    {
+        let db = dbs
+            .iter()
+            .find(|db| db.domain().id() == entry.origin)
+            .ok_or_else(|| eyre!("Origin {} is not configured", entry.origin))?;
+        db.store_pending_message_retry_count_by_message_id(&entry.message_id, &0)?;
+        println!(
+            "Re-enqueued message {:?} from {} to {}",
+            entry.message_id,
+            db.domain(),
+            entry.destination
+        );
+        replayed += 1;
+    }
+    println!("Re-enqueued {} messages", replayed);
+    Ok(())
+}
@@ -13,6 +13,7 @@ use hyperlane_base::agent_main;
 
 use crate::relayer::Relayer;
 
+mod deadletter;
 mod merkle_tree_builder;
 mod msg;
 mod prover;
@@ -21,5 +22,9 @@ mod settings;
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.first().map(String::as_str) == Some("deadletter") {
+        return deadletter::run(&args[1..]);
+    }
     agent_main::<Relayer>().await
 }
@@ -7,7 +7,7 @@ use std::{
 use async_trait::async_trait;
 use derive_new::new;
 use eyre::{Context, Result};
-use hyperlane_base::{db::HyperlaneRocksDB, CoreMetrics};
+use hyperlane_base::{db::HyperlaneRocksDB, record_processed_message, CoreMetrics};
 use hyperlane_core::{HyperlaneChain, HyperlaneDomain, HyperlaneMessage, Mailbox, U256};
 use prometheus::{IntCounter, IntGauge};
 use tracing::{debug, error, info, instrument, trace, warn};
@@ -106,7 +106,7 @@ impl PendingOperation for PendingMessage {
 
     #[instrument]
     async fn prepare(&mut self) -> PendingOperationResult {
-        make_op_try!(|| self.on_reprepare());
+        make_op_try!(|reason: &str| self.on_reprepare(reason));
 
         if !self.is_ready() {
             trace!("Message is not ready to be submitted yet");
@@ -161,7 +161,7 @@ impl PendingOperation for PendingMessage {
             "building metadata"
         ) else {
             info!("Could not fetch metadata");
-            return self.on_reprepare();
+            return self.on_reprepare("Could not fetch metadata");
         };
 
         // Estimate transaction costs for the process call. If there are issues, it's
@@ -185,7 +185,7 @@ impl PendingOperation for PendingMessage {
             "checking if message meets gas payment requirement"
         ) else {
             info!(?tx_cost_estimate, "Gas payment requirement not met yet");
-            return self.on_reprepare();
+            return self.on_reprepare("Gas payment requirement not met yet");
         };
 
         // Go ahead and attempt processing of message to destination chain.
@@ -199,7 +199,7 @@ impl PendingOperation for PendingMessage {
         if let Some(max_limit) = self.ctx.transaction_gas_limit {
             if gas_limit > max_limit {
                 info!("Message delivery estimated gas exceeds max gas limit");
-                return self.on_reprepare();
+                return self.on_reprepare("Message delivery estimated gas exceeds max gas limit");
             }
         }
 
@@ -212,7 +212,7 @@ impl PendingOperation for PendingMessage {
 
     #[instrument]
     async fn submit(&mut self) -> PendingOperationResult {
-        make_op_try!(|| self.on_reprepare());
+        make_op_try!(|reason: &str| self.on_reprepare(reason));
 
         if self.submitted {
             // this message has already been submitted, possibly not by us
@@ -253,14 +253,15 @@ impl PendingOperation for PendingMessage {
                 txid=?tx_outcome.transaction_id,
                 "Transaction attempting to process message reverted"
             );
-            self.on_reprepare()
+            self.on_reprepare("Transaction attempting to process message reverted")
         }
     }
 
     async fn confirm(&mut self) -> PendingOperationResult {
-        make_op_try!(|| {
+        make_op_try!(|reason: &str| {
             // Provider error; just try again later
             // Note: this means that we are using `NotReady` for a retryable error case
+            self.persist_last_error(reason);
             self.inc_attempts();
             PendingOperationResult::NotReady
         });
@@ -286,10 +287,11 @@ impl PendingOperation for PendingMessage {
                 critical: self.record_message_process_success(),
                 "recording message process success"
             );
+            record_processed_message(self.message.origin, self.message.nonce, self.message.id());
             PendingOperationResult::Success
         } else {
             self.reset_attempts();
-            self.on_reprepare()
+            self.on_reprepare("Message not delivered after submission")
         }
     }
 
@@ -326,7 +328,8 @@ impl PendingMessage {
         pm
     }
 
-    fn on_reprepare(&mut self) -> PendingOperationResult {
+    fn on_reprepare(&mut self, reason: &str) -> PendingOperationResult {
+        self.persist_last_error(reason);
         self.inc_attempts();
         self.submitted = false;
         PendingOperationResult::Reprepare
@@ -382,6 +385,18 @@ impl PendingMessage {
         }
     }
 
+    /// Persist the reason for the last failed attempt so it can be inspected
+    /// with the deadletter tooling.
+    fn persist_last_error(&self, reason: &str) {
+        if let Err(e) = self
+            .ctx
+            .origin_db
+            .store_pending_message_last_error_by_message_id(&self.message.id(), &reason.to_owned())
+        {
+            warn!(message_id = ?self.message.id(), err = %e, "Persisting the last error failed for message");
+        }
+    }
+
     /// Get duration we should wait before re-attempting to deliver a message
     /// given the number of retries.
     /// `pub(crate)` for testing purposes
@@ -140,7 +140,7 @@ macro_rules! make_op_try {
                                                 Err(e) => {
                                                     warn!(error=?e, concat!("Error when ", $ctx));
                                                     #[allow(clippy::redundant_closure_call)]
-                                                    return $on_retry();
+                                                    return $on_retry(&format!(concat!("Error when ", $ctx, ": {:?}"), e));
                                                 }
                                             }
                                         };
@@ -1,6 +1,7 @@
 use std::{
     collections::{HashMap, HashSet},
     fmt::{Debug, Formatter},
+    path::PathBuf,
     sync::Arc,
 };
 
@@ -253,6 +254,10 @@ impl BaseAgent for Relayer {
 
         run_all(tasks)
     }
+
+    fn state_dir(settings: &Self::Settings) -> Option<PathBuf> {
+        Some(settings.db.clone())
+    }
 }
 
 impl Relayer {
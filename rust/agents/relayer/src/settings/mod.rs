@@ -22,9 +22,8 @@ use serde::Deserialize;
 use serde_json::Value;
 use tracing::warn;
 
-use crate::settings::matching_list::MatchingList;
-
-pub mod matching_list;
+pub use hyperlane_base::settings::matching_list;
+use hyperlane_base::settings::matching_list::{parse_matching_list, MatchingList};
 
 /// Config for a GasPaymentEnforcementPolicy
 #[derive(Debug, Clone, Default)]
@@ -385,35 +384,6 @@ impl FromRawConf<RawRelayerSettings> for RelayerSettings {
     }
 }
 
-fn parse_matching_list(p: ValueParser) -> ConfigResult<MatchingList> {
-    let mut err = ConfigParsingError::default();
-
-    let raw_list = match &p {
-        ValueParser {
-            val: Value::String(matching_list_str),
-            cwp,
-        } => serde_json::from_str::<Value>(matching_list_str)
-            .context("Expected JSON string")
-            .take_err(&mut err, || cwp.clone()),
-        ValueParser {
-            val: value @ Value::Array(_),
-            ..
-        } => Some((*value).clone()),
-        _ => Err(eyre!("Expected JSON array or stringified JSON"))
-            .take_err(&mut err, || p.cwp.clone()),
-    };
-    let Some(raw_list) = raw_list else {
-        return err.into_result(MatchingList::default());
-    };
-    let p = ValueParser::new(p.cwp.clone(), &raw_list);
-    let ml = p
-        .parse_value::<MatchingList>("Expected matching list")
-        .take_config_err(&mut err)
-        .unwrap_or_default();
-
-    err.into_result(ml)
-}
-
 impl FromRawConf<DeprecatedRawRelayerSettings> for RelayerSettings {
     fn from_config_filtered(
         raw: DeprecatedRawRelayerSettings,
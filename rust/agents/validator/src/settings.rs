@@ -14,6 +14,7 @@ use hyperlane_base::{
         deprecated_parser::{
             DeprecatedRawCheckpointSyncerConf, DeprecatedRawSettings, DeprecatedRawSignerConf,
         },
+        matching_list::{parse_matching_list, MatchingList},
         parser::{RawAgentConf, RawAgentSignerConf, ValueParser},
         CheckpointSyncerConf, Settings, SignerConf,
     },
@@ -43,6 +44,9 @@ pub struct ValidatorSettings {
     pub reorg_period: u64,
     /// How frequently to check for new checkpoints
     pub interval: Duration,
+    /// Messages which checkpoints must not be signed over. Nothing is signed
+    /// from the first matching message onwards.
+    pub signing_blacklist: MatchingList,
 }
 
 /// Raw settings for `Validator`
@@ -65,6 +69,9 @@ pub struct DeprecatedRawValidatorSettings {
     reorgperiod: Option<StrOrInt>,
     /// How frequently to check for new checkpoints
     interval: Option<StrOrInt>,
+    /// This is optional. If no signing blacklist is provided, checkpoints are
+    /// signed over all messages.
+    signingblacklist: Option<String>,
 }
 
 impl_loadable_from_settings!(Validator, DeprecatedRawValidatorSettings -> ValidatorSettings);
@@ -138,6 +145,12 @@ impl FromRawConf<RawValidatorSettings> for ValidatorSettings {
             .map(Duration::from_secs)
             .unwrap_or(Duration::from_secs(5));
 
+        let signing_blacklist = p
+            .chain(&mut err)
+            .get_opt_key("signingBlacklist")
+            .and_then(parse_matching_list)
+            .unwrap_or_default();
+
         cfg_unwrap_all!(cwp, err: [origin_chain_name]);
 
         let reorg_period = p
@@ -159,6 +172,7 @@ impl FromRawConf<RawValidatorSettings> for ValidatorSettings {
             checkpoint_syncer,
             reorg_period,
             interval,
+            signing_blacklist,
         })
     }
 }
@@ -248,6 +262,14 @@ impl FromRawConf<DeprecatedRawValidatorSettings> for ValidatorSettings {
             })
             .unwrap_or(Duration::from_secs(5));
 
+        let signing_blacklist = raw
+            .signingblacklist
+            .and_then(|j| {
+                serde_json::from_str::<MatchingList>(&j)
+                    .take_err(&mut err, || cwp + "signingblacklist")
+            })
+            .unwrap_or_default();
+
         let Some(origin_chain_name) = raw
             .originchainname
             .ok_or_else(|| eyre!("Missing `originchainname`"))
@@ -299,6 +321,7 @@ impl FromRawConf<DeprecatedRawValidatorSettings> for ValidatorSettings {
             checkpoint_syncer,
             reorg_period,
             interval,
+            signing_blacklist,
         })
     }
 }
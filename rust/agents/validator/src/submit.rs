@@ -1,4 +1,5 @@
 use std::num::NonZeroU64;
+use std::ops::RangeInclusive;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use std::vec;
@@ -7,12 +8,15 @@ use eyre::Result;
 use prometheus::IntGauge;
 use tokio::time::sleep;
 use tracing::instrument;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
-use hyperlane_base::{db::HyperlaneRocksDB, CheckpointSyncer, CoreMetrics};
+use hyperlane_base::{
+    db::HyperlaneRocksDB, settings::matching_list::MatchingList, CheckpointSyncer, CoreMetrics,
+};
 use hyperlane_core::{
     accumulator::incremental::IncrementalMerkle, Checkpoint, CheckpointWithMessageId,
-    HyperlaneChain, HyperlaneContract, HyperlaneDomain, HyperlaneSignerExt, Mailbox,
+    HyperlaneChain, HyperlaneContract, HyperlaneDomain, HyperlaneMessage, HyperlaneSignerExt,
+    Mailbox,
 };
 use hyperlane_ethereum::SingletonSignerHandle;
 
@@ -24,6 +28,7 @@ pub(crate) struct ValidatorSubmitter {
     mailbox: Arc<dyn Mailbox>,
     checkpoint_syncer: Arc<dyn CheckpointSyncer>,
     message_db: HyperlaneRocksDB,
+    signing_blacklist: MatchingList,
     metrics: ValidatorSubmitterMetrics,
 }
 
@@ -35,6 +40,7 @@ impl ValidatorSubmitter {
         signer: SingletonSignerHandle,
         checkpoint_syncer: Arc<dyn CheckpointSyncer>,
         message_db: HyperlaneRocksDB,
+        signing_blacklist: MatchingList,
         metrics: ValidatorSubmitterMetrics,
     ) -> Self {
         Self {
@@ -44,6 +50,7 @@ impl ValidatorSubmitter {
             signer,
             checkpoint_syncer,
             message_db,
+            signing_blacklist,
             metrics,
         }
     }
@@ -84,6 +91,13 @@ impl ValidatorSubmitter {
                 .message_db
                 .retrieve_message_by_nonce(tree.count() as u32)?
             {
+                // Every later checkpoint commits to this message too, so stop
+                // ingesting until the blacklist no longer matches it
+                if self.signing_blacklist.msg_matches(&message, false) {
+                    log_blacklisted_message(&message);
+                    break;
+                }
+
                 debug!(index = message.nonce, "Ingesting leaf to tree");
                 let message_id = message.id();
                 tree.ingest(message_id);
@@ -203,21 +217,38 @@ impl ValidatorSubmitter {
             // signed checkpoint, and we should sign the latest checkpoint.
             // This ensures that we still sign even if the latest checkpoint
             // has index 0.
-            if current_index
+            let needs_signing = current_index
                 .map(|i| i < latest_checkpoint.index)
-                .unwrap_or(true)
-            {
-                let signed_checkpoint = self.signer.sign(latest_checkpoint).await?;
-
-                info!(signed_checkpoint = ?signed_checkpoint, signer=?self.signer, "Signed new latest checkpoint");
-                current_index = Some(latest_checkpoint.index);
-
-                self.checkpoint_syncer
-                    .legacy_write_checkpoint(&signed_checkpoint)
-                    .await?;
-                self.metrics
-                    .legacy_latest_checkpoint_processed
-                    .set(signed_checkpoint.value.index as i64);
+                .unwrap_or(true);
+            let signing_blocker = if needs_signing {
+                signing_blocker(
+                    &self.message_db,
+                    &self.signing_blacklist,
+                    current_index.map(|i| i + 1).unwrap_or(0)..=latest_checkpoint.index,
+                )?
+            } else {
+                None
+            };
+            match signing_blocker {
+                Some(SigningBlocker::Blacklisted(message)) => log_blacklisted_message(&message),
+                Some(SigningBlocker::Unindexed(nonce)) => debug!(
+                    index = nonce,
+                    "Message not indexed yet, waiting to check it against the signing blacklist"
+                ),
+                None if needs_signing => {
+                    let signed_checkpoint = self.signer.sign(latest_checkpoint).await?;
+
+                    info!(signed_checkpoint = ?signed_checkpoint, signer=?self.signer, "Signed new latest checkpoint");
+                    current_index = Some(latest_checkpoint.index);
+
+                    self.checkpoint_syncer
+                        .legacy_write_checkpoint(&signed_checkpoint)
+                        .await?;
+                    self.metrics
+                        .legacy_latest_checkpoint_processed
+                        .set(signed_checkpoint.value.index as i64);
+                }
+                None => {}
             }
 
             sleep(self.interval).await;
@@ -225,6 +256,49 @@ impl ValidatorSubmitter {
     }
 }
 
+/// Why a checkpoint covering some messages can't be signed.
+#[derive(Debug)]
+enum SigningBlocker {
+    /// The signing blacklist matches the message.
+    Blacklisted(HyperlaneMessage),
+    /// The message with the nonce isn't indexed yet, so it can't be checked
+    /// against the signing blacklist.
+    Unindexed(u32),
+}
+
+/// Returns what stops a checkpoint covering the messages with nonces in
+/// `nonces` from being signed, if anything. Messages that haven't been
+/// indexed yet block signing until they are, so that a blacklisted message
+/// isn't signed over just because the indexer is behind.
+fn signing_blocker(
+    message_db: &HyperlaneRocksDB,
+    signing_blacklist: &MatchingList,
+    nonces: RangeInclusive<u32>,
+) -> Result<Option<SigningBlocker>> {
+    for nonce in nonces {
+        match message_db.retrieve_message_by_nonce(nonce)? {
+            Some(message) if signing_blacklist.msg_matches(&message, false) => {
+                return Ok(Some(SigningBlocker::Blacklisted(message)))
+            }
+            Some(_) => {}
+            None => return Ok(Some(SigningBlocker::Unindexed(nonce))),
+        }
+    }
+    Ok(None)
+}
+
+fn log_blacklisted_message(message: &HyperlaneMessage) {
+    warn!(
+        index = message.nonce,
+        id = ?message.id(),
+        origin = message.origin,
+        sender = ?message.sender,
+        destination = message.destination,
+        recipient = ?message.recipient,
+        "Message matches the signing blacklist, refusing to sign checkpoints covering it"
+    );
+}
+
 #[derive(Clone)]
 pub(crate) struct ValidatorSubmitterMetrics {
     latest_checkpoint_observed: IntGauge,
@@ -252,3 +326,58 @@ impl ValidatorSubmitterMetrics {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use hyperlane_base::db::test_utils::run_test_db;
+    use hyperlane_core::{KnownHyperlaneDomain, H256};
+
+    use super::*;
+
+    fn message(nonce: u32, recipient: H256) -> HyperlaneMessage {
+        HyperlaneMessage {
+            nonce,
+            recipient,
+            ..HyperlaneMessage::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unindexed_message_blocks_signing() {
+        run_test_db(|db| async move {
+            let message_db =
+                HyperlaneRocksDB::new(&HyperlaneDomain::Known(KnownHyperlaneDomain::Test1), db);
+            let blacklisted_recipient = H256::repeat_byte(0xbb);
+            let signing_blacklist: MatchingList = serde_json::from_str(&format!(
+                r#"[{{"recipientAddress": "{:?}"}}]"#,
+                blacklisted_recipient
+            ))
+            .unwrap();
+            message_db
+                .store_message(&message(0, H256::zero()), 0)
+                .unwrap();
+            // The blacklisted message at nonce 2 isn't indexed until after
+            // the one at nonce 1.
+            message_db
+                .store_message(&message(2, blacklisted_recipient), 0)
+                .unwrap();
+
+            assert!(signing_blocker(&message_db, &signing_blacklist, 0..=0)
+                .unwrap()
+                .is_none());
+            assert!(matches!(
+                signing_blocker(&message_db, &signing_blacklist, 0..=2).unwrap(),
+                Some(SigningBlocker::Unindexed(1))
+            ));
+
+            message_db
+                .store_message(&message(1, H256::zero()), 0)
+                .unwrap();
+            assert!(matches!(
+                signing_blocker(&message_db, &signing_blacklist, 0..=2).unwrap(),
+                Some(SigningBlocker::Blacklisted(message)) if message.nonce == 2
+            ));
+        })
+        .await;
+    }
+}
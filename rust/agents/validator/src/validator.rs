@@ -1,11 +1,13 @@
-use std::{num::NonZeroU64, sync::Arc, time::Duration};
+use std::{num::NonZeroU64, path::PathBuf, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 use derive_more::AsRef;
 use eyre::Result;
 use hyperlane_base::{
     db::{HyperlaneRocksDB, DB},
-    run_all, BaseAgent, CheckpointSyncer, ContractSyncMetrics, CoreMetrics, HyperlaneAgentCore,
+    run_all,
+    settings::matching_list::MatchingList,
+    BaseAgent, CheckpointSyncer, ContractSyncMetrics, CoreMetrics, HyperlaneAgentCore,
     MessageContractSync,
 };
 use hyperlane_core::{
@@ -38,6 +40,7 @@ pub struct Validator {
     reorg_period: u64,
     interval: Duration,
     checkpoint_syncer: Arc<dyn CheckpointSyncer>,
+    signing_blacklist: MatchingList,
 }
 #[async_trait]
 impl BaseAgent for Validator {
@@ -68,6 +71,11 @@ impl BaseAgent for Validator {
 
         let contract_sync_metrics = Arc::new(ContractSyncMetrics::new(&metrics));
 
+        info!(
+            signing_blacklist = %settings.signing_blacklist,
+            "Checkpoints covering messages on the signing blacklist will not be signed"
+        );
+
         let message_sync = settings
             .build_message_indexer(
                 &settings.origin_chain,
@@ -90,6 +98,7 @@ impl BaseAgent for Validator {
             reorg_period: settings.reorg_period,
             interval: settings.interval,
             checkpoint_syncer,
+            signing_blacklist: settings.signing_blacklist,
         })
     }
 
@@ -132,6 +141,10 @@ impl BaseAgent for Validator {
 
         run_all(tasks)
     }
+
+    fn state_dir(settings: &Self::Settings) -> Option<PathBuf> {
+        Some(settings.db.clone())
+    }
 }
 
 impl Validator {
@@ -159,6 +172,7 @@ impl Validator {
             self.signer.clone(),
             self.checkpoint_syncer.clone(),
             self.db.clone(),
+            self.signing_blacklist.clone(),
             ValidatorSubmitterMetrics::new(&self.core.metrics, &self.origin_chain),
         );
 
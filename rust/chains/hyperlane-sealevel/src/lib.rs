@@ -12,6 +12,7 @@ pub use mailbox::*;
 pub use provider::*;
 pub use solana_sdk::signer::keypair::Keypair;
 pub use trait_builder::*;
+pub use utils::is_method_unavailable;
 pub use validator_announce::*;
 
 mod interchain_gas;
@@ -29,7 +29,11 @@ use serializable_account_meta::SimulationReturnData;
 use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
 use solana_client::{
     nonblocking::rpc_client::RpcClient,
-    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSendTransactionConfig},
+    rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::{
+        RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSendTransactionConfig,
+        RpcTransactionConfig,
+    },
     rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
 };
 use solana_sdk::{
@@ -48,12 +52,14 @@ use solana_sdk::{
 use solana_transaction_status::{
     EncodedConfirmedBlock, EncodedTransaction, EncodedTransactionWithStatusMeta,
     UiInnerInstructions, UiInstruction, UiMessage, UiParsedInstruction, UiReturnDataEncoding,
-    UiTransaction, UiTransactionReturnData, UiTransactionStatusMeta,
+    UiTransaction, UiTransactionEncoding, UiTransactionReturnData, UiTransactionStatusMeta,
 };
 
 use crate::RpcClientWithDebug;
 use crate::{
-    utils::{get_account_metas, get_finalized_block_number, simulate_instruction},
+    utils::{
+        get_account_metas, get_finalized_block_number, is_method_unavailable, simulate_instruction,
+    },
     ConnectionConf, SealevelProvider,
 };
 
@@ -64,6 +70,9 @@ const SPL_NOOP: &str = "noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV";
 // TODO: consider a more sane value and/or use IGP gas payments instead.
 const PROCESS_COMPUTE_UNITS: u32 = 1_400_000;
 
+// The max number of signatures `getSignaturesForAddress` will return in a single request.
+const MAX_SIGNATURES_PER_PAGE: usize = 1_000;
+
 /// A reference to a Mailbox contract on some Sealevel chain
 pub struct SealevelMailbox {
     program_id: Pubkey,
@@ -583,6 +592,67 @@ impl SealevelMailboxIndexer {
     }
 
     async fn get_message_with_nonce(&self, nonce: u32) -> ChainResult<(HyperlaneMessage, LogMeta)> {
+        // Some RPC providers disable `getProgramAccounts`. In that case, fall back
+        // to searching the outbox's transaction history.
+        let valid_message_storage_pda_pubkey = match self
+            .get_dispatched_message_pda_with_nonce_via_program_accounts(nonce)
+            .await?
+        {
+            Some(pubkey) => pubkey,
+            None => {
+                warn!(
+                    nonce,
+                    "getProgramAccounts is unavailable, falling back to outbox transaction history"
+                );
+                self.get_dispatched_message_pda_with_nonce_via_outbox_history(nonce)
+                    .await?
+            }
+        };
+
+        // Now that we have the valid message storage PDA pubkey, we can get the full account data.
+        let account = self
+            .rpc_client
+            .get_account_with_commitment(
+                &valid_message_storage_pda_pubkey,
+                CommitmentConfig::finalized(),
+            )
+            .await
+            .map_err(ChainCommunicationError::from_other)?
+            .value
+            .ok_or_else(|| {
+                ChainCommunicationError::from_other_str("Could not find account data")
+            })?;
+        let dispatched_message_account =
+            DispatchedMessageAccount::fetch(&mut account.data.as_ref())
+                .map_err(ChainCommunicationError::from_other)?
+                .into_inner();
+        let hyperlane_message =
+            HyperlaneMessage::read_from(&mut &dispatched_message_account.encoded_message[..])?;
+
+        Ok((
+            hyperlane_message,
+            LogMeta {
+                address: self.mailbox.program_id.to_bytes().into(),
+                block_number: dispatched_message_account.slot,
+                // TODO: get these when building out scraper support.
+                // It's inconvenient to get these :|
+                block_hash: H256::zero(),
+                transaction_id: H512::zero(),
+                transaction_index: 0,
+                log_index: U256::zero(),
+            },
+        ))
+    }
+}
+
+impl SealevelMailboxIndexer {
+    /// Finds the dispatched message PDA with the given nonce by filtering
+    /// all of the mailbox program's accounts with `getProgramAccounts`.
+    /// Returns None if the RPC doesn't support `getProgramAccounts`.
+    async fn get_dispatched_message_pda_with_nonce_via_program_accounts(
+        &self,
+        nonce: u32,
+    ) -> ChainResult<Option<Pubkey>> {
         let target_message_account_bytes = &[
             &hyperlane_sealevel_mailbox::accounts::DISPATCHED_MESSAGE_DISCRIMINATOR[..],
             &nonce.to_le_bytes()[..],
@@ -614,16 +684,18 @@ impl SealevelMailboxIndexer {
             },
             with_context: Some(false),
         };
-        let accounts = self
+        let accounts = match self
             .rpc_client
             .get_program_accounts_with_config(&self.mailbox.program_id, config)
             .await
-            .map_err(ChainCommunicationError::from_other)?;
+        {
+            Ok(accounts) => accounts,
+            Err(err) if is_method_unavailable(&err) => return Ok(None),
+            Err(err) => return Err(ChainCommunicationError::from_other(err)),
+        };
 
         // Now loop through matching accounts and find the one with a valid account pubkey
         // that proves it's an actual message storage PDA.
-        let mut valid_message_storage_pda_pubkey = Option::<Pubkey>::None;
-
         for (pubkey, account) in accounts {
             let unique_message_pubkey = Pubkey::new(&account.data);
             let (expected_pubkey, _bump) = Pubkey::try_find_program_address(
@@ -636,51 +708,194 @@ impl SealevelMailboxIndexer {
                 )
             })?;
             if expected_pubkey == pubkey {
-                valid_message_storage_pda_pubkey = Some(pubkey);
-                break;
+                return Ok(Some(pubkey));
             }
         }
 
-        let valid_message_storage_pda_pubkey =
-            valid_message_storage_pda_pubkey.ok_or_else(|| {
-                ChainCommunicationError::from_other_str(
-                    "Could not find valid message storage PDA pubkey",
+        Err(ChainCommunicationError::from_other_str(
+            "Could not find valid message storage PDA pubkey",
+        ))
+    }
+
+    /// Finds the dispatched message PDA with the given nonce by walking the
+    /// outbox PDA's transaction history from newest to oldest, for RPC providers
+    /// that don't support `getProgramAccounts`.
+    ///
+    /// Every dispatch writes to the outbox, so the outbox count tells us how many
+    /// dispatches are newer than the target nonce, which is used to size the first
+    /// page of signatures that are requested.
+    async fn get_dispatched_message_pda_with_nonce_via_outbox_history(
+        &self,
+        nonce: u32,
+    ) -> ChainResult<Pubkey> {
+        let count = self.mailbox.count(None).await?;
+        if nonce >= count {
+            return Err(ChainCommunicationError::from_other_str(
+                "Nonce is greater than or equal to the outbox count",
+            ));
+        }
+        // The number of dispatches newer than the target, plus the target itself.
+        let remaining = (count - nonce) as usize;
+        let mut page_size = remaining.clamp(1, MAX_SIGNATURES_PER_PAGE);
+
+        let outbox_pda = self.mailbox.outbox().0;
+        let mut before = None;
+        loop {
+            let signatures = self
+                .rpc_client
+                .get_signatures_for_address_with_config(
+                    &outbox_pda,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until: None,
+                        limit: Some(page_size),
+                        commitment: Some(CommitmentConfig::finalized()),
+                    },
                 )
-            })?;
+                .await
+                .map_err(ChainCommunicationError::from_other)?;
 
-        // Now that we have the valid message storage PDA pubkey, we can get the full account data.
-        let account = self
+            let Some(oldest) = signatures.last() else {
+                break;
+            };
+            before = Some(
+                Signature::from_str(&oldest.signature)
+                    .map_err(ChainCommunicationError::from_other)?,
+            );
+            // Failed dispatches and other transactions writing to the outbox take
+            // up room in the first page, so the next ones are full.
+            page_size = MAX_SIGNATURES_PER_PAGE;
+
+            for status in signatures.iter().filter(|status| status.err.is_none()) {
+                let signature = Signature::from_str(&status.signature)
+                    .map_err(ChainCommunicationError::from_other)?;
+                for dispatched_message_pda in self
+                    .get_dispatched_message_pdas_in_transaction(&signature)
+                    .await?
+                {
+                    let Some(dispatched_message_nonce) = self
+                        .get_dispatched_message_nonce(&dispatched_message_pda)
+                        .await?
+                    else {
+                        continue;
+                    };
+                    match dispatched_message_nonce.cmp(&nonce) {
+                        std::cmp::Ordering::Equal => return Ok(dispatched_message_pda),
+                        // We're walking backwards, so we've gone past the target.
+                        std::cmp::Ordering::Less => {
+                            return Err(ChainCommunicationError::from_other_str(
+                                "Could not find dispatched message in outbox transaction history",
+                            ))
+                        }
+                        std::cmp::Ordering::Greater => {}
+                    }
+                }
+            }
+        }
+
+        Err(ChainCommunicationError::from_other_str(
+            "Could not find dispatched message in outbox transaction history",
+        ))
+    }
+
+    /// Gets the dispatched message PDAs written to by the transaction's
+    /// dispatches, whether they're top level or CPIs. The dispatched message
+    /// PDA of a dispatch is only trusted if it's derived from the dispatch's
+    /// unique message account.
+    async fn get_dispatched_message_pdas_in_transaction(
+        &self,
+        signature: &Signature,
+    ) -> ChainResult<Vec<Pubkey>> {
+        let transaction = self
             .rpc_client
-            .get_account_with_commitment(
-                &valid_message_storage_pda_pubkey,
-                CommitmentConfig::finalized(),
+            .get_transaction_with_config(
+                signature,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    commitment: Some(CommitmentConfig::finalized()),
+                    max_supported_transaction_version: Some(0),
+                },
             )
             .await
-            .map_err(ChainCommunicationError::from_other)?
-            .value
+            .map_err(ChainCommunicationError::from_other)?;
+        let inner_instructions: Vec<UiInnerInstructions> = transaction
+            .transaction
+            .meta
+            .as_ref()
+            .and_then(|meta| Option::from(meta.inner_instructions.clone()))
+            .unwrap_or_default();
+        let transaction = transaction
+            .transaction
+            .transaction
+            .decode()
             .ok_or_else(|| {
-                ChainCommunicationError::from_other_str("Could not find account data")
+                ChainCommunicationError::from_other_str("Could not decode transaction")
             })?;
-        let dispatched_message_account =
-            DispatchedMessageAccount::fetch(&mut account.data.as_ref())
-                .map_err(ChainCommunicationError::from_other)?
-                .into_inner();
-        let hyperlane_message =
-            HyperlaneMessage::read_from(&mut &dispatched_message_account.encoded_message[..])?;
 
-        Ok((
-            hyperlane_message,
-            LogMeta {
-                address: self.mailbox.program_id.to_bytes().into(),
-                block_number: dispatched_message_account.slot,
-                // TODO: get these when building out scraper support.
-                // It's inconvenient to get these :|
-                block_hash: H256::zero(),
-                transaction_id: H512::zero(),
-                transaction_index: 0,
-                log_index: U256::zero(),
-            },
-        ))
+        // The program and accounts of every instruction, as indices into the
+        // account keys.
+        let instructions = transaction
+            .message
+            .instructions()
+            .iter()
+            .map(|instruction| (instruction.program_id_index, &instruction.accounts))
+            .chain(
+                inner_instructions
+                    .iter()
+                    .flat_map(|inner| &inner.instructions)
+                    .filter_map(|instruction| match instruction {
+                        UiInstruction::Compiled(compiled) => {
+                            Some((compiled.program_id_index, &compiled.accounts))
+                        }
+                        UiInstruction::Parsed(_) => None,
+                    }),
+            );
+        let account_keys = transaction.message.static_account_keys();
+        let outbox_pda = self.mailbox.outbox().0;
+        let dispatched_message_pdas = instructions
+            .filter_map(|(program_id_index, accounts)| {
+                let key = |index: usize| {
+                    accounts
+                        .get(index)
+                        .and_then(|account| account_keys.get(*account as usize))
+                };
+                // A dispatch's accounts are the outbox PDA first, the unique
+                // message account 6th and the dispatched message PDA 7th.
+                if account_keys.get(program_id_index as usize) != Some(&self.mailbox.program_id)
+                    || key(0) != Some(&outbox_pda)
+                {
+                    return None;
+                }
+                let (unique_message_pubkey, dispatched_message_pda) = (key(5)?, key(6)?);
+                let (expected_pda, _bump) = Pubkey::find_program_address(
+                    mailbox_dispatched_message_pda_seeds!(unique_message_pubkey),
+                    &self.mailbox.program_id,
+                );
+                (expected_pda == *dispatched_message_pda).then_some(expected_pda)
+            })
+            .collect();
+        Ok(dispatched_message_pdas)
+    }
+
+    /// Gets the nonce of a dispatched message PDA, or None if the account
+    /// doesn't exist.
+    async fn get_dispatched_message_nonce(
+        &self,
+        dispatched_message_pda: &Pubkey,
+    ) -> ChainResult<Option<u32>> {
+        let account = self
+            .rpc_client
+            .get_account_with_commitment(dispatched_message_pda, CommitmentConfig::finalized())
+            .await
+            .map_err(ChainCommunicationError::from_other)?
+            .value;
+        account
+            .map(|account| {
+                DispatchedMessageAccount::fetch(&mut account.data.as_ref())
+                    .map(|dispatched_message| dispatched_message.into_inner().nonce)
+                    .map_err(ChainCommunicationError::from_other)
+            })
+            .transpose()
     }
 }
 
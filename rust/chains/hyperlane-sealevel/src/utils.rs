@@ -3,7 +3,11 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use hyperlane_core::{ChainCommunicationError, ChainResult};
 
 use serializable_account_meta::{SerializableAccountMeta, SimulationReturnData};
-use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    nonblocking::rpc_client::RpcClient,
+    rpc_request::RpcError,
+};
 use solana_sdk::{
     commitment_config::CommitmentConfig,
     instruction::{AccountMeta, Instruction},
@@ -91,3 +95,15 @@ pub async fn get_finalized_block_number(rpc_client: &RpcClientWithDebug) -> Chai
         .expect("sealevel block height exceeds u32::MAX");
     Ok(height)
 }
+
+/// Whether the RPC rejected a request because the method isn't available,
+/// e.g. because the provider disabled `getProgramAccounts`.
+pub fn is_method_unavailable(err: &ClientError) -> bool {
+    match err.kind() {
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, message, .. }) => {
+            *code == jsonrpc_core::ErrorCode::MethodNotFound.code()
+                || message.to_lowercase().contains("disabled")
+        }
+        _ => false,
+    }
+}
@@ -1,13 +1,17 @@
-use std::{env, fmt::Debug, sync::Arc};
+use std::{env, fmt::Debug, path::PathBuf, process, sync::Arc};
 
 use async_trait::async_trait;
+use ethers::utils::keccak256;
 use eyre::{Report, Result};
 use futures_util::future::select_all;
-use hyperlane_core::config::*;
+use hyperlane_core::{config::*, H256};
 use tokio::task::JoinHandle;
 use tracing::{debug_span, instrument::Instrumented, Instrument};
 
-use crate::{metrics::CoreMetrics, settings::Settings};
+use crate::{
+    crash_reported, install_crash_report_hook, metrics::CoreMetrics, settings::Settings,
+    CRASH_EXIT_CODE,
+};
 
 /// Properties shared across all hyperlane agents
 #[derive(Debug)]
@@ -43,11 +47,21 @@ pub trait BaseAgent: Send + Sync + Debug {
     /// Start running this agent.
     #[allow(clippy::async_yields_async)]
     async fn run(self) -> Instrumented<JoinHandle<Result<()>>>;
+
+    /// The directory the agent keeps its state in, e.g. its database, if it
+    /// has local state. Crash reports are written under it.
+    fn state_dir(_settings: &Self::Settings) -> Option<PathBuf> {
+        None
+    }
 }
 
 /// Call this from `main` to fully initialize and run the agent for its entire
 /// lifecycle. This assumes only a single agent is being run. This will
-/// initialize the metrics server and tracing as well.
+/// initialize the metrics server and tracing as well, and install a panic hook
+/// which writes a crash report to `CRASH_REPORT_DIR` (defaults to the
+/// `crash_reports` dir of the agent's state dir, or the system's temp dir for
+/// agents without local state). If the agent stops with an error after a
+/// crash report was written, it exits with `CRASH_EXIT_CODE`.
 pub async fn agent_main<A: BaseAgent>() -> Result<()> {
     if env::var("ONELINE_BACKTRACES")
         .map(|v| v.to_lowercase())
@@ -66,12 +80,24 @@ pub async fn agent_main<A: BaseAgent>() -> Result<()> {
     let settings = A::Settings::load()?;
     let core_settings: &Settings = settings.as_ref();
 
+    let crash_report_dir = env::var("CRASH_REPORT_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            A::state_dir(&settings).map_or_else(env::temp_dir, |dir| dir.join("crash_reports"))
+        });
+    let config_digest = H256::from(keccak256(format!("{core_settings:?}")));
+    install_crash_report_hook(A::AGENT_NAME, crash_report_dir, config_digest);
+
     let metrics = settings.as_ref().metrics(A::AGENT_NAME)?;
     core_settings.tracing.start_tracing(&metrics)?;
     let agent = A::from_settings(settings, metrics.clone()).await?;
     metrics.run_http_server();
 
-    agent.run().await.await?
+    let result = agent.run().await.await;
+    if !matches!(result, Ok(Ok(()))) && crash_reported() {
+        process::exit(CRASH_EXIT_CODE);
+    }
+    result?
 }
 
 /// Utility to run multiple tasks and shutdown if any one task ends.
@@ -11,7 +11,7 @@ pub use metrics::ContractSyncMetrics;
 use tokio::time::sleep;
 use tracing::{debug, info};
 
-use crate::settings::IndexSettings;
+use crate::{record_cursor_position, settings::IndexSettings};
 
 mod cursor;
 mod eta_calculator;
@@ -80,6 +80,7 @@ where
                     stored_logs.inc_by(stored as u64);
                     // Update cursor
                     cursor.update(logs).await?;
+                    record_cursor_position(label, chain_name, *range.end());
                 }
                 CursorAction::Sleep(duration) => {
                     sleep(duration).await;
@@ -0,0 +1,131 @@
+use std::{
+    backtrace::Backtrace,
+    collections::BTreeMap,
+    fs,
+    panic::{self, PanicInfo},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use hyperlane_core::H256;
+use serde::Serialize;
+
+/// The exit code used when an agent stops after a panic. Distinct from the
+/// default exit code of a panicking rust program (101) so that operators know
+/// a crash report was written.
+pub const CRASH_EXIT_CODE: i32 = 70;
+
+/// Whether a crash report was written since the hook was installed.
+static CRASH_REPORTED: AtomicBool = AtomicBool::new(false);
+
+/// Progress that is recorded as the agent runs and included in crash reports.
+static CRASH_CONTEXT: Mutex<CrashContext> = Mutex::new(CrashContext {
+    last_processed_message: None,
+    cursor_positions: BTreeMap::new(),
+});
+
+#[derive(Debug, Clone, Serialize)]
+struct CrashContext {
+    last_processed_message: Option<ProcessedMessageRecord>,
+    /// Keyed by `<label>:<chain name>`
+    cursor_positions: BTreeMap<String, u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ProcessedMessageRecord {
+    origin: u32,
+    nonce: u32,
+    id: H256,
+}
+
+#[derive(Debug, Serialize)]
+struct CrashReport<'a> {
+    agent: &'a str,
+    timestamp: u64,
+    config_digest: H256,
+    message: String,
+    location: Option<String>,
+    thread: Option<String>,
+    context: Option<CrashContext>,
+    backtrace: String,
+}
+
+/// Record the last message the agent has processed, i.e. whose delivery was
+/// confirmed, so it will be included in a crash report.
+pub fn record_processed_message(origin: u32, nonce: u32, id: H256) {
+    if let Ok(mut context) = CRASH_CONTEXT.lock() {
+        context.last_processed_message = Some(ProcessedMessageRecord { origin, nonce, id });
+    }
+}
+
+/// Record the position of a sync cursor so it will be included in a crash
+/// report.
+pub fn record_cursor_position(label: &str, chain_name: &str, position: u32) {
+    if let Ok(mut context) = CRASH_CONTEXT.lock() {
+        context
+            .cursor_positions
+            .insert(format!("{label}:{chain_name}"), position);
+    }
+}
+
+/// Install a panic hook which writes a crash report to `dir`, then runs the
+/// previously installed hook so the panic is still logged as usual. Whether
+/// the panic stops the agent is left to the runtime, e.g. tokio isolates it
+/// to the panicking task.
+pub fn install_crash_report_hook(agent: &'static str, dir: PathBuf, config_digest: H256) {
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        match write_crash_report(agent, &dir, config_digest, info) {
+            Ok(path) => {
+                CRASH_REPORTED.store(true, Ordering::SeqCst);
+                eprintln!("Crash report written to {}", path.display());
+            }
+            Err(err) => eprintln!("Failed to write crash report: {err}"),
+        }
+        previous_hook(info);
+    }));
+}
+
+/// Whether a crash report was written, so an agent stopping with an error
+/// can exit with [`CRASH_EXIT_CODE`].
+pub fn crash_reported() -> bool {
+    CRASH_REPORTED.load(Ordering::SeqCst)
+}
+
+fn write_crash_report(
+    agent: &str,
+    dir: &Path,
+    config_digest: H256,
+    info: &PanicInfo,
+) -> std::io::Result<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Box<dyn Any>".to_owned());
+    let report = CrashReport {
+        agent,
+        timestamp,
+        config_digest,
+        message,
+        location: info.location().map(|l| l.to_string()),
+        thread: std::thread::current().name().map(str::to_owned),
+        // Don't block on a lock that may be held by the panicking thread
+        context: CRASH_CONTEXT.try_lock().ok().map(|c| c.clone()),
+        backtrace: Backtrace::force_capture().to_string(),
+    };
+
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{agent}_crash_{timestamp}.json"));
+    fs::write(&path, serde_json::to_vec_pretty(&report)?)?;
+    Ok(path)
+}
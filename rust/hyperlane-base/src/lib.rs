@@ -15,6 +15,9 @@ pub use agent::*;
 mod metrics;
 pub use metrics::*;
 
+mod crash_report;
+pub use crash_report::*;
+
 mod contract_sync;
 pub use contract_sync::*;
 
@@ -8,15 +8,19 @@ use std::{
     marker::PhantomData,
 };
 
-use hyperlane_core::{config::StrOrInt, utils::hex_or_base58_to_h256, HyperlaneMessage, H256};
+use eyre::{eyre, Context};
+use hyperlane_core::{config::*, utils::hex_or_base58_to_h256, HyperlaneMessage, H256};
 use serde::{
     de::{Error, SeqAccess, Visitor},
     Deserialize, Deserializer,
 };
+use serde_json::Value;
+
+use crate::settings::parser::ValueParser;
 
 /// Defines a set of patterns for determining if a message should or should not
-/// be relayed. This is useful for determine if a message matches a given set or
-/// rules.
+/// be relayed, or covered by a signed checkpoint. This is useful for determine
+/// if a message matches a given set or rules.
 ///
 /// Valid options for each of the tuple elements are
 /// - wildcard "*"
@@ -249,6 +253,36 @@ impl Display for MatchingList {
     }
 }
 
+/// Parses a matching list given either as a JSON array or as stringified JSON.
+pub fn parse_matching_list(p: ValueParser) -> ConfigResult<MatchingList> {
+    let mut err = ConfigParsingError::default();
+
+    let raw_list = match &p {
+        ValueParser {
+            val: Value::String(matching_list_str),
+            cwp,
+        } => serde_json::from_str::<Value>(matching_list_str)
+            .context("Expected JSON string")
+            .take_err(&mut err, || cwp.clone()),
+        ValueParser {
+            val: value @ Value::Array(_),
+            ..
+        } => Some((*value).clone()),
+        _ => Err(eyre!("Expected JSON array or stringified JSON"))
+            .take_err(&mut err, || p.cwp.clone()),
+    };
+    let Some(raw_list) = raw_list else {
+        return err.into_result(MatchingList::default());
+    };
+    let p = ValueParser::new(p.cwp.clone(), &raw_list);
+    let ml = p
+        .parse_value::<MatchingList>("Expected matching list")
+        .take_config_err(&mut err)
+        .unwrap_or_default();
+
+    err.into_result(ml)
+}
+
 fn to_serde_err<IE: ToString, OE: Error>(e: IE) -> OE {
     OE::custom(e.to_string())
 }
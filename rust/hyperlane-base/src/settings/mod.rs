@@ -104,6 +104,7 @@ mod trace;
 
 mod checkpoint_syncer;
 pub mod deprecated_parser;
+pub mod matching_list;
 pub mod parser;
 
 /// Declare that an agent can be constructed from settings.
@@ -1,23 +1,35 @@
 use std::collections::{hash_map::Entry, HashMap};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use derive_new::new;
 use eyre::Result;
+use futures_util::future::join_all;
+use rayon::prelude::*;
 use tracing::{debug, instrument, trace};
 
 use hyperlane_core::{
-    Checkpoint, CheckpointWithMessageId, MultisigSignedCheckpoint, SignedCheckpointWithSigner,
-    H160, H256,
+    Checkpoint, CheckpointWithMessageId, MultisigSignedCheckpoint, Signable,
+    SignedCheckpointWithMessageId, SignedCheckpointWithSigner, SignedType, H160, H256,
 };
 
 use crate::CheckpointSyncer;
 
+/// The most recovered signers to cache. The cache is cleared once it's full.
+const MAX_CACHED_SIGNERS: usize = 10_000;
+
+/// Signers recovered from checkpoint signatures, by the signed digest and the
+/// signature.
+type RecoveredSigners = Mutex<HashMap<(H256, [u8; 65]), H160>>;
+
 /// Fetches signed checkpoints from multiple validators to create
 /// MultisigSignedCheckpoints
 #[derive(Clone, Debug, new)]
 pub struct MultisigCheckpointSyncer {
     /// The checkpoint syncer for each valid validator signer address
     checkpoint_syncers: HashMap<H160, Arc<dyn CheckpointSyncer>>,
+    /// Cache of the signers recovered from checkpoint signatures
+    #[new(default)]
+    recovered_signers: Arc<RecoveredSigners>,
 }
 
 impl MultisigCheckpointSyncer {
@@ -130,7 +142,7 @@ impl MultisigCheckpointSyncer {
                         continue;
                     }
                     // Ensure that the signature is actually by the validator
-                    let signer = signed_checkpoint.recover()?;
+                    let signer = recover_signer(&self.recovered_signers, &signed_checkpoint)?;
                     if H256::from(signer) != *validator {
                         debug!(
                             validator = format!("{:#x}", validator),
@@ -262,6 +274,13 @@ impl MultisigCheckpointSyncer {
 
     /// Fetches a MultisigSignedCheckpointWithMessageId if there is a quorum.
     /// Returns Ok(None) if there is no quorum.
+    ///
+    /// The validators' checkpoints are fetched concurrently in waves of
+    /// `threshold` validators, stopping at the first wave that completes a
+    /// quorum, so that no more checkpoints are fetched than needed when the
+    /// first validators have signed. Each wave's signatures are verified in
+    /// parallel on the blocking thread pool, which matters for large
+    /// validator sets.
     #[instrument(err, skip(self))]
     pub async fn fetch_checkpoint(
         &self,
@@ -277,85 +296,200 @@ impl MultisigCheckpointSyncer {
             Vec<SignedCheckpointWithSigner<CheckpointWithMessageId>>,
         > = HashMap::new();
 
-        for validator in validators.iter() {
-            let addr = H160::from(*validator);
-            if let Some(checkpoint_syncer) = self.checkpoint_syncers.get(&addr) {
-                // Gracefully ignore an error fetching the checkpoint from a validator's
-                // checkpoint syncer, which can happen if the validator has not
-                // signed the checkpoint at `index`.
-                if let Ok(Some(signed_checkpoint)) = checkpoint_syncer.fetch_checkpoint(index).await
-                {
-                    // If the signed checkpoint is for a different index, ignore it
-                    if signed_checkpoint.value.index != index {
-                        debug!(
-                            validator = format!("{:#x}", validator),
-                            index = index,
-                            checkpoint_index = signed_checkpoint.value.index,
-                            "Checkpoint index mismatch"
-                        );
-                        continue;
-                    }
-                    // Ensure that the signature is actually by the validator
-                    let signer = signed_checkpoint.recover()?;
-                    if H256::from(signer) != *validator {
-                        debug!(
-                            validator = format!("{:#x}", validator),
-                            index = index,
-                            "Checkpoint signature mismatch"
-                        );
-                        continue;
-                    }
+        for wave in validators.chunks(threshold.max(1)) {
+            let signed_checkpoints = join_all(
+                wave.iter()
+                    .map(|validator| self.fetch_validator_checkpoint(validator, index)),
+            )
+            .await
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
 
-                    // Insert the SignedCheckpointWithSigner into signed_checkpoints_per_root
-                    let signed_checkpoint_with_signer =
-                        SignedCheckpointWithSigner::<CheckpointWithMessageId> {
-                            signer,
-                            signed_checkpoint,
-                        };
-                    let root = signed_checkpoint_with_signer.signed_checkpoint.value.root;
+            let recovered_signers = self.recovered_signers.clone();
+            let (signed_checkpoints, signers) = tokio::task::spawn_blocking(move || {
+                let signers = signed_checkpoints
+                    .par_iter()
+                    .map(|(_, signed_checkpoint)| {
+                        recover_signer(&recovered_signers, signed_checkpoint)
+                    })
+                    .collect::<Vec<_>>();
+                (signed_checkpoints, signers)
+            })
+            .await?;
 
-                    let signature_count = match signed_checkpoints_per_root.entry(root) {
-                        Entry::Occupied(mut entry) => {
-                            let vec = entry.get_mut();
-                            vec.push(signed_checkpoint_with_signer);
-                            vec.len()
-                        }
-                        Entry::Vacant(entry) => {
-                            entry.insert(vec![signed_checkpoint_with_signer]);
-                            1 // length of 1
-                        }
-                    };
+            for ((validator, signed_checkpoint), signer) in
+                signed_checkpoints.into_iter().zip(signers)
+            {
+                // Ensure that the signature is actually by the validator
+                let signer = signer?;
+                if H256::from(signer) != validator {
                     debug!(
                         validator = format!("{:#x}", validator),
                         index = index,
-                        root = format!("{:#x}", root),
-                        signature_count = signature_count,
-                        "Found signed checkpoint"
+                        "Checkpoint signature mismatch"
                     );
-                    // If we've hit a quorum, create a MultisigSignedCheckpoint
-                    if signature_count >= threshold {
-                        if let Some(signed_checkpoints) = signed_checkpoints_per_root.get(&root) {
-                            let checkpoint =
-                                MultisigSignedCheckpoint::<CheckpointWithMessageId>::try_from(
-                                    signed_checkpoints,
-                                )?;
-                            debug!(checkpoint=?checkpoint, "Fetched multisig checkpoint");
-                            return Ok(Some(checkpoint));
-                        }
+                    continue;
+                }
+
+                // Insert the SignedCheckpointWithSigner into signed_checkpoints_per_root
+                let signed_checkpoint_with_signer =
+                    SignedCheckpointWithSigner::<CheckpointWithMessageId> {
+                        signer,
+                        signed_checkpoint,
+                    };
+                let root = signed_checkpoint_with_signer.signed_checkpoint.value.root;
+
+                let signature_count = match signed_checkpoints_per_root.entry(root) {
+                    Entry::Occupied(mut entry) => {
+                        let vec = entry.get_mut();
+                        vec.push(signed_checkpoint_with_signer);
+                        vec.len()
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(vec![signed_checkpoint_with_signer]);
+                        1 // length of 1
+                    }
+                };
+                debug!(
+                    validator = format!("{:#x}", validator),
+                    index = index,
+                    root = format!("{:#x}", root),
+                    signature_count = signature_count,
+                    "Found signed checkpoint"
+                );
+                // If we've hit a quorum, create a MultisigSignedCheckpoint
+                if signature_count >= threshold {
+                    if let Some(signed_checkpoints) = signed_checkpoints_per_root.get(&root) {
+                        let checkpoint =
+                            MultisigSignedCheckpoint::<CheckpointWithMessageId>::try_from(
+                                signed_checkpoints,
+                            )?;
+                        debug!(checkpoint=?checkpoint, "Fetched multisig checkpoint");
+                        return Ok(Some(checkpoint));
                     }
-                } else {
-                    debug!(
-                        validator = format!("{:#x}", validator),
-                        index = index,
-                        "Unable to find signed checkpoint"
-                    );
                 }
-            } else {
-                debug!(%validator, "Unable to find checkpoint syncer");
-                continue;
             }
         }
         debug!("No quorum checkpoint found for message");
         Ok(None)
     }
+
+    /// Fetches the validator's signed checkpoint at `index`, if it has
+    /// published one for that index.
+    async fn fetch_validator_checkpoint(
+        &self,
+        validator: &H256,
+        index: u32,
+    ) -> Option<(H256, SignedCheckpointWithMessageId)> {
+        let addr = H160::from(*validator);
+        let Some(checkpoint_syncer) = self.checkpoint_syncers.get(&addr) else {
+            debug!(%validator, "Unable to find checkpoint syncer");
+            return None;
+        };
+        // Gracefully ignore an error fetching the checkpoint from a validator's
+        // checkpoint syncer, which can happen if the validator has not
+        // signed the checkpoint at `index`.
+        let Ok(Some(signed_checkpoint)) = checkpoint_syncer.fetch_checkpoint(index).await else {
+            debug!(
+                validator = format!("{:#x}", validator),
+                index = index,
+                "Unable to find signed checkpoint"
+            );
+            return None;
+        };
+        // If the signed checkpoint is for a different index, ignore it
+        if signed_checkpoint.value.index != index {
+            debug!(
+                validator = format!("{:#x}", validator),
+                index = index,
+                checkpoint_index = signed_checkpoint.value.index,
+                "Checkpoint index mismatch"
+            );
+            return None;
+        }
+        Some((*validator, signed_checkpoint))
+    }
+}
+
+/// Recovers the signer of a signed checkpoint. Signers are cached by the
+/// signed digest and the signature, since the same checkpoints are verified
+/// again for every message they're used to relay.
+fn recover_signer<T: Signable>(
+    recovered_signers: &RecoveredSigners,
+    signed_checkpoint: &SignedType<T>,
+) -> Result<H160> {
+    let key = (
+        signed_checkpoint.value.eth_signed_message_hash(),
+        <[u8; 65]>::from(signed_checkpoint.signature),
+    );
+    if let Some(signer) = recovered_signers.lock().unwrap().get(&key) {
+        return Ok(*signer);
+    }
+    let signer = signed_checkpoint.recover()?;
+    let mut recovered_signers = recovered_signers.lock().unwrap();
+    if recovered_signers.len() >= MAX_CACHED_SIGNERS {
+        recovered_signers.clear();
+    }
+    recovered_signers.insert(key, signer);
+    Ok(signer)
+}
+
+#[cfg(test)]
+mod test {
+    use ethers::signers::LocalWallet;
+    use hyperlane_core::{HyperlaneSigner, HyperlaneSignerExt};
+    use hyperlane_ethereum::Signers;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_recover_signer_caches_signers_until_full() {
+        let signer: Signers = "1111111111111111111111111111111111111111111111111111111111111111"
+            .parse::<LocalWallet>()
+            .unwrap()
+            .into();
+        let signed = signer
+            .sign(CheckpointWithMessageId {
+                checkpoint: Checkpoint {
+                    mailbox_address: H256::repeat_byte(2),
+                    mailbox_domain: 5,
+                    root: H256::repeat_byte(1),
+                    index: 123,
+                },
+                message_id: H256::repeat_byte(3),
+            })
+            .await
+            .unwrap();
+        let recovered_signers = RecoveredSigners::default();
+
+        assert_eq!(
+            recover_signer(&recovered_signers, &signed).unwrap(),
+            signer.eth_address()
+        );
+        assert_eq!(recovered_signers.lock().unwrap().len(), 1);
+
+        // A cached signer isn't recovered again.
+        for signer in recovered_signers.lock().unwrap().values_mut() {
+            *signer = H160::zero();
+        }
+        assert_eq!(
+            recover_signer(&recovered_signers, &signed).unwrap(),
+            H160::zero()
+        );
+
+        // The cache is cleared once it's full.
+        {
+            let mut recovered_signers = recovered_signers.lock().unwrap();
+            recovered_signers.clear();
+            for i in 0..MAX_CACHED_SIGNERS {
+                recovered_signers.insert((H256::from_low_u64_be(i as u64), [0; 65]), H160::zero());
+            }
+        }
+        assert_eq!(
+            recover_signer(&recovered_signers, &signed).unwrap(),
+            signer.eth_address()
+        );
+        assert_eq!(recovered_signers.lock().unwrap().len(), 1);
+    }
 }
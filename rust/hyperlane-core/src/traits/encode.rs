@@ -179,3 +179,32 @@ impl Decode for bool {
         }
     }
 }
+
+impl Encode for String {
+    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: std::io::Write,
+    {
+        let len = (self.len() as u32).write_to(writer)?;
+        writer.write_all(self.as_bytes())?;
+        Ok(len + self.len())
+    }
+}
+
+impl Decode for String {
+    fn read_from<R>(reader: &mut R) -> Result<Self, HyperlaneProtocolError>
+    where
+        R: std::io::Read,
+        Self: Sized,
+    {
+        let len = u32::read_from(reader)?;
+        let mut buf = vec![0; len as usize];
+        reader.read_exact(&mut buf)?;
+        String::from_utf8(buf).map_err(|_| {
+            HyperlaneProtocolError::IoError(Error::new(
+                ErrorKind::InvalidData,
+                "decoded string invalid utf8",
+            ))
+        })
+    }
+}
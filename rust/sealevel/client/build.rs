@@ -0,0 +1,25 @@
+//! Exposes the target triple to the `self-update` command, which downloads
+//! the release artifact built for it, and compiles in the public key release
+//! artifacts are signed with, from `HYPERLANE_RELEASE_PUBLIC_KEY`.
+
+fn main() {
+    println!(
+        "cargo:rustc-env=TARGET={}",
+        std::env::var("TARGET").unwrap()
+    );
+    println!("cargo:rerun-if-env-changed=HYPERLANE_RELEASE_PUBLIC_KEY");
+    if let Ok(public_key) = std::env::var("HYPERLANE_RELEASE_PUBLIC_KEY") {
+        // Fail the release build rather than shipping a key no update verifies
+        // against.
+        let is_hex_ed25519_key =
+            public_key.len() == 64 && public_key.chars().all(|c| c.is_ascii_hexdigit());
+        assert!(
+            is_hex_ed25519_key,
+            "HYPERLANE_RELEASE_PUBLIC_KEY must be a hex-encoded ed25519 public key"
+        );
+        println!(
+            "cargo:rustc-env=HYPERLANE_RELEASE_PUBLIC_KEY={}",
+            public_key
+        );
+    }
+}
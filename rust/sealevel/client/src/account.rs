@@ -0,0 +1,233 @@
+#[cfg(feature = "geyser")]
+use std::{collections::HashMap, sync::mpsc::RecvTimeoutError};
+use std::{thread, time::Duration};
+
+use solana_account_decoder::UiAccountEncoding;
+use solana_cli_config::Config;
+use solana_client::{pubsub_client::PubsubClient, rpc_config::RpcAccountInfoConfig};
+use solana_sdk::{
+    account::Account,
+    native_token::{lamports_to_sol, sol_to_lamports},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use hyperlane_sealevel_igp::accounts::{
+    IgpAccount, OverheadIgpAccount, ProgramDataAccount as IgpProgramDataAccount,
+};
+use hyperlane_sealevel_mailbox::accounts::{InboxAccount, OutboxAccount};
+use hyperlane_sealevel_multisig_ism_message_id::accounts::{
+    AccessControlAccount, DomainDataAccount,
+};
+use hyperlane_sealevel_validator_announce::accounts::ValidatorStorageLocationsAccount;
+
+#[cfg(feature = "geyser")]
+use crate::geyser::AccountsFilter;
+use crate::{
+    events::{AccountChangedEvent, HyperlaneEvent},
+    shutdown::{self, CancellationToken},
+    strict::skip_or_fail,
+    AccountCmd, AccountSubCmd, AccountType, AccountWatch, Context,
+};
+
+/// How many times to check, a second apart, whether an airdrop is confirmed.
+const AIRDROP_CONFIRMATION_ATTEMPTS: usize = 30;
+
+pub(crate) fn process_account_cmd(ctx: Context, cmd: AccountCmd) {
+    match cmd.cmd {
+        AccountSubCmd::Balance(balance) => {
+            let pubkey = balance.pubkey.unwrap_or(ctx.payer_pubkey);
+            let lamports = ctx
+                .client
+                .get_balance_with_commitment(&pubkey, ctx.commitment)
+                .unwrap()
+                .value;
+            println!(
+                "{}: {} SOL ({} lamports)",
+                pubkey,
+                lamports_to_sol(lamports),
+                lamports
+            );
+        }
+        AccountSubCmd::Airdrop(airdrop) => {
+            let recipient = airdrop.recipient.unwrap_or(ctx.payer_pubkey);
+            request_airdrop(&ctx, &recipient, sol_to_lamports(airdrop.amount));
+        }
+        AccountSubCmd::Watch(watch) => watch_accounts(&ctx, watch),
+    }
+}
+
+/// Requests an airdrop and waits for it to be confirmed.
+pub(crate) fn request_airdrop(ctx: &Context, recipient: &Pubkey, lamports: u64) {
+    let signature = ctx
+        .client
+        .request_airdrop(recipient, lamports)
+        .unwrap_or_else(|e| panic!("Airdrop request failed: {}", e));
+    println!(
+        "Requested airdrop of {} SOL to {}, signature {}",
+        lamports_to_sol(lamports),
+        recipient,
+        signature
+    );
+
+    for _ in 0..AIRDROP_CONFIRMATION_ATTEMPTS {
+        if ctx
+            .client
+            .confirm_transaction_with_commitment(&signature, ctx.commitment)
+            .unwrap()
+            .value
+        {
+            let balance = ctx
+                .client
+                .get_balance_with_commitment(recipient, ctx.commitment)
+                .unwrap()
+                .value;
+            println!(
+                "Airdrop confirmed, balance is now {} SOL",
+                lamports_to_sol(balance)
+            );
+            return;
+        }
+        thread::sleep(Duration::from_secs(1));
+    }
+    panic!("Airdrop {} was not confirmed in time", signature);
+}
+
+/// Subscribes to the accounts, over Geyser if an endpoint is given, and prints
+/// a JSON line for every change, also POSTed to the webhook if any, until
+/// SIGINT or SIGTERM.
+fn watch_accounts(ctx: &Context, watch: AccountWatch) {
+    let strict = ctx.is_strict(true);
+    let metrics = ctx.metrics();
+    let shutdown = &CancellationToken::on_signals();
+    let webhook = watch.webhook.webhook(shutdown);
+    let webhook = webhook.as_ref();
+    let decode = watch.decode;
+
+    let on_change = |pubkey: &Pubkey, slot: u64, account: Account| {
+        if let Some(metrics) = metrics {
+            metrics
+                .account_updates_observed
+                .with_label_values(&[&pubkey.to_string()])
+                .inc();
+        }
+        let decoded = decode.map(|account_type| {
+            skip_or_fail(strict, decode_account(account_type, &account.data), || {
+                format!("account {} as {:?}", pubkey, account_type)
+            })
+            .unwrap_or_else(|| "Failed to decode".into())
+        });
+        let event = HyperlaneEvent::AccountChanged(AccountChangedEvent {
+            pubkey: *pubkey,
+            slot,
+            lamports: account.lamports,
+            owner: account.owner,
+            data_len: account.data.len(),
+            decoded,
+        });
+        println!("{}", event.to_json_line());
+        if let Some(webhook) = webhook {
+            webhook.post(event);
+        }
+    };
+
+    #[cfg(feature = "geyser")]
+    if let Some(endpoint) = watch.geyser.endpoint() {
+        let updates = endpoint
+            .subscribe_accounts(
+                AccountsFilter {
+                    pubkeys: watch.pubkeys.clone(),
+                    ..AccountsFilter::default()
+                },
+                ctx.commitment,
+            )
+            .unwrap_or_else(|e| panic!("Failed to subscribe to the accounts: {}", e));
+        println!("Watching {} accounts over Geyser", watch.pubkeys.len());
+        let mut changes = HashMap::<Pubkey, usize>::new();
+        while !shutdown.is_cancelled() {
+            let update = match updates.recv_timeout(shutdown::CHECK_INTERVAL) {
+                Ok(update) => update,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            on_change(&update.pubkey, update.slot, update.account);
+            *changes.entry(update.pubkey).or_default() += 1;
+        }
+        for pubkey in &watch.pubkeys {
+            println!(
+                "Stopped watching {} after {} changes",
+                pubkey,
+                changes.get(pubkey).copied().unwrap_or_default()
+            );
+        }
+        return;
+    }
+
+    let ws_url = watch
+        .ws_url
+        .unwrap_or_else(|| Config::compute_websocket_url(&ctx.client.url()));
+    let config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        commitment: Some(ctx.commitment),
+        ..RpcAccountInfoConfig::default()
+    };
+    let on_change = &on_change;
+    thread::scope(|scope| {
+        for pubkey in &watch.pubkeys {
+            let (subscription, receiver) =
+                PubsubClient::account_subscribe(&ws_url, pubkey, Some(config.clone()))
+                    .unwrap_or_else(|e| panic!("Failed to subscribe to {}: {}", pubkey, e));
+            println!("Watching {}", pubkey);
+            scope.spawn(move || {
+                // Keep the subscription alive for as long as we're receiving.
+                let _subscription = subscription;
+                let mut changes = 0;
+                while !shutdown.is_cancelled() {
+                    let response = match receiver.recv_timeout(shutdown::CHECK_INTERVAL) {
+                        Ok(response) => response,
+                        Err(e) if e.is_timeout() => continue,
+                        Err(_) => break,
+                    };
+                    let Some(account) = skip_or_fail(
+                        strict,
+                        response
+                            .value
+                            .decode::<Account>()
+                            .ok_or("unsupported encoding"),
+                        || format!("account {} at slot {}", pubkey, response.context.slot),
+                    ) else {
+                        continue;
+                    };
+                    on_change(pubkey, response.context.slot, account);
+                    changes += 1;
+                }
+                println!("Stopped watching {} after {} changes", pubkey, changes);
+            });
+        }
+    });
+}
+
+/// Decodes account data as the given type, formatted for display.
+fn decode_account(account_type: AccountType, data: &[u8]) -> Result<String, ProgramError> {
+    let data = &mut &data[..];
+    match account_type {
+        AccountType::Inbox => InboxAccount::fetch(data).map(|a| format!("{:?}", a.into_inner())),
+        AccountType::Outbox => OutboxAccount::fetch(data).map(|a| format!("{:?}", a.into_inner())),
+        AccountType::MultisigIsmDomainData => {
+            DomainDataAccount::fetch(data).map(|a| format!("{:?}", a.into_inner()))
+        }
+        AccountType::MultisigIsmAccessControl => {
+            AccessControlAccount::fetch(data).map(|a| format!("{:?}", a.into_inner()))
+        }
+        AccountType::IgpProgramData => {
+            IgpProgramDataAccount::fetch(data).map(|a| format!("{:?}", a.into_inner()))
+        }
+        AccountType::Igp => IgpAccount::fetch(data).map(|a| format!("{:?}", a.into_inner())),
+        AccountType::OverheadIgp => {
+            OverheadIgpAccount::fetch(data).map(|a| format!("{:?}", a.into_inner()))
+        }
+        AccountType::ValidatorStorageLocations => {
+            ValidatorStorageLocationsAccount::fetch(data).map(|a| format!("{:?}", a.into_inner()))
+        }
+    }
+}
@@ -0,0 +1,342 @@
+//! Typed reads of on-chain program accounts.
+
+use std::fmt;
+
+use hyperlane_core::H256;
+use solana_client::{client_error::ClientError, rpc_client::RpcClient};
+use solana_program::program_error::ProgramError;
+use solana_sdk::{
+    account::{from_account, Account},
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar,
+};
+
+use account_utils::{AccountData, Data, SizedData as _};
+use hyperlane_sealevel_mailbox::accounts::{
+    DispatchedMessage, DispatchedMessageAccount, Inbox, Outbox, ProcessedMessage,
+    ProcessedMessageAccount,
+};
+
+use crate::{
+    protocol::{ProtocolConfig, MESSAGE_HEADER_BYTES},
+    Context,
+};
+
+/// A source of accounts, along with the protocol config used to derive the
+/// addresses of the accounts to read.
+pub(crate) trait AccountReader {
+    fn protocol(&self) -> &ProtocolConfig;
+
+    /// Reads an account, returning `None` if it doesn't exist.
+    fn read_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, ClientError>;
+}
+
+impl AccountReader for Context {
+    fn protocol(&self) -> &ProtocolConfig {
+        &self.protocol
+    }
+
+    fn read_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, ClientError> {
+        self.client
+            .get_account_with_commitment(pubkey, self.commitment)
+            .map(|response| response.value)
+    }
+}
+
+/// Reads accounts of a chain other than the one the context is connected to.
+pub(crate) struct RpcAccountReader<'a> {
+    pub client: RpcClient,
+    pub commitment: CommitmentConfig,
+    pub protocol: &'a ProtocolConfig,
+}
+
+impl AccountReader for RpcAccountReader<'_> {
+    fn protocol(&self) -> &ProtocolConfig {
+        self.protocol
+    }
+
+    fn read_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, ClientError> {
+        self.client
+            .get_account_with_commitment(pubkey, self.commitment)
+            .map(|response| response.value)
+    }
+}
+
+/// Why an account couldn't be read as the expected type.
+#[derive(Debug)]
+pub(crate) enum AccountError {
+    /// The account couldn't be fetched from the RPC, which may be transient.
+    Rpc { account: Pubkey, error: ClientError },
+    /// The account isn't owned by the program it was expected to belong to.
+    WrongOwner {
+        account: Pubkey,
+        owner: Pubkey,
+        expected_owner: Pubkey,
+    },
+    /// The account data isn't a valid account of the expected type, e.g.
+    /// because its discriminator doesn't match.
+    InvalidData {
+        account: Pubkey,
+        error: ProgramError,
+    },
+}
+
+impl fmt::Display for AccountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccountError::Rpc { account, error } => {
+                write!(f, "Failed to read account {}: {}", account, error)
+            }
+            AccountError::WrongOwner {
+                account,
+                owner,
+                expected_owner,
+            } => write!(
+                f,
+                "Account {} is owned by {}, expected {}",
+                account, owner, expected_owner
+            ),
+            AccountError::InvalidData { account, error } => {
+                write!(f, "Account {} has invalid data: {}", account, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AccountError {}
+
+/// Reads a mailbox's accounts, checking that each is owned by the mailbox
+/// program and deserializes as the expected type. Accounts that don't exist or
+/// aren't initialized are read as `None`.
+pub(crate) struct MailboxReader<'a, R> {
+    reader: &'a R,
+    program_id: Pubkey,
+}
+
+impl<'a, R: AccountReader> MailboxReader<'a, R> {
+    pub(crate) fn new(reader: &'a R, program_id: Pubkey) -> Self {
+        Self { reader, program_id }
+    }
+
+    pub(crate) fn inbox(&self) -> Result<Option<Inbox>, AccountError> {
+        let (inbox_account, _inbox_bump) = self.reader.protocol().inbox_pda(&self.program_id);
+        self.read(&inbox_account)
+    }
+
+    pub(crate) fn outbox(&self) -> Result<Option<Outbox>, AccountError> {
+        let (outbox_account, _outbox_bump) = self.reader.protocol().outbox_pda(&self.program_id);
+        self.read(&outbox_account)
+    }
+
+    /// Reads the message dispatched with the given unique message account.
+    pub(crate) fn dispatched_message(
+        &self,
+        unique_message_pubkey: &Pubkey,
+    ) -> Result<Option<DispatchedMessage>, AccountError> {
+        let (dispatched_message_account, _dispatched_message_bump) = self
+            .reader
+            .protocol()
+            .dispatched_message_pda(&self.program_id, unique_message_pubkey);
+        self.read(&dispatched_message_account)
+    }
+
+    /// Reads the record of a message's processing, which only exists once the
+    /// message has been delivered.
+    pub(crate) fn processed_message(
+        &self,
+        message_id: H256,
+    ) -> Result<Option<ProcessedMessage>, AccountError> {
+        let (processed_message_account, _processed_message_bump) = self
+            .reader
+            .protocol()
+            .processed_message_pda(&self.program_id, message_id);
+        self.read(&processed_message_account)
+    }
+
+    fn read<T: Data>(&self, account: &Pubkey) -> Result<Option<T>, AccountError> {
+        read_program_account(self.reader, &self.program_id, account)
+    }
+}
+
+/// Reads an account of the program, checking that it's owned by the program
+/// and deserializes as `T`. Accounts that don't exist or aren't initialized
+/// are read as `None`.
+pub(crate) fn read_program_account<T: Data>(
+    reader: &impl AccountReader,
+    program_id: &Pubkey,
+    account: &Pubkey,
+) -> Result<Option<T>, AccountError> {
+    let Some(info) = reader
+        .read_account(account)
+        .map_err(|error| AccountError::Rpc {
+            account: *account,
+            error,
+        })?
+    else {
+        return Ok(None);
+    };
+    if info.owner != *program_id {
+        return Err(AccountError::WrongOwner {
+            account: *account,
+            owner: info.owner,
+            expected_owner: *program_id,
+        });
+    }
+    AccountData::<T>::fetch_data(&mut &info.data[..])
+        .map(|data| data.map(|data| *data))
+        .map_err(|error| AccountError::InvalidData {
+            account: *account,
+            error,
+        })
+}
+
+/// Reads the mailbox's inbox, returning `None` if the mailbox isn't
+/// initialized.
+pub(crate) fn read_inbox(
+    reader: &impl AccountReader,
+    mailbox_program_id: &Pubkey,
+) -> Option<Inbox> {
+    MailboxReader::new(reader, *mailbox_program_id)
+        .inbox()
+        .unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Reads the mailbox's outbox, returning `None` if the mailbox isn't
+/// initialized.
+pub(crate) fn read_outbox(
+    reader: &impl AccountReader,
+    mailbox_program_id: &Pubkey,
+) -> Option<Outbox> {
+    MailboxReader::new(reader, *mailbox_program_id)
+        .outbox()
+        .unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Reads the cluster's rent sysvar.
+pub(crate) fn read_rent(reader: &impl AccountReader) -> Rent {
+    let account = reader
+        .read_account(&sysvar::rent::id())
+        .unwrap_or_else(|e| panic!("Failed to read the rent sysvar: {}", e))
+        .expect("Rent sysvar not found");
+    from_account(&account).expect("Invalid rent sysvar")
+}
+
+/// The lamports the payer pays to rent the account a message with a body of
+/// the given length is dispatched to.
+pub(crate) fn required_rent_for_dispatch(reader: &impl AccountReader, body_len: usize) -> u64 {
+    let dispatched_message_size = DispatchedMessageAccount::from(DispatchedMessage::new(
+        0,
+        0,
+        Pubkey::default(),
+        vec![0; MESSAGE_HEADER_BYTES + body_len],
+    ))
+    .size();
+    read_rent(reader).minimum_balance(dispatched_message_size)
+}
+
+/// The lamports the payer pays to rent the processed message account the
+/// delivery of a message creates.
+pub(crate) fn required_rent_for_process(reader: &impl AccountReader) -> u64 {
+    let processed_message_size = ProcessedMessageAccount::from(ProcessedMessage::default()).size();
+    read_rent(reader).minimum_balance(processed_message_size)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use borsh::BorshSerialize;
+    use solana_sdk::account::create_account_for_test;
+
+    use super::*;
+
+    struct TestReader {
+        protocol: ProtocolConfig,
+        accounts: HashMap<Pubkey, Account>,
+    }
+
+    impl AccountReader for TestReader {
+        fn protocol(&self) -> &ProtocolConfig {
+            &self.protocol
+        }
+
+        fn read_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, ClientError> {
+            Ok(self.accounts.get(pubkey).cloned())
+        }
+    }
+
+    /// An initialized account holding `data`.
+    fn account(owner: Pubkey, data: impl BorshSerialize) -> Account {
+        let mut serialized = vec![1];
+        data.serialize(&mut serialized).unwrap();
+        Account {
+            data: serialized,
+            owner,
+            ..Account::default()
+        }
+    }
+
+    fn inbox() -> Inbox {
+        Inbox {
+            local_domain: 1234,
+            processed_count: 5,
+            ..Inbox::default()
+        }
+    }
+
+    #[test]
+    fn test_mailbox_reader() {
+        let program_id = Pubkey::new_unique();
+        let protocol = ProtocolConfig::default();
+        let message_id = H256::random();
+        let (inbox_account, _) = protocol.inbox_pda(&program_id);
+        let (outbox_account, _) = protocol.outbox_pda(&program_id);
+        let (processed_message_account, _) =
+            protocol.processed_message_pda(&program_id, message_id);
+        let reader = TestReader {
+            protocol,
+            accounts: HashMap::from([
+                (inbox_account, account(program_id, inbox())),
+                // Owned by some other program
+                (outbox_account, account(Pubkey::new_unique(), inbox())),
+                // Not a processed message, so the discriminator doesn't match
+                (processed_message_account, account(program_id, inbox())),
+            ]),
+        };
+        let mailbox = MailboxReader::new(&reader, program_id);
+
+        assert_eq!(mailbox.inbox().unwrap(), Some(inbox()));
+        assert!(matches!(
+            mailbox.outbox(),
+            Err(AccountError::WrongOwner { .. })
+        ));
+        assert!(matches!(
+            mailbox.processed_message(message_id),
+            Err(AccountError::InvalidData { .. })
+        ));
+        assert_eq!(mailbox.processed_message(H256::zero()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_required_rent() {
+        let rent = Rent::default();
+        let reader = TestReader {
+            protocol: ProtocolConfig::default(),
+            accounts: HashMap::from([(sysvar::rent::id(), create_account_for_test(&rent))]),
+        };
+
+        // 1 byte initialized flag, 52 byte dispatched message header, 77 byte
+        // message header and the body.
+        assert_eq!(
+            required_rent_for_dispatch(&reader, 100),
+            rent.minimum_balance(1 + 52 + 77 + 100)
+        );
+        // 1 byte initialized flag and 56 byte processed message.
+        assert_eq!(
+            required_rent_for_process(&reader),
+            rent.minimum_balance(1 + 56)
+        );
+    }
+}
@@ -0,0 +1,104 @@
+//! Parsing of addresses given in the formats used across Hyperlane chains.
+
+use std::{fmt, str::FromStr};
+
+use hyperlane_core::{H160, H256};
+
+/// An address on any chain, as a 32 byte Hyperlane address. Parsed from:
+/// - a base58 Solana pubkey,
+/// - a 0x-prefixed 20 byte EVM address, which is left-padded,
+/// - a 32 byte hex string, optionally 0x-prefixed, or
+/// - with the `names` feature, an SNS or ENS name, which is resolved with
+///   [`crate::Context::resolve_address`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Address {
+    Resolved(H256),
+    #[cfg(feature = "names")]
+    Name(String),
+}
+
+impl FromStr for Address {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        #[cfg(feature = "names")]
+        if crate::names::is_name(s) {
+            return Ok(Address::Name(s.to_owned()));
+        }
+        parse_address(s).map(Address::Resolved)
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Address::Resolved(address) => write!(f, "{:?}", address),
+            #[cfg(feature = "names")]
+            Address::Name(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Parses an address in any of the formats supported by [`Address`].
+pub(crate) fn parse_address(s: &str) -> Result<H256, String> {
+    let hex = s.strip_prefix("0x");
+    match (hex, s.len()) {
+        (Some(hex), _) => {
+            let bytes =
+                hex::decode(hex).map_err(|e| format!("Invalid hex address {}: {}", s, e))?;
+            match bytes.len() {
+                20 => Ok(H160::from_slice(&bytes).into()),
+                32 => Ok(H256::from_slice(&bytes)),
+                len => Err(format!(
+                    "Invalid hex address {}: expected 20 or 32 bytes, got {}",
+                    s, len
+                )),
+            }
+        }
+        (None, 64) => hex::decode(s)
+            .map(|bytes| H256::from_slice(&bytes))
+            .map_err(|e| format!("Invalid hex address {}: {}", s, e)),
+        (None, _) => {
+            let bytes = bs58::decode(s)
+                .into_vec()
+                .map_err(|e| format!("Invalid base58 address {}: {}", s, e))?;
+            if bytes.len() != 32 {
+                return Err(format!(
+                    "Invalid base58 address {}: expected 32 bytes, got {}",
+                    s,
+                    bytes.len()
+                ));
+            }
+            Ok(H256::from_slice(&bytes))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use solana_program::pubkey::Pubkey;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_address() {
+        let pubkey = Pubkey::new_unique();
+        assert_eq!(
+            parse_address(&pubkey.to_string()),
+            Ok(H256(pubkey.to_bytes()))
+        );
+
+        let evm_address = H160::random();
+        assert_eq!(
+            parse_address(&format!("{:?}", evm_address)),
+            Ok(H256::from(evm_address))
+        );
+
+        let h256 = H256::random();
+        assert_eq!(parse_address(&format!("{:?}", h256)), Ok(h256));
+        assert_eq!(parse_address(&hex::encode(h256)), Ok(h256));
+
+        assert!(parse_address("0x1234").is_err());
+        assert!(parse_address("not an address").is_err());
+    }
+}
@@ -3,7 +3,7 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use hyperlane_core::H256;
 use solana_program::pubkey::Pubkey;
 
-use std::{fs::File, io::Write, path::Path, str::FromStr};
+use std::{ffi::OsStr, fs::File, io::Write, path::Path, str::FromStr};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct SingularProgramIdArtifact {
@@ -57,3 +57,18 @@ where
     let file = File::open(path).expect("Failed to open JSON file");
     serde_json::from_reader(file).expect("Failed to read JSON file")
 }
+
+/// Reads a YAML file if its extension is `yaml` or `yml`, otherwise a JSON
+/// file.
+pub(crate) fn read_json_or_yaml<T>(path: &Path) -> T
+where
+    T: DeserializeOwned,
+{
+    match path.extension().and_then(OsStr::to_str) {
+        Some("yaml" | "yml") => {
+            let file = File::open(path).expect("Failed to open YAML file");
+            serde_yaml::from_reader(file).expect("Failed to read YAML file")
+        }
+        _ => read_json(path),
+    }
+}
@@ -0,0 +1,294 @@
+//! Dispatching many messages from a file, packing as many dispatches into
+//! each transaction as fit.
+
+use std::{fs::File, path::Path};
+
+use hyperlane_core::H256;
+use serde::Deserialize;
+use solana_sdk::{
+    instruction::Instruction,
+    packet::PACKET_DATA_SIZE,
+    signature::{Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+
+use hyperlane_sealevel_client::instructions::outbox_dispatch_instruction;
+
+use crate::{
+    body::BodySource,
+    dispatch::{check_dispatch_body, get_dispatched_message, transaction_size},
+    domain::DomainArg,
+    sender::{ConcurrentSender, TransactionSender},
+    Context, SendBatch,
+};
+
+/// A message in a batch file. The body is given as UTF-8 or as hex.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchMessage {
+    /// The destination domain ID or chain name.
+    destination: String,
+    /// The recipient, as hex or base58.
+    recipient: String,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    body_hex: Option<String>,
+}
+
+/// A message ready to be dispatched.
+struct PendingDispatch {
+    index: usize,
+    destination: u32,
+    recipient: H256,
+    unique_message_account: Keypair,
+    instruction: Instruction,
+}
+
+pub(crate) fn process_send_batch(ctx: &Context, send_batch: SendBatch) {
+    let mut pending = read_batch_file(&send_batch.file)
+        .into_iter()
+        .enumerate()
+        .map(|(index, message)| {
+            let destination = ctx.domains.resolve(
+                &message
+                    .destination
+                    .parse::<DomainArg>()
+                    .unwrap_or_else(|e| panic!("Message {}: {}", index, e)),
+            );
+            let recipient = ctx
+                .parse_address(&message.recipient)
+                .unwrap_or_else(|e| panic!("Message {}: {}", index, e));
+            let body = match (message.body, message.body_hex) {
+                (Some(_), Some(_)) => panic!("Message {}: both body and bodyHex given", index),
+                (None, Some(body_hex)) => BodySource::Hex(body_hex),
+                (body, None) => BodySource::Utf8(body.unwrap_or_default()),
+            }
+            .read()
+            .unwrap_or_else(|e| panic!("Message {}: {}", index, e));
+            check_dispatch_body(ctx, send_batch.program_id, &body);
+
+            let unique_message_account = Keypair::new();
+            let instruction = outbox_dispatch_instruction(
+                &ctx.protocol,
+                send_batch.program_id,
+                ctx.payer_pubkey,
+                unique_message_account.pubkey(),
+                destination,
+                recipient,
+                body,
+            );
+            PendingDispatch {
+                index,
+                destination,
+                recipient,
+                unique_message_account,
+                instruction,
+            }
+        })
+        .collect::<Vec<_>>();
+    let total = pending.len();
+    println!("Dispatching {} messages", total);
+
+    // A durable nonce can only be used by one transaction at a time.
+    let results = if send_batch.concurrency > 1
+        && ctx.payer_can_sign()
+        && !ctx.simulate_only
+        && ctx.nonce_account.is_none()
+    {
+        send_concurrently(ctx, &send_batch, pending)
+    } else {
+        send_sequentially(ctx, &send_batch, pending)
+    };
+
+    println!("==== Batch results: ====");
+    for (dispatch, message_id, signature) in results {
+        match (message_id, signature) {
+            (Some(message_id), Some(signature)) => {
+                println!(
+                    "index={} destination={} message_id={:?} signature={}",
+                    dispatch.index, dispatch.destination, message_id, signature
+                );
+                ctx.print_message_link(message_id);
+            }
+            _ => println!(
+                "index={} destination={} not dispatched",
+                dispatch.index, dispatch.destination
+            ),
+        }
+    }
+}
+
+/// A dispatch, along with its message ID and transaction signature if it was
+/// dispatched.
+type DispatchResult = (PendingDispatch, Option<H256>, Option<Signature>);
+
+fn send_sequentially(
+    ctx: &Context,
+    send_batch: &SendBatch,
+    mut pending: Vec<PendingDispatch>,
+) -> Vec<DispatchResult> {
+    let mut results = vec![];
+    while !pending.is_empty() {
+        let initial_instructions = ctx
+            .initial_instructions
+            .borrow()
+            .iter()
+            .map(|i| i.instruction.clone())
+            .collect::<Vec<_>>();
+        let count = pack_dispatches(
+            ctx,
+            &initial_instructions,
+            &pending,
+            send_batch.max_messages_per_transaction,
+        );
+        let batch = pending.drain(..count).collect::<Vec<_>>();
+
+        let mut txn = ctx.new_txn();
+        for dispatch in &batch {
+            txn = txn.add_with_description(
+                dispatch.instruction.clone(),
+                format!(
+                    "Dispatch message {} to domain {} recipient {:?}",
+                    dispatch.index, dispatch.destination, dispatch.recipient
+                ),
+            );
+        }
+        let payer_signer = ctx.payer_signer();
+        let mut signers: Vec<&dyn Signer> = vec![&*payer_signer];
+        signers.extend(
+            batch
+                .iter()
+                .map(|dispatch| &dispatch.unique_message_account as &dyn Signer),
+        );
+        let signature = txn
+            .send_for_outcome(&signers)
+            .map(|outcome| outcome.signature);
+
+        for dispatch in batch {
+            let message_id = signature
+                .and_then(|_| {
+                    get_dispatched_message(
+                        ctx,
+                        &send_batch.program_id,
+                        &dispatch.unique_message_account.pubkey(),
+                    )
+                })
+                .map(|message| message.id());
+            results.push((dispatch, message_id, signature));
+        }
+    }
+    results
+}
+
+/// Sends the dispatch transactions with up to `concurrency` in flight. The
+/// initial instructions, e.g. compute budget requests, are included in every
+/// transaction.
+fn send_concurrently(
+    ctx: &Context,
+    send_batch: &SendBatch,
+    mut pending: Vec<PendingDispatch>,
+) -> Vec<DispatchResult> {
+    let initial_instructions = ctx
+        .initial_instructions
+        .borrow_mut()
+        .drain(..)
+        .map(|i| i.instruction)
+        .collect::<Vec<_>>();
+    let mut batches = vec![];
+    while !pending.is_empty() {
+        let count = pack_dispatches(
+            ctx,
+            &initial_instructions,
+            &pending,
+            send_batch.max_messages_per_transaction,
+        );
+        batches.push(pending.drain(..count).collect::<Vec<_>>());
+    }
+
+    let payer_keypair = ctx.payer_keypair().expect("No payer keypair");
+    let rpc_sender = ctx.sender(&ctx.client);
+    let tpu_sender;
+    let transaction_sender: &dyn TransactionSender = if send_batch.tpu {
+        tpu_sender = ctx
+            .tpu_sender(send_batch.ws_url.clone())
+            .unwrap_or_else(|e| panic!("{}", e));
+        &tpu_sender
+    } else {
+        &rpc_sender
+    };
+    let sender = ConcurrentSender::new(
+        &ctx.client,
+        transaction_sender,
+        ctx.commitment,
+        send_batch.concurrency,
+    );
+    let (outcomes, stats) = sender.send_all(batches.len(), |index, recent_blockhash| {
+        let batch = &batches[index];
+        let instructions = initial_instructions
+            .iter()
+            .cloned()
+            .chain(batch.iter().map(|dispatch| dispatch.instruction.clone()))
+            .collect::<Vec<_>>();
+        let mut signers: Vec<&dyn Signer> = vec![payer_keypair];
+        signers.extend(
+            batch
+                .iter()
+                .map(|dispatch| &dispatch.unique_message_account as &dyn Signer),
+        );
+        Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&ctx.payer_pubkey),
+            &signers,
+            recent_blockhash,
+        )
+    });
+    println!("Sent {} transactions: {}", outcomes.len(), stats);
+
+    batches
+        .into_iter()
+        .zip(outcomes)
+        .flat_map(|(batch, outcome)| {
+            let signature = outcome.result.ok().map(|(signature, _)| signature);
+            batch.into_iter().map(move |dispatch| {
+                let message_id = signature
+                    .and_then(|_| {
+                        get_dispatched_message(
+                            ctx,
+                            &send_batch.program_id,
+                            &dispatch.unique_message_account.pubkey(),
+                        )
+                    })
+                    .map(|message| message.id());
+                (dispatch, message_id, signature)
+            })
+        })
+        .collect()
+}
+
+fn read_batch_file(path: &Path) -> Vec<BatchMessage> {
+    let file = File::open(path)
+        .unwrap_or_else(|e| panic!("Failed to open batch file {}: {}", path.display(), e));
+    serde_json::from_reader(file).expect("Invalid batch file")
+}
+
+/// The number of the pending dispatches, at least one, that fit into the next
+/// transaction after the initial instructions.
+fn pack_dispatches(
+    ctx: &Context,
+    initial_instructions: &[Instruction],
+    pending: &[PendingDispatch],
+    max: Option<usize>,
+) -> usize {
+    let mut instructions = initial_instructions.to_vec();
+    let max = max.unwrap_or(usize::MAX).min(pending.len());
+    let mut count = 0;
+    while count < max {
+        instructions.push(pending[count].instruction.clone());
+        if count > 0 && transaction_size(ctx, &instructions) > PACKET_DATA_SIZE {
+            break;
+        }
+        count += 1;
+    }
+    count.max(1)
+}
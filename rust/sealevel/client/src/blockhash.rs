@@ -0,0 +1,58 @@
+//! Caching of recent blockhashes, so that flows sending many transactions
+//! don't fetch a new blockhash for each of them.
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use solana_client::{client_error::ClientError, rpc_client::RpcClient};
+use solana_sdk::hash::Hash;
+
+/// How long a fetched blockhash is reused for by default. Blockhashes are
+/// valid for about a minute, so this leaves plenty of time to land
+/// transactions.
+pub(crate) const DEFAULT_BLOCKHASH_MAX_AGE: Duration = Duration::from_secs(10);
+
+/// A recent blockhash per RPC, refreshed once it's older than the max age.
+pub(crate) struct CachedBlockhash {
+    max_age: Duration,
+    cached: Mutex<HashMap<String, (Hash, Instant)>>,
+}
+
+impl Default for CachedBlockhash {
+    fn default() -> Self {
+        Self::new(DEFAULT_BLOCKHASH_MAX_AGE)
+    }
+}
+
+impl CachedBlockhash {
+    pub(crate) fn new(max_age: Duration) -> Self {
+        Self {
+            max_age,
+            cached: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Gets a recent blockhash of the client's chain, fetching a new one if
+    /// the cached one is too old.
+    pub(crate) fn get(&self, client: &RpcClient) -> Result<Hash, ClientError> {
+        let mut cached = self.cached.lock().unwrap();
+        let url = client.url();
+        match cached.get(&url) {
+            Some((blockhash, fetched_at)) if fetched_at.elapsed() < self.max_age => Ok(*blockhash),
+            _ => {
+                let blockhash = client.get_latest_blockhash()?;
+                cached.insert(url, (blockhash, Instant::now()));
+                Ok(blockhash)
+            }
+        }
+    }
+
+    /// Drops the cached blockhashes, e.g. after a transaction failed because
+    /// its blockhash expired.
+    pub(crate) fn invalidate(&self) {
+        self.cached.lock().unwrap().clear();
+    }
+}
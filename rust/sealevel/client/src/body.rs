@@ -0,0 +1,99 @@
+//! Reading message bodies given in various encodings or from files.
+
+use std::{
+    fs,
+    io::{self, Read as _},
+    path::PathBuf,
+};
+
+use base64::Engine as _;
+use clap::Args;
+
+/// Where the bytes of a message body come from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum BodySource {
+    Utf8(String),
+    Hex(String),
+    Base64(String),
+    File(PathBuf),
+    Stdin,
+}
+
+impl BodySource {
+    /// Reads the message body.
+    pub(crate) fn read(&self) -> Result<Vec<u8>, String> {
+        match self {
+            BodySource::Utf8(body) => Ok(body.as_bytes().to_vec()),
+            BodySource::Hex(body) => hex::decode(body.strip_prefix("0x").unwrap_or(body))
+                .map_err(|e| format!("Invalid hex body: {}", e)),
+            BodySource::Base64(body) => base64::engine::general_purpose::STANDARD
+                .decode(body)
+                .map_err(|e| format!("Invalid base64 body: {}", e)),
+            BodySource::File(path) => fs::read(path)
+                .map_err(|e| format!("Failed to read body file {}: {}", path.display(), e)),
+            BodySource::Stdin => {
+                let mut body = vec![];
+                io::stdin()
+                    .read_to_end(&mut body)
+                    .map_err(|e| format!("Failed to read body from stdin: {}", e))?;
+                Ok(body)
+            }
+        }
+    }
+}
+
+/// Message body arguments. At most one may be given; the UTF-8 body is used
+/// if none are.
+#[derive(Args)]
+#[group(multiple = false)]
+pub(crate) struct BodyArgs {
+    /// The body as a UTF-8 string.
+    #[arg(long, short = 'm', alias = "message", default_value = "Hello, World!")]
+    body_utf8: String,
+    /// The body as hex, optionally 0x-prefixed.
+    #[arg(long)]
+    body_hex: Option<String>,
+    /// The body as base64.
+    #[arg(long)]
+    body_base64: Option<String>,
+    /// A file to read the body from, or `-` for stdin.
+    #[arg(long)]
+    body_file: Option<PathBuf>,
+}
+
+impl BodyArgs {
+    pub(crate) fn source(&self) -> BodySource {
+        if let Some(body) = &self.body_hex {
+            BodySource::Hex(body.clone())
+        } else if let Some(body) = &self.body_base64 {
+            BodySource::Base64(body.clone())
+        } else if let Some(path) = &self.body_file {
+            if path.as_os_str() == "-" {
+                BodySource::Stdin
+            } else {
+                BodySource::File(path.clone())
+            }
+        } else {
+            BodySource::Utf8(self.body_utf8.clone())
+        }
+    }
+
+    /// Reads the message body, panicking if it can't be read.
+    pub(crate) fn read(&self) -> Vec<u8> {
+        self.source().read().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_read_encoded_bodies() {
+        assert_eq!(BodySource::Utf8("hi".into()).read(), Ok(b"hi".to_vec()));
+        assert_eq!(BodySource::Hex("0x6869".into()).read(), Ok(b"hi".to_vec()));
+        assert_eq!(BodySource::Hex("6869".into()).read(), Ok(b"hi".to_vec()));
+        assert_eq!(BodySource::Base64("aGk=".into()).read(), Ok(b"hi".to_vec()));
+        assert!(BodySource::Hex("zz".into()).read().is_err());
+    }
+}
@@ -0,0 +1,66 @@
+use std::{path::Path, thread};
+
+use solana_client::rpc_client::RpcClient;
+
+use crate::{domain::read_chain_metadata_file, exit_code, router::ChainMetadata};
+
+/// Clients for several configured chains, allowing read operations to be run
+/// across all of them concurrently. A failure on one chain doesn't affect the
+/// results of the others.
+pub(crate) struct ChainSet {
+    chains: Vec<(ChainMetadata, RpcClient)>,
+}
+
+impl ChainSet {
+    /// Builds a ChainSet from the chain config file for the given chain names,
+    /// exiting with a config error if the file can't be read or is missing a
+    /// chain.
+    pub(crate) fn from_chain_config_file(chain_config_file: &Path, chain_names: &[String]) -> Self {
+        let mut chain_configs = read_chain_metadata_file(chain_config_file);
+        let chains = chain_names
+            .iter()
+            .map(|chain_name| {
+                let chain_config = chain_configs.remove(chain_name).unwrap_or_else(|| {
+                    exit_code::config_error(format!(
+                        "Chain config not found for chain {} in {}",
+                        chain_name,
+                        chain_config_file.display()
+                    ))
+                });
+                let client = chain_config.client();
+                (chain_config, client)
+            })
+            .collect();
+        Self { chains }
+    }
+
+    /// Runs `f` against every chain concurrently, returning the results keyed
+    /// by chain name in the order the chains were configured.
+    pub(crate) fn query<T, E, F>(&self, f: F) -> Vec<(String, Result<T, String>)>
+    where
+        T: Send,
+        E: ToString,
+        F: Fn(&ChainMetadata, &RpcClient) -> Result<T, E> + Sync,
+    {
+        let f = &f;
+        thread::scope(|scope| {
+            let handles = self
+                .chains
+                .iter()
+                .map(|(chain_config, client)| {
+                    scope.spawn(move || f(chain_config, client).map_err(|e| e.to_string()))
+                })
+                .collect::<Vec<_>>();
+            self.chains
+                .iter()
+                .zip(handles)
+                .map(|((chain_config, _), handle)| {
+                    let result = handle
+                        .join()
+                        .unwrap_or_else(|_| Err("Query panicked".to_owned()));
+                    (chain_config.name().to_owned(), result)
+                })
+                .collect()
+        })
+    }
+}
@@ -0,0 +1,228 @@
+use std::{fs, path::Path};
+
+use hyperlane_core::{
+    CheckpointWithMessageId, Decode as _, HyperlaneMessage, Signable,
+    SignedCheckpointWithMessageId, H160, H256,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::commitment_config::CommitmentConfig;
+
+use hyperlane_sealevel_validator_announce::{
+    accounts::ValidatorStorageLocationsAccount, validator_storage_locations_pda_seeds,
+};
+
+use crate::{
+    accounts::read_outbox, events::HyperlaneEvent, status::find_dispatched_message_by_nonce,
+    strict::skip_or_fail, Context, MailboxCheckpoint,
+};
+
+/// Prints the checkpoint a validator would sign for the outbox's current state
+/// and whether each of the given validators has published it. With `--json`,
+/// only prints the published signatures, as events.
+pub(crate) fn process_mailbox_checkpoint(ctx: &Context, args: MailboxCheckpoint) {
+    let outbox = read_outbox(ctx, &args.program_id)
+        .expect("Outbox account not found. Make sure you are connected to the right RPC.");
+
+    let count = outbox.tree.count();
+    if !args.json {
+        println!("Root: {:?}", outbox.tree.root());
+        println!("Count: {}", count);
+    }
+    if count == 0 {
+        if !args.json {
+            println!("No messages dispatched, there is no checkpoint to sign");
+        }
+        return;
+    }
+
+    let index = (count - 1) as u32;
+    let message_id = find_dispatched_message_by_nonce(
+        &ctx.client,
+        &ctx.protocol,
+        &args.program_id,
+        index,
+        ctx.commitment,
+        ctx.is_strict(false),
+    )
+    .unwrap_or_else(|e| panic!("Failed to get dispatched message {}: {}", index, e))
+    .map(|dispatched_message| {
+        HyperlaneMessage::read_from(&mut &dispatched_message.encoded_message[..])
+            .expect("Invalid dispatched message")
+            .id()
+    })
+    .unwrap_or_else(|| panic!("No message with nonce {} dispatched", index));
+    let checkpoint = CheckpointWithMessageId {
+        checkpoint: hyperlane_core::Checkpoint {
+            mailbox_address: H256(args.program_id.to_bytes()),
+            mailbox_domain: outbox.local_domain,
+            root: outbox.tree.root(),
+            index,
+        },
+        message_id,
+    };
+    let storage_locations = announced_storage_locations(
+        &ctx.client,
+        ctx.commitment,
+        &args.validator_announce_program_id,
+        &args.validators,
+    );
+    let statuses = storage_locations
+        .iter()
+        .map(|storage_locations| {
+            signing_status(storage_locations, &checkpoint, ctx.is_strict(false))
+        })
+        .collect::<Vec<_>>();
+
+    if args.json {
+        for (validator, status) in args.validators.iter().zip(&statuses) {
+            if let SigningStatus::Signed(signed) = status {
+                println!(
+                    "{}",
+                    HyperlaneEvent::checkpoint_signed(*validator, signed).to_json_line()
+                );
+            }
+        }
+        return;
+    }
+
+    println!("Latest message ID: {:?}", message_id);
+    println!("Signing hash: {:?}", checkpoint.signing_hash());
+    println!(
+        "Digest to sign (EIP-191): {:?}",
+        checkpoint.eth_signed_message_hash()
+    );
+    if args.validators.is_empty() {
+        return;
+    }
+    println!("Validators:");
+    for (validator, status) in args.validators.iter().zip(&statuses) {
+        println!("\t{:?}: {}", validator, status.describe());
+    }
+    let signed_count = statuses
+        .iter()
+        .filter(|status| matches!(status, SigningStatus::Signed(_)))
+        .count();
+    println!(
+        "{} of {} validators have signed the latest checkpoint",
+        signed_count,
+        args.validators.len()
+    );
+}
+
+/// The storage locations each of the validators has announced, in order.
+pub(crate) fn announced_storage_locations(
+    client: &RpcClient,
+    commitment: CommitmentConfig,
+    validator_announce_program_id: &Pubkey,
+    validators: &[H160],
+) -> Vec<Vec<String>> {
+    let storage_location_keys = validators
+        .iter()
+        .map(|validator| {
+            Pubkey::find_program_address(
+                validator_storage_locations_pda_seeds!(validator),
+                validator_announce_program_id,
+            )
+            .0
+        })
+        .collect::<Vec<_>>();
+    client
+        .get_multiple_accounts_with_commitment(&storage_location_keys, commitment)
+        .unwrap()
+        .value
+        .into_iter()
+        .map(|account| {
+            account
+                .map(|account| {
+                    ValidatorStorageLocationsAccount::fetch(&mut &account.data[..])
+                        .unwrap()
+                        .into_inner()
+                        .storage_locations
+                })
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+enum SigningStatus {
+    NotAnnounced,
+    NotSigned,
+    SignedDifferent,
+    Signed(Box<SignedCheckpointWithMessageId>),
+}
+
+impl SigningStatus {
+    fn describe(&self) -> &'static str {
+        match self {
+            SigningStatus::NotAnnounced => "not announced",
+            SigningStatus::NotSigned => "not signed yet",
+            SigningStatus::SignedDifferent => "published a DIFFERENT checkpoint at this index",
+            SigningStatus::Signed(_) => "signed",
+        }
+    }
+}
+
+fn signing_status(
+    storage_locations: &[String],
+    checkpoint: &CheckpointWithMessageId,
+    strict: bool,
+) -> SigningStatus {
+    if storage_locations.is_empty() {
+        return SigningStatus::NotAnnounced;
+    }
+    let mut published = storage_locations
+        .iter()
+        .filter_map(|location| fetch_signed_checkpoint(location, checkpoint.index, strict))
+        .collect::<Vec<_>>();
+    if let Some(position) = published
+        .iter()
+        .position(|published| published.value == *checkpoint)
+    {
+        SigningStatus::Signed(Box::new(published.swap_remove(position)))
+    } else if published.is_empty() {
+        SigningStatus::NotSigned
+    } else {
+        SigningStatus::SignedDifferent
+    }
+}
+
+/// Fetches the signed checkpoint at `index` from an announced storage
+/// location, in the same layout the validator's checkpoint syncers write. A
+/// missing checkpoint isn't an error, a malformed one is in strict mode. The
+/// signature isn't verified.
+pub(crate) fn fetch_signed_checkpoint(
+    location: &str,
+    index: u32,
+    strict: bool,
+) -> Option<SignedCheckpointWithMessageId> {
+    let json = if let Some(path) = location.strip_prefix("file://") {
+        fs::read_to_string(Path::new(path).join(format!("{}_with_id.json", index))).ok()?
+    } else if let Some(s3) = location.strip_prefix("s3://") {
+        let mut parts = s3.splitn(3, '/');
+        let bucket = parts.next()?;
+        let region = parts.next()?;
+        let key = match parts.next() {
+            Some(folder) if !folder.is_empty() => {
+                format!("{}/checkpoint_{}_with_id.json", folder, index)
+            }
+            _ => format!("checkpoint_{}_with_id.json", index),
+        };
+        ureq::get(&format!(
+            "https://{}.s3.{}.amazonaws.com/{}",
+            bucket, region, key
+        ))
+        .call()
+        .ok()?
+        .into_string()
+        .ok()?
+    } else {
+        return None;
+    };
+    skip_or_fail(
+        strict,
+        serde_json::from_str::<SignedCheckpointWithMessageId>(&json),
+        || format!("checkpoint {} published at {}", index, location),
+    )
+}
@@ -0,0 +1,71 @@
+//! An opt-in convention for sending a body too large for a single dispatch
+//! transaction as several messages.
+//!
+//! Each chunk's body is prefixed with a magic value, the 32 byte ID of the
+//! whole body (its keccak256 hash), then the chunk's index and the number of
+//! chunks as big endian u16s. Recipients have to implement the convention to
+//! reassemble the body once every chunk has been delivered, as the mailbox
+//! delivers chunks like any other message, in no particular order.
+
+use hyperlane_core::H256;
+use solana_program::keccak;
+
+/// The magic prefix identifying a chunk's body.
+pub(crate) const CHUNK_MAGIC: &[u8; 4] = b"HCHK";
+
+pub(crate) const CHUNK_HEADER_LEN: usize = CHUNK_MAGIC.len() + 32 + 2 + 2;
+
+/// Splits the body into chunks of at most `max_chunk_len` bytes, headers
+/// included.
+pub(crate) fn chunk_message_body(body: &[u8], max_chunk_len: usize) -> Vec<Vec<u8>> {
+    assert!(
+        max_chunk_len > CHUNK_HEADER_LEN,
+        "Chunks of {} bytes can't fit the {} byte chunk header",
+        max_chunk_len,
+        CHUNK_HEADER_LEN
+    );
+    let body_id = H256(keccak::hash(body).to_bytes());
+    let chunks = body
+        .chunks(max_chunk_len - CHUNK_HEADER_LEN)
+        .collect::<Vec<_>>();
+    let count = u16::try_from(chunks.len()).expect("Too many chunks");
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, data)| {
+            let mut chunk = Vec::with_capacity(CHUNK_HEADER_LEN + data.len());
+            chunk.extend_from_slice(CHUNK_MAGIC);
+            chunk.extend_from_slice(body_id.as_bytes());
+            chunk.extend_from_slice(&(index as u16).to_be_bytes());
+            chunk.extend_from_slice(&count.to_be_bytes());
+            chunk.extend_from_slice(data);
+            chunk
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_chunk_message_body() {
+        let body = (0..=255u8).cycle().take(1000).collect::<Vec<_>>();
+        let chunks = chunk_message_body(&body, 400);
+
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|chunk| chunk.len() <= 400));
+        for (index, chunk) in chunks.iter().enumerate() {
+            assert!(chunk.starts_with(CHUNK_MAGIC));
+            assert_eq!(&chunk[4..36], keccak::hash(&body).as_ref());
+            assert_eq!(chunk[36..38], (index as u16).to_be_bytes());
+            assert_eq!(chunk[38..40], 3u16.to_be_bytes());
+        }
+        let reassembled = chunks
+            .iter()
+            .flat_map(|chunk| &chunk[CHUNK_HEADER_LEN..])
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(reassembled, body);
+    }
+}
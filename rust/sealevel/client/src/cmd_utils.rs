@@ -13,6 +13,8 @@ use solana_sdk::{
     signature::{Keypair, Signer},
 };
 
+use crate::rpc::rpc_client;
+
 pub(crate) fn account_exists(client: &RpcClient, account: &Pubkey) -> Result<bool, ClientError> {
     // Using `get_account_with_commitment` instead of `get_account` so we get Ok(None) when the account
     // doesn't exist, rather than an error
@@ -30,7 +32,7 @@ pub(crate) fn deploy_program_idempotent(
     program_path: &str,
     url: &str,
 ) -> Result<(), ClientError> {
-    let client = RpcClient::new(url.to_string());
+    let client = rpc_client(url.to_string(), CommitmentConfig::default());
     if !account_exists(&client, &program_keypair.pubkey())? {
         deploy_program(payer_keypair_path, program_keypair_path, program_path, url);
     } else {
@@ -0,0 +1,119 @@
+//! Shell completion scripts generated from the command definitions, with the
+//! chain names of the domain registry completing chain arguments.
+
+use std::{any::TypeId, ffi::OsStr, io, str::FromStr};
+
+use clap::{
+    builder::{PossibleValue, TypedValueParser},
+    error::ErrorKind,
+    Arg, Command, CommandFactory,
+};
+use clap_complete::generate;
+
+use crate::{
+    domain::{DomainArg, DomainRegistry},
+    Cli, Completions,
+};
+
+/// Prints the completion script of the shell. Chain names are those of the
+/// registry when the script is generated, so scripts are regenerated after
+/// chains are added to the chain metadata file.
+pub(crate) fn print_completions(domains: &DomainRegistry, completions: &Completions) {
+    // Clap only takes static strings as possible values.
+    let chain_names = domains
+        .chain_names()
+        .into_iter()
+        .map(|name| &*Box::leak(name.to_owned().into_boxed_str()))
+        .collect::<Vec<_>>();
+    let mut command = with_chain_names(Cli::command(), &chain_names);
+    generate(
+        completions.shell,
+        &mut command,
+        env!("CARGO_PKG_NAME"),
+        &mut io::stdout(),
+    );
+}
+
+/// Sets the chain names as the possible values of the chain arguments of the
+/// command and its subcommands.
+fn with_chain_names(mut command: Command, chain_names: &[&'static str]) -> Command {
+    if chain_names.is_empty() {
+        return command;
+    }
+    command = command.mut_args(|arg| {
+        if is_chain_arg(&arg) {
+            arg.value_parser(ChainParser(chain_names.to_vec()))
+        } else {
+            arg
+        }
+    });
+
+    let subcommands = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_owned())
+        .collect::<Vec<_>>();
+    for name in subcommands {
+        command =
+            command.mut_subcommand(name, |subcommand| with_chain_names(subcommand, chain_names));
+    }
+    command
+}
+
+fn is_chain_arg(arg: &Arg) -> bool {
+    arg.get_value_parser().type_id() == TypeId::of::<DomainArg>()
+}
+
+/// Parses chain arguments like `DomainArg`, so that domain IDs, like those of
+/// default values, are still valid, but lists chain names as the possible
+/// values to complete.
+#[derive(Clone)]
+struct ChainParser(Vec<&'static str>);
+
+impl TypedValueParser for ChainParser {
+    type Value = DomainArg;
+
+    fn parse_ref(
+        &self,
+        _cmd: &Command,
+        _arg: Option<&Arg>,
+        value: &OsStr,
+    ) -> Result<DomainArg, clap::Error> {
+        value
+            .to_str()
+            .ok_or_else(|| "Invalid UTF-8".to_owned())
+            .and_then(DomainArg::from_str)
+            .map_err(|e| clap::Error::raw(ErrorKind::InvalidValue, e))
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(self.0.iter().copied().map(PossibleValue::new)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_chain_args_complete_chain_names() {
+        let command = with_chain_names(Cli::command(), &["solanamainnet", "eclipsemainnet"]);
+        let send = command
+            .find_subcommand("mailbox")
+            .and_then(|mailbox| mailbox.find_subcommand("send"))
+            .unwrap();
+        let possible_values = |id: &str| {
+            send.get_arguments()
+                .find(|arg| arg.get_id() == id)
+                .unwrap()
+                .get_possible_values()
+                .iter()
+                .map(|value| value.get_name().to_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            possible_values("destination"),
+            vec!["solanamainnet", "eclipsemainnet"]
+        );
+        assert!(possible_values("recipient").is_empty());
+    }
+}
@@ -0,0 +1,606 @@
+//! Inspection of the configuration commands run with, and validation of the
+//! chain config and deployments against the chains. Each option is taken,
+//! from highest to lowest precedence, from its flag, its `HYPERLANE_*`
+//! environment variable, the selected profile or the Solana CLI config for
+//! the RPC URL and keypair, and finally its default.
+
+use std::{
+    collections::HashSet,
+    ffi::{OsStr, OsString},
+    fs::File,
+    path::Path,
+};
+
+use clap::{parser::ValueSource, Arg, ArgAction, ArgMatches, Command, CommandFactory};
+use solana_cli_config::Config;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use hyperlane_sealevel_mailbox::accounts::{Inbox, InboxAccount, Outbox, OutboxAccount};
+
+#[cfg(feature = "tokens")]
+use crate::HYPERLANE_TOKEN_PROG_ID;
+use crate::{
+    chain_set::ChainSet,
+    core::{core_program_ids_path, CoreProgramIds},
+    exit_code,
+    profile::Profile,
+    protocol::ProtocolConfig,
+    router::ChainMetadata,
+    Cli, ConfigShow, ConfigValidate, Context, MAILBOX_PROG_ID, MULTISIG_ISM_MESSAGE_ID_PROG_ID,
+    VALIDATOR_ANNOUNCE_PROG_ID,
+};
+
+/// The options that fall back to the profile, then to the Solana CLI config.
+const PROFILE_OPTIONS: [&str; 2] = ["url", "keypair"];
+
+/// The programs whose IDs `--program-id` flags default to, with the
+/// environment variables those flags take the program ID from.
+fn program_id_env_vars() -> Vec<(&'static str, Pubkey, &'static str)> {
+    let mut program_ids = vec![
+        ("mailbox", MAILBOX_PROG_ID, "HYPERLANE_MAILBOX_PROGRAM_ID"),
+        (
+            "multisig-ism-message-id",
+            MULTISIG_ISM_MESSAGE_ID_PROG_ID,
+            "HYPERLANE_MULTISIG_ISM_MESSAGE_ID_PROGRAM_ID",
+        ),
+        (
+            "validator-announce",
+            VALIDATOR_ANNOUNCE_PROG_ID,
+            "HYPERLANE_VALIDATOR_ANNOUNCE_PROGRAM_ID",
+        ),
+    ];
+    #[cfg(feature = "tokens")]
+    program_ids.push((
+        "token",
+        HYPERLANE_TOKEN_PROG_ID,
+        "HYPERLANE_TOKEN_PROGRAM_ID",
+    ));
+    program_ids
+}
+
+/// Sets `HYPERLANE_<FLAG>` as the environment variable of the subcommands'
+/// flags that don't have one, e.g. `HYPERLANE_ORIGIN` for `relay --origin`.
+/// `--program-id` flags mean a different program for each command, so they
+/// take the variable of the program they default to, e.g.
+/// `HYPERLANE_MAILBOX_PROGRAM_ID`, and none if they have no default. Flags
+/// whose variable would be that of a global option, like the keypair of a
+/// program to deploy, don't take one either.
+pub(crate) fn with_env_vars(mut command: Command) -> Command {
+    let global_env_vars = Cli::command()
+        .get_arguments()
+        .filter_map(Arg::get_env)
+        .map(OsStr::to_owned)
+        .collect::<HashSet<_>>();
+    let program_id_env_vars = program_id_env_vars()
+        .into_iter()
+        .map(|(_, program_id, env)| (program_id.to_string(), env))
+        .collect::<Vec<_>>();
+
+    let subcommands = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_owned())
+        .collect::<Vec<_>>();
+    for name in subcommands {
+        command = command.mut_subcommand(name, |subcommand| {
+            with_subcommand_env_vars(subcommand, &global_env_vars, &program_id_env_vars)
+        });
+    }
+    command
+}
+
+fn with_subcommand_env_vars(
+    mut command: Command,
+    global_env_vars: &HashSet<OsString>,
+    program_id_env_vars: &[(String, &'static str)],
+) -> Command {
+    command = command.mut_args(|arg| {
+        let Some(long) = arg.get_long() else {
+            return arg;
+        };
+        let takes_env_var =
+            arg.get_action().takes_values() || matches!(arg.get_action(), ArgAction::SetTrue);
+        if arg.get_env().is_some() || arg.is_global_set() || !takes_env_var {
+            return arg;
+        }
+        let env = if arg.get_id() == "program_id" {
+            let [default] = arg.get_default_values() else {
+                return arg;
+            };
+            match program_id_env_vars
+                .iter()
+                .find(|(program_id, _)| default.to_str() == Some(program_id.as_str()))
+            {
+                Some((_, env)) => *env,
+                None => return arg,
+            }
+        } else {
+            let env = format!("HYPERLANE_{}", long.replace('-', "_").to_uppercase());
+            if global_env_vars.contains(OsStr::new(&env)) {
+                return arg;
+            }
+            // Clap only takes static strings as environment variables.
+            Box::leak(env.into_boxed_str())
+        };
+        arg.env(env)
+    });
+
+    let subcommands = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_owned())
+        .collect::<Vec<_>>();
+    for name in subcommands {
+        command = command.mut_subcommand(name, |subcommand| {
+            with_subcommand_env_vars(subcommand, global_env_vars, program_id_env_vars)
+        });
+    }
+    command
+}
+
+/// Prints the configuration files, or with `--resolved` the value each
+/// option resolves to and where it comes from.
+pub(crate) fn show_config(
+    show: &ConfigShow,
+    matches: &ArgMatches,
+    profile: &Profile,
+    config_file: Option<&str>,
+    config: &Config,
+) {
+    if show.resolved {
+        print_resolved_config(matches, profile, config);
+        return;
+    }
+
+    match config_file {
+        Some(config_file) => println!("Solana CLI config {}:", config_file),
+        None => println!("No Solana CLI config, using its defaults:"),
+    }
+    println!("\turl: {}", config.json_rpc_url);
+    println!("\tkeypair: {}", config.keypair_path);
+    match (&profile.name, &profile.path) {
+        (Some(name), Some(path)) => {
+            println!("Profile {} from {}:", name, path.display());
+            let values = profile_option_values(profile)
+                .into_iter()
+                .chain(profile_program_ids(profile));
+            for (option, value) in values {
+                if let Some(value) = value {
+                    println!("\t{}: {}", option, value);
+                }
+            }
+        }
+        _ => println!("No profile"),
+    }
+}
+
+fn profile_option_values(profile: &Profile) -> Vec<(&'static str, Option<String>)> {
+    vec![
+        ("url", profile.url.clone()),
+        ("keypair", profile.keypair.clone()),
+    ]
+}
+
+fn profile_program_ids(profile: &Profile) -> Vec<(&'static str, Option<String>)> {
+    let ids = &profile.program_ids;
+    let mut program_ids = vec![
+        ("mailbox", ids.mailbox),
+        ("igp", ids.igp),
+        ("multisig-ism-message-id", ids.multisig_ism_message_id),
+        ("validator-announce", ids.validator_announce),
+    ];
+    #[cfg(feature = "tokens")]
+    program_ids.push(("token", ids.token));
+    program_ids
+        .into_iter()
+        .map(|(name, program_id)| (name, program_id.map(|program_id| program_id.to_string())))
+        .collect()
+}
+
+fn print_resolved_config(matches: &ArgMatches, profile: &Profile, config: &Config) {
+    let profile_source = || format!("profile {}", profile.name.as_deref().unwrap_or_default());
+    match &profile.name {
+        Some(name) => println!("profile = {} ({})", name, option_source(matches, "profile")),
+        None => println!("profile = none"),
+    }
+
+    for arg in Cli::command().get_arguments() {
+        let id = arg.get_id().as_str();
+        if id == "profile" {
+            continue;
+        }
+        let option = arg.get_long().unwrap_or(id);
+        let from_flag_or_env = matches!(
+            matches.value_source(id),
+            Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+        );
+        if PROFILE_OPTIONS.contains(&id) && !from_flag_or_env {
+            let (profile_value, config_value) = match id {
+                "url" => (&profile.url, &config.json_rpc_url),
+                _ => (&profile.keypair, &config.keypair_path),
+            };
+            match profile_value {
+                Some(value) => println!("{} = {} ({})", option, value, profile_source()),
+                None => println!("{} = {} (Solana CLI config)", option, config_value),
+            }
+            continue;
+        }
+        match matches.get_raw(id) {
+            Some(values) => println!(
+                "{} = {} ({})",
+                option,
+                values
+                    .map(|value| value.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(","),
+                option_source(matches, id)
+            ),
+            None => println!("{} unset", option),
+        }
+    }
+
+    // Program IDs are defaults of the commands' arguments, which their
+    // environment variables and the profile can override.
+    let mut program_ids = program_id_env_vars()
+        .into_iter()
+        .map(|(name, program_id, env)| (name, Some(program_id), env))
+        .collect::<Vec<_>>();
+    program_ids.insert(1, ("igp", None, "HYPERLANE_IGP_PROGRAM_ID"));
+    let profile_program_ids = profile_program_ids(profile);
+    for (name, default, env) in program_ids {
+        let profile_program_id = profile_program_ids
+            .iter()
+            .find(|(profile_name, _)| *profile_name == name)
+            .and_then(|(_, program_id)| program_id.clone());
+        print_program_id(name, env, profile_program_id, default, &profile_source);
+    }
+}
+
+fn print_program_id(
+    name: &str,
+    env: &str,
+    profile_program_id: Option<String>,
+    default: Option<Pubkey>,
+    profile_source: &dyn Fn() -> String,
+) {
+    if let Ok(program_id) = std::env::var(env) {
+        println!("{} program = {} (env {})", name, program_id, env);
+        return;
+    }
+    match (profile_program_id, default) {
+        (Some(program_id), _) => {
+            println!("{} program = {} ({})", name, program_id, profile_source())
+        }
+        (None, Some(program_id)) => println!("{} program = {} (default)", name, program_id),
+        (None, None) => println!("{} program unset", name),
+    }
+}
+
+fn option_source(matches: &ArgMatches, id: &str) -> String {
+    match matches.value_source(id) {
+        Some(ValueSource::CommandLine) => "flag".to_owned(),
+        Some(ValueSource::EnvVariable) => Cli::command()
+            .get_arguments()
+            .find(|arg| arg.get_id() == id)
+            .and_then(|arg| arg.get_env())
+            .map(|env| format!("env {}", env.to_string_lossy()))
+            .unwrap_or_else(|| "env".to_owned()),
+        _ => "default".to_owned(),
+    }
+}
+
+/// The checks of a chain, in the order they're run. A check is `None` if it
+/// was skipped because a check before it failed, or fails with the reason.
+#[derive(Default)]
+struct ChainChecks {
+    rpc: Option<Result<(), String>>,
+    mailbox: Option<Result<(), String>>,
+    domain: Option<Result<(), String>>,
+    ism: Option<Result<(), String>>,
+}
+
+impl ChainChecks {
+    fn checks(&self) -> [(&'static str, &Option<Result<(), String>>); 4] {
+        [
+            ("rpc", &self.rpc),
+            ("mailbox", &self.mailbox),
+            ("domain", &self.domain),
+            ("ism", &self.ism),
+        ]
+    }
+
+    fn passed(&self) -> bool {
+        self.checks()
+            .iter()
+            .all(|(_, check)| matches!(check, Some(Ok(()))))
+    }
+}
+
+/// Checks the chains of the chain config file against their deployments,
+/// printing a table of the checks that passed and failed per chain, and
+/// exits with `CONFIG_ERROR` if any failed.
+pub(crate) fn validate_config(ctx: &Context, validate: ConfigValidate) {
+    let chain_set = ChainSet::from_chain_config_file(&validate.chain_config_file, &validate.chains);
+    let protocol = &ctx.protocol;
+    let results = chain_set.query(|chain_config, client| {
+        let program_ids_path = core_program_ids_path(
+            &validate.environments_dir,
+            &validate.environment,
+            chain_config.name(),
+        );
+        Ok::<_, String>(validate_chain(
+            protocol,
+            &program_ids_path,
+            chain_config,
+            client,
+        ))
+    });
+
+    let name_width = results
+        .iter()
+        .map(|(chain_name, _)| chain_name.len())
+        .chain(std::iter::once("chain".len()))
+        .max()
+        .unwrap_or_default();
+    print!("{:width$}", "chain", width = name_width);
+    for (name, _) in ChainChecks::default().checks() {
+        print!("  {:8}", name);
+    }
+    println!();
+    let mut failures = vec![];
+    for (chain_name, result) in &results {
+        print!("{:width$}", chain_name, width = name_width);
+        let checks = match result {
+            Ok(checks) => checks,
+            Err(err) => {
+                println!("  error: {}", err);
+                failures.push(format!("{}: {}", chain_name, err));
+                continue;
+            }
+        };
+        for (name, check) in checks.checks() {
+            let status = match check {
+                Some(Ok(())) => "pass",
+                Some(Err(err)) => {
+                    failures.push(format!("{} {}: {}", chain_name, name, err));
+                    "FAIL"
+                }
+                None => "skipped",
+            };
+            print!("  {:8}", status);
+        }
+        println!();
+    }
+
+    if !failures.is_empty() {
+        println!();
+        for failure in &failures {
+            println!("{}", failure);
+        }
+    }
+    if !results
+        .iter()
+        .all(|(_, result)| result.as_ref().map_or(false, ChainChecks::passed))
+    {
+        std::process::exit(exit_code::CONFIG_ERROR);
+    }
+}
+
+fn validate_chain(
+    protocol: &ProtocolConfig,
+    program_ids_path: &Path,
+    chain_config: &ChainMetadata,
+    client: &RpcClient,
+) -> ChainChecks {
+    let mut checks = ChainChecks {
+        rpc: Some(check_rpc(client, chain_config.genesis_hash())),
+        ..Default::default()
+    };
+    if checks.rpc != Some(Ok(())) {
+        return checks;
+    }
+
+    let (program_ids, inbox, outbox) = match check_mailbox(protocol, program_ids_path, client) {
+        Ok(mailbox) => mailbox,
+        Err(err) => {
+            checks.mailbox = Some(Err(err));
+            return checks;
+        }
+    };
+    checks.mailbox = Some(Ok(()));
+    checks.domain = Some(check_domain(chain_config, &inbox, &outbox));
+    checks.ism = Some(check_isms(
+        client,
+        &[
+            ("default ISM", inbox.default_ism),
+            ("multisig ISM", program_ids.multisig_ism_message_id),
+        ],
+    ));
+    checks
+}
+
+/// Checks that the RPC can be reached and serves the chain of the expected
+/// genesis hash, if any.
+pub(crate) fn check_rpc(
+    client: &RpcClient,
+    expected_genesis_hash: Option<&str>,
+) -> Result<(), String> {
+    let genesis_hash = client
+        .get_genesis_hash()
+        .map_err(|e| format!("{} can't be reached: {}", client.url(), e))?;
+    match expected_genesis_hash {
+        Some(expected) if expected != genesis_hash.to_string() => Err(format!(
+            "{} serves genesis hash {}, expected {}",
+            client.url(),
+            genesis_hash,
+            expected
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that the mailbox is deployed and that its inbox and outbox are
+/// initialized and owned by it, returning the deployment's program IDs and
+/// the accounts.
+fn check_mailbox(
+    protocol: &ProtocolConfig,
+    program_ids_path: &Path,
+    client: &RpcClient,
+) -> Result<(CoreProgramIds, Box<Inbox>, Box<Outbox>), String> {
+    let program_ids: CoreProgramIds = File::open(program_ids_path)
+        .map_err(|e| e.to_string())
+        .and_then(|file| serde_json::from_reader(file).map_err(|e| e.to_string()))
+        .map_err(|e| {
+            format!(
+                "Program IDs {} can't be read: {}",
+                program_ids_path.display(),
+                e
+            )
+        })?;
+    let mailbox = program_ids.mailbox;
+    let (inbox_account, _inbox_bump) = protocol.inbox_pda(&mailbox);
+    let (outbox_account, _outbox_bump) = protocol.outbox_pda(&mailbox);
+    let accounts = client
+        .get_multiple_accounts(&[mailbox, inbox_account, outbox_account])
+        .map_err(|e| e.to_string())?;
+    match &accounts[0] {
+        Some(account) if account.executable => {}
+        _ => return Err(format!("Mailbox {} isn't deployed", mailbox)),
+    }
+    for (name, account_key, account) in [
+        ("Inbox", inbox_account, &accounts[1]),
+        ("Outbox", outbox_account, &accounts[2]),
+    ] {
+        match account {
+            Some(account) if account.owner != mailbox => {
+                return Err(format!(
+                    "{} {} is owned by {}, not the mailbox {}",
+                    name, account_key, account.owner, mailbox
+                ))
+            }
+            Some(_) => {}
+            None => {
+                return Err(format!(
+                    "{} {} of mailbox {} isn't initialized",
+                    name, account_key, mailbox
+                ))
+            }
+        }
+    }
+
+    let inbox = accounts[1]
+        .as_ref()
+        .and_then(|account| InboxAccount::fetch(&mut &account.data[..]).ok())
+        .ok_or_else(|| format!("Inbox {} doesn't decode", inbox_account))?
+        .into_inner();
+    let outbox = accounts[2]
+        .as_ref()
+        .and_then(|account| OutboxAccount::fetch(&mut &account.data[..]).ok())
+        .ok_or_else(|| format!("Outbox {} doesn't decode", outbox_account))?
+        .into_inner();
+    Ok((program_ids, inbox, outbox))
+}
+
+/// Checks that the mailbox's local domain is the chain's domain.
+fn check_domain(
+    chain_config: &ChainMetadata,
+    inbox: &Inbox,
+    outbox: &Outbox,
+) -> Result<(), String> {
+    let domain = chain_config.domain_id();
+    for (name, local_domain) in [
+        ("Inbox", inbox.local_domain),
+        ("Outbox", outbox.local_domain),
+    ] {
+        if local_domain != domain {
+            return Err(format!(
+                "{} local domain is {}, expected {}",
+                name, local_domain, domain
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the ISMs are deployed programs.
+fn check_isms(client: &RpcClient, isms: &[(&str, Pubkey)]) -> Result<(), String> {
+    let accounts = client
+        .get_multiple_accounts(&isms.iter().map(|(_, ism)| *ism).collect::<Vec<_>>())
+        .map_err(|e| e.to_string())?;
+    for ((name, ism), account) in isms.iter().zip(accounts) {
+        if !account.map_or(false, |account| account.executable) {
+            return Err(format!("{} {} isn't deployed", name, ism));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_subcommand_flags_take_env_vars() {
+        let command = with_env_vars(Cli::command());
+        let env = |path: &[&str], id: &str| {
+            let mut subcommand = &command;
+            for name in path {
+                subcommand = subcommand.find_subcommand(name).unwrap();
+            }
+            subcommand
+                .get_arguments()
+                .find(|arg| arg.get_id() == id)
+                .unwrap()
+                .get_env()
+                .map(|env| env.to_string_lossy().into_owned())
+        };
+        assert_eq!(
+            env(&["relay"], "origin").as_deref(),
+            Some("HYPERLANE_ORIGIN")
+        );
+        assert_eq!(
+            env(&["mailbox", "send"], "program_id").as_deref(),
+            Some("HYPERLANE_MAILBOX_PROGRAM_ID")
+        );
+        assert_eq!(
+            env(&["validator-announce", "announce"], "program_id").as_deref(),
+            Some("HYPERLANE_VALIDATOR_ANNOUNCE_PROGRAM_ID")
+        );
+        // Routing ISMs have no default program ID.
+        assert_eq!(env(&["ism", "routing", "show"], "program_id"), None);
+        // Global options keep their own.
+        assert_eq!(
+            command
+                .get_arguments()
+                .find(|arg| arg.get_id() == "url")
+                .and_then(Arg::get_env),
+            Some(OsStr::new("HYPERLANE_URL"))
+        );
+    }
+
+    #[test]
+    fn test_check_domain() {
+        let chain_config: ChainMetadata = serde_json::from_value(json!({
+            "chainId": 1399811149,
+            "name": "solanamainnet",
+            "rpcUrls": [{ "http": "http://localhost:8899" }],
+        }))
+        .unwrap();
+        let inbox = Inbox {
+            local_domain: 1399811149,
+            ..Default::default()
+        };
+        let outbox = Outbox {
+            local_domain: 1399811149,
+            ..Default::default()
+        };
+        assert_eq!(check_domain(&chain_config, &inbox, &outbox), Ok(()));
+
+        let outbox = Outbox {
+            local_domain: 1,
+            ..Default::default()
+        };
+        assert!(check_domain(&chain_config, &inbox, &outbox).is_err());
+    }
+}
@@ -1,19 +1,53 @@
+use hyperlane_core::H256;
+use solana_account_decoder::UiAccountEncoding;
+use solana_cli_config::Config;
 use solana_client::{
+    client_error::ClientError,
+    nonce_utils,
     rpc_client::RpcClient,
-    rpc_config::{RpcSendTransactionConfig, RpcTransactionConfig},
+    rpc_config::{
+        RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig, RpcTransactionConfig,
+    },
 };
 use solana_sdk::{
+    account::Account,
     commitment_config::CommitmentConfig,
+    hash::Hash,
     instruction::Instruction,
     message::Message,
     pubkey::Pubkey,
-    signature::{Keypair, Signer},
+    signature::{Keypair, Signature, Signer},
     signer::null_signer::NullSigner,
     signers::Signers,
+    system_instruction,
     transaction::Transaction,
 };
 use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
-use std::{cell::RefCell, io::Read};
+use std::{cell::RefCell, io::Read, path::PathBuf, sync::Arc, time::Instant};
+use tracing::{debug, debug_span};
+
+#[cfg(feature = "names")]
+use crate::names::NameResolver;
+use crate::{
+    address::Address,
+    blockhash::CachedBlockhash,
+    decode::HyperlanePrograms,
+    domain::DomainRegistry,
+    exit_code,
+    explorer::Explorers,
+    jito::JitoBundleSender,
+    metrics::Metrics,
+    proposal::{format_proposal, ProposalFormat},
+    protocol::ProtocolConfig,
+    receipt::{write_receipt, TxOutcome},
+    rpc::rpc_client,
+    sender::{
+        ConfirmationStrategy, RpcTransactionSender, SolanaRpcSender, TpuTransactionSender,
+        TransactionSender,
+    },
+    tx::append_transaction,
+    tx_logs::{format_transaction_logs, print_client_error, print_transaction_failure},
+};
 
 pub(crate) struct PayerKeypair {
     pub keypair: Keypair,
@@ -27,6 +61,28 @@ pub(crate) struct Context {
     pub commitment: CommitmentConfig,
     pub initial_instructions: RefCell<Vec<InstructionWithDescription>>,
     pub require_tx_approval: bool,
+    pub simulate_only: bool,
+    pub protocol: ProtocolConfig,
+    pub domains: DomainRegistry,
+    pub receipts_dir: Option<PathBuf>,
+    /// A durable nonce account, whose authority is the payer, to build
+    /// transactions against instead of a recent blockhash.
+    pub nonce_account: Option<Pubkey>,
+    strict: Option<bool>,
+    /// When set, transactions are written unsigned to this file, to be signed
+    /// with `tx sign`, instead of being sent.
+    pub unsigned_txns_out: Option<PathBuf>,
+    #[cfg(feature = "names")]
+    names: NameResolver,
+    /// Explorers to link sent transactions and dispatched messages to, unless
+    /// links are turned off.
+    explorers: Option<Explorers>,
+    metrics: Option<Arc<Metrics>>,
+    confirmation: ConfirmationStrategy,
+    /// Whether to print progress, like the wait for a confirmation, on
+    /// stderr.
+    progress: bool,
+    blockhash: CachedBlockhash,
 }
 
 pub(crate) struct InstructionWithDescription {
@@ -49,10 +105,12 @@ where
 pub(crate) struct TxnBuilder<'ctx, 'rpc> {
     ctx: &'ctx Context,
     client: Option<&'rpc RpcClient>,
+    sender: Option<&'rpc dyn TransactionSender>,
     instructions_with_descriptions: Vec<InstructionWithDescription>,
 }
 
 impl Context {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         client: RpcClient,
         payer_pubkey: Pubkey,
@@ -60,6 +118,13 @@ impl Context {
         commitment: CommitmentConfig,
         initial_instructions: RefCell<Vec<InstructionWithDescription>>,
         require_tx_approval: bool,
+        simulate_only: bool,
+        protocol: ProtocolConfig,
+        domains: DomainRegistry,
+        receipts_dir: Option<PathBuf>,
+        nonce_account: Option<Pubkey>,
+        strict: Option<bool>,
+        unsigned_txns_out: Option<PathBuf>,
     ) -> Self {
         Self {
             client,
@@ -68,13 +133,143 @@ impl Context {
             commitment,
             initial_instructions,
             require_tx_approval,
+            simulate_only,
+            protocol,
+            domains,
+            receipts_dir,
+            nonce_account,
+            strict,
+            unsigned_txns_out,
+            #[cfg(feature = "names")]
+            names: NameResolver::default(),
+            explorers: None,
+            metrics: None,
+            confirmation: ConfirmationStrategy::new(commitment),
+            progress: true,
+            blockhash: CachedBlockhash::default(),
+        }
+    }
+
+    #[cfg(feature = "names")]
+    pub(crate) fn with_names(mut self, names: NameResolver) -> Self {
+        self.names = names;
+        self
+    }
+
+    pub(crate) fn with_explorers(mut self, explorers: Explorers) -> Self {
+        self.explorers = Some(explorers);
+        self
+    }
+
+    pub(crate) fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub(crate) fn with_confirmation(mut self, confirmation: ConfirmationStrategy) -> Self {
+        self.confirmation = confirmation;
+        self
+    }
+
+    /// Turns off progress output, which garbles logs when not printed to a
+    /// terminal.
+    pub(crate) fn without_progress(mut self) -> Self {
+        self.progress = false;
+        self
+    }
+
+    /// Whether progress is printed.
+    pub(crate) fn shows_progress(&self) -> bool {
+        self.progress
+    }
+
+    /// How sent transactions are waited for.
+    pub(crate) fn confirmation(&self) -> &ConfirmationStrategy {
+        &self.confirmation
+    }
+
+    /// Sends transactions through the client.
+    pub(crate) fn sender<'a>(&self, client: &'a RpcClient) -> RpcTransactionSender<'a> {
+        RpcTransactionSender::new(client, self.commitment.commitment, self.progress)
+    }
+
+    /// Reads accounts from and sends transactions through the RPC at `url`,
+    /// another chain's RPC for example.
+    pub(crate) fn rpc_sender(&self, url: impl Into<String>) -> SolanaRpcSender {
+        SolanaRpcSender::new(url)
+            .commitment(self.commitment)
+            .progress(self.progress)
+            .protocol(self.protocol.clone())
+    }
+
+    /// Sends transactions directly to the leaders' TPUs, tracking the leader
+    /// schedule through a client of its own to the RPC. The websocket URL is
+    /// derived from the RPC URL unless given.
+    pub(crate) fn tpu_sender(
+        &self,
+        ws_url: Option<String>,
+    ) -> Result<TpuTransactionSender, ClientError> {
+        let url = self.client.url();
+        let ws_url = ws_url.unwrap_or_else(|| Config::compute_websocket_url(&url));
+        TpuTransactionSender::new(
+            Arc::new(rpc_client(url, self.commitment)),
+            &ws_url,
+            self.progress,
+        )
+    }
+
+    /// Sends transactions as bundles through the Jito block engine.
+    pub(crate) fn jito_sender(&self, block_engine_url: &str) -> JitoBundleSender {
+        JitoBundleSender::new(block_engine_url, self.progress)
+    }
+
+    /// The metrics to record to, if they're served.
+    pub(crate) fn metrics(&self) -> Option<&Metrics> {
+        self.metrics.as_deref()
+    }
+
+    /// Prints a link to the transaction sent through the client, unless
+    /// links are turned off.
+    pub(crate) fn print_transaction_link(&self, client: &RpcClient, signature: &Signature) {
+        if let Some(explorers) = &self.explorers {
+            println!(
+                "Explorer: {}",
+                explorers.transaction_url(&client.url(), signature)
+            );
         }
     }
 
+    /// Prints a link to the dispatched message, unless links are turned off.
+    pub(crate) fn print_message_link(&self, message_id: H256) {
+        if let Some(explorers) = &self.explorers {
+            println!("Hyperlane explorer: {}", explorers.message_url(message_id));
+        }
+    }
+
+    /// Resolves the address, looking it up if it's a name.
+    pub(crate) fn resolve_address(&self, address: &Address) -> H256 {
+        match address {
+            Address::Resolved(address) => *address,
+            #[cfg(feature = "names")]
+            Address::Name(name) => self
+                .names
+                .resolve(&self.client, name)
+                .unwrap_or_else(|e| panic!("{}", e)),
+        }
+    }
+
+    /// Parses an address in any of the formats supported by [`Address`] and
+    /// resolves it.
+    pub(crate) fn parse_address(&self, s: &str) -> Result<H256, String> {
+        s.parse::<Address>()
+            .map(|address| self.resolve_address(&address))
+    }
+
     pub(crate) fn new_txn(&self) -> TxnBuilder {
         TxnBuilder {
             ctx: self,
             client: None,
+            sender: None,
             instructions_with_descriptions: self
                 .initial_instructions
                 .borrow_mut()
@@ -83,6 +278,13 @@ impl Context {
         }
     }
 
+    /// Whether data that can't be decoded is a hard error rather than
+    /// skipped. Unless set explicitly, long-running service commands are
+    /// strict and others aren't.
+    pub(crate) fn is_strict(&self, service: bool) -> bool {
+        self.strict.unwrap_or(service)
+    }
+
     pub(crate) fn payer_can_sign(&self) -> bool {
         self.payer_keypair.is_some()
     }
@@ -95,6 +297,14 @@ impl Context {
         }
     }
 
+    /// The payer keypair, which unlike the signer from [`Self::payer_signer`]
+    /// can be shared across threads.
+    pub(crate) fn payer_keypair(&self) -> Option<&Keypair> {
+        self.payer_keypair
+            .as_ref()
+            .map(|payer_keypair| &payer_keypair.keypair)
+    }
+
     pub(crate) fn payer_keypair_path(&self) -> &String {
         &self
             .payer_keypair
@@ -134,6 +344,13 @@ impl<'ctx, 'rpc> TxnBuilder<'ctx, 'rpc> {
         self
     }
 
+    /// Sends the transaction through the sender rather than through the
+    /// client's `sendTransaction`, e.g. as a Jito bundle.
+    pub(crate) fn with_sender(mut self, sender: &'rpc dyn TransactionSender) -> Self {
+        self.sender = Some(sender);
+        self
+    }
+
     pub(crate) fn instructions(&self) -> Vec<Instruction> {
         self.instructions_with_descriptions
             .iter()
@@ -141,20 +358,45 @@ impl<'ctx, 'rpc> TxnBuilder<'ctx, 'rpc> {
             .collect()
     }
 
+    fn descriptions(&self) -> Vec<Option<String>> {
+        self.instructions_with_descriptions
+            .iter()
+            .map(|i| i.description.clone())
+            .collect()
+    }
+
     pub(crate) fn pretty_print_transaction(&self) {
         println!("\t==== Instructions: ====");
 
-        for (i, InstructionWithDescription { description, .. }) in
-            self.instructions_with_descriptions.iter().enumerate()
+        // Instructions of the programs at their default IDs are decoded.
+        let programs = HyperlanePrograms::default();
+        for (
+            i,
+            InstructionWithDescription {
+                instruction,
+                description,
+            },
+        ) in self.instructions_with_descriptions.iter().enumerate()
         {
             println!(
                 "\tInstruction {}: {}",
                 i,
                 description.as_deref().unwrap_or("No description provided")
             );
+            if let Some(Ok(decoded)) = programs.decode(instruction) {
+                println!("\t\t{:?}", decoded);
+            }
         }
 
-        let message = Message::new(&self.instructions(), None);
+        // With a durable nonce the transaction stays valid until the nonce is
+        // advanced, so print it in full for it to be signed later.
+        let message = match self.ctx.nonce_account {
+            Some(_) => {
+                let client = self.client.unwrap_or(&self.ctx.client);
+                self.message(&self.blockhash(client))
+            }
+            None => Message::new(&self.instructions(), None),
+        };
         let txn = Transaction::new_unsigned(message);
         println!(
             "\t==== Transaction in base58: ====\n\t{}",
@@ -167,10 +409,94 @@ impl<'ctx, 'rpc> TxnBuilder<'ctx, 'rpc> {
         self.send(&[&*payer_signer])
     }
 
+    pub(crate) fn try_send_with_payer(
+        self,
+    ) -> Result<Option<EncodedConfirmedTransactionWithStatusMeta>, ClientError> {
+        let payer_signer = self.ctx.payer_signer();
+        self.try_send(&[&*payer_signer])
+    }
+
+    /// Sends the transaction with the payer, or if a proposal format is
+    /// given, prints the instructions as a proposal for the payer, which is
+    /// then a multisig, instead.
+    pub(crate) fn send_with_payer_or_propose(
+        self,
+        proposal: Option<ProposalFormat>,
+    ) -> Option<EncodedConfirmedTransactionWithStatusMeta> {
+        let Some(format) = proposal else {
+            return self.send_with_payer();
+        };
+        println!("==== Multisig proposal for {}: ====", self.ctx.payer_pubkey);
+        println!(
+            "{}",
+            format_proposal(
+                format,
+                &self.ctx.payer_pubkey,
+                &self.instructions_with_descriptions
+            )
+        );
+        None
+    }
+
     pub(crate) fn send<T: Signers>(
         self,
         signers: &T,
     ) -> Option<EncodedConfirmedTransactionWithStatusMeta> {
+        match self.try_send(signers) {
+            Ok(txn) => txn,
+            Err(err) => exit_code::exit_on_transaction_error(err),
+        }
+    }
+
+    /// Like `send`, but returns the transaction's outcome instead of the raw
+    /// confirmed transaction.
+    pub(crate) fn send_for_outcome<T: Signers>(self, signers: &T) -> Option<TxOutcome> {
+        self.send(signers).as_ref().and_then(TxOutcome::new)
+    }
+
+    /// Like `send`, but returns the error if the transaction fails to be sent
+    /// or confirmed instead of panicking, for callers that retry.
+    pub(crate) fn try_send<T: Signers>(
+        mut self,
+        signers: &T,
+    ) -> Result<Option<EncodedConfirmedTransactionWithStatusMeta>, ClientError> {
+        if self.ctx.simulate_only {
+            self.simulate();
+        }
+
+        self.add_nonce_advance();
+
+        let _span = debug_span!("send_transaction").entered();
+        for InstructionWithDescription {
+            instruction,
+            description,
+        } in &self.instructions_with_descriptions
+        {
+            debug!(
+                program_id = %instruction.program_id,
+                description = description.as_deref().unwrap_or_default(),
+                accounts = instruction.accounts.len(),
+                data = %hex::encode(&instruction.data),
+                "Instruction"
+            );
+        }
+
+        if let Some(unsigned_txns_out) = &self.ctx.unsigned_txns_out {
+            self.pretty_print_transaction();
+            let client = self.client.unwrap_or(&self.ctx.client);
+            let blockhash = self.blockhash(client);
+            let mut txn = Transaction::new_unsigned(self.message(&blockhash));
+            // Sign with the signers at hand, e.g. ephemeral keypairs. Missing
+            // signatures, like the payer's, are added by `tx sign`.
+            txn.partial_sign(signers, blockhash);
+            append_transaction(unsigned_txns_out, &txn);
+            println!(
+                "Wrote unsigned transaction to {}",
+                unsigned_txns_out.display()
+            );
+            return Ok(None);
+        }
+
         // If the payer can't sign, it's presumed that the payer is intended
         // to be a Squads multisig, which must be submitted via a separate
         // process.
@@ -183,7 +509,7 @@ impl<'ctx, 'rpc> TxnBuilder<'ctx, 'rpc> {
 
             wait_for_user_confirmation();
 
-            return None;
+            return Ok(None);
         }
 
         // Print the tx as an indication for what's about to happen
@@ -195,34 +521,45 @@ impl<'ctx, 'rpc> TxnBuilder<'ctx, 'rpc> {
 
         let client = self.client.unwrap_or(&self.ctx.client);
 
-        let recent_blockhash = client.get_latest_blockhash().unwrap();
-        let txn = Transaction::new_signed_with_payer(
-            &self.instructions(),
-            Some(&self.ctx.payer_pubkey),
-            signers,
-            recent_blockhash,
-        );
+        let blockhash = self.blockhash(client);
+        let mut txn = Transaction::new_unsigned(self.message(&blockhash));
+        debug_span!("sign", %blockhash).in_scope(|| {
+            txn.sign(signers, blockhash);
+            debug!(signature = %txn.signatures[0], "Signed");
+        });
 
-        let signature = client
-            .send_and_confirm_transaction_with_spinner_and_config(
-                &txn,
-                self.ctx.commitment,
-                RpcSendTransactionConfig {
-                    preflight_commitment: Some(self.ctx.commitment.commitment),
-                    ..RpcSendTransactionConfig::default()
-                },
-            )
+        let sent_at = Instant::now();
+        let signature = debug_span!("send_and_confirm")
+            .in_scope(|| match self.sender {
+                Some(sender) => sender.send_and_confirm(&txn, &self.ctx.confirmation),
+                None => self
+                    .ctx
+                    .sender(client)
+                    .send_and_confirm(&txn, &self.ctx.confirmation),
+            })
             .map_err(|err| {
-                eprintln!("{:#?}", err);
+                if let Some(metrics) = self.ctx.metrics() {
+                    metrics.transaction_failures.inc();
+                }
+                // The blockhash may have expired, fetch a new one for retries.
+                self.ctx.blockhash.invalidate();
+                print_client_error(&err, &self.descriptions());
                 err
-            })
-            .unwrap();
+            })?;
+        debug!(%signature, "Confirmed");
+        if let Some(metrics) = self.ctx.metrics() {
+            metrics.transactions_sent.inc();
+            metrics
+                .confirmation_latency
+                .observe(sent_at.elapsed().as_secs_f64());
+        }
+        self.ctx.print_transaction_link(client, &signature);
 
         // If the commitment level set in the client is less than `finalized`,
         // the only way to reliably read the tx is to use the deprecated
         // `CommitmentConfig::single()` commitment...
         #[allow(deprecated)]
-        client
+        let confirmed_txn = client
             .get_transaction_with_config(
                 &signature,
                 RpcTransactionConfig {
@@ -231,7 +568,175 @@ impl<'ctx, 'rpc> TxnBuilder<'ctx, 'rpc> {
                     ..RpcTransactionConfig::default()
                 },
             )
-            .ok()
+            .ok();
+
+        if let Some(receipts_dir) = &self.ctx.receipts_dir {
+            write_receipt(
+                receipts_dir,
+                client,
+                self.ctx.commitment,
+                &txn,
+                &self.instructions_with_descriptions,
+                confirmed_txn.as_ref().and_then(TxOutcome::new).as_ref(),
+            );
+        }
+
+        Ok(confirmed_txn)
+    }
+
+    /// Builds the transaction's message against the given blockhash, with the
+    /// payer as the fee payer. This doesn't need an RPC, so it's shared by
+    /// transactions that are sent right away and ones that are signed later.
+    pub(crate) fn message(&self, blockhash: &Hash) -> Message {
+        Message::new_with_blockhash(
+            &self.instructions(),
+            Some(&self.ctx.payer_pubkey),
+            blockhash,
+        )
+    }
+
+    /// Prepends an instruction advancing the durable nonce, if one is used.
+    /// It must be the transaction's first instruction.
+    fn add_nonce_advance(&mut self) {
+        if let Some(nonce_account) = self.ctx.nonce_account {
+            self.instructions_with_descriptions.insert(
+                0,
+                InstructionWithDescription {
+                    instruction: system_instruction::advance_nonce_account(
+                        &nonce_account,
+                        &self.ctx.payer_pubkey,
+                    ),
+                    description: Some(format!("Advance durable nonce {}", nonce_account)),
+                },
+            );
+        }
+    }
+
+    /// The blockhash to build the transaction against: the durable nonce if
+    /// one is used, otherwise a recent blockhash.
+    fn blockhash(&self, client: &RpcClient) -> Hash {
+        let Some(nonce_account) = self.ctx.nonce_account else {
+            return self.ctx.blockhash.get(client).unwrap();
+        };
+        let account =
+            nonce_utils::get_account_with_commitment(client, &nonce_account, self.ctx.commitment)
+                .unwrap_or_else(|e| {
+                    panic!("Failed to fetch nonce account {}: {}", nonce_account, e)
+                });
+        let nonce_data = nonce_utils::data_from_account(&account)
+            .unwrap_or_else(|e| panic!("Invalid nonce account {}: {}", nonce_account, e));
+        assert_eq!(
+            nonce_data.authority, self.ctx.payer_pubkey,
+            "The payer must be the authority of nonce account {}",
+            nonce_account
+        );
+        nonce_data.blockhash()
+    }
+
+    /// Simulates the transaction without broadcasting it, printing its logs,
+    /// compute unit usage and the changes to its writable accounts, then
+    /// exits. Signatures aren't verified, so this works for transactions
+    /// that are to be signed by a multisig too.
+    fn simulate(self) -> ! {
+        self.pretty_print_transaction();
+
+        let client = self.client.unwrap_or(&self.ctx.client);
+        let message = Message::new(&self.instructions(), Some(&self.ctx.payer_pubkey));
+        let writable_accounts = message
+            .account_keys
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| message.is_writable(*i))
+            .map(|(_, key)| *key)
+            .collect::<Vec<_>>();
+        let txn = Transaction::new_unsigned(message);
+
+        let pre_accounts = client
+            .get_multiple_accounts_with_commitment(&writable_accounts, self.ctx.commitment)
+            .unwrap()
+            .value;
+        let result = client
+            .simulate_transaction_with_config(
+                &txn,
+                RpcSimulateTransactionConfig {
+                    sig_verify: false,
+                    replace_recent_blockhash: true,
+                    commitment: Some(self.ctx.commitment),
+                    accounts: Some(RpcSimulateTransactionAccountsConfig {
+                        encoding: Some(UiAccountEncoding::Base64),
+                        addresses: writable_accounts
+                            .iter()
+                            .map(|key| key.to_string())
+                            .collect(),
+                    }),
+                    ..RpcSimulateTransactionConfig::default()
+                },
+            )
+            .unwrap()
+            .value;
+
+        println!("\t==== Simulation logs: ====");
+        for line in format_transaction_logs(result.logs.as_deref().unwrap_or_default()) {
+            println!("\t{}", line);
+        }
+        if let Some(units_consumed) = result.units_consumed {
+            println!("\tCompute units consumed: {}", units_consumed);
+        }
+
+        println!("\t==== Account changes: ====");
+        let post_accounts = result.accounts.unwrap_or_default();
+        for ((key, pre), post) in writable_accounts
+            .iter()
+            .zip(pre_accounts)
+            .zip(post_accounts)
+        {
+            let post = post.and_then(|account| account.decode::<Account>());
+            print_account_diff(key, pre.as_ref(), post.as_ref());
+        }
+
+        match result.err {
+            Some(err) => {
+                print_transaction_failure(Some(&err), &[], &self.descriptions());
+                println!("Simulation failed");
+                std::process::exit(exit_code::SIMULATION_FAILURE);
+            }
+            None => {
+                println!("Simulation succeeded, transaction not sent");
+                std::process::exit(0);
+            }
+        }
+    }
+}
+
+fn print_account_diff(key: &Pubkey, pre: Option<&Account>, post: Option<&Account>) {
+    match (pre, post) {
+        (None, None) => {}
+        (None, Some(post)) => println!(
+            "\t{}: created, {} lamports, {} bytes, owner {}",
+            key,
+            post.lamports,
+            post.data.len(),
+            post.owner
+        ),
+        (Some(_), None) => println!("\t{}: closed", key),
+        (Some(pre), Some(post)) => {
+            if pre == post {
+                return;
+            }
+            println!(
+                "\t{}: lamports {} -> {}, {} bytes -> {} bytes{}",
+                key,
+                pre.lamports,
+                post.lamports,
+                pre.data.len(),
+                post.data.len(),
+                if pre.owner != post.owner {
+                    format!(", owner {} -> {}", pre.owner, post.owner)
+                } else {
+                    String::new()
+                }
+            );
+        }
     }
 }
 
@@ -4,49 +4,105 @@ use solana_program::pubkey::Pubkey;
 use solana_sdk::signature::Signer;
 
 use std::collections::HashMap;
-use std::{fs::File, path::Path};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
 
 use crate::{
     artifacts::{read_json, write_json},
+    chain_set::ChainSet,
     cmd_utils::{create_and_write_keypair, create_new_directory, deploy_program},
     multisig_ism::deploy_multisig_ism_message_id,
-    Context, CoreCmd, CoreDeploy, CoreSubCmd,
+    Context, CoreCmd, CoreDeploy, CoreOverview, CoreSubCmd,
 };
 use hyperlane_core::H256;
 use hyperlane_sealevel_igp::accounts::{SOL_DECIMALS, TOKEN_EXCHANGE_RATE_SCALE};
+use hyperlane_sealevel_mailbox::accounts::{InboxAccount, OutboxAccount};
 
 pub(crate) fn process_core_cmd(mut ctx: Context, cmd: CoreCmd) {
     match cmd.cmd {
         CoreSubCmd::Deploy(core) => {
-            let environments_dir = create_new_directory(&core.environments_dir, &core.environment);
-            let chain_dir = create_new_directory(&environments_dir, &core.chain);
-            let core_dir = create_new_directory(&chain_dir, "core");
-            let key_dir = create_new_directory(&core_dir, "keys");
-
-            let ism_program_id = deploy_multisig_ism_message_id(
-                &mut ctx,
-                &core.built_so_dir,
-                core.use_existing_keys,
-                &key_dir,
-            );
-
-            let mailbox_program_id = deploy_mailbox(&mut ctx, &core, &key_dir, ism_program_id);
-
-            let validator_announce_program_id =
-                deploy_validator_announce(&mut ctx, &core, &key_dir, mailbox_program_id);
-
-            let (igp_program_id, overhead_igp_account, igp_account) =
-                deploy_igp(&mut ctx, &core, &key_dir);
-
-            let program_ids = CoreProgramIds {
-                mailbox: mailbox_program_id,
-                validator_announce: validator_announce_program_id,
-                multisig_ism_message_id: ism_program_id,
-                igp_program_id,
-                overhead_igp_account,
-                igp_account,
-            };
-            write_program_ids(&core_dir, program_ids);
+            deploy_core(&mut ctx, &core);
+        }
+        CoreSubCmd::Overview(overview) => process_core_overview(&ctx, overview),
+    }
+}
+
+/// Deploys and initializes the core programs, writing their program IDs to
+/// the chain's core artifacts directory.
+pub(crate) fn deploy_core(ctx: &mut Context, core: &CoreDeploy) -> CoreProgramIds {
+    let environments_dir = create_new_directory(&core.environments_dir, &core.environment);
+    let chain_dir = create_new_directory(&environments_dir, &core.chain);
+    let core_dir = create_new_directory(&chain_dir, "core");
+    let key_dir = create_new_directory(&core_dir, "keys");
+
+    let ism_program_id =
+        deploy_multisig_ism_message_id(ctx, &core.built_so_dir, core.use_existing_keys, &key_dir);
+
+    let mailbox_program_id = deploy_mailbox(ctx, core, &key_dir, ism_program_id);
+
+    let validator_announce_program_id =
+        deploy_validator_announce(ctx, core, &key_dir, mailbox_program_id);
+
+    let (igp_program_id, overhead_igp_account, igp_account) = deploy_igp(ctx, core, &key_dir);
+
+    let program_ids = CoreProgramIds {
+        mailbox: mailbox_program_id,
+        validator_announce: validator_announce_program_id,
+        multisig_ism_message_id: ism_program_id,
+        igp_program_id,
+        overhead_igp_account,
+        igp_account,
+    };
+    write_program_ids(&core_dir, &program_ids);
+    program_ids
+}
+
+/// Prints the state of the core deployments of several chains, which are
+/// queried concurrently.
+fn process_core_overview(ctx: &Context, overview: CoreOverview) {
+    let chain_set = ChainSet::from_chain_config_file(&overview.chain_config_file, &overview.chains);
+    let protocol = &ctx.protocol;
+    let results = chain_set.query(|chain_config, client| {
+        let program_ids = read_core_program_ids(
+            &overview.environments_dir,
+            &overview.environment,
+            chain_config.name(),
+        );
+        let (inbox_account, _inbox_bump) = protocol.inbox_pda(&program_ids.mailbox);
+        let (outbox_account, _outbox_bump) = protocol.outbox_pda(&program_ids.mailbox);
+        client
+            .get_multiple_accounts(&[inbox_account, outbox_account])
+            .map(|accounts| {
+                let inbox = accounts[0].as_ref().map(|account| {
+                    InboxAccount::fetch(&mut &account.data[..])
+                        .unwrap()
+                        .into_inner()
+                });
+                let outbox = accounts[1].as_ref().map(|account| {
+                    OutboxAccount::fetch(&mut &account.data[..])
+                        .unwrap()
+                        .into_inner()
+                });
+                (program_ids.mailbox, inbox, outbox)
+            })
+    });
+
+    for (chain_name, result) in results {
+        match result {
+            Ok((mailbox, Some(inbox), Some(outbox))) => println!(
+                "{}: mailbox={} dispatched={} processed={} default_ism={}",
+                chain_name,
+                mailbox,
+                outbox.tree.count(),
+                inbox.processed_count,
+                inbox.default_ism
+            ),
+            Ok((mailbox, _, _)) => {
+                println!("{}: mailbox={} not initialized", chain_name, mailbox)
+            }
+            Err(err) => println!("{}: error: {}", chain_name, err),
         }
     }
 }
@@ -310,7 +366,7 @@ pub struct CoreProgramIds {
     pub igp_account: Pubkey,
 }
 
-fn write_program_ids(core_dir: &Path, program_ids: CoreProgramIds) {
+fn write_program_ids(core_dir: &Path, program_ids: &CoreProgramIds) {
     write_json(&core_dir.join("program-ids.json"), program_ids);
 }
 
@@ -319,10 +375,18 @@ pub(crate) fn read_core_program_ids(
     environment: &str,
     chain: &str,
 ) -> CoreProgramIds {
-    let path = environments_dir
+    read_json(&core_program_ids_path(environments_dir, environment, chain))
+}
+
+/// The file the program IDs of a chain's core deployment are written to.
+pub(crate) fn core_program_ids_path(
+    environments_dir: &Path,
+    environment: &str,
+    chain: &str,
+) -> PathBuf {
+    environments_dir
         .join(environment)
         .join(chain)
         .join("core")
-        .join("program-ids.json");
-    read_json(&path)
+        .join("program-ids.json")
 }
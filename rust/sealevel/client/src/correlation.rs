@@ -0,0 +1,62 @@
+//! A simple request / reply convention for correlating messages.
+//!
+//! A tagged message body is prefixed with a magic value followed by a 32 byte
+//! request ID. A reply to a request is tagged with the same request ID, which
+//! allows the requester to match replies to its outgoing requests.
+
+use hyperlane_core::H256;
+
+/// The magic prefix identifying a tagged message body.
+pub(crate) const REQUEST_ID_MAGIC: &[u8; 4] = b"HREQ";
+
+const TAG_LEN: usize = REQUEST_ID_MAGIC.len() + 32;
+
+/// Prefixes the message body with the request ID.
+pub(crate) fn tag_message_body(request_id: H256, body: &[u8]) -> Vec<u8> {
+    let mut tagged = Vec::with_capacity(TAG_LEN + body.len());
+    tagged.extend_from_slice(REQUEST_ID_MAGIC);
+    tagged.extend_from_slice(request_id.as_bytes());
+    tagged.extend_from_slice(body);
+    tagged
+}
+
+/// Splits a tagged message body into its request ID and the untagged body.
+/// Returns None if the body isn't tagged.
+pub(crate) fn untag_message_body(body: &[u8]) -> Option<(H256, &[u8])> {
+    if body.len() < TAG_LEN || !body.starts_with(REQUEST_ID_MAGIC) {
+        return None;
+    }
+    let request_id = H256::from_slice(&body[REQUEST_ID_MAGIC.len()..TAG_LEN]);
+    Some((request_id, &body[TAG_LEN..]))
+}
+
+/// Returns true if the message body is tagged with the request ID.
+pub(crate) fn is_reply_to(request_id: H256, body: &[u8]) -> bool {
+    untag_message_body(body)
+        .map(|(tagged_request_id, _)| tagged_request_id == request_id)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tag_untag_message_body() {
+        let request_id = H256::random();
+        let tagged = tag_message_body(request_id, b"hello");
+
+        assert_eq!(
+            untag_message_body(&tagged),
+            Some((request_id, &b"hello"[..]))
+        );
+        assert!(is_reply_to(request_id, &tagged));
+        assert!(!is_reply_to(H256::random(), &tagged));
+    }
+
+    #[test]
+    fn test_untag_untagged_message_body() {
+        assert_eq!(untag_message_body(b"hello"), None);
+        assert_eq!(untag_message_body(&[0u8; TAG_LEN]), None);
+    }
+}
@@ -0,0 +1,198 @@
+//! Decoding of the Hyperlane programs' instructions, the reverse of building
+//! them with the programs' `instruction` modules, to show what a transaction
+//! does.
+
+use account_utils::DiscriminatorDecode;
+use borsh::BorshDeserialize as _;
+use solana_program::{instruction::Instruction, program_error::ProgramError, pubkey::Pubkey};
+
+use hyperlane_sealevel_igp::instruction::Instruction as IgpInstruction;
+use hyperlane_sealevel_mailbox::instruction::Instruction as MailboxInstruction;
+use hyperlane_sealevel_multisig_ism_message_id::instruction::Instruction as MultisigIsmInstruction;
+#[cfg(feature = "tokens")]
+use hyperlane_sealevel_token_lib::instruction::Instruction as TokenInstruction;
+use hyperlane_sealevel_validator_announce::instruction::Instruction as ValidatorAnnounceInstruction;
+
+#[cfg(feature = "tokens")]
+use crate::HYPERLANE_TOKEN_PROG_ID;
+use crate::{MAILBOX_PROG_ID, MULTISIG_ISM_MESSAGE_ID_PROG_ID, VALIDATOR_ANNOUNCE_PROG_ID};
+
+pub(crate) fn decode_mailbox_instruction(
+    instruction: &Instruction,
+) -> Result<MailboxInstruction, ProgramError> {
+    MailboxInstruction::from_instruction_data(&instruction.data)
+}
+
+pub(crate) fn decode_igp_instruction(
+    instruction: &Instruction,
+) -> Result<IgpInstruction, ProgramError> {
+    IgpInstruction::try_from_slice(&instruction.data)
+        .map_err(|_| ProgramError::InvalidInstructionData)
+}
+
+/// Decodes an instruction particular to the multisig ISM. The instructions of
+/// the ISM interface it also implements, like `Verify`, don't decode.
+pub(crate) fn decode_multisig_ism_message_id_instruction(
+    instruction: &Instruction,
+) -> Result<MultisigIsmInstruction, ProgramError> {
+    decode_discriminated(&instruction.data)
+}
+
+pub(crate) fn decode_validator_announce_instruction(
+    instruction: &Instruction,
+) -> Result<ValidatorAnnounceInstruction, ProgramError> {
+    ValidatorAnnounceInstruction::from_instruction_data(&instruction.data)
+}
+
+/// Decodes an instruction of a warp route, whichever its kind of token. The
+/// `Handle` instructions the mailbox invokes it with don't decode.
+#[cfg(feature = "tokens")]
+pub(crate) fn decode_token_instruction(
+    instruction: &Instruction,
+) -> Result<TokenInstruction, ProgramError> {
+    decode_discriminated(&instruction.data)
+}
+
+/// `DiscriminatorDecode::decode`, which panics on data shorter than the
+/// discriminator.
+fn decode_discriminated<T: DiscriminatorDecode>(data: &[u8]) -> Result<T, ProgramError> {
+    if data.len() < T::DISCRIMINATOR_LENGTH {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    T::decode(data)
+}
+
+#[derive(Debug)]
+pub(crate) enum DecodedInstruction {
+    Mailbox(MailboxInstruction),
+    Igp(IgpInstruction),
+    MultisigIsmMessageId(MultisigIsmInstruction),
+    ValidatorAnnounce(ValidatorAnnounceInstruction),
+    #[cfg(feature = "tokens")]
+    Token(TokenInstruction),
+}
+
+/// The programs whose instructions are decoded, by program ID.
+pub(crate) struct HyperlanePrograms {
+    pub mailbox: Pubkey,
+    pub igp: Option<Pubkey>,
+    pub multisig_ism_message_id: Pubkey,
+    pub validator_announce: Pubkey,
+    #[cfg(feature = "tokens")]
+    pub warp_routes: Vec<Pubkey>,
+}
+
+impl Default for HyperlanePrograms {
+    /// The programs at the IDs commands default to. The IGP has no default ID.
+    fn default() -> Self {
+        Self {
+            mailbox: MAILBOX_PROG_ID,
+            igp: None,
+            multisig_ism_message_id: MULTISIG_ISM_MESSAGE_ID_PROG_ID,
+            validator_announce: VALIDATOR_ANNOUNCE_PROG_ID,
+            #[cfg(feature = "tokens")]
+            warp_routes: vec![HYPERLANE_TOKEN_PROG_ID],
+        }
+    }
+}
+
+impl HyperlanePrograms {
+    /// Decodes the instruction with the decoder of its program, or returns
+    /// `None` if it's not an instruction of any of the programs.
+    pub(crate) fn decode(
+        &self,
+        instruction: &Instruction,
+    ) -> Option<Result<DecodedInstruction, ProgramError>> {
+        let program_id = instruction.program_id;
+        let decoded = if program_id == self.mailbox {
+            decode_mailbox_instruction(instruction).map(DecodedInstruction::Mailbox)
+        } else if Some(program_id) == self.igp {
+            decode_igp_instruction(instruction).map(DecodedInstruction::Igp)
+        } else if program_id == self.multisig_ism_message_id {
+            decode_multisig_ism_message_id_instruction(instruction)
+                .map(DecodedInstruction::MultisigIsmMessageId)
+        } else if program_id == self.validator_announce {
+            decode_validator_announce_instruction(instruction)
+                .map(DecodedInstruction::ValidatorAnnounce)
+        } else {
+            #[cfg(feature = "tokens")]
+            if self.warp_routes.contains(&program_id) {
+                return Some(decode_token_instruction(instruction).map(DecodedInstruction::Token));
+            }
+            return None;
+        };
+        Some(decoded)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use hyperlane_sealevel_mailbox::instruction::{init_instruction, Init};
+    use hyperlane_sealevel_multisig_ism_message_id::instruction::{
+        set_validators_and_threshold_instruction, Domained, ValidatorsAndThreshold,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_decode_mailbox_instruction() {
+        let default_ism = Pubkey::new_unique();
+        let instruction =
+            init_instruction(MAILBOX_PROG_ID, 1234, default_ism, Pubkey::new_unique()).unwrap();
+
+        assert_eq!(
+            decode_mailbox_instruction(&instruction),
+            Ok(MailboxInstruction::Init(Init {
+                local_domain: 1234,
+                default_ism,
+            }))
+        );
+        assert!(matches!(
+            HyperlanePrograms::default().decode(&instruction),
+            Some(Ok(DecodedInstruction::Mailbox(MailboxInstruction::Init(_))))
+        ));
+    }
+
+    #[test]
+    fn test_decode_multisig_ism_message_id_instruction() {
+        let validators_and_threshold = ValidatorsAndThreshold {
+            validators: vec![Default::default()],
+            threshold: 1,
+        };
+        let instruction = set_validators_and_threshold_instruction(
+            MULTISIG_ISM_MESSAGE_ID_PROG_ID,
+            Pubkey::new_unique(),
+            1234,
+            validators_and_threshold.clone(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            decode_multisig_ism_message_id_instruction(&instruction),
+            Ok(MultisigIsmInstruction::SetValidatorsAndThreshold(
+                Domained {
+                    domain: 1234,
+                    data: validators_and_threshold,
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_decode_short_discriminated_data() {
+        let instruction =
+            Instruction::new_with_bytes(MULTISIG_ISM_MESSAGE_ID_PROG_ID, &[1, 1], vec![]);
+
+        assert_eq!(
+            decode_multisig_ism_message_id_instruction(&instruction),
+            Err(ProgramError::InvalidInstructionData)
+        );
+        assert!(HyperlanePrograms::default()
+            .decode(&Instruction::new_with_bytes(
+                Pubkey::new_unique(),
+                &[],
+                vec![]
+            ))
+            .is_none());
+    }
+}
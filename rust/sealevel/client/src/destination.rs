@@ -0,0 +1,214 @@
+//! Queries of the destination chain of a message sent from the command line:
+//! whether the recipient is deployed before dispatching, as a message to an
+//! address without a program or contract can never be delivered, and whether
+//! the message was delivered after.
+
+use std::{
+    io::{self, Write as _},
+    thread,
+    time::{Duration, Instant},
+};
+
+use clap::Args;
+#[cfg(feature = "evm")]
+use clap::{error::ErrorKind, CommandFactory};
+use hyperlane_core::H256;
+use solana_client::rpc_client::RpcClient;
+use solana_program::pubkey::Pubkey;
+
+#[cfg(feature = "evm")]
+use crate::Cli;
+use crate::{accounts::MailboxReader, exit_code, rpc::rpc_client, Context, MAILBOX_PROG_ID};
+
+/// How often the destination is polled for the delivery of a message.
+const DELIVERY_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The destination chain of a message. It's a Solana chain, the configured
+/// one by default, unless `--destination-evm-rpc-url` is given.
+#[derive(Args)]
+pub(crate) struct DestinationArgs {
+    /// RPC URL of the Solana destination chain. Defaults to the configured
+    /// URL, but the recipient is only checked there if the configured chain
+    /// is the destination.
+    #[arg(long)]
+    destination_url: Option<String>,
+    /// The mailbox on the Solana destination chain.
+    #[arg(long, default_value_t = MAILBOX_PROG_ID)]
+    destination_mailbox: Pubkey,
+    /// RPC URL of an EVM destination chain.
+    #[cfg(feature = "evm")]
+    #[arg(long, conflicts_with = "destination_url")]
+    destination_evm_rpc_url: Option<String>,
+    /// The mailbox contract address on the EVM destination chain, required
+    /// to wait for the delivery of a message.
+    #[cfg(feature = "evm")]
+    #[arg(long, requires = "destination_evm_rpc_url")]
+    destination_evm_mailbox: Option<ethers::types::Address>,
+}
+
+impl DestinationArgs {
+    fn client(&self, ctx: &Context) -> Option<RpcClient> {
+        self.destination_url
+            .as_ref()
+            .map(|url| rpc_client(url.clone(), ctx.commitment))
+    }
+
+    /// Exits with a configuration error unless the recipient is an
+    /// executable program on a Solana destination, or a contract on an EVM
+    /// destination. Without a destination RPC, the recipient is only checked
+    /// on the configured chain if it's the destination, i.e. if the
+    /// destination mailbox on it is of the destination domain.
+    pub(crate) fn check_recipient(&self, ctx: &Context, destination: u32, recipient: H256) {
+        #[cfg(feature = "evm")]
+        if let Some(rpc_url) = &self.destination_evm_rpc_url {
+            let address = ethers::types::Address::from_slice(&recipient.0[12..]);
+            if !crate::evm::evm_contract_deployed(rpc_url, address) {
+                recipient_not_deployed(format!("Recipient {:?} has no contract code", address));
+            }
+            return;
+        }
+
+        let recipient = Pubkey::new_from_array(recipient.0);
+        let destination_client = self.client(ctx);
+        if destination_client.is_none() && !self.is_local_domain(ctx, destination) {
+            eprintln!(
+                "Warning: not checking recipient {}, pass --destination-url to check it on \
+                 domain {}",
+                recipient, destination
+            );
+            return;
+        }
+        let account = destination_client
+            .as_ref()
+            .unwrap_or(&ctx.client)
+            .get_account_with_commitment(&recipient, ctx.commitment)
+            .unwrap_or_else(|e| {
+                exit_code::config_error(format!(
+                    "Failed to get recipient {} from the destination: {}",
+                    recipient, e
+                ))
+            })
+            .value;
+        match account {
+            Some(account) if account.executable => {}
+            Some(_) => recipient_not_deployed(format!("Recipient {} isn't a program", recipient)),
+            None => recipient_not_deployed(format!("Recipient {} doesn't exist", recipient)),
+        }
+    }
+
+    /// Whether the destination mailbox on the configured chain is of the
+    /// domain.
+    fn is_local_domain(&self, ctx: &Context, domain: u32) -> bool {
+        MailboxReader::new(ctx, self.destination_mailbox)
+            .outbox()
+            .unwrap_or_else(|e| {
+                exit_code::config_error(format!(
+                    "Failed to read mailbox {}: {}",
+                    self.destination_mailbox, e
+                ))
+            })
+            .map_or(false, |outbox| outbox.local_domain == domain)
+    }
+
+    /// Polls the destination until the message is delivered, printing the
+    /// wait on stderr unless progress is turned off, and exits with
+    /// `TIMEOUT` if it isn't delivered within the timeout.
+    pub(crate) fn wait_for_delivery(&self, ctx: &Context, message_id: H256, timeout: Duration) {
+        let destination_client = self.client(ctx);
+        let destination_client = destination_client.as_ref().unwrap_or(&ctx.client);
+        let started_at = Instant::now();
+        let mut waited = false;
+        loop {
+            match self.delivered(ctx, destination_client, message_id) {
+                Ok(true) => break,
+                Ok(false) => {}
+                Err(e) => {
+                    if waited {
+                        eprintln!();
+                        waited = false;
+                    }
+                    eprintln!(
+                        "Warning: failed to check the delivery of the message: {}",
+                        e
+                    );
+                }
+            }
+            if started_at.elapsed() >= timeout {
+                if waited {
+                    eprintln!();
+                }
+                eprintln!(
+                    "Error: message {:?} wasn't delivered within {}s",
+                    message_id,
+                    timeout.as_secs()
+                );
+                std::process::exit(exit_code::TIMEOUT);
+            }
+            if ctx.shows_progress() {
+                eprint!(
+                    "\rWaiting for message to be delivered... {}s",
+                    started_at.elapsed().as_secs()
+                );
+                io::stderr().flush().ok();
+                waited = true;
+            }
+            thread::sleep(DELIVERY_POLL_INTERVAL);
+        }
+        if waited {
+            eprintln!();
+        }
+        println!(
+            "Delivered message {:?} after {}s",
+            message_id,
+            started_at.elapsed().as_secs()
+        );
+    }
+
+    /// Exits with a usage error if the delivery can't be waited for, which
+    /// on an EVM destination needs its mailbox. Called before dispatching,
+    /// as clap can't require an argument only if two others are given.
+    pub(crate) fn check_wait_for_delivery(&self) {
+        #[cfg(feature = "evm")]
+        if self.destination_evm_rpc_url.is_some() && self.destination_evm_mailbox.is_none() {
+            Cli::command()
+                .error(
+                    ErrorKind::MissingRequiredArgument,
+                    "--destination-evm-mailbox is required to wait for the delivery of a message \
+                     on an EVM destination",
+                )
+                .exit();
+        }
+    }
+
+    /// Whether the message was delivered. RPC errors are returned rather
+    /// than exiting, so that polling goes on until the timeout.
+    fn delivered(
+        &self,
+        ctx: &Context,
+        destination_client: &RpcClient,
+        message_id: H256,
+    ) -> Result<bool, String> {
+        #[cfg(feature = "evm")]
+        if let (Some(rpc_url), Some(mailbox)) =
+            (&self.destination_evm_rpc_url, self.destination_evm_mailbox)
+        {
+            return crate::evm::evm_message_delivered(rpc_url, mailbox, message_id);
+        }
+
+        let (processed_message_key, _processed_message_bump) = ctx
+            .protocol
+            .processed_message_pda(&self.destination_mailbox, message_id);
+        destination_client
+            .get_account_with_commitment(&processed_message_key, ctx.commitment)
+            .map(|response| response.value.is_some())
+            .map_err(|e| e.to_string())
+    }
+}
+
+fn recipient_not_deployed(reason: String) -> ! {
+    exit_code::config_error(format!(
+        "{} on the destination, so the message could never be delivered. Pass \
+         --skip-recipient-check to dispatch it anyway.",
+        reason
+    ))
+}
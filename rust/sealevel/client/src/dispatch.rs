@@ -0,0 +1,442 @@
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "geyser")]
+use std::sync::mpsc::RecvTimeoutError;
+
+use hyperlane_core::{Decode as _, HyperlaneMessage, H256};
+use solana_account_decoder::UiAccountEncoding;
+use solana_cli_config::Config;
+use solana_client::{
+    pubsub_client::PubsubClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_program::pubkey::Pubkey;
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    message::Message,
+    packet::PACKET_DATA_SIZE,
+    signature::{Keypair, Signer as _},
+    transaction::Transaction,
+};
+
+use hyperlane_sealevel_client::instructions::outbox_dispatch_instruction;
+use hyperlane_sealevel_mailbox::accounts::DispatchedMessageAccount;
+
+#[cfg(feature = "geyser")]
+use crate::geyser::{AccountsFilter, GeyserEndpoint};
+use crate::{
+    accounts::{required_rent_for_dispatch, MailboxReader},
+    correlation::{is_reply_to, tag_message_body},
+    domain::DomainArg,
+    exit_code,
+    protocol::MESSAGE_HEADER_BYTES,
+    shutdown::{self, CancellationToken},
+    strict::skip_or_fail,
+    Broadcast, Context,
+};
+
+/// The size of the serialized transaction, including its signatures.
+pub(crate) fn transaction_size(ctx: &Context, instructions: &[Instruction]) -> usize {
+    let txn = Transaction::new_with_payer(instructions, Some(&ctx.payer_pubkey));
+    bincode::serialized_size(&txn).unwrap() as usize
+}
+
+/// The length of the largest message body the mailbox accepts that fits into
+/// a dispatch transaction, after the context's initial instructions.
+pub(crate) fn max_dispatch_body_len(ctx: &Context, program_id: Pubkey) -> usize {
+    let mut instructions = ctx
+        .initial_instructions
+        .borrow()
+        .iter()
+        .map(|i| i.instruction.clone())
+        .collect::<Vec<_>>();
+    instructions.push(outbox_dispatch_instruction(
+        &ctx.protocol,
+        program_id,
+        ctx.payer_pubkey,
+        Pubkey::new_unique(),
+        0,
+        H256::zero(),
+        vec![],
+    ));
+    // The compact length prefix of the instruction data grows by a byte once
+    // the body is added.
+    let max_len = PACKET_DATA_SIZE.saturating_sub(transaction_size(ctx, &instructions) + 1);
+    max_len.min(ctx.protocol.max_message_body_bytes)
+}
+
+/// Panics if the message body is too large for the mailbox to accept or to
+/// fit into a dispatch transaction.
+pub(crate) fn check_dispatch_body(ctx: &Context, program_id: Pubkey, message_body: &[u8]) {
+    ctx.protocol.check_message_body(message_body);
+    let max_len = max_dispatch_body_len(ctx, program_id);
+    assert!(
+        message_body.len() <= max_len,
+        "Message body of {} bytes doesn't fit into a dispatch transaction, which fits at most {} \
+         bytes. `mailbox send --chunk` can split it across several messages.",
+        message_body.len(),
+        max_len
+    );
+}
+
+/// Exits with a configuration error unless the mailbox's outbox is of the
+/// origin domain, so that a message isn't dispatched from a mailbox of
+/// another chain than meant, with an origin the destination doesn't expect.
+pub(crate) fn check_origin_domain(ctx: &Context, program_id: Pubkey, origin: u32) {
+    let outbox = MailboxReader::new(ctx, program_id)
+        .outbox()
+        .unwrap_or_else(|e| panic!("{}", e))
+        .unwrap_or_else(|| {
+            exit_code::config_error(format!("Mailbox {} isn't initialized", program_id))
+        });
+    if outbox.local_domain != origin {
+        exit_code::config_error(format!(
+            "Mailbox {} is of domain {}, not of the origin domain {}. Make sure you are \
+             connected to the right RPC and mailbox.",
+            program_id, outbox.local_domain, origin
+        ));
+    }
+}
+
+/// Dispatches a message from the payer, returning the unique message account
+/// and whether the transaction was submitted.
+pub(crate) fn dispatch_message(
+    ctx: &Context,
+    program_id: Pubkey,
+    destination_domain: u32,
+    recipient: H256,
+    message_body: Vec<u8>,
+) -> (Pubkey, bool) {
+    let unique_message_account_keypair = Keypair::new();
+    let instruction = outbox_dispatch_instruction(
+        &ctx.protocol,
+        program_id,
+        ctx.payer_pubkey,
+        unique_message_account_keypair.pubkey(),
+        destination_domain,
+        recipient,
+        message_body,
+    );
+    let tx_result = ctx
+        .new_txn()
+        .add_with_description(
+            instruction,
+            format!(
+                "Dispatch message to domain {} recipient {:?}",
+                destination_domain, recipient
+            ),
+        )
+        .send(&[&*ctx.payer_signer(), &unique_message_account_keypair]);
+    (unique_message_account_keypair.pubkey(), tx_result.is_some())
+}
+
+/// Gets the dispatched message stored in the PDA relating to the unique message account.
+pub(crate) fn get_dispatched_message(
+    ctx: &Context,
+    program_id: &Pubkey,
+    unique_message_account: &Pubkey,
+) -> Option<HyperlaneMessage> {
+    MailboxReader::new(ctx, *program_id)
+        .dispatched_message(unique_message_account)
+        .unwrap_or_else(|e| panic!("{}", e))
+        .map(|dispatched_message| {
+            HyperlaneMessage::read_from(&mut &dispatched_message.encoded_message[..]).unwrap()
+        })
+}
+
+/// Filters the mailbox's accounts down to the dispatched messages whose
+/// bodies are tagged with the request ID.
+fn tagged_dispatched_messages_config(
+    request_id: H256,
+    commitment: CommitmentConfig,
+) -> RpcProgramAccountsConfig {
+    let (body_offset, tag) = tagged_body_memcmp(request_id);
+    RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+            body_offset,
+            tag,
+        ))]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(commitment),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    }
+}
+
+/// The offset of the message body in dispatched message accounts, and the tag
+/// bodies tagged with the request ID start with.
+fn tagged_body_memcmp(request_id: H256) -> (usize, Vec<u8>) {
+    // The initialized flag, the dispatched message fields preceding the encoded
+    // message, and the message header.
+    let body_offset = 1 + 8 + 4 + 8 + 32 + MESSAGE_HEADER_BYTES;
+    (body_offset, tag_message_body(request_id, &[]))
+}
+
+/// Decodes the message of a dispatched message account, if it's tagged with
+/// the request ID.
+fn tagged_dispatched_message(
+    strict: bool,
+    pubkey: &dyn std::fmt::Display,
+    data: &[u8],
+    request_id: H256,
+) -> Option<HyperlaneMessage> {
+    let dispatched_message = skip_or_fail(
+        strict,
+        DispatchedMessageAccount::fetch(&mut &data[..]),
+        || format!("dispatched message account {}", pubkey),
+    )?
+    .into_inner();
+    skip_or_fail(
+        strict,
+        HyperlaneMessage::read_from(&mut &dispatched_message.encoded_message[..]),
+        || format!("message in dispatched message account {}", pubkey),
+    )
+    .filter(|message| is_reply_to(request_id, &message.body))
+}
+
+/// Finds all dispatched messages whose bodies are tagged with the request ID.
+pub(crate) fn find_tagged_dispatched_messages(
+    ctx: &Context,
+    program_id: &Pubkey,
+    request_id: H256,
+) -> Vec<HyperlaneMessage> {
+    let config = tagged_dispatched_messages_config(request_id, ctx.commitment);
+    let strict = ctx.is_strict(false);
+    ctx.client
+        .get_program_accounts_with_config(program_id, config)
+        .unwrap()
+        .into_iter()
+        .filter_map(|(pubkey, account)| {
+            tagged_dispatched_message(strict, &pubkey, &account.data, request_id)
+        })
+        .collect()
+}
+
+/// Calls `on_found` with the messages tagged with the request ID that are
+/// already dispatched, then `on_reply` with each one as it's dispatched, until
+/// SIGINT or SIGTERM. Dispatched message accounts are streamed from the Geyser
+/// endpoint if one is given, or else from the websocket, at the URL derived
+/// from the RPC URL by default.
+pub(crate) fn watch_tagged_dispatched_messages(
+    ctx: &Context,
+    program_id: &Pubkey,
+    request_id: H256,
+    ws_url: Option<String>,
+    #[cfg(feature = "geyser")] geyser: Option<GeyserEndpoint>,
+    on_found: impl FnOnce(&[HyperlaneMessage]),
+    mut on_reply: impl FnMut(HyperlaneMessage),
+) {
+    let strict = ctx.is_strict(true);
+    let shutdown = CancellationToken::on_signals();
+
+    #[cfg(feature = "geyser")]
+    if let Some(endpoint) = geyser {
+        let updates = endpoint
+            .subscribe_accounts(
+                AccountsFilter {
+                    owners: vec![*program_id],
+                    memcmps: vec![tagged_body_memcmp(request_id)],
+                    ..AccountsFilter::default()
+                },
+                ctx.commitment,
+            )
+            .unwrap_or_else(|e| panic!("Failed to subscribe to {}: {}", program_id, e));
+        let mut found = find_tagged_message_ids(ctx, program_id, request_id, on_found);
+        while !shutdown.is_cancelled() {
+            let update = match updates.recv_timeout(shutdown::CHECK_INTERVAL) {
+                Ok(update) => update,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            if let Some(message) =
+                tagged_dispatched_message(strict, &update.pubkey, &update.account.data, request_id)
+            {
+                if found.insert(message.id()) {
+                    on_reply(message);
+                }
+            }
+        }
+        return;
+    }
+
+    let ws_url = ws_url.unwrap_or_else(|| Config::compute_websocket_url(&ctx.client.url()));
+    let (_subscription, receiver) = PubsubClient::program_subscribe(
+        &ws_url,
+        program_id,
+        Some(tagged_dispatched_messages_config(
+            request_id,
+            ctx.commitment,
+        )),
+    )
+    .unwrap_or_else(|e| panic!("Failed to subscribe to {}: {}", program_id, e));
+    let mut found = find_tagged_message_ids(ctx, program_id, request_id, on_found);
+    while !shutdown.is_cancelled() {
+        let response = match receiver.recv_timeout(shutdown::CHECK_INTERVAL) {
+            Ok(response) => response,
+            Err(e) if e.is_timeout() => continue,
+            Err(_) => break,
+        };
+        let pubkey = response.value.pubkey;
+        let Some(account) = skip_or_fail(
+            strict,
+            response
+                .value
+                .account
+                .decode::<Account>()
+                .ok_or("unsupported encoding"),
+            || format!("account {}", pubkey),
+        ) else {
+            continue;
+        };
+        if let Some(message) = tagged_dispatched_message(strict, &pubkey, &account.data, request_id)
+        {
+            if found.insert(message.id()) {
+                on_reply(message);
+            }
+        }
+    }
+}
+
+/// Finds the messages tagged with the request ID that are already dispatched,
+/// passing them to `on_found`, and returns their IDs so that they aren't
+/// replied to again when streamed. Searching only once subscribed means a
+/// message dispatched in between is streamed, and maybe found too, rather
+/// than missed by both.
+fn find_tagged_message_ids(
+    ctx: &Context,
+    program_id: &Pubkey,
+    request_id: H256,
+    on_found: impl FnOnce(&[HyperlaneMessage]),
+) -> HashSet<H256> {
+    let messages = find_tagged_dispatched_messages(ctx, program_id, request_id);
+    on_found(&messages);
+    messages.iter().map(HyperlaneMessage::id).collect()
+}
+
+/// The lamports required to dispatch a message.
+pub(crate) struct DispatchQuote {
+    /// The rent for the dispatched message PDA.
+    pub rent: u64,
+    /// The transaction fee.
+    pub fee: u64,
+}
+
+impl DispatchQuote {
+    pub(crate) fn total(&self) -> u64 {
+        self.rent + self.fee
+    }
+}
+
+/// Quotes the lamports required to dispatch a message with a body of the given length.
+pub(crate) fn quote_dispatch(
+    ctx: &Context,
+    program_id: Pubkey,
+    message_body_len: usize,
+) -> DispatchQuote {
+    let rent = required_rent_for_dispatch(ctx, message_body_len);
+
+    let instruction = outbox_dispatch_instruction(
+        &ctx.protocol,
+        program_id,
+        ctx.payer_pubkey,
+        Pubkey::new_unique(),
+        0,
+        H256::zero(),
+        vec![0; message_body_len],
+    );
+    let recent_blockhash = ctx.client.get_latest_blockhash().unwrap();
+    let message =
+        Message::new_with_blockhash(&[instruction], Some(&ctx.payer_pubkey), &recent_blockhash);
+    let fee = ctx.client.get_fee_for_message(&message).unwrap();
+
+    DispatchQuote { rent, fee }
+}
+
+pub(crate) fn process_broadcast(ctx: &Context, broadcast: Broadcast) {
+    let recipient_overrides = broadcast
+        .recipient_overrides
+        .iter()
+        .map(|recipient_override| {
+            let (domain, recipient) = recipient_override.split_once('=').unwrap_or_else(|| {
+                panic!(
+                    "Invalid recipient override {}, expected <domain>=<recipient>",
+                    recipient_override
+                )
+            });
+            (
+                ctx.domains
+                    .resolve(&domain.parse::<DomainArg>().expect("Invalid domain")),
+                ctx.parse_address(recipient).unwrap(),
+            )
+        })
+        .collect::<HashMap<u32, H256>>();
+    let default_recipient = broadcast
+        .recipient
+        .as_ref()
+        .map(|recipient| ctx.resolve_address(recipient));
+
+    let dispatches = broadcast
+        .destinations
+        .iter()
+        .map(|destination| {
+            let destination = ctx.domains.resolve(destination);
+            let recipient = recipient_overrides
+                .get(&destination)
+                .copied()
+                .or(default_recipient)
+                .unwrap_or_else(|| panic!("No recipient for destination {}", destination));
+            (destination, recipient)
+        })
+        .collect::<Vec<_>>();
+
+    let message_body = broadcast.body.read();
+    check_dispatch_body(ctx, broadcast.program_id, &message_body);
+    let quote_per_message = quote_dispatch(ctx, broadcast.program_id, message_body.len()).total();
+    println!(
+        "Broadcasting to {} destinations, estimated cost {} lamports ({} lamports per message)",
+        dispatches.len(),
+        quote_per_message * dispatches.len() as u64,
+        quote_per_message,
+    );
+
+    let results = dispatches
+        .into_iter()
+        .map(|(destination, recipient)| {
+            let (unique_message_account, submitted) = dispatch_message(
+                ctx,
+                broadcast.program_id,
+                destination,
+                recipient,
+                message_body.clone(),
+            );
+            let message_id = submitted
+                .then(|| {
+                    get_dispatched_message(ctx, &broadcast.program_id, &unique_message_account)
+                })
+                .flatten()
+                .map(|message| message.id());
+            (destination, recipient, message_id)
+        })
+        .collect::<Vec<_>>();
+
+    println!("==== Broadcast results: ====");
+    for (destination, recipient, message_id) in results {
+        match message_id {
+            Some(message_id) => {
+                println!(
+                    "destination={} recipient={:?} message_id={:?}",
+                    destination, recipient, message_id
+                );
+                ctx.print_message_link(message_id);
+            }
+            None => println!(
+                "destination={} recipient={:?} not dispatched",
+                destination, recipient
+            ),
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Resolution of chain names to Hyperlane domain IDs.
+
+use std::{collections::HashMap, fmt, fs::File, path::Path, str::FromStr};
+
+use hyperlane_core::KnownHyperlaneDomain;
+
+use crate::{exit_code, router::ChainMetadata};
+
+/// A domain given on the command line, either as a domain ID or a chain name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum DomainArg {
+    Id(u32),
+    Name(String),
+}
+
+impl FromStr for DomainArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err("Empty domain".to_owned());
+        }
+        Ok(s.parse::<u32>()
+            .map(DomainArg::Id)
+            .unwrap_or_else(|_| DomainArg::Name(s.to_lowercase())))
+    }
+}
+
+impl fmt::Display for DomainArg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DomainArg::Id(id) => write!(f, "{}", id),
+            DomainArg::Name(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// The public Solana clusters' chain names, RPC URLs and genesis hashes, so
+/// that RPCs of these chains are checked without a chain metadata file.
+const PUBLIC_CLUSTERS: [(&str, &str, &str); 3] = [
+    (
+        "solanamainnet",
+        "https://api.mainnet-beta.solana.com",
+        "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d",
+    ),
+    (
+        "solanadevnet",
+        "https://api.devnet.solana.com",
+        "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG",
+    ),
+    (
+        "solanatestnet",
+        "https://api.testnet.solana.com",
+        "4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY",
+    ),
+];
+
+/// Resolves chain names to domain IDs using the known Hyperlane domains,
+/// overridden by any chains in a chain metadata file.
+#[derive(Debug, Default)]
+pub(crate) struct DomainRegistry {
+    overrides: HashMap<String, u32>,
+    genesis_hashes: HashMap<String, String>,
+    /// The chains by their RPC URL, without trailing slash.
+    rpc_urls: HashMap<String, String>,
+}
+
+impl DomainRegistry {
+    pub(crate) fn load(chain_metadata_file: Option<&Path>) -> Self {
+        let chain_configs = chain_metadata_file
+            .map(read_chain_metadata_file)
+            .unwrap_or_default();
+        let overrides = chain_configs
+            .values()
+            .map(|chain_config| (chain_config.name().to_lowercase(), chain_config.domain_id()))
+            .collect();
+        let genesis_hashes = chain_configs
+            .values()
+            .filter_map(|chain_config| {
+                chain_config.genesis_hash().map(|genesis_hash| {
+                    (chain_config.name().to_lowercase(), genesis_hash.to_owned())
+                })
+            })
+            .collect();
+        let rpc_urls = chain_configs
+            .values()
+            .map(|chain_config| {
+                (
+                    chain_config.rpc_url().trim_end_matches('/').to_owned(),
+                    chain_config.name().to_lowercase(),
+                )
+            })
+            .collect();
+        Self {
+            overrides,
+            genesis_hashes,
+            rpc_urls,
+        }
+    }
+
+    /// The names of the chains in the chain metadata file, sorted.
+    pub(crate) fn chain_names(&self) -> Vec<&str> {
+        let mut names = self
+            .overrides
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    /// The expected genesis hash of the chain, if the chain metadata file
+    /// includes one or it's a public Solana cluster.
+    pub(crate) fn genesis_hash(&self, chain: &str) -> Option<&str> {
+        let chain = chain.to_lowercase();
+        self.genesis_hashes
+            .get(&chain)
+            .map(String::as_str)
+            .or_else(|| {
+                PUBLIC_CLUSTERS
+                    .iter()
+                    .find(|(name, _, _)| *name == chain)
+                    .map(|(_, _, genesis_hash)| *genesis_hash)
+            })
+    }
+
+    /// The chain served at the RPC URL, if it's the RPC URL of a chain in the
+    /// chain metadata file or of a public Solana cluster.
+    pub(crate) fn chain_served_at(&self, rpc_url: &str) -> Option<&str> {
+        let rpc_url = rpc_url.trim_end_matches('/');
+        self.rpc_urls.get(rpc_url).map(String::as_str).or_else(|| {
+            PUBLIC_CLUSTERS
+                .iter()
+                .find(|(_, url, _)| *url == rpc_url)
+                .map(|(name, _, _)| *name)
+        })
+    }
+
+    /// The domain ID of the chain, if it's in the chain metadata file or a
+    /// known Hyperlane domain.
+    pub(crate) fn lookup(&self, name: &str) -> Option<u32> {
+        let name = name.to_lowercase();
+        self.overrides.get(&name).copied().or_else(|| {
+            KnownHyperlaneDomain::from_str(&name)
+                .ok()
+                .map(|domain| domain as u32)
+        })
+    }
+
+    /// Resolves the domain ID, exiting with a config error if the chain name
+    /// is unknown.
+    pub(crate) fn resolve(&self, domain: &DomainArg) -> u32 {
+        match domain {
+            DomainArg::Id(id) => *id,
+            DomainArg::Name(name) => self.lookup(name).unwrap_or_else(|| {
+                exit_code::config_error(format!(
+                    "Unknown chain {}, provide a domain ID or a chain metadata file",
+                    name
+                ))
+            }),
+        }
+    }
+}
+
+/// Reads the chain metadata file's chains by name, exiting with a config
+/// error if it can't be read.
+pub(crate) fn read_chain_metadata_file(path: &Path) -> HashMap<String, ChainMetadata> {
+    let file = File::open(path).unwrap_or_else(|e| {
+        exit_code::config_error(format!(
+            "Failed to open chain metadata file {}: {}",
+            path.display(),
+            e
+        ))
+    });
+    serde_json::from_reader(file).unwrap_or_else(|e| {
+        exit_code::config_error(format!(
+            "Invalid chain metadata file {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_resolve_known_domain() {
+        let registry = DomainRegistry::default();
+        assert_eq!(registry.resolve(&"ethereum".parse().unwrap()), 1);
+        assert_eq!(registry.resolve(&"BSC".parse().unwrap()), 56);
+        assert_eq!(registry.resolve(&"13375".parse().unwrap()), 13375);
+    }
+
+    #[test]
+    fn test_resolve_override() {
+        let registry = DomainRegistry {
+            overrides: HashMap::from([("solanadevnet".to_owned(), 1399811151)]),
+            ..Default::default()
+        };
+        assert_eq!(
+            registry.resolve(&"solanadevnet".parse().unwrap()),
+            1399811151
+        );
+    }
+
+    #[test]
+    fn test_chain_served_at_public_cluster() {
+        let registry = DomainRegistry::default();
+        let chain = registry
+            .chain_served_at("https://api.devnet.solana.com/")
+            .unwrap();
+        assert_eq!(chain, "solanadevnet");
+        assert_eq!(
+            registry.genesis_hash(chain),
+            Some("EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG")
+        );
+        assert_eq!(registry.chain_served_at("http://localhost:8899"), None);
+    }
+}
@@ -0,0 +1,169 @@
+//! Cost estimates for sending a message: the origin dispatch, the IGP payment
+//! for its destination gas and, for Sealevel destinations, its delivery.
+
+use hyperlane_core::{HyperlaneMessage, H256};
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, message::Message, native_token::lamports_to_sol,
+    transaction::Transaction,
+};
+
+use crate::{
+    accounts::{read_outbox, required_rent_for_process, RpcAccountReader},
+    dispatch::{check_dispatch_body, quote_dispatch},
+    process::process_instruction,
+    quote_igp_payment,
+    rpc::rpc_client,
+    Context, Estimate, MAX_COMPUTE_UNIT_LIMIT,
+};
+
+pub(crate) fn process_estimate_cmd(ctx: Context, estimate: Estimate) {
+    let message_body = estimate.body.read();
+    check_dispatch_body(&ctx, estimate.program_id, &message_body);
+    let destination = ctx.domains.resolve(&estimate.destination);
+
+    let dispatch = quote_dispatch(&ctx, estimate.program_id, message_body.len());
+    println!("Origin dispatch:");
+    print_cost("Dispatched message account rent", dispatch.rent);
+    print_cost("Transaction fee", dispatch.fee);
+
+    let delivery = estimate.destination_url.as_ref().map(|destination_url| {
+        let outbox = read_outbox(&ctx, &estimate.program_id).expect("Mailbox not initialized");
+        let message = HyperlaneMessage {
+            version: ctx.protocol.message_version,
+            nonce: outbox.tree.count() as u32,
+            origin: outbox.local_domain,
+            sender: H256(ctx.payer_pubkey.to_bytes()),
+            destination,
+            recipient: H256(estimate.recipient.to_bytes()),
+            body: message_body,
+        };
+        let metadata = estimate
+            .metadata
+            .as_deref()
+            .map(|metadata| {
+                hex::decode(metadata.trim_start_matches("0x")).expect("Invalid hex metadata")
+            })
+            .unwrap_or_default();
+        let destination_reader = RpcAccountReader {
+            client: rpc_client(destination_url.clone(), ctx.commitment),
+            commitment: ctx.commitment,
+            protocol: &ctx.protocol,
+        };
+        estimate_delivery(&ctx, &destination_reader, &estimate, &message, metadata)
+    });
+
+    let gas_amount = estimate.gas_amount.or_else(|| match &delivery {
+        Some(Ok(delivery)) => Some(delivery.units),
+        _ => None,
+    });
+    let igp_payment = match (
+        gas_amount,
+        estimate.igp_account.or(estimate.overhead_igp_account),
+    ) {
+        (Some(gas_amount), Some(_)) => {
+            let (payment, gas_overhead) = quote_igp_payment(
+                &ctx,
+                estimate.igp_account,
+                estimate.overhead_igp_account,
+                destination,
+                gas_amount,
+            )
+            .unwrap_or_else(|e| panic!("Failed to quote gas payment: {}", e));
+            println!("IGP payment for {} destination gas:", gas_amount);
+            if estimate.overhead_igp_account.is_some() {
+                println!("\tGas overhead: {}", gas_overhead);
+            }
+            print_cost("Gas payment", payment);
+            payment
+        }
+        (None, Some(_)) => {
+            println!("IGP payment: pass --gas-amount or --destination-url to quote it");
+            0
+        }
+        (_, None) => 0,
+    };
+    print_cost("Total on the origin", dispatch.total() + igp_payment);
+
+    match delivery {
+        Some(Ok(delivery)) => {
+            println!("Destination delivery:");
+            println!("\tCompute units: {}", delivery.units);
+            print_cost("Processed message account rent", delivery.rent);
+            print_cost("Transaction fee", delivery.fee);
+            print_cost("Total on the destination", delivery.rent + delivery.fee);
+        }
+        Some(Err(e)) => println!("Destination delivery: simulation failed: {}", e),
+        None => println!("Destination delivery: pass --destination-url to simulate it"),
+    }
+}
+
+fn print_cost(what: &str, lamports: u64) {
+    println!(
+        "\t{}: {} lamports ({} SOL)",
+        what,
+        lamports,
+        lamports_to_sol(lamports)
+    );
+}
+
+struct DeliveryEstimate {
+    /// The compute units the delivery consumed when simulated.
+    units: u64,
+    rent: u64,
+    fee: u64,
+}
+
+/// Simulates the delivery of the message on the destination. The payer must
+/// exist on the destination, but doesn't sign.
+fn estimate_delivery(
+    ctx: &Context,
+    destination: &RpcAccountReader,
+    estimate: &Estimate,
+    message: &HyperlaneMessage,
+    metadata: Vec<u8>,
+) -> Result<DeliveryEstimate, String> {
+    let client = &destination.client;
+    let instruction = process_instruction(
+        client,
+        ctx.commitment,
+        &ctx.protocol,
+        estimate.destination_mailbox,
+        ctx.payer_pubkey,
+        message,
+        metadata,
+    )?;
+    let message = Message::new_with_blockhash(
+        &[
+            ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT),
+            instruction,
+        ],
+        Some(&ctx.payer_pubkey),
+        &client.get_latest_blockhash().map_err(|e| e.to_string())?,
+    );
+    let fee = client
+        .get_fee_for_message(&message)
+        .map_err(|e| e.to_string())?;
+    let result = client
+        .simulate_transaction_with_config(
+            &Transaction::new_unsigned(message),
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                commitment: Some(ctx.commitment),
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )
+        .map_err(|e| e.to_string())?
+        .value;
+    if let Some(err) = result.err {
+        return Err(format!(
+            "{}, pass --metadata if the recipient's ISM requires it",
+            err
+        ));
+    }
+    Ok(DeliveryEstimate {
+        units: result.units_consumed.unwrap_or_default(),
+        rent: required_rent_for_process(destination),
+        fee,
+    })
+}
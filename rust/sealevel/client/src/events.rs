@@ -0,0 +1,208 @@
+//! A typed model of the events observed on Hyperlane chains. The serialized
+//! form is stable: it's tagged by `type`, and fields are camelCase with
+//! pubkeys as base58 strings and hashes as 0x-prefixed hex.
+
+use hyperlane_core::{Decode as _, HyperlaneMessage, SignedCheckpointWithMessageId, H160, H256};
+use serde::{Deserialize, Serialize};
+use solana_program::pubkey::Pubkey;
+
+use hyperlane_sealevel_igp::accounts::GasPaymentData;
+use hyperlane_sealevel_mailbox::accounts::{DispatchedMessage, ProcessedMessage};
+
+use crate::serde::serde_pubkey;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum HyperlaneEvent {
+    Dispatched(DispatchedEvent),
+    GasPaid(GasPaidEvent),
+    Processed(ProcessedEvent),
+    CheckpointSigned(CheckpointSignedEvent),
+    AccountChanged(AccountChangedEvent),
+    DeliverySucceeded(DeliveryEvent),
+    DeliveryFailed(DeliveryFailedEvent),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DispatchedEvent {
+    pub message_id: H256,
+    pub nonce: u32,
+    pub origin: u32,
+    pub destination: u32,
+    pub sender: H256,
+    pub recipient: H256,
+    /// The message body as 0x-prefixed hex.
+    pub body: String,
+    /// The slot of the dispatch, or its block number on EVM chains.
+    pub slot: u64,
+    /// The signature, or hash on EVM chains, of the dispatch transaction, if
+    /// known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GasPaidEvent {
+    pub message_id: H256,
+    pub destination: u32,
+    #[serde(with = "serde_pubkey")]
+    pub igp: Pubkey,
+    pub gas_amount: u64,
+    pub payment: u64,
+    pub sequence: u64,
+    pub slot: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProcessedEvent {
+    pub message_id: H256,
+    /// The processed message's sequence, unknown for deliveries only known
+    /// from the index.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u64>,
+    pub slot: u64,
+    /// The signature of the process transaction, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CheckpointSignedEvent {
+    pub validator: H160,
+    pub origin: u32,
+    pub mailbox_address: H256,
+    pub root: H256,
+    pub index: u32,
+    pub message_id: H256,
+    /// The signature as 0x-prefixed hex.
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AccountChangedEvent {
+    #[serde(with = "serde_pubkey")]
+    pub pubkey: Pubkey,
+    pub slot: u64,
+    pub lamports: u64,
+    #[serde(with = "serde_pubkey")]
+    pub owner: Pubkey,
+    pub data_len: usize,
+    /// The account's data decoded as the watched account type, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decoded: Option<String>,
+}
+
+/// A relayer's delivery of the origin's message with the nonce.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DeliveryEvent {
+    pub origin: u32,
+    pub destination: u32,
+    pub nonce: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DeliveryFailedEvent {
+    pub origin: u32,
+    pub destination: u32,
+    pub nonce: u32,
+    pub reason: String,
+}
+
+impl HyperlaneEvent {
+    /// Serializes the event as a single line of JSON.
+    pub(crate) fn to_json_line(&self) -> String {
+        serde_json::to_string(self).expect("Failed to serialize event")
+    }
+
+    pub(crate) fn dispatched(dispatched_message: &DispatchedMessage) -> Self {
+        let message = HyperlaneMessage::read_from(&mut &dispatched_message.encoded_message[..])
+            .expect("Invalid dispatched message");
+        Self::dispatched_message(&message, dispatched_message.slot, None)
+    }
+
+    /// The dispatch of the message at the slot, or block on EVM chains.
+    pub(crate) fn dispatched_message(
+        message: &HyperlaneMessage,
+        slot: u64,
+        signature: Option<String>,
+    ) -> Self {
+        HyperlaneEvent::Dispatched(DispatchedEvent {
+            message_id: message.id(),
+            nonce: message.nonce,
+            origin: message.origin,
+            destination: message.destination,
+            sender: message.sender,
+            recipient: message.recipient,
+            body: format!("0x{}", hex::encode(&message.body)),
+            slot,
+            signature,
+        })
+    }
+
+    pub(crate) fn gas_paid(gas_payment: &GasPaymentData) -> Self {
+        HyperlaneEvent::GasPaid(GasPaidEvent {
+            message_id: gas_payment.message_id,
+            destination: gas_payment.destination_domain,
+            igp: gas_payment.igp,
+            gas_amount: gas_payment.gas_amount,
+            payment: gas_payment.payment,
+            sequence: gas_payment.sequence_number,
+            slot: gas_payment.slot,
+        })
+    }
+
+    pub(crate) fn processed(processed_message: &ProcessedMessage) -> Self {
+        HyperlaneEvent::Processed(ProcessedEvent {
+            message_id: processed_message.message_id,
+            sequence: Some(processed_message.sequence),
+            slot: processed_message.slot,
+            signature: None,
+        })
+    }
+
+    pub(crate) fn checkpoint_signed(
+        validator: H160,
+        signed: &SignedCheckpointWithMessageId,
+    ) -> Self {
+        HyperlaneEvent::CheckpointSigned(CheckpointSignedEvent {
+            validator,
+            origin: signed.value.mailbox_domain,
+            mailbox_address: signed.value.mailbox_address,
+            root: signed.value.root,
+            index: signed.value.index,
+            message_id: signed.value.message_id,
+            signature: format!("0x{}", signed.signature),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_stable_serialization() {
+        let event = HyperlaneEvent::Processed(ProcessedEvent {
+            message_id: H256::zero(),
+            sequence: Some(7),
+            slot: 42,
+            signature: None,
+        });
+        let json = event.to_json_line();
+        assert_eq!(
+            json,
+            r#"{"type":"processed","messageId":"0x0000000000000000000000000000000000000000000000000000000000000000","sequence":7,"slot":42}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<HyperlaneEvent>(&json).unwrap(),
+            event
+        );
+    }
+}
@@ -0,0 +1,171 @@
+//! Dispatching and finding messages on an EVM origin chain, and queries of
+//! EVM destination chains.
+
+use std::{cmp::Ordering, sync::Arc};
+
+use ethers::{
+    contract::abigen,
+    middleware::SignerMiddleware,
+    providers::{Http, Middleware, Provider},
+    signers::{LocalWallet, Signer},
+    types::{Address, H256 as EvmH256},
+};
+use hyperlane_core::{Decode as _, HyperlaneMessage, H256};
+
+use crate::EvmOutbox;
+
+abigen!(
+    EvmMailbox,
+    r#"[
+        function dispatch(uint32 destinationDomain, bytes32 recipientAddress, bytes calldata messageBody) external returns (bytes32)
+        function delivered(bytes32 messageId) external view returns (bool)
+        event Dispatch(address indexed sender, uint32 indexed destination, bytes32 indexed recipient, bytes message)
+        event DispatchId(bytes32 indexed messageId)
+    ]"#
+);
+
+/// The most blocks whose `Dispatch` events are requested at once, which RPC
+/// providers commonly limit `eth_getLogs` ranges to.
+const BLOCKS_PER_LOGS_QUERY: u64 = 10_000;
+
+/// A message dispatched from an EVM mailbox.
+pub(crate) struct EvmDispatchedMessage {
+    pub message: HyperlaneMessage,
+    pub block_number: u64,
+    pub transaction_hash: EvmH256,
+}
+
+/// Dispatches a message from the EVM mailbox, returning the message ID.
+pub(crate) fn dispatch_evm_message(
+    outbox: &EvmOutbox,
+    destination_domain: u32,
+    recipient: H256,
+    message_body: Vec<u8>,
+) -> Option<H256> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(async {
+            let provider =
+                Provider::<Http>::try_from(outbox.rpc_url.as_str()).expect("Invalid RPC URL");
+            let chain_id = provider.get_chainid().await.unwrap();
+            let wallet = outbox
+                .private_key
+                .parse::<LocalWallet>()
+                .expect("Invalid private key")
+                .with_chain_id(chain_id.as_u64());
+            println!(
+                "Dispatching from EVM mailbox {:?} on chain {} with sender {:?}",
+                outbox.mailbox,
+                chain_id,
+                wallet.address()
+            );
+
+            let mailbox = EvmMailbox::new(
+                outbox.mailbox,
+                Arc::new(SignerMiddleware::new(provider, wallet)),
+            );
+            let call = mailbox.dispatch(destination_domain, recipient.0, message_body.into());
+            let receipt = call
+                .send()
+                .await
+                .unwrap()
+                .await
+                .unwrap()
+                .expect("Dispatch transaction dropped from mempool");
+            println!("Dispatch transaction {:?}", receipt.transaction_hash);
+
+            receipt.logs.into_iter().find_map(|log| {
+                mailbox
+                    .decode_event::<DispatchIdFilter>("DispatchId", log.topics, log.data)
+                    .ok()
+                    .map(|event| H256(event.message_id))
+            })
+        })
+}
+
+/// Finds the message with the nonce dispatched from the EVM mailbox by walking
+/// its `Dispatch` events back from the latest block. Nonces increase with the
+/// block, so the walk stops at the first older message.
+pub(crate) fn find_evm_dispatched_message(
+    rpc_url: &str,
+    mailbox: Address,
+    nonce: u32,
+) -> Option<EvmDispatchedMessage> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(async {
+            let provider = Provider::<Http>::try_from(rpc_url).expect("Invalid RPC URL");
+            let mailbox = EvmMailbox::new(mailbox, Arc::new(provider.clone()));
+            let mut to_block = provider.get_block_number().await.unwrap().as_u64();
+            loop {
+                let from_block = to_block.saturating_sub(BLOCKS_PER_LOGS_QUERY - 1);
+                let dispatches = mailbox
+                    .event::<DispatchFilter>()
+                    .from_block(from_block)
+                    .to_block(to_block)
+                    .query_with_meta()
+                    .await
+                    .unwrap_or_else(|e| {
+                        panic!(
+                            "Failed to get dispatches in blocks {}..={}: {}",
+                            from_block, to_block, e
+                        )
+                    });
+                for (dispatch, meta) in dispatches.into_iter().rev() {
+                    let message = HyperlaneMessage::read_from(&mut &dispatch.message[..])
+                        .expect("Invalid dispatched message");
+                    match message.nonce.cmp(&nonce) {
+                        Ordering::Equal => {
+                            return Some(EvmDispatchedMessage {
+                                message,
+                                block_number: meta.block_number.as_u64(),
+                                transaction_hash: meta.transaction_hash,
+                            })
+                        }
+                        Ordering::Less => return None,
+                        Ordering::Greater => {}
+                    }
+                }
+                if from_block == 0 {
+                    return None;
+                }
+                to_block = from_block - 1;
+            }
+        })
+}
+
+/// Returns whether the message has been delivered to the EVM mailbox.
+pub(crate) fn evm_message_delivered(
+    rpc_url: &str,
+    mailbox: Address,
+    message_id: H256,
+) -> Result<bool, String> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(async {
+            let provider = Provider::<Http>::try_from(rpc_url).expect("Invalid RPC URL");
+            EvmMailbox::new(mailbox, Arc::new(provider))
+                .delivered(message_id.0)
+                .call()
+                .await
+                .map_err(|e| e.to_string())
+        })
+}
+
+/// Returns whether there's contract code at the address.
+pub(crate) fn evm_contract_deployed(rpc_url: &str, address: Address) -> bool {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(async {
+            let provider = Provider::<Http>::try_from(rpc_url).expect("Invalid RPC URL");
+            !provider.get_code(address, None).await.unwrap().is_empty()
+        })
+}
@@ -0,0 +1,176 @@
+//! Example invocations generated from the command definitions, with chain
+//! names and addresses filled in from the domain registry.
+
+use std::path::PathBuf;
+
+use clap::{Arg, ArgAction, Command, CommandFactory};
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    domain::{DomainArg, DomainRegistry},
+    Cli, Examples, MAILBOX_PROG_ID, MULTISIG_ISM_MESSAGE_ID_PROG_ID, VALIDATOR_ANNOUNCE_PROG_ID,
+};
+
+/// The domains examples use when no chain metadata file is given.
+const DEFAULT_EXAMPLE_DOMAINS: [u32; 2] = [13375, 13376];
+
+/// Substitutes example values for the arguments of a command.
+struct ExampleValues {
+    /// The local chain and the remote chain, by name if known.
+    chains: [DomainArg; 2],
+    domains: [u32; 2],
+    payer: Pubkey,
+}
+
+impl ExampleValues {
+    fn new(domains: &DomainRegistry, payer: Pubkey) -> Self {
+        let chains = match domains.chain_names()[..] {
+            [local, remote, ..] => [
+                DomainArg::Name(local.to_owned()),
+                DomainArg::Name(remote.to_owned()),
+            ],
+            _ => DEFAULT_EXAMPLE_DOMAINS.map(DomainArg::Id),
+        };
+        Self {
+            domains: [domains.resolve(&chains[0]), domains.resolve(&chains[1])],
+            chains,
+            payer,
+        }
+    }
+
+    /// The example value of an argument of the command at `path`, or a
+    /// placeholder if there's no sensible example value.
+    fn value(&self, path: &[String], arg: &Arg) -> String {
+        let id = arg.get_id().as_str();
+        match id {
+            "destination" | "destinations" => self.chains[1].to_string(),
+            "origin" | "local_domain" => self.domains[0].to_string(),
+            "remote_domains" | "remote_domain" | "destination_domain" | "domain" => {
+                self.domains[1].to_string()
+            }
+            "recipient" | "new_owner" | "pubkey" | "pubkeys" => self.payer.to_string(),
+            "program_id" => match path.first().map(String::as_str) {
+                Some("multisig-ism-message-id" | "ism") => MULTISIG_ISM_MESSAGE_ID_PROG_ID,
+                Some("validator-announce") => VALIDATOR_ANNOUNCE_PROG_ID,
+                _ => MAILBOX_PROG_ID,
+            }
+            .to_string(),
+            _ => arg
+                .get_value_names()
+                .and_then(|names| names.first())
+                .map(|name| format!("<{}>", name))
+                .unwrap_or_else(|| format!("<{}>", id.to_uppercase())),
+        }
+    }
+
+    /// Whether the argument is included in examples. Required arguments are,
+    /// as are destination chains so that examples use the registry's chains.
+    fn includes(arg: &Arg) -> bool {
+        if matches!(arg.get_action(), ArgAction::SetTrue) {
+            return false;
+        }
+        arg.is_required_set() || matches!(arg.get_id().as_str(), "destination" | "destinations")
+    }
+
+    /// An invocation of the command at `path`, with its required arguments.
+    fn invocation(&self, path: &[String], command: &Command) -> String {
+        let mut invocation = vec![env!("CARGO_PKG_NAME").to_owned()];
+        invocation.extend(path.iter().cloned());
+        for arg in command.get_arguments() {
+            if !Self::includes(arg) {
+                continue;
+            }
+            let value = self.value(path, arg);
+            match arg.get_long() {
+                Some(long) => invocation.push(format!("--{} {}", long, value)),
+                None => invocation.push(value),
+            }
+        }
+        invocation.join(" ")
+    }
+}
+
+pub(crate) fn process_examples(domains: &DomainRegistry, payer: Pubkey, examples: Examples) {
+    print_examples(domains, payer, &examples.command);
+}
+
+/// Prints examples for the command given on the command line and exits if
+/// `--example` is given. This happens before the arguments are validated, so
+/// a command's required arguments needn't be given to get its examples.
+pub(crate) fn print_examples_if_requested() {
+    let matches = Cli::command().ignore_errors(true).get_matches();
+    if !matches.get_flag("example") {
+        return;
+    }
+    let mut path = vec![];
+    let mut subcommand_matches = &matches;
+    while let Some((name, matches)) = subcommand_matches.subcommand() {
+        path.push(name.to_owned());
+        subcommand_matches = matches;
+    }
+    let domains = DomainRegistry::load(
+        matches
+            .get_one::<PathBuf>("chain_metadata_file")
+            .map(PathBuf::as_path),
+    );
+    print_examples(&domains, Pubkey::new_unique(), &path);
+    std::process::exit(0);
+}
+
+/// Prints an example for each command at or under `path`.
+fn print_examples(domains: &DomainRegistry, payer: Pubkey, path: &[String]) {
+    let cli = Cli::command();
+    let mut command = &cli;
+    // Commands may be given by their aliases, but examples use their names.
+    let mut names = vec![];
+    for name in path {
+        command = command
+            .find_subcommand(name)
+            .unwrap_or_else(|| panic!("Unknown command {}", path.join(" ")));
+        names.push(command.get_name().to_owned());
+    }
+    let values = ExampleValues::new(domains, payer);
+    print_command_examples(&values, &mut names, command);
+}
+
+fn print_command_examples(values: &ExampleValues, path: &mut Vec<String>, command: &Command) {
+    if !command.has_subcommands() {
+        if let Some(about) = command.get_about() {
+            println!("# {}", about);
+        }
+        println!("{}\n", values.invocation(path, command));
+        return;
+    }
+    for subcommand in command.get_subcommands() {
+        if subcommand.get_name() == "help" {
+            continue;
+        }
+        path.push(subcommand.get_name().to_owned());
+        print_command_examples(values, path, subcommand);
+        path.pop();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_invocation_fills_required_arguments() {
+        let values = ExampleValues::new(&DomainRegistry::default(), Pubkey::new_unique());
+        let path = vec!["mailbox".to_owned(), "send".to_owned()];
+        let cli = Cli::command();
+        let command = cli
+            .find_subcommand("mailbox")
+            .and_then(|mailbox| mailbox.find_subcommand("send"))
+            .unwrap();
+        assert_eq!(
+            values.invocation(&path, command),
+            format!(
+                "{} mailbox send --destination 13376 --recipient {}",
+                env!("CARGO_PKG_NAME"),
+                values.payer
+            )
+        );
+    }
+}
@@ -0,0 +1,62 @@
+//! Process exit codes, kept stable so that scripts can branch on why a
+//! command failed. Commands exit with 0 on success, with one of the codes
+//! below for the failures scripts commonly handle, and panic, exiting with
+//! 101, on anything else.
+
+use std::{fmt::Display, io};
+
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_request::{RpcError, RpcResponseErrorData},
+};
+
+/// A transaction failed simulation, either with `--simulate-only` or in the
+/// RPC's preflight check before it was sent.
+pub(crate) const SIMULATION_FAILURE: i32 = 2;
+/// A sent transaction wasn't confirmed within the confirmation timeout.
+pub(crate) const TIMEOUT: i32 = 3;
+/// The configuration is invalid, e.g. a config file can't be read or the RPC
+/// serves a different chain than expected.
+pub(crate) const CONFIG_ERROR: i32 = 4;
+/// `monitor` found anomalies, like stuck messages.
+pub(crate) const ANOMALY: i32 = 5;
+
+/// Prints the configuration error and exits with `CONFIG_ERROR`.
+pub(crate) fn config_error(err: impl Display) -> ! {
+    eprintln!("Error: {}", err);
+    std::process::exit(CONFIG_ERROR)
+}
+
+/// Exits with the code for a transaction that failed to be sent or
+/// confirmed, once the error has been printed, or panics if the failure has
+/// no code of its own.
+pub(crate) fn exit_on_transaction_error(err: ClientError) -> ! {
+    match transaction_error_code(&err) {
+        Some(code) => std::process::exit(code),
+        None => panic!("Failed to send transaction: {}", err),
+    }
+}
+
+fn transaction_error_code(err: &ClientError) -> Option<i32> {
+    match err.kind() {
+        ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(_),
+            ..
+        }) => Some(SIMULATION_FAILURE),
+        ClientErrorKind::Io(err) if err.kind() == io::ErrorKind::TimedOut => Some(TIMEOUT),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_transaction_error_code() {
+        let timeout = io::Error::new(io::ErrorKind::TimedOut, "not confirmed");
+        assert_eq!(transaction_error_code(&timeout.into()), Some(TIMEOUT));
+        let other = ClientErrorKind::Custom("failed".to_owned());
+        assert_eq!(transaction_error_code(&other.into()), None);
+    }
+}
@@ -0,0 +1,100 @@
+//! Links to sent transactions on a Solana explorer and to dispatched messages
+//! on the Hyperlane explorer.
+
+use hyperlane_core::H256;
+use solana_sdk::signature::Signature;
+
+pub(crate) const DEFAULT_SOLANA_EXPLORER_URL: &str = "https://explorer.solana.com";
+pub(crate) const DEFAULT_HYPERLANE_EXPLORER_URL: &str = "https://explorer.hyperlane.xyz";
+
+pub(crate) struct Explorers {
+    solana_explorer_url: String,
+    /// The cluster to link to, overriding the one derived from the RPC URL.
+    cluster: Option<String>,
+    hyperlane_explorer_url: String,
+}
+
+impl Explorers {
+    pub(crate) fn new(
+        solana_explorer_url: String,
+        cluster: Option<String>,
+        hyperlane_explorer_url: String,
+    ) -> Self {
+        Self {
+            solana_explorer_url: solana_explorer_url.trim_end_matches('/').to_owned(),
+            cluster,
+            hyperlane_explorer_url: hyperlane_explorer_url.trim_end_matches('/').to_owned(),
+        }
+    }
+
+    /// The link to a transaction sent through the RPC at `rpc_url`.
+    pub(crate) fn transaction_url(&self, rpc_url: &str, signature: &Signature) -> String {
+        let cluster = self.cluster.as_deref().unwrap_or(rpc_url);
+        format!(
+            "{}/tx/{}{}",
+            self.solana_explorer_url,
+            signature,
+            cluster_query(cluster)
+        )
+    }
+
+    pub(crate) fn message_url(&self, message_id: H256) -> String {
+        format!("{}/message/{:?}", self.hyperlane_explorer_url, message_id)
+    }
+}
+
+/// The query selecting the cluster on the Solana explorer. The cluster is
+/// either a cluster name or an RPC URL, whose cluster is guessed from its
+/// host, falling back to linking to the RPC as a custom cluster.
+fn cluster_query(cluster: &str) -> String {
+    let cluster_name = if cluster.contains("devnet") {
+        "devnet"
+    } else if cluster.contains("testnet") {
+        "testnet"
+    } else if cluster.contains("mainnet") {
+        return String::new();
+    } else {
+        return format!("?cluster=custom&customUrl={}", percent_encode(cluster));
+    };
+    format!("?cluster={}", cluster_name)
+}
+
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_transaction_url_cluster() {
+        let explorers = Explorers::new(
+            format!("{}/", DEFAULT_SOLANA_EXPLORER_URL),
+            None,
+            DEFAULT_HYPERLANE_EXPLORER_URL.to_owned(),
+        );
+        let signature = Signature::default();
+        assert_eq!(
+            explorers.transaction_url("https://api.mainnet-beta.solana.com", &signature),
+            format!("https://explorer.solana.com/tx/{}", signature)
+        );
+        assert_eq!(
+            explorers.transaction_url("https://api.devnet.solana.com", &signature),
+            format!(
+                "https://explorer.solana.com/tx/{}?cluster=devnet",
+                signature
+            )
+        );
+        assert!(explorers
+            .transaction_url("http://localhost:8899", &signature)
+            .ends_with("?cluster=custom&customUrl=http%3A%2F%2Flocalhost%3A8899"));
+    }
+}
@@ -0,0 +1,221 @@
+//! Failover between RPC endpoints. Requests go to the first healthy endpoint,
+//! in the order given, and are retried on the next one if the endpoint can't
+//! be reached or serves a slot lagging far behind the others. An endpoint
+//! that failed is skipped for a cooldown, growing with each consecutive
+//! failure, before it's tried first again.
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use serde_json::Value;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind, Result as ClientResult},
+    rpc_request::{RpcError, RpcRequest, RpcResponseErrorData},
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use tracing::warn;
+
+/// How many slots an endpoint may lag behind the highest slot seen from any
+/// endpoint before its responses are considered stale, about a minute.
+const MAX_SLOT_LAG: u64 = 150;
+/// JSON-RPC error code of requests whose minimum context slot the node
+/// hasn't reached.
+const MIN_CONTEXT_SLOT_NOT_REACHED: i64 = -32016;
+const INITIAL_COOLDOWN: Duration = Duration::from_secs(10);
+const MAX_COOLDOWN: Duration = Duration::from_secs(300);
+
+/// The health of an endpoint, as tracked from the outcomes of its requests.
+#[derive(Debug, Default)]
+struct EndpointHealth {
+    consecutive_failures: u32,
+    /// Until when the endpoint is only tried after the healthy ones.
+    unhealthy_until: Option<Instant>,
+}
+
+impl EndpointHealth {
+    fn is_healthy(&self, now: Instant) -> bool {
+        self.unhealthy_until.map_or(true, |until| now >= until)
+    }
+
+    fn record_success(&mut self) {
+        *self = Self::default();
+    }
+
+    fn record_failure(&mut self, now: Instant) {
+        let cooldown = INITIAL_COOLDOWN
+            .saturating_mul(1 << self.consecutive_failures.min(8))
+            .min(MAX_COOLDOWN);
+        self.consecutive_failures += 1;
+        self.unhealthy_until = Some(now + cooldown);
+    }
+}
+
+struct Endpoint<T> {
+    sender: T,
+    health: Mutex<EndpointHealth>,
+}
+
+/// An RPC transport over an ordered list of endpoints, failing over from one
+/// to the next on connection errors and stale slots.
+pub(crate) struct FailoverClient<T> {
+    endpoints: Vec<Endpoint<T>>,
+    /// The highest context slot any endpoint has responded with.
+    highest_slot: AtomicU64,
+}
+
+impl<T: RpcSender> FailoverClient<T> {
+    pub(crate) fn new(senders: Vec<T>) -> Self {
+        assert!(!senders.is_empty(), "No RPC endpoints given");
+        Self {
+            endpoints: senders
+                .into_iter()
+                .map(|sender| Endpoint {
+                    sender,
+                    health: Mutex::default(),
+                })
+                .collect(),
+            highest_slot: AtomicU64::new(0),
+        }
+    }
+
+    /// The endpoints to try, the healthy ones first, each in the given order.
+    fn endpoints_to_try(&self) -> Vec<&Endpoint<T>> {
+        let now = Instant::now();
+        let (mut healthy, unhealthy): (Vec<_>, Vec<_>) = self
+            .endpoints
+            .iter()
+            .partition(|endpoint| endpoint.health.lock().unwrap().is_healthy(now));
+        healthy.extend(unhealthy);
+        healthy
+    }
+
+    /// Whether a response at the slot lags too far behind the other
+    /// endpoints' responses.
+    fn is_stale(&self, slot: u64) -> bool {
+        let highest_slot = self.highest_slot.fetch_max(slot, Ordering::Relaxed);
+        highest_slot.saturating_sub(slot) > MAX_SLOT_LAG
+    }
+}
+
+/// Whether the request failed because of the endpoint rather than the
+/// request, so that it may succeed on another endpoint.
+fn is_endpoint_error(err: &ClientError) -> bool {
+    match err.kind() {
+        ClientErrorKind::Io(_) => true,
+        ClientErrorKind::Reqwest(err) => {
+            err.is_connect()
+                || err.is_timeout()
+                || err
+                    .status()
+                    .map_or(false, |status| status.is_server_error())
+        }
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, data, .. }) => {
+            *code == MIN_CONTEXT_SLOT_NOT_REACHED
+                || matches!(data, RpcResponseErrorData::NodeUnhealthy { .. })
+        }
+        _ => false,
+    }
+}
+
+/// The slot of the response's context, for responses that have one.
+fn context_slot(response: &Value) -> Option<u64> {
+    response.get("context")?.get("slot")?.as_u64()
+}
+
+#[async_trait]
+impl<T: RpcSender + Send + Sync + 'static> RpcSender for FailoverClient<T> {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        let endpoints = self.endpoints_to_try();
+        let mut last_err = None;
+        for (i, endpoint) in endpoints.iter().enumerate() {
+            let is_last = i + 1 == endpoints.len();
+            let url = endpoint.sender.url();
+            match endpoint.sender.send(request, params.clone()).await {
+                Ok(response) => match context_slot(&response) {
+                    Some(slot) if self.is_stale(slot) && !is_last => {
+                        warn!(%url, %request, slot, "RPC endpoint is lagging, failing over");
+                        endpoint
+                            .health
+                            .lock()
+                            .unwrap()
+                            .record_failure(Instant::now());
+                        last_err = Some(
+                            ClientErrorKind::Custom(format!("{} is lagging at slot {}", url, slot))
+                                .into(),
+                        );
+                    }
+                    _ => {
+                        endpoint.health.lock().unwrap().record_success();
+                        return Ok(response);
+                    }
+                },
+                Err(err) if is_endpoint_error(&err) => {
+                    warn!(%url, %request, %err, "RPC endpoint failed, failing over");
+                    endpoint
+                        .health
+                        .lock()
+                        .unwrap()
+                        .record_failure(Instant::now());
+                    last_err = Some(err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Err(last_err.expect("No RPC endpoints"))
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.endpoints
+            .iter()
+            .map(|endpoint| endpoint.sender.get_transport_stats())
+            .fold(RpcTransportStats::default(), |total, stats| {
+                RpcTransportStats {
+                    request_count: total.request_count + stats.request_count,
+                    elapsed_time: total.elapsed_time + stats.elapsed_time,
+                    rate_limited_time: total.rate_limited_time + stats.rate_limited_time,
+                }
+            })
+    }
+
+    /// The first endpoint's URL, e.g. to derive the websocket URL from.
+    fn url(&self) -> String {
+        self.endpoints[0].sender.url()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_endpoint_health_cooldown() {
+        let now = Instant::now();
+        let mut health = EndpointHealth::default();
+        assert!(health.is_healthy(now));
+        health.record_failure(now);
+        assert!(!health.is_healthy(now));
+        assert!(health.is_healthy(now + INITIAL_COOLDOWN));
+        // The cooldown doubles with each consecutive failure.
+        health.record_failure(now);
+        assert!(!health.is_healthy(now + INITIAL_COOLDOWN));
+        assert!(health.is_healthy(now + INITIAL_COOLDOWN * 2));
+        health.record_success();
+        assert!(health.is_healthy(now));
+    }
+
+    #[test]
+    fn test_context_slot() {
+        assert_eq!(
+            context_slot(&json!({ "context": { "slot": 42 }, "value": null })),
+            Some(42)
+        );
+        assert_eq!(context_slot(&json!(42)), None);
+    }
+}
@@ -0,0 +1,458 @@
+//! Account streaming over Yellowstone Geyser gRPC subscriptions, for watching
+//! accounts where websocket `accountSubscribe` and `programSubscribe`
+//! subscriptions miss updates under load. Only the messages of the
+//! `geyser.Geyser/Subscribe` call that are used here are declared, so that
+//! building doesn't need protoc or the generated client crate.
+
+use std::{
+    collections::HashMap,
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use clap::Args;
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{
+    codec::ProstCodec,
+    codegen::http::uri::PathAndQuery,
+    transport::{ClientTlsConfig, Endpoint},
+    Streaming,
+};
+use tracing::debug;
+
+const SUBSCRIBE_PATH: &str = "/geyser.Geyser/Subscribe";
+
+/// The name of the subscription's only accounts filter.
+const FILTER_NAME: &str = "hyperlane";
+
+/// How many requests to the endpoint can be queued: the subscription and the
+/// replies to its pings.
+const REQUEST_BUFFER: usize = 4;
+
+#[derive(Args)]
+pub(crate) struct GeyserArgs {
+    /// Yellowstone Geyser gRPC endpoint to stream account updates from,
+    /// instead of the RPC's websocket.
+    #[arg(long, env = "HYPERLANE_GEYSER_URL")]
+    geyser_url: Option<String>,
+    /// Access token of the Geyser endpoint, sent as the `x-token` header.
+    #[arg(
+        long,
+        env = "HYPERLANE_GEYSER_X_TOKEN",
+        hide_env_values = true,
+        requires = "geyser_url"
+    )]
+    geyser_x_token: Option<String>,
+}
+
+impl GeyserArgs {
+    /// The Geyser endpoint, if a URL is given.
+    pub(crate) fn endpoint(&self) -> Option<GeyserEndpoint> {
+        self.geyser_url.as_ref().map(|url| GeyserEndpoint {
+            url: url.clone(),
+            x_token: self.geyser_x_token.clone(),
+        })
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct GeyserEndpoint {
+    url: String,
+    x_token: Option<String>,
+}
+
+/// Which accounts to stream updates of: accounts with any of the pubkeys or
+/// owned by any of the owners, whose data matches all the memcmp filters.
+#[derive(Default)]
+pub(crate) struct AccountsFilter {
+    pub pubkeys: Vec<Pubkey>,
+    pub owners: Vec<Pubkey>,
+    /// Offsets in the account data, and the bytes expected there.
+    pub memcmps: Vec<(usize, Vec<u8>)>,
+}
+
+pub(crate) struct AccountUpdate {
+    pub pubkey: Pubkey,
+    pub slot: u64,
+    pub account: Account,
+}
+
+impl GeyserEndpoint {
+    /// Subscribes to updates of the accounts matching the filter. Updates are
+    /// streamed to the returned receiver from a background thread, until the
+    /// receiver is dropped or the endpoint ends the stream, which disconnects
+    /// the receiver.
+    pub(crate) fn subscribe_accounts(
+        &self,
+        filter: AccountsFilter,
+        commitment: CommitmentConfig,
+    ) -> Result<Receiver<AccountUpdate>, String> {
+        let request = proto::SubscribeRequest::accounts(filter, commitment);
+        let endpoint = self.clone();
+        let (subscribed_sender, subscribed) = mpsc::channel();
+        let (update_sender, updates) = mpsc::channel();
+        thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap()
+                .block_on(async {
+                    let (requests, mut stream) = match endpoint.subscribe(request.clone()).await {
+                        Ok(subscription) => {
+                            let _ = subscribed_sender.send(Ok(()));
+                            subscription
+                        }
+                        Err(e) => {
+                            let _ = subscribed_sender.send(Err(e));
+                            return;
+                        }
+                    };
+                    loop {
+                        let update = match stream.message().await {
+                            Ok(Some(update)) => update,
+                            Ok(None) => break,
+                            Err(status) => {
+                                eprintln!(
+                                    "Geyser subscription to {} failed: {}",
+                                    endpoint.url, status
+                                );
+                                break;
+                            }
+                        };
+                        match update.update_oneof {
+                            Some(proto::UpdateOneof::Account(account)) => {
+                                let Some(update) = account.into_update() else {
+                                    debug!("Skipping malformed Geyser account update");
+                                    continue;
+                                };
+                                if update_sender.send(update).is_err() {
+                                    break;
+                                }
+                            }
+                            // Endpoints behind load balancers close streams
+                            // the client doesn't ping. If the endpoint stopped
+                            // reading requests, the stream ends next.
+                            Some(proto::UpdateOneof::Ping(_)) => {
+                                let _ = requests.send(request.ping()).await;
+                            }
+                            None => {}
+                        }
+                    }
+                });
+        });
+        subscribed
+            .recv()
+            .map_err(|_| "Geyser subscription thread panicked".to_owned())??;
+        Ok(updates)
+    }
+
+    /// Opens the `Subscribe` stream with the request, returning the sender of
+    /// further requests along with the stream of updates.
+    async fn subscribe(
+        &self,
+        request: proto::SubscribeRequest,
+    ) -> Result<
+        (
+            tokio::sync::mpsc::Sender<proto::SubscribeRequest>,
+            Streaming<proto::SubscribeUpdate>,
+        ),
+        String,
+    > {
+        let mut endpoint = Endpoint::from_shared(self.url.clone())
+            .map_err(|e| format!("Invalid Geyser URL {}: {}", self.url, e))?;
+        if self.url.starts_with("https") {
+            endpoint = endpoint
+                .tls_config(ClientTlsConfig::new())
+                .map_err(|e| format!("Invalid TLS config for {}: {}", self.url, e))?;
+        }
+        let channel = endpoint
+            .connect()
+            .await
+            .map_err(|e| format!("Failed to connect to {}: {}", self.url, e))?;
+        let mut client = tonic::client::Grpc::new(channel);
+        client
+            .ready()
+            .await
+            .map_err(|e| format!("Geyser endpoint {} isn't ready: {}", self.url, e))?;
+
+        let (requests, receiver) = tokio::sync::mpsc::channel(REQUEST_BUFFER);
+        requests.send(request).await.unwrap();
+        let mut streaming_request = tonic::Request::new(ReceiverStream::new(receiver));
+        if let Some(x_token) = &self.x_token {
+            streaming_request.metadata_mut().insert(
+                "x-token",
+                x_token
+                    .parse()
+                    .map_err(|_| "Invalid Geyser x-token".to_owned())?,
+            );
+        }
+        let stream = client
+            .streaming(
+                streaming_request,
+                PathAndQuery::from_static(SUBSCRIBE_PATH),
+                ProstCodec::default(),
+            )
+            .await
+            .map_err(|status| format!("Failed to subscribe to {}: {}", self.url, status))?
+            .into_inner();
+        Ok((requests, stream))
+    }
+}
+
+/// The messages of Yellowstone's `geyser.proto` that are used, with their
+/// field numbers. Fields and update kinds that aren't declared are skipped
+/// when decoding.
+mod proto {
+    use super::*;
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeRequest {
+        #[prost(map = "string, message", tag = "1")]
+        pub accounts: HashMap<String, SubscribeRequestFilterAccounts>,
+        #[prost(enumeration = "CommitmentLevel", optional, tag = "6")]
+        pub commitment: Option<i32>,
+        #[prost(message, optional, tag = "9")]
+        pub ping: Option<SubscribeRequestPing>,
+    }
+
+    impl SubscribeRequest {
+        pub fn accounts(filter: AccountsFilter, commitment: CommitmentConfig) -> Self {
+            let commitment = if commitment.is_finalized() {
+                CommitmentLevel::Finalized
+            } else if commitment.is_confirmed() {
+                CommitmentLevel::Confirmed
+            } else {
+                CommitmentLevel::Processed
+            };
+            Self {
+                accounts: HashMap::from([(
+                    FILTER_NAME.to_owned(),
+                    SubscribeRequestFilterAccounts {
+                        account: filter.pubkeys.iter().map(Pubkey::to_string).collect(),
+                        owner: filter.owners.iter().map(Pubkey::to_string).collect(),
+                        filters: filter
+                            .memcmps
+                            .into_iter()
+                            .map(|(offset, bytes)| SubscribeRequestFilterAccountsFilter {
+                                filter: Some(AccountsFilterOneof::Memcmp(
+                                    SubscribeRequestFilterAccountsFilterMemcmp {
+                                        offset: offset as u64,
+                                        data: Some(MemcmpDataOneof::Bytes(bytes)),
+                                    },
+                                )),
+                            })
+                            .collect(),
+                    },
+                )]),
+                commitment: Some(commitment as i32),
+                ping: None,
+            }
+        }
+
+        /// The reply to a ping. The filters are sent again, so that the
+        /// subscription is unchanged whether or not the endpoint treats the
+        /// reply as a new subscription.
+        pub fn ping(&self) -> Self {
+            Self {
+                ping: Some(SubscribeRequestPing { id: 1 }),
+                ..self.clone()
+            }
+        }
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeRequestFilterAccounts {
+        #[prost(string, repeated, tag = "2")]
+        pub account: Vec<String>,
+        #[prost(string, repeated, tag = "3")]
+        pub owner: Vec<String>,
+        #[prost(message, repeated, tag = "4")]
+        pub filters: Vec<SubscribeRequestFilterAccountsFilter>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeRequestFilterAccountsFilter {
+        #[prost(oneof = "AccountsFilterOneof", tags = "1")]
+        pub filter: Option<AccountsFilterOneof>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum AccountsFilterOneof {
+        #[prost(message, tag = "1")]
+        Memcmp(SubscribeRequestFilterAccountsFilterMemcmp),
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeRequestFilterAccountsFilterMemcmp {
+        #[prost(uint64, tag = "1")]
+        pub offset: u64,
+        #[prost(oneof = "MemcmpDataOneof", tags = "2")]
+        pub data: Option<MemcmpDataOneof>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum MemcmpDataOneof {
+        #[prost(bytes, tag = "2")]
+        Bytes(Vec<u8>),
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeRequestPing {
+        #[prost(int32, tag = "1")]
+        pub id: i32,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum CommitmentLevel {
+        Processed = 0,
+        Confirmed = 1,
+        Finalized = 2,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeUpdate {
+        #[prost(string, repeated, tag = "1")]
+        pub filters: Vec<String>,
+        #[prost(oneof = "UpdateOneof", tags = "2, 6")]
+        pub update_oneof: Option<UpdateOneof>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum UpdateOneof {
+        #[prost(message, tag = "2")]
+        Account(SubscribeUpdateAccount),
+        #[prost(message, tag = "6")]
+        Ping(SubscribeUpdatePing),
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeUpdateAccount {
+        #[prost(message, optional, tag = "1")]
+        pub account: Option<SubscribeUpdateAccountInfo>,
+        #[prost(uint64, tag = "2")]
+        pub slot: u64,
+        #[prost(bool, tag = "3")]
+        pub is_startup: bool,
+    }
+
+    impl SubscribeUpdateAccount {
+        /// The update, unless the account or its pubkeys are missing or
+        /// malformed.
+        pub fn into_update(self) -> Option<AccountUpdate> {
+            let info = self.account?;
+            Some(AccountUpdate {
+                pubkey: Pubkey::try_from(&info.pubkey[..]).ok()?,
+                slot: self.slot,
+                account: Account {
+                    lamports: info.lamports,
+                    data: info.data,
+                    owner: Pubkey::try_from(&info.owner[..]).ok()?,
+                    executable: info.executable,
+                    rent_epoch: info.rent_epoch,
+                },
+            })
+        }
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeUpdateAccountInfo {
+        #[prost(bytes, tag = "1")]
+        pub pubkey: Vec<u8>,
+        #[prost(uint64, tag = "2")]
+        pub lamports: u64,
+        #[prost(bytes, tag = "3")]
+        pub owner: Vec<u8>,
+        #[prost(bool, tag = "4")]
+        pub executable: bool,
+        #[prost(uint64, tag = "5")]
+        pub rent_epoch: u64,
+        #[prost(bytes, tag = "6")]
+        pub data: Vec<u8>,
+        #[prost(uint64, tag = "7")]
+        pub write_version: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeUpdatePing {}
+}
+
+#[cfg(test)]
+mod test {
+    use prost::Message as _;
+
+    use super::{proto::*, *};
+
+    #[test]
+    fn test_subscribe_request_encoding() {
+        let owner = Pubkey::new_unique();
+        let request = SubscribeRequest::accounts(
+            AccountsFilter {
+                owners: vec![owner],
+                memcmps: vec![(3, vec![1, 2])],
+                ..AccountsFilter::default()
+            },
+            CommitmentConfig::confirmed(),
+        );
+        let encoded = request.encode_to_vec();
+        assert_eq!(SubscribeRequest::decode(&encoded[..]).unwrap(), request);
+
+        // The filter map entry, field 1, holds the owner, field 3 of the
+        // accounts filter, as a base58 string.
+        let owner = owner.to_string();
+        assert!(encoded
+            .windows(owner.len() + 2)
+            .any(|window| window[0] == 3 << 3 | 2
+                && window[1] as usize == owner.len()
+                && &window[2..] == owner.as_bytes()));
+        // The commitment, field 6, is confirmed.
+        assert!(encoded.ends_with(&[6 << 3, CommitmentLevel::Confirmed as u8]));
+
+        let ping = request.ping();
+        assert_eq!(ping.accounts, request.accounts);
+        assert_eq!(ping.ping, Some(SubscribeRequestPing { id: 1 }));
+    }
+
+    #[test]
+    fn test_account_update_decoding() {
+        let pubkey = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let update = SubscribeUpdate {
+            filters: vec![FILTER_NAME.to_owned()],
+            update_oneof: Some(UpdateOneof::Account(SubscribeUpdateAccount {
+                account: Some(SubscribeUpdateAccountInfo {
+                    pubkey: pubkey.to_bytes().to_vec(),
+                    lamports: 42,
+                    owner: owner.to_bytes().to_vec(),
+                    executable: false,
+                    rent_epoch: 7,
+                    data: vec![1, 2, 3],
+                    write_version: 1,
+                }),
+                slot: 100,
+                is_startup: false,
+            })),
+        };
+        let decoded = SubscribeUpdate::decode(&update.encode_to_vec()[..]).unwrap();
+        let Some(UpdateOneof::Account(account)) = decoded.update_oneof else {
+            panic!("Expected an account update");
+        };
+        let update = account.into_update().unwrap();
+        assert_eq!(update.pubkey, pubkey);
+        assert_eq!(update.slot, 100);
+        assert_eq!(update.account.owner, owner);
+        assert_eq!(update.account.lamports, 42);
+        assert_eq!(update.account.data, vec![1, 2, 3]);
+
+        // Updates of undeclared kinds, like slot updates (field 3), decode
+        // without one.
+        let slot_update = [3 << 3 | 2, 2, 1 << 3, 5];
+        assert_eq!(
+            SubscribeUpdate::decode(&slot_update[..])
+                .unwrap()
+                .update_oneof,
+            None
+        );
+    }
+}
@@ -1,23 +1,31 @@
 use std::collections::HashMap;
 
+use borsh::BorshSerialize as _;
 use hyperlane_core::H256;
 use hyperlane_sealevel_connection_client::router::RemoteRouterConfig;
 use hyperlane_sealevel_hello_world::{
     accounts::{HelloWorldStorage, HelloWorldStorageAccount},
     instruction::{
         enroll_remote_routers_instruction, init_instruction,
-        set_interchain_security_module_instruction,
+        set_interchain_security_module_instruction, HelloWorldInstruction, HelloWorldMessage,
     },
     program_storage_pda_seeds,
 };
+use hyperlane_sealevel_mailbox::{mailbox_message_dispatch_authority_pda_seeds, spl_noop};
 use serde::{Deserialize, Serialize};
-use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer as _},
+    system_program,
+};
 
 use crate::{
     cmd_utils::account_exists,
     router::{
-        deploy_routers, ChainMetadata, ConnectionClient, Ownable, RouterConfig, RouterConfigGetter,
-        RouterDeployer,
+        deploy_routers, enroll_remote_routers_from_file, export_remote_routers,
+        igp_payment_account_metas, ChainMetadata, ConnectionClient, Ownable, Router, RouterConfig,
+        RouterConfigGetter, RouterDeployer,
     },
     Context, CoreProgramIds, HelloWorldCmd, HelloWorldDeploy, HelloWorldSubCmd, RpcClient,
 };
@@ -36,6 +44,39 @@ pub(crate) fn process_helloworld_cmd(mut ctx: Context, cmd: HelloWorldCmd) {
                 .into_inner();
             println!("HelloWorld storage: {:?}", storage);
         }
+        HelloWorldSubCmd::Send(send) => {
+            let destination = ctx.domains.resolve(&send.destination);
+            let unique_message_account_keypair = Keypair::new();
+            let instruction = HelloWorldDeployer::new().dispatch_instruction(
+                &ctx,
+                &ctx.client,
+                send.program_id,
+                ctx.payer_pubkey,
+                unique_message_account_keypair.pubkey(),
+                HelloWorldMessage {
+                    destination,
+                    message: send.message,
+                },
+            );
+            ctx.new_txn()
+                .add_with_description(
+                    instruction,
+                    format!("Send a HelloWorld message to domain {}", destination),
+                )
+                .send(&[&*ctx.payer_signer(), &unique_message_account_keypair]);
+        }
+        HelloWorldSubCmd::EnrollRemoteRouters(enroll) => enroll_remote_routers_from_file(
+            &ctx,
+            &HelloWorldDeployer::new(),
+            &enroll.program_id,
+            &enroll.file,
+        ),
+        HelloWorldSubCmd::ExportRouters(export) => export_remote_routers(
+            &ctx,
+            &HelloWorldDeployer::new(),
+            &export.program_id,
+            export.file.as_deref(),
+        ),
     }
 }
 
@@ -63,10 +104,8 @@ impl HelloWorldDeployer {
     }
 }
 
-impl RouterDeployer<HelloWorldConfig> for HelloWorldDeployer {
-    fn program_name(&self, _config: &HelloWorldConfig) -> &str {
-        "hyperlane_sealevel_hello_world"
-    }
+impl Router for HelloWorldDeployer {
+    type Payload = HelloWorldMessage;
 
     fn enroll_remote_routers_instruction(
         &self,
@@ -83,6 +122,77 @@ impl RouterDeployer<HelloWorldConfig> for HelloWorldDeployer {
         storage.routers
     }
 
+    fn dispatch_instruction(
+        &self,
+        ctx: &Context,
+        client: &RpcClient,
+        program_id: Pubkey,
+        sender: Pubkey,
+        unique_message_account: Pubkey,
+        payload: HelloWorldMessage,
+    ) -> Instruction {
+        let storage = self.get_storage(client, &program_id);
+        let (program_storage_account, _program_storage_bump) =
+            Pubkey::find_program_address(program_storage_pda_seeds!(), &program_id);
+        let (dispatch_authority_account, _dispatch_authority_bump) = Pubkey::find_program_address(
+            mailbox_message_dispatch_authority_pda_seeds!(),
+            &program_id,
+        );
+        let (mailbox_outbox_account, _mailbox_outbox_bump) =
+            ctx.protocol.outbox_pda(&storage.mailbox);
+        let (dispatched_message_account, _dispatched_message_bump) = ctx
+            .protocol
+            .dispatched_message_pda(&storage.mailbox, &unique_message_account);
+
+        // 0.  [writeable] Program storage.
+        // 1.  [executable] The Mailbox program.
+        // 2.  [writeable] Outbox PDA.
+        // 3.  [] This program's dispatch authority.
+        // 4.  [executable] System program.
+        // 5.  [executable] SPL Noop program.
+        // 6.  [signer] Payer.
+        // 7.  [signer] Unique message account.
+        // 8.  [writeable] Dispatched message PDA.
+        //     ---- if an IGP is configured ----
+        // 9.  [executable] The IGP program.
+        // 10. [writeable] The IGP program data.
+        // 11. [writeable] The gas payment PDA.
+        // 12. [] OPTIONAL - The Overhead IGP program, if the configured IGP is an Overhead IGP.
+        // 13. [writeable] The IGP account.
+        //     ---- end if an IGP is configured ----
+        let mut accounts = vec![
+            AccountMeta::new(program_storage_account, false),
+            AccountMeta::new_readonly(storage.mailbox, false),
+            AccountMeta::new(mailbox_outbox_account, false),
+            AccountMeta::new_readonly(dispatch_authority_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_noop::id(), false),
+            AccountMeta::new(sender, true),
+            AccountMeta::new_readonly(unique_message_account, true),
+            AccountMeta::new(dispatched_message_account, false),
+        ];
+        accounts.extend(igp_payment_account_metas(
+            ctx,
+            client,
+            storage.igp,
+            &unique_message_account,
+        ));
+
+        Instruction {
+            program_id,
+            data: HelloWorldInstruction::SendHelloWorld(payload)
+                .try_to_vec()
+                .unwrap(),
+            accounts,
+        }
+    }
+}
+
+impl RouterDeployer<HelloWorldConfig> for HelloWorldDeployer {
+    fn program_name(&self, _config: &HelloWorldConfig) -> &str {
+        "hyperlane_sealevel_hello_world"
+    }
+
     fn init_program_idempotent(
         &self,
         ctx: &mut Context,
@@ -0,0 +1,208 @@
+//! A JSON-RPC client over plain HTTP, which unlike `solana-client`'s
+//! `RpcClient` also builds for wasm32, where requests go through the
+//! browser's `fetch`. It covers what's needed to read accounts and to sign and
+//! send transactions built with the instruction builders.
+
+use std::fmt;
+
+use base64::Engine as _;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use solana_sdk::{
+    hash::Hash, instruction::Instruction, pubkey::Pubkey, signature::Signature, signers::Signers,
+    transaction::Transaction,
+};
+
+/// Why a request failed.
+#[derive(Debug)]
+pub enum HttpRpcError {
+    Http(reqwest::Error),
+    /// The RPC returned an error.
+    Rpc {
+        code: i64,
+        message: String,
+    },
+    /// The RPC returned a result that couldn't be parsed.
+    InvalidResponse(String),
+}
+
+impl fmt::Display for HttpRpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpRpcError::Http(err) => err.fmt(f),
+            HttpRpcError::Rpc { code, message } => write!(f, "RPC error {}: {}", code, message),
+            HttpRpcError::InvalidResponse(err) => write!(f, "invalid RPC response: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for HttpRpcError {}
+
+impl From<reqwest::Error> for HttpRpcError {
+    fn from(err: reqwest::Error) -> Self {
+        HttpRpcError::Http(err)
+    }
+}
+
+#[derive(Deserialize)]
+struct RpcResponse {
+    result: Option<Value>,
+    error: Option<RpcResponseError>,
+}
+
+#[derive(Deserialize)]
+struct RpcResponseError {
+    code: i64,
+    message: String,
+}
+
+/// A result along with the slot it was read at.
+#[derive(Deserialize)]
+struct WithContext<T> {
+    value: T,
+}
+
+/// The status of a sent transaction.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureStatus {
+    pub slot: u64,
+    /// `processed`, `confirmed` or `finalized`.
+    pub confirmation_status: Option<String>,
+    /// The error the transaction failed with, if it did.
+    pub err: Option<Value>,
+}
+
+/// Sends requests to a Solana JSON-RPC endpoint at a commitment level.
+pub struct HttpRpcClient {
+    url: String,
+    client: reqwest::Client,
+    /// `processed`, `confirmed` or `finalized`.
+    commitment: String,
+}
+
+impl HttpRpcClient {
+    pub fn new(url: impl Into<String>, commitment: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            client: reqwest::Client::new(),
+            commitment: commitment.into(),
+        }
+    }
+
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<T, HttpRpcError> {
+        let response: RpcResponse = self
+            .client
+            .post(&self.url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))
+            .send()
+            .await?
+            .json()
+            .await?;
+        if let Some(RpcResponseError { code, message }) = response.error {
+            return Err(HttpRpcError::Rpc { code, message });
+        }
+        serde_json::from_value(response.result.unwrap_or_default())
+            .map_err(|e| HttpRpcError::InvalidResponse(e.to_string()))
+    }
+
+    pub async fn get_latest_blockhash(&self) -> Result<Hash, HttpRpcError> {
+        #[derive(Deserialize)]
+        struct Blockhash {
+            blockhash: String,
+        }
+        let blockhash: WithContext<Blockhash> = self
+            .request(
+                "getLatestBlockhash",
+                json!([{ "commitment": self.commitment }]),
+            )
+            .await?;
+        blockhash
+            .value
+            .blockhash
+            .parse()
+            .map_err(|e| HttpRpcError::InvalidResponse(format!("invalid blockhash: {}", e)))
+    }
+
+    /// Gets the account's data, if the account exists.
+    pub async fn get_account_data(&self, pubkey: &Pubkey) -> Result<Option<Vec<u8>>, HttpRpcError> {
+        #[derive(Deserialize)]
+        struct Account {
+            /// The base64-encoded data and the encoding.
+            data: (String, String),
+        }
+        let account: WithContext<Option<Account>> = self
+            .request(
+                "getAccountInfo",
+                json!([
+                    pubkey.to_string(),
+                    { "encoding": "base64", "commitment": self.commitment },
+                ]),
+            )
+            .await?;
+        account
+            .value
+            .map(|account| {
+                base64::engine::general_purpose::STANDARD
+                    .decode(account.data.0)
+                    .map_err(|e| HttpRpcError::InvalidResponse(format!("invalid data: {}", e)))
+            })
+            .transpose()
+    }
+
+    /// Sends the signed transaction, returning its signature.
+    pub async fn send_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Signature, HttpRpcError> {
+        let transaction = base64::engine::general_purpose::STANDARD
+            .encode(bincode::serialize(transaction).expect("failed to serialize transaction"));
+        let signature: String = self
+            .request(
+                "sendTransaction",
+                json!([
+                    transaction,
+                    { "encoding": "base64", "preflightCommitment": self.commitment },
+                ]),
+            )
+            .await?;
+        signature
+            .parse()
+            .map_err(|e| HttpRpcError::InvalidResponse(format!("invalid signature: {}", e)))
+    }
+
+    /// Signs a transaction of the instructions with a recent blockhash and
+    /// sends it, returning its signature. It's confirmed once
+    /// `get_signature_status` shows the commitment level it's waited for.
+    pub async fn sign_and_send<T: Signers>(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+        signers: &T,
+    ) -> Result<Signature, HttpRpcError> {
+        let blockhash = self.get_latest_blockhash().await?;
+        let transaction =
+            Transaction::new_signed_with_payer(instructions, Some(payer), signers, blockhash);
+        self.send_transaction(&transaction).await
+    }
+
+    /// Gets the status of the transaction, if the RPC has seen it.
+    pub async fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<SignatureStatus>, HttpRpcError> {
+        let mut statuses: WithContext<Vec<Option<SignatureStatus>>> = self
+            .request("getSignatureStatuses", json!([[signature.to_string()]]))
+            .await?;
+        Ok(statuses.value.pop().flatten())
+    }
+}
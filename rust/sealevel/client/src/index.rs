@@ -0,0 +1,1399 @@
+//! A local SQLite index of mailboxes' dispatched and processed messages, so
+//! messages can be searched repeatedly without scanning the mailbox program's
+//! accounts each time. Syncing only fetches the transaction signatures of
+//! messages that aren't indexed yet.
+//!
+//! Dispatched messages are marked delivered if the destination mailbox's
+//! processed messages are indexed in the same database.
+//!
+//! The first sync of a mailbox scans all of its message accounts at once.
+//! Later syncs resume from the last scanned nonce and processed message
+//! sequence, fetching messages one by one and re-scanning a window before
+//! them in case they were rolled back. A sync can also backfill dispatched
+//! messages from the mailbox's transaction history, decoding them from the
+//! dispatched message accounts the mailbox logs through the SPL noop program.
+//! Later backfills stop at the newest transaction the last one walked.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufWriter, Write},
+    ops::Range,
+    path::Path,
+    sync::Arc,
+};
+
+use hyperlane_core::{Decode as _, HyperlaneMessage, H256};
+use parquet::{
+    data_type::{BoolType, ByteArray, ByteArrayType, DataType, Int32Type, Int64Type},
+    file::{
+        properties::WriterProperties,
+        writer::{SerializedFileWriter, SerializedRowGroupWriter},
+    },
+    schema::parser::parse_message_type,
+};
+use rusqlite::{params, Connection, OptionalExtension as _};
+use serde_json::json;
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::{
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{
+    account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+};
+
+use hyperlane_sealevel_mailbox::accounts::{
+    DispatchedMessage, DispatchedMessageAccount, ProcessedMessage, ProcessedMessageAccount,
+    DISPATCHED_MESSAGE_DISCRIMINATOR, PROCESSED_MESSAGE_DISCRIMINATOR,
+};
+
+use crate::{
+    accounts::{read_inbox, read_outbox},
+    events::{DispatchedEvent, HyperlaneEvent, ProcessedEvent},
+    logs::{dispatched_messages, get_transaction},
+    proof::outbox_proof,
+    resume::first_nonce_at_slot,
+    status::find_gas_payments,
+    strict::skip_or_fail,
+    Context, ExportFormat, IndexCmd, IndexExport, IndexProof, IndexQuery, IndexSubCmd, IndexSync,
+};
+
+/// The schema of Parquet exports. Unsigned integers are stored as signed ones
+/// of the same width, annotated as unsigned.
+const PARQUET_SCHEMA: &str = "
+message indexed_message {
+    REQUIRED BYTE_ARRAY mailbox (UTF8);
+    REQUIRED INT32 nonce (INTEGER(32, false));
+    REQUIRED BYTE_ARRAY message_id (UTF8);
+    REQUIRED INT32 version (INTEGER(8, false));
+    REQUIRED INT32 origin (INTEGER(32, false));
+    REQUIRED BYTE_ARRAY sender (UTF8);
+    REQUIRED INT32 destination (INTEGER(32, false));
+    REQUIRED BYTE_ARRAY recipient (UTF8);
+    REQUIRED BYTE_ARRAY body;
+    REQUIRED INT64 slot (INTEGER(64, false));
+    OPTIONAL BYTE_ARRAY signature (UTF8);
+    OPTIONAL INT64 gas_amount (INTEGER(64, false));
+    OPTIONAL INT64 gas_payment (INTEGER(64, false));
+    REQUIRED BOOLEAN delivered;
+    OPTIONAL INT64 delivery_slot (INTEGER(64, false));
+    OPTIONAL BYTE_ARRAY delivery_signature (UTF8);
+}
+";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS dispatched_messages (
+    mailbox TEXT NOT NULL,
+    nonce INTEGER NOT NULL,
+    message_id TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    origin INTEGER NOT NULL,
+    sender TEXT NOT NULL,
+    destination INTEGER NOT NULL,
+    recipient TEXT NOT NULL,
+    body BLOB NOT NULL,
+    slot INTEGER NOT NULL,
+    signature TEXT,
+    PRIMARY KEY (mailbox, nonce)
+);
+CREATE INDEX IF NOT EXISTS dispatched_messages_message_id
+    ON dispatched_messages (message_id);
+CREATE TABLE IF NOT EXISTS processed_messages (
+    mailbox TEXT NOT NULL,
+    sequence INTEGER NOT NULL,
+    message_id TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    signature TEXT,
+    PRIMARY KEY (mailbox, sequence)
+);
+CREATE INDEX IF NOT EXISTS processed_messages_message_id
+    ON processed_messages (message_id);
+CREATE TABLE IF NOT EXISTS sync_state (
+    mailbox TEXT PRIMARY KEY,
+    last_nonce INTEGER,
+    last_slot INTEGER,
+    last_sequence INTEGER,
+    last_backfill_signature TEXT
+);
+";
+
+pub(crate) fn process_index_cmd(ctx: Context, cmd: IndexCmd) {
+    match cmd.cmd {
+        IndexSubCmd::Sync(sync) => sync_index(&ctx, sync),
+        IndexSubCmd::Query(query) => query_index(&ctx, query),
+        IndexSubCmd::Proof(proof) => prove_message(&ctx, proof),
+        IndexSubCmd::Export(export) => export_index(&ctx, export),
+    }
+}
+
+/// A dispatched message as indexed, with its delivery if that's indexed too.
+#[derive(Debug)]
+pub(crate) struct IndexedMessage {
+    pub mailbox: String,
+    pub message: HyperlaneMessage,
+    pub message_id: H256,
+    pub slot: u64,
+    pub signature: Option<String>,
+    /// The slot and signature of the message's processing on the destination.
+    pub delivery: Option<(u64, Option<String>)>,
+}
+
+impl IndexedMessage {
+    /// The message's dispatch, followed by its delivery if indexed.
+    pub(crate) fn events(&self) -> Vec<HyperlaneEvent> {
+        let message = &self.message;
+        let dispatched = HyperlaneEvent::Dispatched(DispatchedEvent {
+            message_id: self.message_id,
+            nonce: message.nonce,
+            origin: message.origin,
+            destination: message.destination,
+            sender: message.sender,
+            recipient: message.recipient,
+            body: format!("0x{}", hex::encode(&message.body)),
+            slot: self.slot,
+            signature: self.signature.clone(),
+        });
+        let processed = self.delivery.as_ref().map(|(slot, signature)| {
+            HyperlaneEvent::Processed(ProcessedEvent {
+                message_id: self.message_id,
+                sequence: None,
+                slot: *slot,
+                signature: signature.clone(),
+            })
+        });
+        std::iter::once(dispatched).chain(processed).collect()
+    }
+}
+
+/// Which dispatched messages to query. Unset fields match any message.
+#[derive(Default)]
+pub(crate) struct MessageFilter {
+    pub mailbox: Option<Pubkey>,
+    pub nonce: Option<u32>,
+    pub message_id: Option<H256>,
+    pub sender: Option<H256>,
+    pub recipient: Option<H256>,
+    pub destination: Option<u32>,
+    pub undelivered: bool,
+    pub limit: usize,
+}
+
+/// How far a mailbox has been scanned.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct SyncState {
+    last_nonce: Option<u32>,
+    /// The slot of the message with the last nonce.
+    last_slot: Option<u64>,
+    last_sequence: Option<u64>,
+    /// The newest transaction of the mailbox's history a backfill walked,
+    /// where the next backfill stops.
+    last_backfill_signature: Option<String>,
+}
+
+pub(crate) struct MessageIndex {
+    conn: Connection,
+}
+
+impl MessageIndex {
+    /// Opens the index at `path`, creating it if it doesn't exist.
+    pub(crate) fn open(path: &Path) -> rusqlite::Result<Self> {
+        Self::new(Connection::open(path)?)
+    }
+
+    fn new(conn: Connection) -> rusqlite::Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        // Indexes created before backfills resumed lack the column.
+        if conn
+            .prepare("SELECT last_backfill_signature FROM sync_state")
+            .is_err()
+        {
+            conn.execute(
+                "ALTER TABLE sync_state ADD COLUMN last_backfill_signature TEXT",
+                [],
+            )?;
+        }
+        Ok(Self { conn })
+    }
+
+    fn sync_state(&self, mailbox: &Pubkey) -> rusqlite::Result<SyncState> {
+        self.conn
+            .query_row(
+                "SELECT last_nonce, last_slot, last_sequence, last_backfill_signature
+                 FROM sync_state WHERE mailbox = ?1",
+                [mailbox.to_string()],
+                |row| {
+                    Ok(SyncState {
+                        last_nonce: row.get(0)?,
+                        last_slot: row.get::<_, Option<i64>>(1)?.map(|slot| slot as u64),
+                        last_sequence: row
+                            .get::<_, Option<i64>>(2)?
+                            .map(|sequence| sequence as u64),
+                        last_backfill_signature: row.get(3)?,
+                    })
+                },
+            )
+            .optional()
+            .map(Option::unwrap_or_default)
+    }
+
+    fn set_sync_state(&self, mailbox: &Pubkey, state: &SyncState) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO sync_state
+             (mailbox, last_nonce, last_slot, last_sequence, last_backfill_signature)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                mailbox.to_string(),
+                state.last_nonce,
+                state.last_slot.map(|slot| slot as i64),
+                state.last_sequence.map(|sequence| sequence as i64),
+                state.last_backfill_signature,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Removes the mailbox's messages from `nonce` and processed messages
+    /// from `sequence` on, which no longer exist after a rollback.
+    fn remove_from(&self, mailbox: &Pubkey, nonce: u32, sequence: u64) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "DELETE FROM dispatched_messages WHERE mailbox = ?1 AND nonce >= ?2",
+            params![mailbox.to_string(), nonce],
+        )?;
+        self.conn.execute(
+            "DELETE FROM processed_messages WHERE mailbox = ?1 AND sequence >= ?2",
+            params![mailbox.to_string(), sequence as i64],
+        )?;
+        Ok(())
+    }
+
+    fn insert_dispatched(
+        &self,
+        mailbox: &Pubkey,
+        dispatched_message: &DispatchedMessage,
+        message: &HyperlaneMessage,
+        signature: Option<String>,
+    ) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO dispatched_messages
+             (mailbox, nonce, message_id, version, origin, sender, destination, recipient, body,
+              slot, signature)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                mailbox.to_string(),
+                dispatched_message.nonce,
+                format!("{:?}", message.id()),
+                message.version,
+                message.origin,
+                format!("{:?}", message.sender),
+                message.destination,
+                format!("{:?}", message.recipient),
+                message.body,
+                dispatched_message.slot as i64,
+                signature,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn insert_processed(
+        &self,
+        mailbox: &Pubkey,
+        processed_message: &ProcessedMessage,
+        signature: Option<String>,
+    ) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO processed_messages
+             (mailbox, sequence, message_id, slot, signature)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                mailbox.to_string(),
+                processed_message.sequence as i64,
+                format!("{:?}", processed_message.message_id),
+                processed_message.slot as i64,
+                signature,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// The dispatched messages matching the filter, most recent first.
+    pub(crate) fn query(&self, filter: &MessageFilter) -> rusqlite::Result<Vec<IndexedMessage>> {
+        let mut statement = self.conn.prepare(
+            "SELECT d.mailbox, d.version, d.nonce, d.origin, d.sender, d.destination,
+                    d.recipient, d.body, d.slot, d.signature, p.slot, p.signature
+             FROM dispatched_messages d
+             LEFT JOIN processed_messages p ON p.message_id = d.message_id
+             WHERE (?1 IS NULL OR d.mailbox = ?1)
+               AND (?2 IS NULL OR d.nonce = ?2)
+               AND (?3 IS NULL OR d.message_id = ?3)
+               AND (?4 IS NULL OR d.sender = ?4)
+               AND (?5 IS NULL OR d.recipient = ?5)
+               AND (?6 IS NULL OR d.destination = ?6)
+               AND (NOT ?7 OR p.message_id IS NULL)
+             ORDER BY d.slot DESC, d.nonce DESC
+             LIMIT ?8",
+        )?;
+        let rows = statement.query_map(
+            params![
+                filter.mailbox.map(|mailbox| mailbox.to_string()),
+                filter.nonce,
+                filter.message_id.map(|id| format!("{:?}", id)),
+                filter.sender.map(|sender| format!("{:?}", sender)),
+                filter.recipient.map(|recipient| format!("{:?}", recipient)),
+                filter.destination,
+                filter.undelivered,
+                filter.limit as i64,
+            ],
+            |row| {
+                let message = HyperlaneMessage {
+                    version: row.get(1)?,
+                    nonce: row.get(2)?,
+                    origin: row.get(3)?,
+                    sender: parse_h256(row.get_ref(4)?.as_str()?),
+                    destination: row.get(5)?,
+                    recipient: parse_h256(row.get_ref(6)?.as_str()?),
+                    body: row.get(7)?,
+                };
+                let delivery = match row.get::<_, Option<i64>>(10)? {
+                    Some(slot) => Some((slot as u64, row.get(11)?)),
+                    None => None,
+                };
+                Ok(IndexedMessage {
+                    mailbox: row.get(0)?,
+                    message_id: message.id(),
+                    message,
+                    slot: row.get::<_, i64>(8)? as u64,
+                    signature: row.get(9)?,
+                    delivery,
+                })
+            },
+        )?;
+        rows.collect()
+    }
+
+    /// The IDs of the mailbox's indexed dispatched messages, in nonce order,
+    /// as long as no nonce is missing.
+    pub(crate) fn message_ids(&self, mailbox: &Pubkey) -> rusqlite::Result<Vec<H256>> {
+        let mut statement = self.conn.prepare(
+            "SELECT nonce, message_id FROM dispatched_messages
+             WHERE mailbox = ?1
+             ORDER BY nonce",
+        )?;
+        let rows = statement.query_map(params![mailbox.to_string()], |row| {
+            Ok((row.get::<_, u32>(0)?, parse_h256(row.get_ref(1)?.as_str()?)))
+        })?;
+        let mut message_ids = vec![];
+        for row in rows {
+            let (nonce, message_id) = row?;
+            if nonce as usize != message_ids.len() {
+                break;
+            }
+            message_ids.push(message_id);
+        }
+        Ok(message_ids)
+    }
+
+    /// The nonces of the mailbox's indexed dispatched messages, in order.
+    pub(crate) fn nonces(&self, mailbox: &Pubkey) -> rusqlite::Result<Vec<u32>> {
+        let mut statement = self.conn.prepare(
+            "SELECT nonce FROM dispatched_messages
+             WHERE mailbox = ?1
+             ORDER BY nonce",
+        )?;
+        let rows = statement.query_map(params![mailbox.to_string()], |row| row.get(0))?;
+        rows.collect()
+    }
+}
+
+/// Hashes in the index are written by the index, so are always valid.
+fn parse_h256(s: &str) -> H256 {
+    s.parse().expect("Invalid hash in index")
+}
+
+/// Indexes the mailbox's dispatched and processed messages that aren't
+/// indexed yet.
+fn sync_index(ctx: &Context, sync: IndexSync) {
+    let index = MessageIndex::open(&sync.db)
+        .unwrap_or_else(|e| panic!("Failed to open index {}: {}", sync.db.display(), e));
+    let mailbox = sync.program_id;
+    let strict = ctx.is_strict(false);
+    let fetch_signature = !sync.no_signatures;
+    let mut state = index.sync_state(&mailbox).unwrap();
+
+    let outbox = read_outbox(ctx, &mailbox).expect("Mailbox not initialized");
+    let count = outbox.tree.count() as u32;
+    let processed_count = read_inbox(ctx, &mailbox)
+        .expect("Mailbox not initialized")
+        .processed_count;
+
+    // Index everything in one transaction, which is much faster than
+    // committing each message.
+    let tx = index.conn.unchecked_transaction().unwrap();
+    index.remove_from(&mailbox, count, processed_count).unwrap();
+    if state.last_nonce >= Some(count) {
+        state.last_nonce = count.checked_sub(1);
+        state.last_slot = None;
+    }
+    if state.last_sequence >= Some(processed_count) {
+        state.last_sequence = processed_count.checked_sub(1);
+    }
+
+    let dispatched_start = match state.last_nonce {
+        None if !sync.resume.is_override() => None,
+        last_nonce => Some(sync.resume.start_nonce(last_nonce, |slot| {
+            first_nonce_at_slot(
+                &ctx.client,
+                &ctx.protocol,
+                &mailbox,
+                ctx.commitment,
+                strict,
+                count,
+                slot,
+            )
+        })),
+    };
+    let mut dispatched_indexed = 0;
+    for (pubkey, account) in message_accounts(
+        &ctx.client,
+        &mailbox,
+        DISPATCHED_MESSAGE_DISCRIMINATOR,
+        dispatched_start.map(|start| MessageKey::Nonce(start..count)),
+        ctx.commitment,
+    ) {
+        let Some(dispatched_message) = skip_or_fail(
+            strict,
+            DispatchedMessageAccount::fetch(&mut &account.data[..]),
+            || format!("dispatched message account {}", pubkey),
+        ) else {
+            continue;
+        };
+        let dispatched_message = dispatched_message.into_inner();
+        let Some(message) = skip_or_fail(
+            strict,
+            HyperlaneMessage::read_from(&mut &dispatched_message.encoded_message[..]),
+            || format!("message in dispatched message account {}", pubkey),
+        ) else {
+            continue;
+        };
+        let signature = fetch_signature
+            .then(|| first_signature(&ctx.client, &pubkey))
+            .flatten();
+        index
+            .insert_dispatched(&mailbox, &dispatched_message, &message, signature)
+            .unwrap();
+        dispatched_indexed += 1;
+        if state.last_nonce <= Some(dispatched_message.nonce) {
+            state.last_nonce = Some(dispatched_message.nonce);
+            state.last_slot = Some(dispatched_message.slot);
+        }
+    }
+
+    let mut backfilled = 0;
+    if sync.backfill {
+        let until = state.last_backfill_signature.as_ref().map(|signature| {
+            signature
+                .parse::<Signature>()
+                .expect("Invalid signature in index")
+        });
+        let history =
+            dispatched_messages_from_history(&ctx.client, &mailbox, ctx.commitment, until);
+        match history.error {
+            // Messages recovered before the error are still indexed, but the
+            // next backfill walks the rest of the history again.
+            Some(e) => eprintln!(
+                "Warning: the backfill stopped early, failed to get the mailbox's transaction \
+                 history: {}",
+                e
+            ),
+            None => {
+                if let Some(newest_signature) = history.newest_signature {
+                    state.last_backfill_signature = Some(newest_signature);
+                }
+            }
+        }
+        for (dispatched_message, signature) in history.recovered {
+            let Some(message) = skip_or_fail(
+                strict,
+                HyperlaneMessage::read_from(&mut &dispatched_message.encoded_message[..]),
+                || format!("message logged by transaction {}", signature),
+            ) else {
+                continue;
+            };
+            // Messages rolled back since can still be in the history.
+            if message.origin != outbox.local_domain || message.nonce >= count {
+                continue;
+            }
+            index
+                .insert_dispatched(&mailbox, &dispatched_message, &message, Some(signature))
+                .unwrap();
+            backfilled += 1;
+            if state.last_nonce <= Some(dispatched_message.nonce) {
+                state.last_nonce = Some(dispatched_message.nonce);
+                state.last_slot = Some(dispatched_message.slot);
+            }
+        }
+    }
+
+    let processed_start = state.last_sequence.map(|last_sequence| {
+        (last_sequence + 1).saturating_sub(u64::from(sync.resume.rescan_window))
+    });
+    let mut processed_indexed = 0;
+    for (pubkey, account) in message_accounts(
+        &ctx.client,
+        &mailbox,
+        PROCESSED_MESSAGE_DISCRIMINATOR,
+        processed_start.map(|start| MessageKey::Sequence(start..processed_count)),
+        ctx.commitment,
+    ) {
+        let Some(processed_message) = skip_or_fail(
+            strict,
+            ProcessedMessageAccount::fetch(&mut &account.data[..]),
+            || format!("processed message account {}", pubkey),
+        ) else {
+            continue;
+        };
+        let processed_message = processed_message.into_inner();
+        let signature = fetch_signature
+            .then(|| first_signature(&ctx.client, &pubkey))
+            .flatten();
+        index
+            .insert_processed(&mailbox, &processed_message, signature)
+            .unwrap();
+        processed_indexed += 1;
+        state.last_sequence = state.last_sequence.max(Some(processed_message.sequence));
+    }
+
+    index.set_sync_state(&mailbox, &state).unwrap();
+    tx.commit().unwrap();
+
+    println!(
+        "Indexed {} dispatched and {} processed messages of mailbox {} into {}",
+        dispatched_indexed,
+        processed_indexed,
+        mailbox,
+        sync.db.display()
+    );
+    if sync.backfill {
+        println!(
+            "Backfilled {} dispatched messages from the transaction history",
+            backfilled
+        );
+    }
+    if let (Some(last_nonce), Some(last_slot)) = (state.last_nonce, state.last_slot) {
+        println!("Scanned up to nonce {} (slot {})", last_nonce, last_slot);
+    }
+}
+
+/// The end of the key of message accounts, which follows the initialized flag
+/// and the discriminator and is at most 8 bytes long.
+const MESSAGE_KEY_END: usize = 1 + 8 + 8;
+
+/// The most accounts `getMultipleAccounts` returns per request.
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// The keys of the message accounts to index: dispatched message accounts
+/// are keyed by nonce and processed message accounts by sequence.
+enum MessageKey {
+    Nonce(Range<u32>),
+    Sequence(Range<u64>),
+}
+
+impl MessageKey {
+    /// Whether the account data's key, following the initialized flag and the
+    /// discriminator, is in the range.
+    fn contains(&self, data: &[u8]) -> bool {
+        let key = data.get(1 + 8..).unwrap_or_default();
+        match self {
+            Self::Nonce(range) => key
+                .get(..4)
+                .and_then(|key| key.try_into().ok())
+                .map_or(false, |key| range.contains(&u32::from_le_bytes(key))),
+            Self::Sequence(range) => key
+                .get(..8)
+                .and_then(|key| key.try_into().ok())
+                .map_or(false, |key| range.contains(&u64::from_le_bytes(key))),
+        }
+    }
+}
+
+/// The mailbox's message accounts with the discriminator, fetched in a single
+/// scan. If keys are given, only the accounts with a key in the range are
+/// fetched: the scan then only returns the leading bytes up to the key, and
+/// the accounts with a key in the range are fetched in batches. They're
+/// filtered here rather than by the RPC, which can only match a single key per
+/// request.
+fn message_accounts(
+    client: &RpcClient,
+    mailbox: &Pubkey,
+    discriminator: &[u8; 8],
+    keys: Option<MessageKey>,
+    commitment: CommitmentConfig,
+) -> Vec<(Pubkey, Account)> {
+    // The initialized flag precedes the discriminator.
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+            1,
+            discriminator.to_vec(),
+        ))]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: keys.as_ref().map(|_| UiDataSliceConfig {
+                offset: 0,
+                length: MESSAGE_KEY_END,
+            }),
+            commitment: Some(commitment),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    let accounts = client
+        .get_program_accounts_with_config(mailbox, config)
+        .unwrap();
+    let Some(keys) = keys else {
+        return accounts;
+    };
+    let pubkeys = accounts
+        .into_iter()
+        .filter(|(_, account)| keys.contains(&account.data))
+        .map(|(pubkey, _)| pubkey)
+        .collect::<Vec<_>>();
+    pubkeys
+        .chunks(MAX_MULTIPLE_ACCOUNTS)
+        .flat_map(|pubkeys| {
+            let accounts = client
+                .get_multiple_accounts_with_commitment(pubkeys, commitment)
+                .unwrap()
+                .value;
+            // Accounts closed since the scan are skipped.
+            pubkeys
+                .iter()
+                .zip(accounts)
+                .filter_map(|(pubkey, account)| account.map(|account| (*pubkey, account)))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// The messages the mailbox dispatched, recovered from its transaction
+/// history.
+struct History {
+    /// The messages, newest first, along with the signatures of their
+    /// transactions.
+    recovered: Vec<(DispatchedMessage, String)>,
+    /// The newest transaction walked, if there were any new ones.
+    newest_signature: Option<String>,
+    /// The RPC error the walk stopped at, before reaching the end of the
+    /// history.
+    error: Option<String>,
+}
+
+/// Walks the mailbox's transaction history from the newest transaction back
+/// to `until`, or to the oldest one if `until` isn't given, recovering the
+/// messages it dispatched.
+fn dispatched_messages_from_history(
+    client: &RpcClient,
+    mailbox: &Pubkey,
+    commitment: CommitmentConfig,
+    until: Option<Signature>,
+) -> History {
+    let mut history = History {
+        recovered: vec![],
+        newest_signature: None,
+        error: None,
+    };
+    let mut before = None;
+    loop {
+        let statuses = match client.get_signatures_for_address_with_config(
+            mailbox,
+            GetConfirmedSignaturesForAddress2Config {
+                before,
+                until,
+                limit: None,
+                commitment: Some(commitment),
+            },
+        ) {
+            Ok(statuses) => statuses,
+            Err(e) => {
+                history.error = Some(e.to_string());
+                break;
+            }
+        };
+        let Some(oldest) = statuses.last() else {
+            break;
+        };
+        before = Some(oldest.signature.parse::<Signature>().unwrap());
+        if history.newest_signature.is_none() {
+            history.newest_signature = statuses.first().map(|status| status.signature.clone());
+        }
+        for status in statuses.into_iter().filter(|status| status.err.is_none()) {
+            let signature = status.signature.parse::<Signature>().unwrap();
+            let txn = match get_transaction(client, &signature, commitment) {
+                Ok(txn) => txn,
+                Err(e) => {
+                    history.error = Some(format!("transaction {}: {}", signature, e));
+                    return history;
+                }
+            };
+            history.recovered.extend(
+                dispatched_messages(&txn)
+                    .into_iter()
+                    .map(|dispatched_message| (dispatched_message, status.signature.clone())),
+            );
+        }
+    }
+    history
+}
+
+/// The signature of the transaction that created the account, which is its
+/// oldest. Dispatched and processed message accounts are only written to by
+/// the transaction creating them.
+fn first_signature(client: &RpcClient, pubkey: &Pubkey) -> Option<String> {
+    client
+        .get_signatures_for_address(pubkey)
+        .ok()?
+        .pop()
+        .map(|status| status.signature)
+}
+
+fn query_index(ctx: &Context, query: IndexQuery) {
+    let index = MessageIndex::open(&query.db)
+        .unwrap_or_else(|e| panic!("Failed to open index {}: {}", query.db.display(), e));
+    let filter = MessageFilter {
+        mailbox: query.program_id,
+        nonce: query.nonce,
+        message_id: query.message_id,
+        sender: query
+            .sender
+            .as_ref()
+            .map(|sender| ctx.resolve_address(sender)),
+        recipient: query
+            .recipient
+            .as_ref()
+            .map(|recipient| ctx.resolve_address(recipient)),
+        destination: query
+            .destination
+            .as_ref()
+            .map(|destination| ctx.domains.resolve(destination)),
+        undelivered: query.undelivered,
+        limit: query.limit,
+    };
+    let messages = index.query(&filter).unwrap();
+    for indexed in &messages {
+        if query.json {
+            for event in indexed.events() {
+                println!("{}", event.to_json_line());
+            }
+            continue;
+        }
+        println!(
+            "Nonce {} ({}): {:?}",
+            indexed.message.nonce, indexed.mailbox, indexed.message_id
+        );
+        println!(
+            "\t{} -> {}, sender {:?}, recipient {:?}",
+            indexed.message.origin,
+            indexed.message.destination,
+            indexed.message.sender,
+            indexed.message.recipient
+        );
+        println!(
+            "\tDispatched: slot {}{}",
+            indexed.slot,
+            fmt_signature(indexed.signature.as_deref())
+        );
+        match &indexed.delivery {
+            Some((slot, signature)) => println!(
+                "\tDelivered: slot {}{}",
+                slot,
+                fmt_signature(signature.as_deref())
+            ),
+            None => println!("\tDelivered: not indexed"),
+        }
+    }
+    if !query.json {
+        println!("{} messages", messages.len());
+    }
+}
+
+/// Proves the message against the tree of the mailbox's indexed messages,
+/// checking the tree against the outbox if the index is up to date.
+fn prove_message(ctx: &Context, proof: IndexProof) {
+    let index = MessageIndex::open(&proof.db)
+        .unwrap_or_else(|e| panic!("Failed to open index {}: {}", proof.db.display(), e));
+    let indexed = index
+        .query(&MessageFilter {
+            mailbox: Some(proof.program_id),
+            message_id: Some(proof.message_id),
+            limit: 1,
+            ..MessageFilter::default()
+        })
+        .unwrap()
+        .pop()
+        .unwrap_or_else(|| {
+            panic!(
+                "Message {:?} of mailbox {} isn't indexed",
+                proof.message_id, proof.program_id
+            )
+        });
+
+    let mut message_ids = index.message_ids(&proof.program_id).unwrap();
+    let count = proof
+        .count
+        .map_or(message_ids.len(), |count| count as usize);
+    if count > message_ids.len() {
+        panic!(
+            "Only the first {} messages are indexed without gaps, sync the index to prove \
+             against {}",
+            message_ids.len(),
+            count
+        );
+    }
+    message_ids.truncate(count);
+    let merkle_proof = outbox_proof(&message_ids, indexed.message.nonce).unwrap_or_else(|| {
+        panic!(
+            "Message nonce {} isn't in the tree of {} messages",
+            indexed.message.nonce, count
+        )
+    });
+    let root = merkle_proof.root();
+
+    if let Some(outbox) = read_outbox(ctx, &proof.program_id) {
+        if outbox.tree.count() == count {
+            assert_eq!(
+                root,
+                outbox.tree.root(),
+                "The indexed messages don't match the outbox's tree"
+            );
+        } else if proof.count.is_none() {
+            println!(
+                "The outbox has {} messages, {} are indexed. Proving against the root of the \
+                 indexed ones.",
+                outbox.tree.count(),
+                count
+            );
+        }
+    }
+
+    if proof.json {
+        println!(
+            "{}",
+            json!({
+                "messageId": format!("{:?}", merkle_proof.leaf),
+                "leafIndex": merkle_proof.index,
+                "count": count,
+                "root": format!("{:?}", root),
+                "path": merkle_proof
+                    .path
+                    .iter()
+                    .map(|node| format!("{:?}", node))
+                    .collect::<Vec<_>>(),
+            })
+        );
+        return;
+    }
+    println!("Message ID: {:?}", merkle_proof.leaf);
+    println!("Leaf index: {}", merkle_proof.index);
+    println!("Root: {:?} (count {})", root, count);
+    println!("Path:");
+    for node in merkle_proof.path.iter() {
+        println!("\t{:?}", node);
+    }
+}
+
+fn export_index(ctx: &Context, export: IndexExport) {
+    let index = MessageIndex::open(&export.db)
+        .unwrap_or_else(|e| panic!("Failed to open index {}: {}", export.db.display(), e));
+    let mut messages = index
+        .query(&MessageFilter {
+            mailbox: export.program_id,
+            limit: i64::MAX as usize,
+            ..MessageFilter::default()
+        })
+        .unwrap();
+    messages.reverse();
+    // The gas amount and payment of the message's gas payments, summed.
+    let gas_payments_by_message_id = export.igp_program_id.map(|igp_program_id| {
+        let mut gas_payments_by_message_id = HashMap::<H256, (u64, u64)>::new();
+        for gas_payment in find_gas_payments(
+            &ctx.client,
+            &igp_program_id,
+            ctx.commitment,
+            ctx.is_strict(false),
+        ) {
+            let (gas_amount, payment) = gas_payments_by_message_id
+                .entry(gas_payment.message_id)
+                .or_default();
+            *gas_amount += gas_payment.gas_amount;
+            *payment += gas_payment.payment;
+        }
+        gas_payments_by_message_id
+    });
+    let gas_payments = messages
+        .iter()
+        .map(|indexed| {
+            gas_payments_by_message_id
+                .as_ref()
+                .map(|gas_payments_by_message_id| {
+                    gas_payments_by_message_id
+                        .get(&indexed.message_id)
+                        .copied()
+                        .unwrap_or_default()
+                })
+        })
+        .collect::<Vec<_>>();
+
+    let file = File::create(&export.out)
+        .unwrap_or_else(|e| panic!("Failed to create {}: {}", export.out.display(), e));
+    let mut writer = BufWriter::new(file);
+    let written = match export.format {
+        ExportFormat::Csv => write_csv(&mut writer, &messages, &gas_payments),
+        ExportFormat::Parquet => write_parquet(&mut writer, &messages, &gas_payments)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e)),
+    };
+    written
+        .and_then(|()| writer.flush())
+        .unwrap_or_else(|e| panic!("Failed to write {}: {}", export.out.display(), e));
+    println!(
+        "Exported {} messages to {}",
+        messages.len(),
+        export.out.display()
+    );
+}
+
+/// Writes the messages as CSV, with their gas payments if looked up. None of
+/// the fields, numbers, hex and base58 strings, need quoting.
+fn write_csv(
+    writer: &mut impl Write,
+    messages: &[IndexedMessage],
+    gas_payments: &[Option<(u64, u64)>],
+) -> io::Result<()> {
+    writeln!(
+        writer,
+        "mailbox,nonce,message_id,version,origin,sender,destination,recipient,body,slot,\
+         signature,gas_amount,gas_payment,delivered,delivery_slot,delivery_signature"
+    )?;
+    for (indexed, gas_payment) in messages.iter().zip(gas_payments) {
+        let message = &indexed.message;
+        let (gas_amount, payment) = match gas_payment {
+            Some((gas_amount, payment)) => (gas_amount.to_string(), payment.to_string()),
+            None => Default::default(),
+        };
+        let (delivery_slot, delivery_signature) = match &indexed.delivery {
+            Some((slot, signature)) => (slot.to_string(), signature.clone().unwrap_or_default()),
+            None => Default::default(),
+        };
+        writeln!(
+            writer,
+            "{},{},{:?},{},{},{:?},{},{:?},0x{},{},{},{},{},{},{},{}",
+            indexed.mailbox,
+            message.nonce,
+            indexed.message_id,
+            message.version,
+            message.origin,
+            message.sender,
+            message.destination,
+            message.recipient,
+            hex::encode(&message.body),
+            indexed.slot,
+            indexed.signature.as_deref().unwrap_or_default(),
+            gas_amount,
+            payment,
+            indexed.delivery.is_some(),
+            delivery_slot,
+            delivery_signature
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes the messages as a Parquet file of a single row group, with the
+/// columns of the CSV export. Message bodies are written as bytes.
+fn write_parquet(
+    writer: &mut (impl Write + Send),
+    messages: &[IndexedMessage],
+    gas_payments: &[Option<(u64, u64)>],
+) -> parquet::errors::Result<()> {
+    let schema = Arc::new(parse_message_type(PARQUET_SCHEMA)?);
+    let mut file_writer = SerializedFileWriter::new(
+        writer,
+        schema,
+        Arc::new(WriterProperties::builder().build()),
+    )?;
+    let mut row_group = file_writer.next_row_group()?;
+
+    let string = |value: String| ByteArray::from(value.into_bytes());
+    write_column::<ByteArrayType, _>(
+        &mut row_group,
+        messages
+            .iter()
+            .map(|indexed| string(indexed.mailbox.clone()))
+            .map(Some),
+    )?;
+    write_column::<Int32Type, _>(
+        &mut row_group,
+        messages
+            .iter()
+            .map(|indexed| indexed.message.nonce as i32)
+            .map(Some),
+    )?;
+    write_column::<ByteArrayType, _>(
+        &mut row_group,
+        messages
+            .iter()
+            .map(|indexed| string(format!("{:?}", indexed.message_id)))
+            .map(Some),
+    )?;
+    write_column::<Int32Type, _>(
+        &mut row_group,
+        messages
+            .iter()
+            .map(|indexed| indexed.message.version as i32)
+            .map(Some),
+    )?;
+    write_column::<Int32Type, _>(
+        &mut row_group,
+        messages
+            .iter()
+            .map(|indexed| indexed.message.origin as i32)
+            .map(Some),
+    )?;
+    write_column::<ByteArrayType, _>(
+        &mut row_group,
+        messages
+            .iter()
+            .map(|indexed| string(format!("{:?}", indexed.message.sender)))
+            .map(Some),
+    )?;
+    write_column::<Int32Type, _>(
+        &mut row_group,
+        messages
+            .iter()
+            .map(|indexed| indexed.message.destination as i32)
+            .map(Some),
+    )?;
+    write_column::<ByteArrayType, _>(
+        &mut row_group,
+        messages
+            .iter()
+            .map(|indexed| string(format!("{:?}", indexed.message.recipient)))
+            .map(Some),
+    )?;
+    write_column::<ByteArrayType, _>(
+        &mut row_group,
+        messages
+            .iter()
+            .map(|indexed| ByteArray::from(indexed.message.body.clone()))
+            .map(Some),
+    )?;
+    write_column::<Int64Type, _>(
+        &mut row_group,
+        messages.iter().map(|indexed| indexed.slot as i64).map(Some),
+    )?;
+    write_column::<ByteArrayType, _>(
+        &mut row_group,
+        messages
+            .iter()
+            .map(|indexed| indexed.signature.clone().map(string)),
+    )?;
+    write_column::<Int64Type, _>(
+        &mut row_group,
+        gas_payments
+            .iter()
+            .map(|gas_payment| gas_payment.map(|(gas_amount, _)| gas_amount as i64)),
+    )?;
+    write_column::<Int64Type, _>(
+        &mut row_group,
+        gas_payments
+            .iter()
+            .map(|gas_payment| gas_payment.map(|(_, payment)| payment as i64)),
+    )?;
+    write_column::<BoolType, _>(
+        &mut row_group,
+        messages
+            .iter()
+            .map(|indexed| indexed.delivery.is_some())
+            .map(Some),
+    )?;
+    write_column::<Int64Type, _>(
+        &mut row_group,
+        messages
+            .iter()
+            .map(|indexed| indexed.delivery.as_ref().map(|(slot, _)| *slot as i64)),
+    )?;
+    write_column::<ByteArrayType, _>(
+        &mut row_group,
+        messages.iter().map(|indexed| {
+            indexed
+                .delivery
+                .as_ref()
+                .and_then(|(_, signature)| signature.clone())
+                .map(string)
+        }),
+    )?;
+
+    row_group.close()?;
+    file_writer.close()?;
+    Ok(())
+}
+
+/// Writes the next column of the row group, with `None` values as nulls.
+fn write_column<T: DataType, W: Write + Send>(
+    row_group: &mut SerializedRowGroupWriter<'_, W>,
+    values: impl Iterator<Item = Option<T::T>>,
+) -> parquet::errors::Result<()> {
+    let mut column = row_group
+        .next_column()?
+        .expect("More columns written than in the schema");
+    let (def_levels, values): (Vec<i16>, Vec<Option<T::T>>) =
+        values.map(|value| (value.is_some() as i16, value)).unzip();
+    column.typed::<T>().write_batch(
+        &values.into_iter().flatten().collect::<Vec<_>>(),
+        Some(&def_levels),
+        None,
+    )?;
+    column.close()
+}
+
+fn fmt_signature(signature: Option<&str>) -> String {
+    signature
+        .map(|signature| format!(", transaction {}", signature))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use parquet::{
+        file::reader::{FileReader as _, SerializedFileReader},
+        record::Field,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_query_joins_deliveries() {
+        let index = MessageIndex::new(Connection::open_in_memory().unwrap()).unwrap();
+        let origin_mailbox = Pubkey::new_unique();
+        let destination_mailbox = Pubkey::new_unique();
+        let messages = (0..2)
+            .map(|nonce| HyperlaneMessage {
+                version: 3,
+                nonce,
+                origin: 1,
+                sender: H256::repeat_byte(1),
+                destination: 2,
+                recipient: H256::repeat_byte(2 + nonce as u8),
+                body: vec![nonce as u8],
+            })
+            .collect::<Vec<_>>();
+        for message in &messages {
+            // Only the dispatched message's nonce and slot are indexed.
+            let dispatched_message = DispatchedMessage::new(
+                message.nonce,
+                10 + u64::from(message.nonce),
+                Pubkey::new_unique(),
+                vec![],
+            );
+            index
+                .insert_dispatched(&origin_mailbox, &dispatched_message, message, None)
+                .unwrap();
+        }
+        index
+            .insert_processed(
+                &destination_mailbox,
+                &ProcessedMessage::new(0, messages[0].id(), 20),
+                Some("signature".to_owned()),
+            )
+            .unwrap();
+
+        let all = index
+            .query(&MessageFilter {
+                limit: 10,
+                ..MessageFilter::default()
+            })
+            .unwrap();
+        assert_eq!(all.len(), 2);
+        // Most recent first.
+        assert_eq!(all[0].message_id, messages[1].id());
+        assert_eq!(all[0].delivery, None);
+        assert_eq!(all[1].message_id, messages[0].id());
+        assert_eq!(all[1].delivery, Some((20, Some("signature".to_owned()))));
+
+        let undelivered = index
+            .query(&MessageFilter {
+                mailbox: Some(origin_mailbox),
+                undelivered: true,
+                limit: 10,
+                ..MessageFilter::default()
+            })
+            .unwrap();
+        assert_eq!(undelivered.len(), 1);
+        assert_eq!(undelivered[0].message.nonce, 1);
+
+        let by_recipient = index
+            .query(&MessageFilter {
+                recipient: Some(messages[0].recipient),
+                limit: 10,
+                ..MessageFilter::default()
+            })
+            .unwrap();
+        assert_eq!(by_recipient.len(), 1);
+        assert_eq!(by_recipient[0].message.nonce, 0);
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let message = HyperlaneMessage {
+            nonce: 3,
+            body: vec![0xab],
+            ..HyperlaneMessage::default()
+        };
+        let messages = vec![IndexedMessage {
+            mailbox: "mailbox".to_owned(),
+            message_id: message.id(),
+            message,
+            slot: 10,
+            signature: Some("signature".to_owned()),
+            delivery: Some((20, None)),
+        }];
+        let mut csv = vec![];
+        write_csv(&mut csv, &messages, &[Some((1000, 5))]).unwrap();
+
+        let csv = String::from_utf8(csv).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        let header = lines[0].split(',').collect::<Vec<_>>();
+        let row = lines[1].split(',').collect::<Vec<_>>();
+        assert_eq!(header.len(), row.len());
+        let field = |name| row[header.iter().position(|column| *column == name).unwrap()];
+        assert_eq!(field("nonce"), "3");
+        assert_eq!(field("body"), "0xab");
+        assert_eq!(field("gas_amount"), "1000");
+        assert_eq!(field("gas_payment"), "5");
+        assert_eq!(field("delivered"), "true");
+        assert_eq!(field("delivery_slot"), "20");
+        assert_eq!(field("delivery_signature"), "");
+    }
+
+    #[test]
+    fn test_write_parquet() {
+        let message = HyperlaneMessage {
+            nonce: 3,
+            body: vec![0xab],
+            ..HyperlaneMessage::default()
+        };
+        let messages = vec![IndexedMessage {
+            mailbox: "mailbox".to_owned(),
+            message_id: message.id(),
+            message,
+            slot: u64::MAX,
+            signature: None,
+            delivery: None,
+        }];
+        let path = std::env::temp_dir().join(format!("index-{}.parquet", std::process::id()));
+        let mut file = File::create(&path).unwrap();
+        write_parquet(&mut file, &messages, &[Some((1000, 5))]).unwrap();
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let rows = reader
+            .get_row_iter(None)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(rows.len(), 1);
+        let field = |name| {
+            rows[0]
+                .get_column_iter()
+                .find(|(column, _)| *column == name)
+                .map(|(_, field)| field.clone())
+                .unwrap()
+        };
+        assert_eq!(field("nonce"), Field::UInt(3));
+        assert_eq!(field("body"), Field::Bytes(vec![0xab].into()));
+        assert_eq!(field("slot"), Field::ULong(u64::MAX));
+        assert_eq!(field("signature"), Field::Null);
+        assert_eq!(field("gas_amount"), Field::ULong(1000));
+        assert_eq!(field("delivered"), Field::Bool(false));
+    }
+
+    #[test]
+    fn test_message_key_contains() {
+        let dispatched = |nonce| {
+            let mut data = vec![0; 1 + 8 + 4 + 8 + 32 + 4];
+            DispatchedMessageAccount::from(DispatchedMessage::new(
+                nonce,
+                1234,
+                Pubkey::new_unique(),
+                vec![],
+            ))
+            .store_in_slice(&mut data)
+            .unwrap();
+            data
+        };
+        let keys = MessageKey::Nonce(3..5);
+        assert!(!keys.contains(&dispatched(2)));
+        assert!(keys.contains(&dispatched(3)));
+        assert!(keys.contains(&dispatched(4)));
+        assert!(!keys.contains(&dispatched(5)));
+        assert!(!keys.contains(&[1]));
+
+        let mut processed = vec![0; 1 + 8 + 8 + 32 + 8];
+        ProcessedMessageAccount::from(ProcessedMessage::new(7, H256::zero(), 1234))
+            .store_in_slice(&mut processed)
+            .unwrap();
+        assert!(MessageKey::Sequence(0..8).contains(&processed));
+        assert!(!MessageKey::Sequence(8..9).contains(&processed));
+    }
+
+    #[test]
+    fn test_sync_state() {
+        let index = MessageIndex::new(Connection::open_in_memory().unwrap()).unwrap();
+        let mailbox = Pubkey::new_unique();
+        assert_eq!(index.sync_state(&mailbox).unwrap(), SyncState::default());
+        let state = SyncState {
+            last_nonce: Some(7),
+            last_slot: Some(1234),
+            last_sequence: None,
+            last_backfill_signature: Some(Signature::new_unique().to_string()),
+        };
+        index.set_sync_state(&mailbox, &state).unwrap();
+        assert_eq!(index.sync_state(&mailbox).unwrap(), state);
+        assert_eq!(
+            index.sync_state(&Pubkey::new_unique()).unwrap(),
+            SyncState::default()
+        );
+    }
+
+    #[test]
+    fn test_sync_state_of_index_without_backfill_signature() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE sync_state (
+                mailbox TEXT PRIMARY KEY,
+                last_nonce INTEGER,
+                last_slot INTEGER,
+                last_sequence INTEGER
+            );",
+        )
+        .unwrap();
+        let mailbox = Pubkey::new_unique();
+        conn.execute(
+            "INSERT INTO sync_state VALUES (?1, 7, 1234, NULL)",
+            [mailbox.to_string()],
+        )
+        .unwrap();
+
+        let index = MessageIndex::new(conn).unwrap();
+        assert_eq!(
+            index.sync_state(&mailbox).unwrap(),
+            SyncState {
+                last_nonce: Some(7),
+                last_slot: Some(1234),
+                last_sequence: None,
+                last_backfill_signature: None,
+            }
+        );
+    }
+}
@@ -0,0 +1,59 @@
+//! Instruction builders shared by the CLI and web dashboards that construct
+//! the same transactions.
+
+use hyperlane_core::H256;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+use hyperlane_sealevel_mailbox::{
+    instruction::{Instruction as MailboxInstruction, OutboxDispatch},
+    spl_noop,
+};
+
+use crate::protocol::ProtocolConfig;
+
+/// Creates an OutboxDispatch instruction where the payer is the message sender.
+pub fn outbox_dispatch_instruction(
+    protocol: &ProtocolConfig,
+    program_id: Pubkey,
+    payer: Pubkey,
+    unique_message_account: Pubkey,
+    destination_domain: u32,
+    recipient: H256,
+    message_body: Vec<u8>,
+) -> Instruction {
+    let (outbox_account, _outbox_bump) = protocol.outbox_pda(&program_id);
+    let (dispatched_message_account, _dispatched_message_bump) =
+        protocol.dispatched_message_pda(&program_id, &unique_message_account);
+
+    let ixn = MailboxInstruction::OutboxDispatch(OutboxDispatch {
+        sender: payer,
+        destination_domain,
+        recipient,
+        message_body,
+    });
+
+    // 0. [writeable] Outbox PDA.
+    // 1. [signer] Message sender signer.
+    // 2. [executable] System program.
+    // 3. [executable] SPL Noop program.
+    // 4. [signer] Payer.
+    // 5. [signer] Unique message account.
+    // 6. [writeable] Dispatched message PDA.
+    Instruction {
+        program_id,
+        data: ixn.into_instruction_data().unwrap(),
+        accounts: vec![
+            AccountMeta::new(outbox_account, false),
+            AccountMeta::new_readonly(payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_noop::id(), false),
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(unique_message_account, true),
+            AccountMeta::new(dispatched_message_account, false),
+        ],
+    }
+}
@@ -0,0 +1,99 @@
+//! Introspection of the ISM protecting a recipient: which ISM the mailbox
+//! verifies the recipient's messages with, what kind of ISM it is and how it's
+//! currently configured.
+
+use hyperlane_core::ModuleType;
+use solana_program::pubkey::Pubkey;
+
+use hyperlane_sealevel_multisig_ism_message_id::{accounts::DomainData, domain_data_pda_seeds};
+use hyperlane_sealevel_test_ism::{instruction::storage_pda_key, program::TestIsmStorage};
+
+use crate::{
+    accounts::{read_inbox, read_program_account},
+    process::{ism_module_type, recipient_ism},
+    Context, IsmInspect,
+};
+
+/// Prints the recipient's ISM, its module type and its parameters: the
+/// validators and threshold for each of the given origins for multisig ISMs,
+/// or whether the test ISM accepts messages.
+pub(crate) fn inspect_ism(ctx: &Context, inspect: IsmInspect) {
+    let recipient = Pubkey::new_from_array(ctx.resolve_address(&inspect.recipient).0);
+    let ism = recipient_ism(
+        &ctx.client,
+        ctx.commitment,
+        &ctx.protocol,
+        inspect.mailbox,
+        ctx.payer_pubkey,
+        recipient,
+    )
+    .unwrap_or_else(|e| panic!("Failed to get the ISM of recipient {}: {}", recipient, e));
+    let default_ism = read_inbox(ctx, &inspect.mailbox)
+        .expect("Inbox account not found. Make sure you are connected to the right RPC.")
+        .default_ism;
+    println!(
+        "Recipient {} is protected by ISM {} ({})",
+        recipient,
+        ism,
+        if ism == default_ism {
+            "the mailbox's default ISM"
+        } else {
+            "set by the recipient"
+        }
+    );
+
+    let module_type = ism_module_type(&ctx.client, ctx.commitment, ctx.payer_pubkey, ism)
+        .unwrap_or_else(|e| panic!("Failed to get the module type of ISM {}: {}", ism, e));
+    println!("Module type: {:?}", module_type);
+
+    match module_type {
+        ModuleType::MessageIdMultisig | ModuleType::MerkleRootMultisig => {
+            if inspect.origins.is_empty() {
+                println!("Pass --origins to show the validators and threshold for origin domains");
+            }
+            for origin in &inspect.origins {
+                let origin = ctx.domains.resolve(origin);
+                let (domain_data_key, _domain_data_bump) =
+                    Pubkey::find_program_address(domain_data_pda_seeds!(origin), &ism);
+                match read_program_account::<DomainData>(ctx, &ism, &domain_data_key)
+                    .unwrap_or_else(|e| panic!("{}", e))
+                {
+                    Some(domain_data) => {
+                        let validators_and_threshold = domain_data.validators_and_threshold;
+                        println!(
+                            "Origin domain {}: threshold {} of {} validators",
+                            origin,
+                            validators_and_threshold.threshold,
+                            validators_and_threshold.validators.len()
+                        );
+                        for validator in validators_and_threshold.validators {
+                            println!("\t{:?}", validator);
+                        }
+                    }
+                    None => println!("Origin domain {}: no validators set", origin),
+                }
+            }
+        }
+        // The test ISM reports an unused module type.
+        ModuleType::Unused => {
+            match read_program_account::<TestIsmStorage>(ctx, &ism, &storage_pda_key(&ism)) {
+                Ok(Some(storage)) => println!(
+                    "Test ISM, {} every message",
+                    if storage.accept {
+                        "accepting"
+                    } else {
+                        "rejecting"
+                    }
+                ),
+                _ => println!("Unknown ISM, no parameters to show"),
+            }
+        }
+        // There are no Sealevel routing or aggregation ISM programs whose
+        // accounts could be read or configured.
+        ModuleType::Routing | ModuleType::Aggregation => println!(
+            "{:?} ISMs aren't implemented on Sealevel, their parameters can't be shown",
+            module_type
+        ),
+        _ => println!("No parameters to show for {:?} ISMs", module_type),
+    }
+}
@@ -0,0 +1,218 @@
+//! Submission of transactions as Jito bundles, which land through Jito's
+//! block engine during congestion when a tip is paid. The tip is a transfer to
+//! one of Jito's tip accounts, included in the bundled transaction.
+
+use std::{
+    collections::HashMap,
+    io::{self, Write as _},
+    str::FromStr,
+    sync::Mutex,
+    thread,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::json;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_sdk::{
+    instruction::Instruction, pubkey::Pubkey, signature::Signature, system_instruction,
+    transaction::Transaction,
+};
+use tracing::debug;
+
+use crate::sender::{ConfirmationStrategy, TransactionSender};
+
+/// The least tip Jito's block engine accepts for a bundle.
+pub(crate) const MIN_TIP_LAMPORTS: u64 = 1000;
+
+/// Jito's tip accounts. Tips are spread over them to avoid write lock
+/// contention.
+const TIP_ACCOUNTS: [&str; 8] = [
+    "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5",
+    "HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe",
+    "Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY",
+    "ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49",
+    "DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh",
+    "ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt",
+    "DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL",
+    "3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT",
+];
+
+/// An instruction paying the tip to one of the tip accounts.
+pub(crate) fn tip_instruction(payer: &Pubkey, tip_lamports: u64) -> Instruction {
+    let index = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos() as usize
+        % TIP_ACCOUNTS.len();
+    let tip_account = Pubkey::from_str(TIP_ACCOUNTS[index]).unwrap();
+    system_instruction::transfer(payer, &tip_account, tip_lamports)
+}
+
+#[derive(Deserialize)]
+struct JsonRpcResponse<T> {
+    result: Option<T>,
+    error: Option<JsonRpcError>,
+}
+
+#[derive(Deserialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct BundleStatuses {
+    value: Vec<BundleStatus>,
+}
+
+/// The status of a bundle that landed.
+#[derive(Deserialize)]
+struct BundleStatus {
+    slot: u64,
+    confirmation_status: Option<String>,
+    /// `{"Ok": null}` if the bundle's transactions succeeded.
+    err: serde_json::Value,
+}
+
+/// Sends transactions as single-transaction bundles through a Jito block
+/// engine, and polls the bundles' statuses until they land.
+pub(crate) struct JitoBundleSender {
+    /// The block engine's bundles endpoint.
+    bundles_url: String,
+    /// The IDs of sent bundles, by the signature of their transaction.
+    bundle_ids: Mutex<HashMap<Signature, String>>,
+    progress: bool,
+}
+
+impl JitoBundleSender {
+    /// A sender to the block engine at `block_engine_url`, e.g.
+    /// `https://mainnet.block-engine.jito.wtf`. Transactions must pay a tip
+    /// with `tip_instruction` to be included.
+    pub(crate) fn new(block_engine_url: &str, progress: bool) -> Self {
+        Self {
+            bundles_url: format!("{}/api/v1/bundles", block_engine_url.trim_end_matches('/')),
+            bundle_ids: Mutex::default(),
+            progress,
+        }
+    }
+
+    fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<T, ClientError> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        debug!(url = %self.bundles_url, %request, "Block engine request");
+        let response: JsonRpcResponse<T> = ureq::post(&self.bundles_url)
+            .send_json(request)
+            .map_err(|e| ClientErrorKind::Custom(format!("{} failed: {}", method, e)))?
+            .into_json()?;
+        match (response.result, response.error) {
+            (Some(result), _) => Ok(result),
+            (None, Some(error)) => Err(ClientErrorKind::Custom(format!(
+                "{} failed: {} (code {})",
+                method, error.message, error.code
+            ))
+            .into()),
+            (None, None) => {
+                Err(ClientErrorKind::Custom(format!("{} returned nothing", method)).into())
+            }
+        }
+    }
+}
+
+impl TransactionSender for JitoBundleSender {
+    fn send(&self, txn: &Transaction) -> Result<Signature, ClientError> {
+        let encoded = bs58::encode(bincode::serialize(txn).unwrap()).into_string();
+        let bundle_id: String = self.call("sendBundle", json!([[encoded]]))?;
+        let signature = txn.signatures[0];
+        println!("Sent bundle {} with transaction {}", bundle_id, signature);
+        self.bundle_ids.lock().unwrap().insert(signature, bundle_id);
+        Ok(signature)
+    }
+
+    fn confirm(
+        &self,
+        signature: &Signature,
+        strategy: &ConfirmationStrategy,
+    ) -> Result<(), ClientError> {
+        let bundle_id = self
+            .bundle_ids
+            .lock()
+            .unwrap()
+            .get(signature)
+            .cloned()
+            .ok_or_else(|| {
+                ClientErrorKind::Custom(format!(
+                    "Transaction {} wasn't sent as a bundle",
+                    signature
+                ))
+            })?;
+        let required = confirmation_rank(if strategy.commitment.is_finalized() {
+            "finalized"
+        } else if strategy.commitment.is_at_least_confirmed() {
+            "confirmed"
+        } else {
+            "processed"
+        });
+
+        let started_at = Instant::now();
+        let mut waited = false;
+        let result = loop {
+            let statuses: BundleStatuses =
+                self.call("getBundleStatuses", json!([[bundle_id.clone()]]))?;
+            match statuses.value.first() {
+                Some(status) if status.err.get("Ok").is_none() => {
+                    break Err(ClientErrorKind::Custom(format!(
+                        "Bundle {} failed in slot {}: {}",
+                        bundle_id, status.slot, status.err
+                    ))
+                    .into())
+                }
+                Some(status)
+                    if status
+                        .confirmation_status
+                        .as_deref()
+                        .map_or(false, |status| confirmation_rank(status) >= required) =>
+                {
+                    debug!(%bundle_id, slot = status.slot, "Bundle landed");
+                    break Ok(());
+                }
+                _ if started_at.elapsed() >= strategy.timeout => {
+                    break Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        format!(
+                            "Bundle {} didn't land within {}s",
+                            bundle_id,
+                            strategy.timeout.as_secs()
+                        ),
+                    )
+                    .into())
+                }
+                _ => {}
+            }
+            if self.progress {
+                eprint!(
+                    "\rWaiting for bundle to land... {}s",
+                    started_at.elapsed().as_secs()
+                );
+                io::stderr().flush().ok();
+                waited = true;
+            }
+            thread::sleep(strategy.poll_interval);
+        };
+        if waited {
+            eprintln!();
+        }
+        result
+    }
+}
+
+fn confirmation_rank(confirmation_status: &str) -> u8 {
+    match confirmation_status {
+        "finalized" => 2,
+        "confirmed" => 1,
+        _ => 0,
+    }
+}
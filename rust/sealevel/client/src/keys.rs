@@ -0,0 +1,99 @@
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, write_keypair_file, Keypair, Signer as _};
+
+use hyperlane_sealevel_mailbox::mailbox_message_dispatch_authority_pda_seeds;
+
+use crate::{Context, KeysCmd, KeysDerivePdas, KeysNew, KeysSubCmd};
+
+pub(crate) fn process_keys_cmd(ctx: &Context, cmd: KeysCmd) {
+    match cmd.cmd {
+        KeysSubCmd::New(new) => new_keypair(new),
+        KeysSubCmd::Pubkey(pubkey) => {
+            let keypair = read_keypair_file(&pubkey.keypair_file).unwrap_or_else(|e| {
+                panic!(
+                    "Failed to read keypair file {}: {}",
+                    pubkey.keypair_file.display(),
+                    e
+                )
+            });
+            println!("{}", keypair.pubkey());
+        }
+        KeysSubCmd::DerivePdas(derive) => derive_pdas(ctx, derive),
+    }
+}
+
+fn new_keypair(new: KeysNew) {
+    if let Some(prefix) = new.starts_with.as_deref() {
+        if let Some(invalid) = prefix
+            .chars()
+            .find(|c| bs58::decode(c.to_string()).into_vec().is_err())
+        {
+            panic!("{} is not a base58 character", invalid);
+        }
+    }
+    let keypair = loop {
+        let keypair = Keypair::new();
+        match new.starts_with.as_deref() {
+            Some(prefix) if !keypair.pubkey().to_string().starts_with(prefix) => continue,
+            _ => break keypair,
+        }
+    };
+
+    match new.outfile {
+        Some(outfile) => {
+            if outfile.exists() && !new.force {
+                panic!(
+                    "{} already exists, use --force to overwrite it",
+                    outfile.display()
+                );
+            }
+            write_keypair_file(&keypair, &outfile).unwrap_or_else(|e| {
+                panic!("Failed to write keypair to {}: {}", outfile.display(), e)
+            });
+            println!(
+                "Wrote keypair for {} to {}",
+                keypair.pubkey(),
+                outfile.display()
+            );
+        }
+        None => {
+            println!("Pubkey: {}", keypair.pubkey());
+            println!("Keypair: {:?}", keypair.to_bytes());
+        }
+    }
+}
+
+fn derive_pdas(ctx: &Context, derive: KeysDerivePdas) {
+    let mailbox = &derive.mailbox;
+    print_pda("Inbox", ctx.protocol.inbox_pda(mailbox));
+    print_pda("Outbox", ctx.protocol.outbox_pda(mailbox));
+    if let Some(recipient) = derive.recipient {
+        print_pda(
+            &format!("Process authority for recipient {}", recipient),
+            ctx.protocol.process_authority_pda(mailbox, &recipient),
+        );
+    }
+    if let Some(sender) = derive.sender {
+        print_pda(
+            &format!("Dispatch authority of sender {}", sender),
+            Pubkey::find_program_address(mailbox_message_dispatch_authority_pda_seeds!(), &sender),
+        );
+    }
+    if let Some(unique_message_pubkey) = derive.unique_message_pubkey {
+        print_pda(
+            &format!("Dispatched message for {}", unique_message_pubkey),
+            ctx.protocol
+                .dispatched_message_pda(mailbox, &unique_message_pubkey),
+        );
+    }
+    if let Some(message_id) = derive.message_id {
+        print_pda(
+            &format!("Processed message for {:?}", message_id),
+            ctx.protocol.processed_message_pda(mailbox, message_id),
+        );
+    }
+}
+
+fn print_pda(name: &str, (pda, bump): (Pubkey, u8)) {
+    println!("{}: {}, bump={}", name, pda, bump);
+}
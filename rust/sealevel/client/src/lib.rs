@@ -0,0 +1,8 @@
+//! The parts of the Sealevel client that build and send transactions without
+//! `solana-client`, so that they also build for wasm32, e.g. for web
+//! dashboards that construct the same transactions as the CLI. The CLI itself
+//! is the crate's binary, behind the `cli` feature.
+
+pub mod http_rpc;
+pub mod instructions;
+pub mod protocol;
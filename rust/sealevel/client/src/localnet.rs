@@ -0,0 +1,445 @@
+//! Deterministic fixtures for local test validators, so that demos and bug
+//! reproductions start from identical state on every machine.
+
+use std::{
+    fs::File,
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use borsh::BorshSerialize;
+use hyperlane_sealevel_mailbox::spl_noop;
+use hyperlane_sealevel_test_send_receiver::{
+    program::{IsmReturnDataMode, TestSendReceiverInstruction},
+    test_send_receiver_storage_pda_seeds,
+};
+use serde_json::json;
+use solana_cli_config::Config;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_sdk::{
+    hash::hashv,
+    native_token::sol_to_lamports,
+    pubkey::Pubkey,
+    signature::{keypair_from_seed, write_keypair_file, Keypair, Signer},
+    system_program,
+};
+
+use crate::{
+    account::request_airdrop,
+    artifacts::write_json,
+    cmd_utils::{create_and_write_keypair, create_new_directory, deploy_program},
+    core::deploy_core,
+    test_ism::deploy_test_ism,
+    Context, CoreDeploy, LocalnetCmd, LocalnetFixtures, LocalnetSubCmd, LocalnetUp,
+};
+#[cfg(feature = "tokens")]
+use crate::{warp_route::deploy_warp_route, WarpRouteDeploy};
+
+/// The chains the fixture mailboxes are deployed for, by name and domain.
+const FIXTURE_CHAINS: [(&str, u32); 2] = [("sealeveltest1", 13375), ("sealeveltest2", 13376)];
+
+/// The core program keypair files deployed for each fixture chain.
+const CORE_KEYPAIR_FILES: [&str; 4] = [
+    "hyperlane_sealevel_multisig_ism_message_id-keypair.json",
+    "hyperlane_sealevel_mailbox-keypair.json",
+    "hyperlane_sealevel_validator_announce-keypair.json",
+    "hyperlane_sealevel_igp-keypair.json",
+];
+
+/// How long a started validator is given to start serving RPC requests.
+const VALIDATOR_STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+/// The RPC port of a started validator if the RPC URL doesn't give one.
+const DEFAULT_RPC_PORT: u16 = 8899;
+
+#[cfg(feature = "tokens")]
+const FIXTURE_WARP_ROUTE_NAME: &str = "fixtures";
+
+pub(crate) fn process_localnet_cmd(mut ctx: Context, cmd: LocalnetCmd) {
+    match cmd.cmd {
+        LocalnetSubCmd::Fixtures(fixtures) => seed_fixtures(&mut ctx, fixtures),
+        LocalnetSubCmd::Up(up) => localnet_up(&mut ctx, up),
+    }
+}
+
+/// Seeds a fresh localnet with mailboxes for two chains, a native to synthetic
+/// warp route between them and a funded demo wallet. All keypairs are derived
+/// from the fixture seed, so every run produces the same addresses.
+fn seed_fixtures(ctx: &mut Context, fixtures: LocalnetFixtures) {
+    let environment_dir = create_new_directory(&fixtures.environments_dir, &fixtures.environment);
+
+    let chain_config_file = environment_dir.join("chain-config.json");
+    let url = ctx.client.url();
+    write_json(
+        &chain_config_file,
+        FIXTURE_CHAINS
+            .iter()
+            .map(|(name, domain)| {
+                (
+                    name.to_string(),
+                    json!({
+                        "chainId": domain,
+                        "name": name,
+                        "rpcUrls": [{ "http": url }],
+                    }),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>(),
+    );
+
+    for (chain, domain) in FIXTURE_CHAINS {
+        let key_dir = create_new_directory(
+            &create_new_directory(&create_new_directory(&environment_dir, chain), "core"),
+            "keys",
+        );
+        for keypair_file in CORE_KEYPAIR_FILES {
+            write_fixture_keypair(
+                &fixtures.seed,
+                &format!("{}/{}", chain, keypair_file),
+                &key_dir.join(keypair_file),
+            );
+        }
+
+        let program_ids = deploy_core(
+            ctx,
+            &CoreDeploy {
+                local_domain: domain,
+                environment: fixtures.environment.clone(),
+                gas_oracle_config_file: None,
+                overhead_config_file: None,
+                chain: chain.to_owned(),
+                use_existing_keys: true,
+                environments_dir: fixtures.environments_dir.clone(),
+                remote_domains: FIXTURE_CHAINS
+                    .iter()
+                    .map(|(_, remote_domain)| *remote_domain)
+                    .filter(|remote_domain| *remote_domain != domain)
+                    .collect(),
+                built_so_dir: fixtures.built_so_dir.clone(),
+            },
+        );
+        println!("{}: mailbox={}", chain, program_ids.mailbox);
+    }
+
+    #[cfg(feature = "tokens")]
+    {
+        let warp_route_dir = create_new_directory(
+            &create_new_directory(&environment_dir, "warp-routes"),
+            FIXTURE_WARP_ROUTE_NAME,
+        );
+        let key_dir = create_new_directory(&warp_route_dir, "keys");
+        for keypair_file in [
+            "hyperlane_sealevel_token_native-sealeveltest1.json",
+            "hyperlane_sealevel_token-sealeveltest2.json",
+        ] {
+            write_fixture_keypair(
+                &fixtures.seed,
+                &format!("warp-routes/{}", keypair_file),
+                &key_dir.join(keypair_file),
+            );
+        }
+
+        let token_config_file = warp_route_dir.join("token-config.json");
+        write_json(
+            &token_config_file,
+            json!({
+                "sealeveltest1": {
+                    "type": "native",
+                    "decimals": 9,
+                },
+                "sealeveltest2": {
+                    "type": "synthetic",
+                    "decimals": 9,
+                    "name": "Solana",
+                    "symbol": "SOL",
+                },
+            }),
+        );
+
+        deploy_warp_route(
+            ctx,
+            WarpRouteDeploy {
+                environment: fixtures.environment.clone(),
+                environments_dir: fixtures.environments_dir.clone(),
+                built_so_dir: fixtures.built_so_dir.clone(),
+                warp_route_name: FIXTURE_WARP_ROUTE_NAME.to_owned(),
+                token_config_file,
+                chain_config_file,
+                ata_payer_funding_amount: Some(sol_to_lamports(1.0)),
+            },
+        );
+    }
+
+    let demo_wallet_file = environment_dir.join("demo-wallet-keypair.json");
+    let demo_wallet = write_fixture_keypair(&fixtures.seed, "demo-wallet", &demo_wallet_file);
+    request_airdrop(
+        ctx,
+        &demo_wallet.pubkey(),
+        sol_to_lamports(fixtures.demo_wallet_sol),
+    );
+
+    println!(
+        "Localnet fixtures seeded, demo wallet {} at {}",
+        demo_wallet.pubkey(),
+        demo_wallet_file.display()
+    );
+}
+
+/// Brings up a local development environment, automating the setup the
+/// integration tests do by hand, and writes a Solana CLI config for it to
+/// pass with `--config`.
+fn localnet_up(ctx: &mut Context, up: LocalnetUp) {
+    let environment_dir = create_new_directory(&up.environments_dir, &up.environment);
+    let url = ctx.client.url();
+
+    if ctx.client.get_health().is_ok() {
+        println!("Using the validator running at {}", url);
+    } else {
+        start_validator(ctx, &up, &environment_dir);
+    }
+
+    let payer_lamports = sol_to_lamports(up.payer_sol);
+    let balance = ctx
+        .client
+        .get_balance_with_commitment(&ctx.payer_pubkey, ctx.commitment)
+        .expect("Failed to get payer balance")
+        .value;
+    if balance < payer_lamports {
+        request_airdrop(ctx, &ctx.payer_pubkey, payer_lamports - balance);
+    }
+
+    let program_ids = deploy_core(
+        ctx,
+        &CoreDeploy {
+            local_domain: up.local_domain,
+            environment: up.environment.clone(),
+            gas_oracle_config_file: None,
+            overhead_config_file: None,
+            chain: up.chain.clone(),
+            use_existing_keys: true,
+            environments_dir: up.environments_dir.clone(),
+            remote_domains: up.remote_domains.clone(),
+            built_so_dir: up.built_so_dir.clone(),
+        },
+    );
+
+    let key_dir = create_new_directory(
+        &create_new_directory(&create_new_directory(&environment_dir, &up.chain), "core"),
+        "keys",
+    );
+    let test_ism = deploy_test_ism(ctx, &up.built_so_dir, true, &key_dir);
+    let recipient = deploy_recipient(
+        ctx,
+        &up.built_so_dir,
+        &key_dir,
+        program_ids.mailbox,
+        test_ism,
+    );
+
+    let config_file = environment_dir.join("config.yml");
+    Config {
+        json_rpc_url: url.clone(),
+        keypair_path: ctx.payer_keypair_path().clone(),
+        ..Config::default()
+    }
+    .save(config_file.to_str().unwrap())
+    .unwrap_or_else(|e| panic!("Failed to write config {}: {}", config_file.display(), e));
+
+    let addresses = json!({
+        "chain": up.chain,
+        "domain": up.local_domain,
+        "rpcUrl": url,
+        "payer": ctx.payer_pubkey.to_string(),
+        "mailbox": program_ids.mailbox.to_string(),
+        "validatorAnnounce": program_ids.validator_announce.to_string(),
+        "multisigIsmMessageId": program_ids.multisig_ism_message_id.to_string(),
+        "igpProgramId": program_ids.igp_program_id.to_string(),
+        "igpAccount": program_ids.igp_account.to_string(),
+        "overheadIgpAccount": program_ids.overhead_igp_account.to_string(),
+        "testIsm": test_ism.to_string(),
+        "recipient": recipient.to_string(),
+    });
+    write_json(&environment_dir.join("localnet.json"), addresses.clone());
+
+    println!("Localnet is up:");
+    println!("{}", serde_json::to_string_pretty(&addresses).unwrap());
+    println!(
+        "Use it with `hyperlane-sealevel-client --config {}`",
+        config_file.display()
+    );
+}
+
+/// Starts a `solana-test-validator` serving RPC at the context's URL, which
+/// keeps running after the command exits, and waits until it's healthy.
+fn start_validator(ctx: &Context, up: &LocalnetUp, environment_dir: &Path) {
+    let url = ctx.client.url();
+    let host = url
+        .split("://")
+        .nth(1)
+        .and_then(|rest| rest.split(['/', ':']).next())
+        .unwrap_or_default();
+    if !matches!(host, "localhost" | "127.0.0.1") {
+        panic!("No validator is running at {}, and it isn't local", url);
+    }
+    let rpc_port = url
+        .rsplit(':')
+        .next()
+        .and_then(|port| port.trim_end_matches('/').parse::<u16>().ok())
+        .unwrap_or(DEFAULT_RPC_PORT);
+
+    let ledger_dir = up
+        .ledger_dir
+        .clone()
+        .unwrap_or_else(|| environment_dir.join("ledger"));
+    let log_file_path = environment_dir.join("validator.log");
+    let log_file = File::create(&log_file_path)
+        .unwrap_or_else(|e| panic!("Failed to create {}: {}", log_file_path.display(), e));
+
+    let mut command = Command::new("solana-test-validator");
+    command
+        .arg("--reset")
+        .arg("--ledger")
+        .arg(&ledger_dir)
+        .arg("--rpc-port")
+        .arg(rpc_port.to_string());
+    if let Some(spl_programs_dir) = &up.spl_programs_dir {
+        command
+            .arg("--bpf-program")
+            .arg(spl_noop::id().to_string())
+            .arg(spl_programs_dir.join("spl_noop.so"));
+    }
+    let mut validator = command
+        .stdin(Stdio::null())
+        .stdout(log_file)
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to start solana-test-validator");
+    println!(
+        "Started solana-test-validator (pid {}) with ledger {}, logging to {}",
+        validator.id(),
+        ledger_dir.display(),
+        log_file_path.display()
+    );
+
+    let started_at = Instant::now();
+    while ctx.client.get_health().is_err() {
+        if let Some(status) = validator.try_wait().expect("Failed to wait for validator") {
+            panic!(
+                "solana-test-validator exited with {}, see {}",
+                status,
+                log_file_path.display()
+            );
+        }
+        if started_at.elapsed() >= VALIDATOR_STARTUP_TIMEOUT {
+            panic!(
+                "solana-test-validator isn't healthy after {}s, see {}",
+                VALIDATOR_STARTUP_TIMEOUT.as_secs(),
+                log_file_path.display()
+            );
+        }
+        thread::sleep(Duration::from_secs(1));
+    }
+}
+
+/// Deploys a test recipient that successfully handles every message, secured
+/// by the given ISM.
+fn deploy_recipient(
+    ctx: &mut Context,
+    built_so_dir: &Path,
+    key_dir: &Path,
+    mailbox: Pubkey,
+    ism: Pubkey,
+) -> Pubkey {
+    let (keypair, keypair_path) = create_and_write_keypair(
+        key_dir,
+        "hyperlane_sealevel_test_send_receiver-keypair.json",
+        true,
+    );
+    let program_id = keypair.pubkey();
+
+    deploy_program(
+        ctx.payer_keypair_path(),
+        keypair_path.to_str().unwrap(),
+        built_so_dir
+            .join("hyperlane_sealevel_test_send_receiver.so")
+            .to_str()
+            .unwrap(),
+        &ctx.client.url(),
+    );
+
+    println!("Deployed test recipient at program ID {}", program_id);
+
+    let (storage_pda_key, _storage_pda_bump) =
+        Pubkey::find_program_address(test_send_receiver_storage_pda_seeds!(), &program_id);
+    ctx.new_txn()
+        .add_with_description(
+            Instruction {
+                program_id,
+                data: TestSendReceiverInstruction::Init(mailbox)
+                    .try_to_vec()
+                    .unwrap(),
+                accounts: vec![
+                    AccountMeta::new_readonly(system_program::id(), false),
+                    AccountMeta::new(ctx.payer_pubkey, true),
+                    AccountMeta::new(storage_pda_key, false),
+                ],
+            },
+            format!("Initializing test recipient with mailbox {}", mailbox),
+        )
+        .add_with_description(
+            Instruction {
+                program_id,
+                data: TestSendReceiverInstruction::SetInterchainSecurityModule(
+                    Some(ism),
+                    IsmReturnDataMode::EncodeOption,
+                )
+                .try_to_vec()
+                .unwrap(),
+                accounts: vec![AccountMeta::new(storage_pda_key, false)],
+            },
+            format!("Setting test recipient ISM to {}", ism),
+        )
+        .send_with_payer();
+
+    program_id
+}
+
+/// Derives a keypair from the fixture seed and the keypair's name, and writes
+/// it to `path`.
+fn write_fixture_keypair(seed: &str, name: &str, path: &Path) -> Keypair {
+    let keypair = fixture_keypair(seed, name);
+    write_keypair_file(&keypair, path)
+        .unwrap_or_else(|e| panic!("Failed to write keypair {}: {}", path.display(), e));
+    keypair
+}
+
+fn fixture_keypair(seed: &str, name: &str) -> Keypair {
+    keypair_from_seed(hashv(&[seed.as_bytes(), b"/", name.as_bytes()]).as_ref())
+        .expect("Failed to derive fixture keypair")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fixture_keypairs_are_deterministic() {
+        assert_eq!(
+            fixture_keypair("seed", "demo-wallet").to_bytes(),
+            fixture_keypair("seed", "demo-wallet").to_bytes()
+        );
+        assert_ne!(
+            fixture_keypair("seed", "demo-wallet").pubkey(),
+            fixture_keypair(
+                "seed",
+                "sealeveltest1/hyperlane_sealevel_mailbox-keypair.json"
+            )
+            .pubkey()
+        );
+        assert_ne!(
+            fixture_keypair("seed", "demo-wallet").pubkey(),
+            fixture_keypair("other seed", "demo-wallet").pubkey()
+        );
+    }
+}
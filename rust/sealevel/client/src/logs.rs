@@ -0,0 +1,187 @@
+//! Decoding of what the mailbox logs through CPIs to the SPL noop program.
+//! Dispatching a message logs its whole dispatched message account, and
+//! processing one logs its ID, so messages can be recovered from their
+//! transactions even once their accounts are gone.
+
+use hyperlane_core::{Decode as _, HyperlaneMessage, H256};
+use solana_client::{
+    client_error::ClientError, rpc_client::RpcClient, rpc_config::RpcTransactionConfig,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+    transaction::VersionedTransaction,
+};
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, UiInstruction, UiLoadedAddresses,
+    UiTransactionEncoding, UiTransactionStatusMeta,
+};
+
+use hyperlane_sealevel_mailbox::{
+    accounts::{DispatchedMessage, DispatchedMessageAccount, DISPATCHED_MESSAGE_DISCRIMINATOR},
+    spl_noop,
+};
+
+use crate::{Context, MessageFromTx};
+
+/// The prefix of the mailbox's log of a processed message's ID.
+const PROCESSED_MESSAGE_LOG_PREFIX: &str = "Hyperlane inbox: ";
+
+/// Fetches a confirmed transaction, including its inner instructions.
+pub(crate) fn get_transaction(
+    client: &RpcClient,
+    signature: &Signature,
+    commitment: CommitmentConfig,
+) -> Result<EncodedConfirmedTransactionWithStatusMeta, ClientError> {
+    client.get_transaction_with_config(
+        signature,
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(commitment),
+            max_supported_transaction_version: Some(0),
+        },
+    )
+}
+
+/// The keys instructions index into: the transaction's static keys followed
+/// by those loaded from address lookup tables.
+pub(crate) fn account_keys(
+    versioned: &VersionedTransaction,
+    meta: &UiTransactionStatusMeta,
+) -> Vec<Pubkey> {
+    let mut account_keys = versioned.message.static_account_keys().to_vec();
+    if let Some(loaded_addresses) = Option::<UiLoadedAddresses>::from(meta.loaded_addresses.clone())
+    {
+        account_keys.extend(
+            loaded_addresses
+                .writable
+                .iter()
+                .chain(&loaded_addresses.readonly)
+                .filter_map(|pubkey| pubkey.parse::<Pubkey>().ok()),
+        );
+    }
+    account_keys
+}
+
+/// The data of the transaction's inner SPL noop instructions, which programs
+/// log data through.
+fn noop_logs(txn: &EncodedConfirmedTransactionWithStatusMeta) -> Vec<Vec<u8>> {
+    let (Some(meta), Some(versioned)) = (
+        txn.transaction.meta.as_ref(),
+        txn.transaction.transaction.decode(),
+    ) else {
+        return vec![];
+    };
+    let account_keys = account_keys(&versioned, meta);
+    let inner_instructions: Vec<_> =
+        Option::from(meta.inner_instructions.clone()).unwrap_or_default();
+    inner_instructions
+        .iter()
+        .flat_map(|inner| &inner.instructions)
+        .filter_map(|instruction| match instruction {
+            UiInstruction::Compiled(compiled)
+                if account_keys.get(compiled.program_id_index as usize)
+                    == Some(&spl_noop::id()) =>
+            {
+                bs58::decode(&compiled.data).into_vec().ok()
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The dispatched message accounts the transaction logged.
+pub(crate) fn dispatched_messages(
+    txn: &EncodedConfirmedTransactionWithStatusMeta,
+) -> Vec<DispatchedMessage> {
+    noop_logs(txn)
+        .iter()
+        .filter_map(|data| dispatched_message_log(data))
+        .collect()
+}
+
+/// The IDs of the messages the transaction logged as processed.
+pub(crate) fn processed_message_ids(txn: &EncodedConfirmedTransactionWithStatusMeta) -> Vec<H256> {
+    noop_logs(txn)
+        .iter()
+        .filter_map(|data| processed_message_id_log(data))
+        .collect()
+}
+
+/// Decodes a dispatched message account logged on dispatch. Other logs, like
+/// those of processed messages, decode as `None`.
+fn dispatched_message_log(data: &[u8]) -> Option<DispatchedMessage> {
+    // The initialized flag precedes the discriminator.
+    if data.get(1..9) != Some(&DISPATCHED_MESSAGE_DISCRIMINATOR[..]) {
+        return None;
+    }
+    DispatchedMessageAccount::fetch(&mut &data[..])
+        .ok()
+        .map(|account| *account.into_inner())
+}
+
+/// Decodes the ID of a message logged on processing.
+fn processed_message_id_log(data: &[u8]) -> Option<H256> {
+    std::str::from_utf8(data)
+        .ok()?
+        .strip_prefix(PROCESSED_MESSAGE_LOG_PREFIX)?
+        .parse()
+        .ok()
+}
+
+/// Prints the messages the transaction dispatched and processed, as logged.
+pub(crate) fn print_messages_from_tx(ctx: &Context, args: MessageFromTx) {
+    let txn = get_transaction(&ctx.client, &args.signature, ctx.commitment)
+        .unwrap_or_else(|e| panic!("Failed to get transaction {}: {}", args.signature, e));
+    let dispatched_messages = dispatched_messages(&txn);
+    let processed_message_ids = processed_message_ids(&txn);
+    if dispatched_messages.is_empty() && processed_message_ids.is_empty() {
+        println!("Transaction {} logged no messages", args.signature);
+    }
+    for dispatched_message in dispatched_messages {
+        match HyperlaneMessage::read_from(&mut &dispatched_message.encoded_message[..]) {
+            Ok(message) => {
+                println!(
+                    "Dispatched message {:?} at slot {}:",
+                    message.id(),
+                    dispatched_message.slot
+                );
+                println!("{:#?}", message);
+            }
+            Err(e) => println!(
+                "Dispatched message with nonce {} that doesn't decode: {}",
+                dispatched_message.nonce, e
+            ),
+        }
+    }
+    for message_id in processed_message_ids {
+        println!("Processed message {:?}", message_id);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use borsh::BorshSerialize as _;
+
+    use super::*;
+
+    #[test]
+    fn test_dispatched_message_log() {
+        let dispatched_message =
+            DispatchedMessage::new(7, 1234, Pubkey::new_unique(), vec![1, 2, 3]);
+        // Logged with the initialized flag of the account.
+        let mut data = vec![1];
+        dispatched_message.serialize(&mut data).unwrap();
+
+        assert_eq!(dispatched_message_log(&data), Some(dispatched_message));
+        assert_eq!(processed_message_id_log(&data), None);
+    }
+
+    #[test]
+    fn test_processed_message_id_log() {
+        let message_id = H256::random();
+        let data = format!("Hyperlane inbox: {:?}", message_id).into_bytes();
+
+        assert_eq!(processed_message_id_log(&data), Some(message_id));
+        assert_eq!(dispatched_message_log(&data), None);
+    }
+}
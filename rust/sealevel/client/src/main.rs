@@ -3,78 +3,197 @@
 // #![deny(missing_docs)] // FIXME
 #![deny(unsafe_code)]
 
-use std::{path::PathBuf, str::FromStr};
+use std::{path::PathBuf, str::FromStr, sync::Arc, time::Duration};
 
-use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap::{ArgAction, Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use solana_clap_utils::input_validators::{is_keypair, is_url, normalize_to_url_if_moniker};
 use solana_cli_config::{Config, CONFIG_FILE};
 use solana_client::rpc_client::RpcClient;
 use solana_program::pubkey;
+#[cfg(feature = "tokens")]
+use solana_sdk::instruction::AccountMeta;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
     compute_budget::ComputeBudgetInstruction,
-    instruction::{AccountMeta, Instruction},
+    instruction::Instruction,
     pubkey::Pubkey,
-    signature::{read_keypair_file, Keypair, Signer as _},
-    system_program,
+    signature::{read_keypair_file, Keypair, Signature, Signer as _},
 };
 
+#[cfg(feature = "tokens")]
 use account_utils::DiscriminatorEncode;
-use hyperlane_core::{H160, H256};
+use hyperlane_core::{HyperlaneMessage, H160, H256};
+// Part of the library, and used by the modules as `crate::protocol`.
+use hyperlane_sealevel_client::protocol;
+#[cfg(feature = "tokens")]
 use hyperlane_sealevel_connection_client::router::RemoteRouterConfig;
 use hyperlane_sealevel_igp::{
     accounts::{
         GasOracle, GasPaymentAccount, IgpAccount, InterchainGasPaymasterType, OverheadIgpAccount,
         ProgramDataAccount as IgpProgramDataAccount, RemoteGasData,
     },
-    igp_gas_payment_pda_seeds, igp_program_data_pda_seeds,
+    igp_program_data_pda_seeds,
     instruction::{GasOracleConfig, GasOverheadConfig},
 };
-use hyperlane_sealevel_mailbox::{
-    accounts::{InboxAccount, OutboxAccount},
-    instruction::{Instruction as MailboxInstruction, OutboxDispatch},
-    mailbox_dispatched_message_pda_seeds, mailbox_inbox_pda_seeds,
-    mailbox_message_dispatch_authority_pda_seeds, mailbox_outbox_pda_seeds,
-    mailbox_processed_message_pda_seeds, spl_noop,
-};
+use hyperlane_sealevel_mailbox::accounts::{InboxAccount, OutboxAccount};
 
+#[cfg(feature = "tokens")]
 use hyperlane_sealevel_token::{
-    hyperlane_token_ata_payer_pda_seeds, hyperlane_token_mint_pda_seeds,
-    spl_associated_token_account::get_associated_token_address_with_program_id, spl_token_2022,
-};
-use hyperlane_sealevel_token_collateral::{
-    hyperlane_token_escrow_pda_seeds, plugin::CollateralPlugin,
+    hyperlane_token_ata_payer_pda_seeds, hyperlane_token_mint_pda_seeds, spl_token_2022,
 };
+#[cfg(feature = "tokens")]
+use hyperlane_sealevel_token_collateral::hyperlane_token_escrow_pda_seeds;
+#[cfg(feature = "tokens")]
 use hyperlane_sealevel_token_lib::{
-    accounts::HyperlaneTokenAccount,
     hyperlane_token_pda_seeds,
     instruction::{Instruction as HtInstruction, TransferRemote as HtTransferRemote},
 };
+#[cfg(feature = "tokens")]
 use hyperlane_sealevel_token_native::hyperlane_token_native_collateral_pda_seeds;
 use hyperlane_sealevel_validator_announce::{
     accounts::ValidatorStorageLocationsAccount,
-    instruction::{
-        AnnounceInstruction as ValidatorAnnounceAnnounceInstruction,
-        Instruction as ValidatorAnnounceInstruction,
-    },
-    replay_protection_pda_seeds, validator_announce_pda_seeds,
+    instruction::AnnounceInstruction as ValidatorAnnounceAnnounceInstruction,
     validator_storage_locations_pda_seeds,
 };
-use warp_route::parse_token_account_data;
+#[cfg(feature = "tokens")]
+use warp_route::{parse_token_account_data, print_token_summary, TokenTransfer, WarpRouteDeployer};
 
+mod account;
+mod accounts;
+mod address;
 mod artifacts;
+mod batch;
+mod blockhash;
+mod body;
+mod chain_set;
+mod checkpoint;
+mod chunk;
 mod cmd_utils;
+mod completions;
+mod config;
 mod context;
 mod r#core;
+mod correlation;
+mod decode;
+mod destination;
+mod dispatch;
+mod domain;
+mod estimate;
+mod events;
+#[cfg(feature = "evm")]
+mod evm;
+mod examples;
+mod exit_code;
+mod explorer;
+mod failover;
+#[cfg(feature = "geyser")]
+mod geyser;
 mod helloworld;
+#[cfg(feature = "index")]
+mod index;
+mod ism;
+mod jito;
+mod keys;
+mod localnet;
+mod logs;
+mod message_id;
+mod metadata;
+mod metrics;
+#[cfg(feature = "index")]
+mod monitor;
 mod multisig_ism;
+#[cfg(feature = "names")]
+mod names;
+mod process;
+mod profile;
+mod proof;
+mod proposal;
+mod rate_limit;
+mod receipt;
+mod relay;
+#[cfg(feature = "index")]
+mod report;
+mod resume;
 mod router;
+mod rpc;
+mod rpc_fixture;
+mod schema;
+#[cfg(all(feature = "self-update", unix))]
+mod self_update;
+mod sender;
 mod serde;
+mod shutdown;
+mod status;
+mod strict;
+mod test_ism;
+mod tx;
+mod tx_inspect;
+mod tx_logs;
+mod validators;
+mod verify;
+#[cfg(feature = "tokens")]
 mod warp_route;
-
+mod webhook;
+#[cfg(feature = "tokens")]
+mod wizard;
+
+use crate::account::process_account_cmd;
+use crate::accounts::{read_inbox, read_outbox};
+use crate::address::Address;
+use crate::batch::process_send_batch;
+use crate::body::BodyArgs;
+use crate::checkpoint::process_mailbox_checkpoint;
+use crate::chunk::chunk_message_body;
+use crate::completions::print_completions;
+use crate::config::{check_rpc, show_config, validate_config, with_env_vars};
+use crate::correlation::{tag_message_body, untag_message_body};
+use crate::destination::DestinationArgs;
+use crate::dispatch::{
+    check_dispatch_body, check_origin_domain, dispatch_message, find_tagged_dispatched_messages,
+    get_dispatched_message, max_dispatch_body_len, process_broadcast,
+    watch_tagged_dispatched_messages,
+};
+use crate::domain::{DomainArg, DomainRegistry};
+use crate::estimate::process_estimate_cmd;
+use crate::examples::{print_examples_if_requested, process_examples};
+use crate::explorer::{Explorers, DEFAULT_HYPERLANE_EXPLORER_URL, DEFAULT_SOLANA_EXPLORER_URL};
+#[cfg(feature = "geyser")]
+use crate::geyser::GeyserArgs;
 use crate::helloworld::process_helloworld_cmd;
+#[cfg(feature = "index")]
+use crate::index::process_index_cmd;
+use crate::ism::inspect_ism;
+use crate::keys::process_keys_cmd;
+use crate::localnet::process_localnet_cmd;
+use crate::message_id::process_message_cmd;
+#[cfg(feature = "index")]
+use crate::monitor::process_monitor_cmd;
 use crate::multisig_ism::process_multisig_ism_message_id_cmd;
+use crate::process::simulate_return_data;
+use crate::profile::Profile;
+use crate::proposal::ProposalFormat;
+use crate::protocol::ProtocolConfig;
+use crate::relay::process_relay_cmd;
+#[cfg(feature = "index")]
+use crate::report::{process_report_cmd, Route};
+use crate::resume::ResumeArgs;
+#[cfg(feature = "tokens")]
+use crate::router::{enroll_remote_routers_from_file, export_remote_routers, Router as _};
+use crate::rpc::{failover_rpc_client, replay_rpc_client};
+use crate::schema::print_schema;
+#[cfg(all(feature = "self-update", unix))]
+use crate::self_update::process_self_update;
+use crate::sender::{ConfirmationLevel, ConfirmationStrategy};
+use crate::status::process_message_status;
+use crate::test_ism::process_test_ism_cmd;
+use crate::tx::{process_tx_cmd, write_transactions};
+use crate::validators::query_validators;
+use crate::verify::process_verify_cmd;
+#[cfg(feature = "tokens")]
 use crate::warp_route::process_warp_route_cmd;
+use crate::webhook::WebhookArgs;
+#[cfg(feature = "tokens")]
+use crate::wizard::process_token_deploy;
 pub(crate) use crate::{context::*, core::*};
 
 // Note: from solana_program_runtime::compute_budget
@@ -89,44 +208,229 @@ const ECLIPSE_DOMAIN: u32 = 13375; // TODO import from hyperlane
 struct Cli {
     #[command(subcommand)]
     cmd: HyperlaneSealevelCmd,
-    #[arg(long, short)]
+    #[arg(long, short, env = "HYPERLANE_URL")]
     url: Option<String>,
-    #[arg(long, short)]
+    /// RPCs to fail over to, in order, when the RPC can't be reached or lags
+    /// behind. Can be given multiple times.
+    #[arg(long, env = "HYPERLANE_FALLBACK_URL", value_delimiter = ',')]
+    fallback_url: Vec<String>,
+    #[arg(long, short, env = "HYPERLANE_KEYPAIR")]
     keypair: Option<String>,
-    #[arg(long, short = 'b', default_value_t = MAX_COMPUTE_UNIT_LIMIT)]
+    #[arg(
+        long,
+        short = 'b',
+        env = "HYPERLANE_COMPUTE_BUDGET",
+        default_value_t = MAX_COMPUTE_UNIT_LIMIT
+    )]
     compute_budget: u32,
-    #[arg(long, short = 'a')]
+    #[arg(long, short = 'a', env = "HYPERLANE_HEAP_SIZE")]
     heap_size: Option<u32>,
-    #[arg(long, short = 'C')]
+    #[arg(long, short = 'C', env = "HYPERLANE_CONFIG")]
     config: Option<String>,
-    #[arg(long, default_value_t = false)]
+    /// The profile to take the RPC URL, keypair and program IDs from, unless
+    /// given by flags. Defaults to `local`, if the profiles file has it.
+    #[arg(long, env = "HYPERLANE_PROFILE")]
+    profile: Option<String>,
+    /// JSON file mapping profile names to profiles. Defaults to
+    /// `~/.config/hyperlane/sealevel-profiles.json`, if it exists.
+    #[arg(long, env = "HYPERLANE_PROFILES_FILE")]
+    profiles_file: Option<PathBuf>,
+    #[arg(long, env = "HYPERLANE_REQUIRE_TX_APPROVAL", default_value_t = false)]
     require_tx_approval: bool,
+    /// Simulate transactions instead of sending them. The first transaction a
+    /// command would send is simulated, its logs, compute unit usage and
+    /// account changes are printed, and the command exits.
+    #[arg(long, env = "HYPERLANE_SIMULATE_ONLY", default_value_t = false)]
+    simulate_only: bool,
+    /// JSON file overriding protocol constants for forks or tweaked testnets.
+    #[arg(long, env = "HYPERLANE_PROTOCOL_CONFIG")]
+    protocol_config: Option<PathBuf>,
+    /// Chain metadata file used to resolve chain names to domain IDs, in
+    /// addition to the known Hyperlane domains.
+    #[arg(long, env = "HYPERLANE_CHAIN_METADATA_FILE")]
+    chain_metadata_file: Option<PathBuf>,
+    /// The chain the RPC is expected to serve. Its genesis hash is checked
+    /// against the one in the chain metadata file, or the built-in one of a
+    /// public Solana cluster, before doing anything. Defaults to the
+    /// profile's chain, or to the chain whose RPC URL is used.
+    #[arg(long, env = "HYPERLANE_CHAIN")]
+    chain: Option<String>,
+    /// Continue even if the RPC's genesis hash doesn't match the chain's.
+    #[arg(
+        long,
+        env = "HYPERLANE_ALLOW_GENESIS_MISMATCH",
+        default_value_t = false
+    )]
+    allow_genesis_mismatch: bool,
+    /// Directory to write a receipt to for every transaction sent, with the
+    /// signed transaction, its confirmation slot, its instructions and the
+    /// resulting states of the accounts it wrote to.
+    #[arg(long, env = "HYPERLANE_RECEIPTS_DIR")]
+    receipts_dir: Option<PathBuf>,
+    /// Durable nonce account to build transactions against instead of a
+    /// recent blockhash, so they can be signed offline or approved slowly by
+    /// a multisig. The payer must be the nonce authority.
+    #[arg(long, env = "HYPERLANE_NONCE_ACCOUNT")]
+    nonce_account: Option<Pubkey>,
+    /// Fail instead of skipping data that can't be decoded, such as program
+    /// accounts with an unexpected layout. Enabled by default for long-running
+    /// commands like `account watch`, pass `--strict=false` to disable.
+    #[arg(long, env = "HYPERLANE_STRICT", num_args = 0..=1, default_missing_value = "true")]
+    strict: Option<bool>,
+    /// Ethereum RPC to resolve ENS names in address arguments with.
+    #[cfg(feature = "names")]
+    #[arg(long, env = "ENS_RPC_URL")]
+    ens_rpc_url: Option<String>,
+    /// Solana explorer to link sent transactions to.
+    #[arg(long, env = "HYPERLANE_EXPLORER_URL", default_value = DEFAULT_SOLANA_EXPLORER_URL)]
+    explorer_url: String,
+    /// The cluster to link transactions to on the Solana explorer, e.g.
+    /// `devnet` or an RPC URL for a custom cluster. Derived from the RPC URL
+    /// by default.
+    #[arg(long, env = "HYPERLANE_EXPLORER_CLUSTER")]
+    explorer_cluster: Option<String>,
+    /// Hyperlane explorer to link dispatched messages to.
+    #[arg(
+        long,
+        env = "HYPERLANE_MESSAGE_EXPLORER_URL",
+        default_value = DEFAULT_HYPERLANE_EXPLORER_URL
+    )]
+    hyperlane_explorer_url: String,
+    /// Don't print explorer links.
+    #[arg(long, env = "HYPERLANE_QUIET", default_value_t = false)]
+    quiet: bool,
+    /// Don't print progress, like the wait for a transaction's confirmation,
+    /// for logs in CI. Failures exit with stable codes: 2 if a transaction
+    /// failed simulation, 3 if it wasn't confirmed in time, 4 if the
+    /// configuration is invalid and 5 if `monitor` found anomalies.
+    #[arg(long, env = "HYPERLANE_NO_PROGRESS", default_value_t = false)]
+    no_progress: bool,
+    /// The commitment level to wait for sent transactions to reach.
+    #[arg(
+        long,
+        env = "HYPERLANE_CONFIRMATION_LEVEL",
+        value_enum,
+        default_value_t = ConfirmationLevel::Processed
+    )]
+    confirmation_level: ConfirmationLevel,
+    /// Seconds to wait for a sent transaction to be confirmed before failing.
+    #[arg(long, env = "HYPERLANE_CONFIRMATION_TIMEOUT", default_value_t = 90)]
+    confirmation_timeout: u64,
+    /// Milliseconds between polls of a sent transaction's status.
+    #[arg(
+        long,
+        env = "HYPERLANE_CONFIRMATION_POLL_INTERVAL",
+        default_value_t = 500
+    )]
+    confirmation_poll_interval: u64,
+    /// Limit RPC requests to this many per second, for public RPCs with rate
+    /// limits. Rate limited requests are retried with backoff either way.
+    #[arg(long, env = "HYPERLANE_RPC_RATE_LIMIT")]
+    rpc_rate_limit: Option<f64>,
+    /// Record every RPC request and its response to this fixture, to replay
+    /// with `--replay-rpc`.
+    #[arg(long, env = "HYPERLANE_RECORD_RPC", conflicts_with = "replay_rpc")]
+    record_rpc: Option<PathBuf>,
+    /// Replay the RPC session recorded in this fixture with `--record-rpc`
+    /// instead of talking to the RPC, e.g. for regression tests.
+    #[arg(long, env = "HYPERLANE_REPLAY_RPC")]
+    replay_rpc: Option<PathBuf>,
+    /// Port to serve Prometheus metrics on, for long-running commands like
+    /// `account watch`.
+    #[arg(long, env = "HYPERLANE_METRICS_PORT")]
+    metrics_port: Option<u16>,
+    /// Log what's being done to stderr: `-v` for transactions being built,
+    /// signed and confirmed, `-vv` for every RPC request too.
+    #[arg(long, short, action = ArgAction::Count, global = true)]
+    verbose: u8,
+    /// Print example invocations of the command instead of running it.
+    #[arg(long, global = true, default_value_t = false)]
+    example: bool,
 }
 
 #[derive(Subcommand)]
 enum HyperlaneSealevelCmd {
     Core(CoreCmd),
+    #[command(visible_alias = "mb")]
     Mailbox(MailboxCmd),
+    #[cfg(feature = "tokens")]
     Token(TokenCmd),
     Igp(IgpCmd),
+    #[command(visible_alias = "va")]
     ValidatorAnnounce(ValidatorAnnounceCmd),
+    #[command(visible_alias = "mim")]
     MultisigIsmMessageId(MultisigIsmMessageIdCmd),
+    Ism(IsmCmd),
+    #[cfg(feature = "tokens")]
+    #[command(visible_alias = "wr")]
     WarpRoute(WarpRouteCmd),
+    #[command(visible_alias = "hw")]
     HelloWorld(HelloWorldCmd),
+    /// Inspects and funds accounts.
+    Account(AccountCmd),
+    /// Generates keypairs and derives program addresses.
+    Keys(KeysCmd),
+    /// Seeds local test validators.
+    Localnet(LocalnetCmd),
+    /// Computes message IDs.
+    Message(MessageCmd),
+    /// Builds transactions to sign offline, signs them and sends them.
+    Tx(TxCmd),
+    /// Resolves SNS and ENS names.
+    #[cfg(feature = "names")]
+    Names(NamesCmd),
+    /// Checks deployments for misconfigurations.
+    Verify(VerifyCmd),
+    /// Estimates the cost of sending a message: the dispatch on the origin,
+    /// the IGP payment and, for Sealevel destinations, the delivery.
+    Estimate(Estimate),
+    /// Relays messages between two Sealevel chains of an environment,
+    /// paying for delivery itself rather than relying on IGP payments.
+    Relay(Relay),
+    /// Keeps a local index of messages to search without scanning the
+    /// mailbox's accounts.
+    #[cfg(feature = "index")]
+    Index(IndexCmd),
+    /// Monitors mailboxes for stuck or skipped messages, for alerting.
+    #[cfg(feature = "index")]
+    Monitor(MonitorCmd),
+    /// Reports on the indexed messages.
+    #[cfg(feature = "index")]
+    Report(ReportCmd),
+    /// Prints example invocations of commands.
+    Examples(Examples),
+    /// Prints a shell completion script, completing chain arguments with the
+    /// chains of the chain metadata file.
+    Completions(Completions),
+    /// Prints IDL-like JSON descriptions of the programs' instructions, for
+    /// external tooling to encode and decode them.
+    Schema(Schema),
+    /// Shows the configuration commands run with, or validates a chain config
+    /// against the chains.
+    Config(ConfigCmd),
+    /// Updates this binary from a release feed.
+    #[cfg(all(feature = "self-update", unix))]
+    SelfUpdate(SelfUpdate),
 }
 
+#[cfg(feature = "tokens")]
 #[derive(Args)]
 pub(crate) struct WarpRouteCmd {
     #[command(subcommand)]
     cmd: WarpRouteSubCmd,
 }
 
+#[cfg(feature = "tokens")]
 #[derive(Subcommand)]
 pub(crate) enum WarpRouteSubCmd {
     Deploy(WarpRouteDeploy),
     DestinationGas(DestinationGasArgs),
+    /// Queries the collateral or synthetic supply of the warp route's routers
+    /// on each of its chains concurrently.
+    Balances(WarpRouteBalances),
 }
 
+#[cfg(feature = "tokens")]
 #[derive(Args)]
 pub(crate) struct WarpRouteDeploy {
     #[arg(long)]
@@ -145,6 +449,22 @@ pub(crate) struct WarpRouteDeploy {
     ata_payer_funding_amount: Option<u64>,
 }
 
+#[cfg(feature = "tokens")]
+#[derive(Args)]
+pub(crate) struct WarpRouteBalances {
+    #[arg(long)]
+    environment: String,
+    #[arg(long)]
+    environments_dir: PathBuf,
+    #[arg(long)]
+    warp_route_name: String,
+    #[arg(long)]
+    token_config_file: PathBuf,
+    #[arg(long)]
+    chain_config_file: PathBuf,
+}
+
+#[cfg(feature = "tokens")]
 #[derive(Args)]
 struct DestinationGasArgs {
     #[arg(long)]
@@ -162,6 +482,20 @@ struct CoreCmd {
 #[derive(Subcommand)]
 enum CoreSubCmd {
     Deploy(CoreDeploy),
+    /// Queries the core deployments of several chains concurrently.
+    Overview(CoreOverview),
+}
+
+#[derive(Args)]
+struct CoreOverview {
+    #[arg(long)]
+    environment: String,
+    #[arg(long)]
+    environments_dir: PathBuf,
+    #[arg(long)]
+    chain_config_file: PathBuf,
+    #[arg(long, num_args = 1.., value_delimiter = ',')]
+    chains: Vec<String>,
 }
 
 #[derive(Args)]
@@ -186,6 +520,626 @@ struct CoreDeploy {
     built_so_dir: PathBuf,
 }
 
+#[derive(Args)]
+struct Examples {
+    /// The command to print examples for, e.g. `mailbox send`. Examples of
+    /// all commands are printed if none is given.
+    command: Vec<String>,
+}
+
+#[derive(Args)]
+struct Completions {
+    /// The shell to print the completion script of.
+    shell: clap_complete::Shell,
+}
+
+#[derive(Args)]
+struct ConfigCmd {
+    #[command(subcommand)]
+    cmd: ConfigSubCmd,
+}
+
+#[derive(Subcommand)]
+enum ConfigSubCmd {
+    /// Prints the Solana CLI config and the selected profile.
+    Show(ConfigShow),
+    /// Checks that the chains' RPCs can be reached, their mailboxes are
+    /// deployed with the chains' domains and their ISMs are deployed,
+    /// printing a table of the checks per chain. Exits with 4 if any check
+    /// failed.
+    Validate(ConfigValidate),
+}
+
+#[derive(Args)]
+pub(crate) struct ConfigShow {
+    /// Print the value every option resolves to once flags, `HYPERLANE_*`
+    /// environment variables, the profile, the Solana CLI config and
+    /// defaults are merged, and where it comes from.
+    #[arg(long, default_value_t = false)]
+    resolved: bool,
+}
+
+#[derive(Args)]
+pub(crate) struct ConfigValidate {
+    #[arg(long)]
+    environment: String,
+    #[arg(long)]
+    environments_dir: PathBuf,
+    #[arg(long)]
+    chain_config_file: PathBuf,
+    #[arg(long, num_args = 1.., value_delimiter = ',')]
+    chains: Vec<String>,
+}
+
+#[derive(Args)]
+pub(crate) struct Schema {
+    /// The program to describe. All programs are described if none is given.
+    #[arg(long, value_enum)]
+    program: Option<SchemaProgram>,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+pub(crate) enum SchemaProgram {
+    Mailbox,
+    Igp,
+    MultisigIsmMessageId,
+    ValidatorAnnounce,
+    #[cfg(feature = "tokens")]
+    Token,
+}
+
+#[derive(Args)]
+pub(crate) struct Estimate {
+    /// The origin mailbox.
+    #[arg(long, short, default_value_t = MAILBOX_PROG_ID)]
+    program_id: Pubkey,
+    /// The destination domain ID or chain name.
+    #[arg(long, short, visible_alias = "destination-chain")]
+    destination: DomainArg,
+    #[arg(long, short)]
+    recipient: Pubkey,
+    #[command(flatten)]
+    body: BodyArgs,
+    /// The IGP account to quote the gas payment with. Not needed if an
+    /// overhead IGP is given.
+    #[arg(long)]
+    igp_account: Option<Pubkey>,
+    /// The overhead IGP account the payment is made to, if any.
+    #[arg(long)]
+    overhead_igp_account: Option<Pubkey>,
+    /// The destination gas to quote the IGP payment for. Defaults to the
+    /// compute units of the simulated delivery.
+    #[arg(long)]
+    gas_amount: Option<u64>,
+    /// RPC URL of the destination chain, if it's a Sealevel chain, to
+    /// simulate the delivery on. The payer must exist on the destination.
+    #[arg(long)]
+    destination_url: Option<String>,
+    /// The destination mailbox.
+    #[arg(long, default_value_t = MAILBOX_PROG_ID)]
+    destination_mailbox: Pubkey,
+    /// The ISM metadata to simulate the delivery with, as hex. Defaults to
+    /// none, which only ISMs that accept any message verify.
+    #[arg(long)]
+    metadata: Option<String>,
+}
+
+#[derive(Args)]
+pub(crate) struct Relay {
+    #[arg(long)]
+    environment: String,
+    #[arg(long)]
+    environments_dir: PathBuf,
+    #[arg(long)]
+    chain_config_file: PathBuf,
+    /// The chain to relay messages from.
+    #[arg(long)]
+    origin: String,
+    /// The chain to relay messages to. Messages to other chains are skipped.
+    #[arg(long)]
+    destination: String,
+    /// Keep relaying new messages as they're dispatched instead of exiting
+    /// once all dispatched messages are relayed.
+    #[arg(long, default_value_t = false)]
+    daemon: bool,
+    /// File recording the last processed nonce, to resume from. Defaults to
+    /// `relay-<destination>.json` in the origin's directory of the
+    /// environment.
+    #[arg(long)]
+    checkpoint_file: Option<PathBuf>,
+    #[command(flatten)]
+    resume: ResumeArgs,
+    /// Seconds to wait between polls for new messages and validator
+    /// signatures.
+    #[arg(long, default_value_t = 5)]
+    poll_interval: u64,
+    /// How many times to retry delivering a message before skipping it.
+    /// Without `--daemon`, also how many polls to wait for messages that
+    /// aren't ready, e.g. for lack of validator signatures, before exiting.
+    #[arg(long, default_value_t = 5)]
+    max_retries: u32,
+    /// Jito block engine to submit deliveries to as bundles, paying a tip, to
+    /// land them during congestion, e.g.
+    /// `https://mainnet.block-engine.jito.wtf`.
+    #[arg(long)]
+    jito_url: Option<String>,
+    /// The tip paid for each bundle, in lamports, with `--jito-url`.
+    #[arg(
+        long,
+        default_value_t = jito::MIN_TIP_LAMPORTS,
+        value_parser = clap::value_parser!(u64).range(jito::MIN_TIP_LAMPORTS..)
+    )]
+    tip_lamports: u64,
+    /// Sends deliveries without simulating them first.
+    #[arg(long)]
+    skip_preflight: bool,
+    /// Events POSTed to the webhook: `dispatched` once for every message of the
+    /// origin, `deliverySucceeded` and `deliveryFailed`.
+    #[command(flatten)]
+    webhook: WebhookArgs,
+}
+
+#[cfg(feature = "index")]
+#[derive(Args)]
+pub(crate) struct MonitorCmd {
+    #[command(subcommand)]
+    cmd: MonitorSubCmd,
+}
+
+#[cfg(feature = "index")]
+#[derive(Subcommand)]
+pub(crate) enum MonitorSubCmd {
+    /// Compares the outbox's message count with the indexed messages and
+    /// their deliveries, alerting on nonces missing from the index and on
+    /// messages not delivered in time. Exits with 5 on anomalies, unless
+    /// watching.
+    Outbox(MonitorOutbox),
+    /// Checks the outboxes of several chains' core deployments against the
+    /// index like `outbox`, reading the outboxes concurrently.
+    Outboxes(MonitorOutboxes),
+}
+
+#[cfg(feature = "index")]
+#[derive(Args)]
+pub(crate) struct MonitorOutbox {
+    #[arg(long, default_value_t = MAILBOX_PROG_ID)]
+    mailbox: Pubkey,
+    #[command(flatten)]
+    checks: MonitorChecks,
+}
+
+#[cfg(feature = "index")]
+#[derive(Args)]
+pub(crate) struct MonitorOutboxes {
+    #[arg(long)]
+    environment: String,
+    #[arg(long)]
+    environments_dir: PathBuf,
+    #[arg(long)]
+    chain_config_file: PathBuf,
+    #[arg(long, num_args = 1.., value_delimiter = ',')]
+    chains: Vec<String>,
+    #[command(flatten)]
+    checks: MonitorChecks,
+}
+
+#[cfg(feature = "index")]
+#[derive(Args)]
+pub(crate) struct MonitorChecks {
+    /// The SQLite index, synced with `index sync` for the mailbox and the
+    /// destination mailboxes.
+    #[arg(long, default_value = "messages.sqlite")]
+    db: PathBuf,
+    /// Slots after its dispatch after which an undelivered message is stuck.
+    #[arg(long, default_value_t = 1500)]
+    stuck_after_slots: u64,
+    /// How many messages the index may be behind the outbox, as dispatches
+    /// since the last sync aren't indexed yet.
+    #[arg(long, default_value_t = 0)]
+    max_index_lag: u32,
+    /// Check again every this many seconds rather than once.
+    #[arg(long)]
+    watch: Option<u64>,
+}
+
+#[cfg(feature = "index")]
+#[derive(Args)]
+pub(crate) struct ReportCmd {
+    #[command(subcommand)]
+    cmd: ReportSubCmd,
+}
+
+#[cfg(feature = "index")]
+#[derive(Subcommand)]
+pub(crate) enum ReportSubCmd {
+    /// Prints the p50, p90 and p99 times from dispatch to delivery of a
+    /// route's most recent delivered messages. Deliveries are only known for
+    /// Sealevel destinations whose mailbox is synced into the index.
+    Latency(ReportLatency),
+}
+
+#[cfg(feature = "index")]
+#[derive(Args)]
+pub(crate) struct ReportLatency {
+    /// The route, as `<origin>-><destination>` domain IDs or chain names.
+    #[arg(long)]
+    route: Route,
+    /// How many of the most recent delivered messages to report on.
+    #[arg(long, default_value_t = 100)]
+    last: usize,
+    #[arg(long, default_value = "messages.sqlite")]
+    db: PathBuf,
+    /// RPC URL of the destination chain, to get the delivery times from.
+    /// Defaults to the configured URL, which dispatch times are from.
+    #[arg(long)]
+    destination_url: Option<String>,
+}
+
+#[cfg(feature = "index")]
+#[derive(Args)]
+pub(crate) struct IndexCmd {
+    #[command(subcommand)]
+    cmd: IndexSubCmd,
+}
+
+#[cfg(feature = "index")]
+#[derive(Subcommand)]
+pub(crate) enum IndexSubCmd {
+    /// Indexes the mailbox's dispatched and processed messages that aren't
+    /// indexed yet.
+    Sync(IndexSync),
+    /// Searches the indexed dispatched messages.
+    Query(IndexQuery),
+    /// Proves an indexed message against the outbox's merkle tree,
+    /// reconstructed from the indexed messages.
+    Proof(IndexProof),
+    /// Exports the indexed dispatched messages with their deliveries, and
+    /// optionally their gas payments, for spreadsheets and data pipelines.
+    Export(IndexExport),
+}
+
+#[cfg(feature = "index")]
+#[derive(Args)]
+pub(crate) struct IndexExport {
+    #[arg(long, default_value = "messages.sqlite")]
+    db: PathBuf,
+    /// The origin mailbox. Messages of all indexed mailboxes by default.
+    #[arg(long, short)]
+    program_id: Option<Pubkey>,
+    #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+    format: ExportFormat,
+    /// The file to write the messages to.
+    #[arg(long)]
+    out: PathBuf,
+    /// The IGP program to look up the messages' gas payments in, all of
+    /// which are fetched at once. Gas payments aren't exported otherwise.
+    #[arg(long)]
+    igp_program_id: Option<Pubkey>,
+}
+
+#[cfg(feature = "index")]
+#[derive(Copy, Clone, ValueEnum)]
+pub(crate) enum ExportFormat {
+    /// Comma-separated values with a header row.
+    Csv,
+    /// An Apache Parquet file, with message bodies as bytes rather than hex.
+    Parquet,
+}
+
+#[cfg(feature = "index")]
+#[derive(Args)]
+pub(crate) struct IndexProof {
+    /// The ID of the message to prove.
+    message_id: H256,
+    #[arg(long, default_value = "messages.sqlite")]
+    db: PathBuf,
+    /// The origin mailbox.
+    #[arg(long, short, default_value_t = MAILBOX_PROG_ID)]
+    program_id: Pubkey,
+    /// Prove against the root of the tree of this many messages, e.g. a
+    /// checkpoint's index plus one. All indexed messages by default.
+    #[arg(long)]
+    count: Option<u32>,
+    /// Print the proof as JSON.
+    #[arg(long, default_value_t = false)]
+    json: bool,
+}
+
+#[cfg(feature = "index")]
+#[derive(Args)]
+pub(crate) struct IndexSync {
+    #[arg(long, short, default_value_t = MAILBOX_PROG_ID)]
+    program_id: Pubkey,
+    /// The SQLite database to index into, created if it doesn't exist.
+    #[arg(long, default_value = "messages.sqlite")]
+    db: PathBuf,
+    /// Don't look up the transaction of each new message, which takes an RPC
+    /// request per message.
+    #[arg(long, default_value_t = false)]
+    no_signatures: bool,
+    /// Also walk the mailbox's transaction history, recovering the messages
+    /// it dispatched from the logs of their transactions. Later backfills
+    /// only walk the transactions since the last one.
+    #[arg(long, default_value_t = false)]
+    backfill: bool,
+    #[command(flatten)]
+    resume: ResumeArgs,
+}
+
+#[cfg(feature = "index")]
+#[derive(Args)]
+pub(crate) struct IndexQuery {
+    #[arg(long, default_value = "messages.sqlite")]
+    db: PathBuf,
+    /// The origin mailbox. Messages of all indexed mailboxes by default.
+    #[arg(long, short)]
+    program_id: Option<Pubkey>,
+    #[arg(long, short)]
+    nonce: Option<u32>,
+    #[arg(long)]
+    message_id: Option<H256>,
+    #[arg(long)]
+    sender: Option<Address>,
+    #[arg(long, short)]
+    recipient: Option<Address>,
+    /// The destination domain ID or chain name.
+    #[arg(long, short)]
+    destination: Option<DomainArg>,
+    /// Only messages whose delivery isn't indexed.
+    #[arg(long, default_value_t = false)]
+    undelivered: bool,
+    #[arg(long, default_value_t = 100)]
+    limit: usize,
+    /// Print each message's dispatch, and its delivery if indexed, as JSON
+    /// event lines.
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Args)]
+pub(crate) struct VerifyCmd {
+    #[command(subcommand)]
+    cmd: VerifySubCmd,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum VerifySubCmd {
+    /// Checks the cross-chain invariants of an environment's deployment,
+    /// printing a pass or fail line per check and exiting with an error if
+    /// any failed.
+    Deployment(VerifyDeployment),
+}
+
+#[derive(Args)]
+pub(crate) struct VerifyDeployment {
+    #[arg(long)]
+    environment: String,
+    #[arg(long)]
+    environments_dir: PathBuf,
+    #[arg(long)]
+    chain_config_file: PathBuf,
+    /// Sealevel chains to check, defaulting to all with a core deployment in
+    /// the environment.
+    #[arg(long, value_delimiter = ',')]
+    chains: Vec<String>,
+    /// Warp routes to check, defaulting to all in the environment.
+    #[cfg(feature = "tokens")]
+    #[arg(long, value_delimiter = ',')]
+    warp_routes: Vec<String>,
+}
+
+#[derive(Args)]
+pub(crate) struct TxCmd {
+    #[command(subcommand)]
+    cmd: TxSubCmd,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum TxSubCmd {
+    /// Runs a command, writing the transactions it would send unsigned to a
+    /// file instead of sending them. Use with `--nonce-account` for the
+    /// transactions not to expire before they're broadcast.
+    Build(TxBuild),
+    /// Signs the transactions in a file with the payer and other signers, on
+    /// a machine that doesn't need network access.
+    Sign(TxSign),
+    /// Sends the signed transactions in a file.
+    Broadcast(TxBroadcast),
+    /// Fetches a sent transaction and decodes the instructions of the
+    /// Hyperlane programs in it, printing their arguments and the PDAs they
+    /// involve.
+    Inspect(TxInspect),
+}
+
+#[derive(Args)]
+pub(crate) struct TxBuild {
+    /// File to write the unsigned transactions to, one base64 encoded
+    /// transaction per line.
+    #[arg(long)]
+    out: PathBuf,
+    /// The command to build transactions of, e.g. `mailbox transfer-ownership
+    /// <new-owner>`.
+    #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+    command: Vec<String>,
+}
+
+/// The command given to `tx build`.
+#[derive(Parser)]
+#[command(no_binary_name = true)]
+struct TxBuildCommand {
+    #[command(subcommand)]
+    cmd: HyperlaneSealevelCmd,
+}
+
+#[derive(Args)]
+pub(crate) struct TxSign {
+    /// File with the transactions to sign. The signatures are added in place.
+    #[arg(long)]
+    transactions: PathBuf,
+    /// Keypair files of signers besides the payer.
+    #[arg(long)]
+    signers: Vec<String>,
+}
+
+#[derive(Args)]
+pub(crate) struct TxBroadcast {
+    /// File with the signed transactions to send.
+    #[arg(long)]
+    transactions: PathBuf,
+    /// Once all transactions are confirmed, wait for them to be finalized.
+    #[arg(long, default_value_t = false)]
+    finalize: bool,
+}
+
+#[derive(Args)]
+pub(crate) struct TxInspect {
+    /// The signature of the transaction.
+    signature: Signature,
+    #[arg(long, default_value_t = MAILBOX_PROG_ID)]
+    mailbox: Pubkey,
+    /// The IGP program, to also decode its instructions.
+    #[arg(long)]
+    igp_program_id: Option<Pubkey>,
+    #[arg(long, default_value_t = MULTISIG_ISM_MESSAGE_ID_PROG_ID)]
+    multisig_ism_program_id: Pubkey,
+    #[arg(long, default_value_t = VALIDATOR_ANNOUNCE_PROG_ID)]
+    validator_announce_program_id: Pubkey,
+    /// Warp route programs to decode the instructions of.
+    #[cfg(feature = "tokens")]
+    #[arg(long = "warp-route", default_values_t = [HYPERLANE_TOKEN_PROG_ID])]
+    warp_routes: Vec<Pubkey>,
+}
+
+#[cfg(feature = "names")]
+#[derive(Args)]
+struct NamesCmd {
+    #[command(subcommand)]
+    cmd: NamesSubCmd,
+}
+
+#[cfg(feature = "names")]
+#[derive(Subcommand)]
+enum NamesSubCmd {
+    /// Resolves a `.sol` name with SNS or any other name with ENS.
+    Resolve(NamesResolve),
+}
+
+#[cfg(feature = "names")]
+#[derive(Args)]
+struct NamesResolve {
+    name: String,
+}
+
+#[derive(Args)]
+struct MessageCmd {
+    #[command(subcommand)]
+    cmd: MessageSubCmd,
+}
+
+#[derive(Subcommand)]
+enum MessageSubCmd {
+    /// Computes the ID of an encoded message, or predicts the ID of the
+    /// message the payer would dispatch next. What isn't given is read from
+    /// the mailbox.
+    Id(MessageId),
+    /// Recovers the messages a transaction dispatched or processed from its
+    /// logs, even once their accounts are gone.
+    FromTx(MessageFromTx),
+}
+
+#[derive(Args)]
+struct MessageId {
+    /// The hex encoded message to compute the ID of.
+    #[arg(long, conflicts_with_all = ["origin", "nonce", "sender", "destination", "recipient"])]
+    message: Option<String>,
+    /// The origin domain ID or chain name. Defaults to the mailbox's local
+    /// domain.
+    #[arg(long)]
+    origin: Option<DomainArg>,
+    /// The nonce. Defaults to the nonce of the mailbox's next dispatch.
+    #[arg(long)]
+    nonce: Option<u32>,
+    /// The sender. Defaults to the payer.
+    #[arg(long)]
+    sender: Option<Address>,
+    /// The destination domain ID or chain name.
+    #[arg(long, required_unless_present = "message")]
+    destination: Option<DomainArg>,
+    /// The recipient.
+    #[arg(long, required_unless_present = "message")]
+    recipient: Option<Address>,
+    #[command(flatten)]
+    body: BodyArgs,
+    #[arg(long, short, default_value_t = MAILBOX_PROG_ID)]
+    program_id: Pubkey,
+}
+
+#[derive(Args)]
+struct MessageFromTx {
+    /// The signature of the transaction.
+    signature: Signature,
+}
+
+#[derive(Args)]
+struct LocalnetCmd {
+    #[command(subcommand)]
+    cmd: LocalnetSubCmd,
+}
+
+#[derive(Subcommand)]
+enum LocalnetSubCmd {
+    /// Deploys mailboxes for two chains, a warp route between them and a
+    /// funded demo wallet, all with keypairs derived from a fixed seed.
+    Fixtures(LocalnetFixtures),
+    /// Starts a local validator, or connects to the one running at the RPC URL,
+    /// and deploys a ready-to-use environment: a mailbox and IGP, a test ISM, a
+    /// recipient that accepts every message, and a funded payer.
+    Up(LocalnetUp),
+}
+
+#[derive(Args)]
+struct LocalnetUp {
+    #[arg(long, default_value = "localnet")]
+    environment: String,
+    #[arg(long)]
+    environments_dir: PathBuf,
+    #[arg(long)]
+    built_so_dir: PathBuf,
+    #[arg(long, default_value = "sealeveltest1")]
+    chain: String,
+    #[arg(long, default_value_t = ECLIPSE_DOMAIN)]
+    local_domain: u32,
+    #[arg(long, num_args = 1.., value_delimiter = ',', default_value = "13376")]
+    remote_domains: Vec<u32>,
+    /// Directory of the SPL program shared objects loaded into a started
+    /// validator, which must include `spl_noop.so`.
+    #[arg(long)]
+    spl_programs_dir: Option<PathBuf>,
+    /// The ledger directory of a started validator. Defaults to `ledger` in
+    /// the environment's directory.
+    #[arg(long)]
+    ledger_dir: Option<PathBuf>,
+    /// How much SOL to airdrop to the payer.
+    #[arg(long, default_value_t = 100.0)]
+    payer_sol: f64,
+}
+
+#[derive(Args)]
+struct LocalnetFixtures {
+    #[arg(long, default_value = "local-fixtures")]
+    environment: String,
+    #[arg(long)]
+    environments_dir: PathBuf,
+    #[arg(long)]
+    built_so_dir: PathBuf,
+    /// The seed all fixture keypairs are derived from.
+    #[arg(long, default_value = "hyperlane-localnet-fixtures")]
+    seed: String,
+    /// How much SOL to airdrop to the demo wallet.
+    #[arg(long, default_value_t = 100.0)]
+    demo_wallet_sol: f64,
+}
+
 #[derive(Args)]
 struct MailboxCmd {
     #[command(subcommand)]
@@ -197,49 +1151,218 @@ enum MailboxSubCmd {
     Init(Init),
     Query(Query),
     Send(Outbox),
+    /// Dispatches a message from an EVM origin chain's mailbox.
+    #[cfg(feature = "evm")]
+    SendFromEvm(EvmOutbox),
+    Broadcast(Broadcast),
+    /// Dispatches the messages in a JSON file, packing as many dispatches
+    /// into each transaction as fit.
+    SendBatch(SendBatch),
+    FindTagged(FindTagged),
     Delivered(Delivered),
+    /// Tracks a message from its dispatch on the origin, Sealevel or EVM, to
+    /// its delivery on the destination.
+    Status(MessageStatus),
+    /// Prints the latest checkpoint and whether validators have signed it.
+    Checkpoint(MailboxCheckpoint),
     TransferOwnership(TransferOwnership),
     SetDefaultIsm(SetDefaultIsm),
+    GetOwner(Query),
+    GetDefaultIsm(Query),
+    Inbox(InboxCmd),
+    Outbox(OutboxCmd),
+}
+
+#[derive(Args)]
+struct InboxCmd {
+    #[command(subcommand)]
+    cmd: InboxSubCmd,
+}
+
+#[derive(Subcommand)]
+enum InboxSubCmd {
+    /// Prints the inbox's default ISM and processed message count.
+    Show(Query),
+}
+
+#[derive(Args)]
+struct OutboxCmd {
+    #[command(subcommand)]
+    cmd: OutboxSubCmd,
+}
+
+#[derive(Subcommand)]
+enum OutboxSubCmd {
+    /// Prints the outbox's merkle tree state and owner.
+    Show(Query),
 }
 
 const MAILBOX_PROG_ID: Pubkey = pubkey!("692KZJaoe2KRcD6uhCQDLLXnLNA5ZLnfvdqjE4aX9iu1");
+#[cfg(feature = "tokens")]
 const HYPERLANE_TOKEN_PROG_ID: Pubkey = pubkey!("3MzUPjP5LEkiHH82nEAe28Xtz9ztuMqWc8UmuKxrpVQH");
 const MULTISIG_ISM_MESSAGE_ID_PROG_ID: Pubkey =
     pubkey!("2YjtZDiUoptoSsA5eVrDCcX6wxNK6YoEVW7y82x5Z2fw");
 const VALIDATOR_ANNOUNCE_PROG_ID: Pubkey = pubkey!("DH43ae1LwemXAboWwSh8zc9pG8j72gKUEXNi57w8fEnn");
 
 #[derive(Args)]
-struct Init {
-    #[arg(long, short, default_value_t = MAILBOX_PROG_ID)]
-    program_id: Pubkey,
-    #[arg(long, short, default_value_t = ECLIPSE_DOMAIN)]
-    local_domain: u32,
-    #[arg(long, short, default_value_t = MULTISIG_ISM_MESSAGE_ID_PROG_ID)]
-    default_ism: Pubkey,
+struct Init {
+    #[arg(long, short, default_value_t = MAILBOX_PROG_ID)]
+    program_id: Pubkey,
+    #[arg(long, short, default_value_t = ECLIPSE_DOMAIN)]
+    local_domain: u32,
+    #[arg(long, short, default_value_t = MULTISIG_ISM_MESSAGE_ID_PROG_ID)]
+    default_ism: Pubkey,
+}
+
+#[derive(Args)]
+struct Query {
+    #[arg(long, short, default_value_t = MAILBOX_PROG_ID)]
+    program_id: Pubkey,
+}
+
+#[derive(Args)]
+struct SetDefaultIsm {
+    #[arg(long, short)]
+    program_id: Pubkey,
+    #[arg(long, short)]
+    default_ism: Pubkey,
+    /// Print the instruction as a proposal for the owner multisig instead of
+    /// sending it.
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "squads")]
+    as_multisig_proposal: Option<ProposalFormat>,
+}
+
+#[derive(Args)]
+struct Outbox {
+    /// The destination domain ID or chain name.
+    #[arg(long, short, visible_alias = "destination-chain", default_value_t = DomainArg::Id(ECLIPSE_DOMAIN))]
+    destination: DomainArg,
+    #[arg(long, short)]
+    recipient: Pubkey,
+    #[command(flatten)]
+    body: BodyArgs,
+    #[arg(long, short, default_value_t = MAILBOX_PROG_ID)]
+    program_id: Pubkey,
+    /// Tags the message body with a request ID so replies can be correlated.
+    #[arg(long)]
+    request_id: Option<H256>,
+    /// Splits a body too large for a single dispatch transaction across
+    /// several messages, which the recipient must reassemble.
+    #[arg(long)]
+    chunk: bool,
+    /// The origin domain ID or chain name. If given, the message isn't
+    /// dispatched unless the mailbox is of the origin domain.
+    #[arg(long, visible_alias = "origin-chain")]
+    origin: Option<DomainArg>,
+    /// Dispatch without checking that the recipient is deployed on the
+    /// destination.
+    #[arg(long)]
+    skip_recipient_check: bool,
+    /// Wait for the message to be delivered on the destination after
+    /// dispatching it.
+    #[arg(long)]
+    wait_for_delivery: bool,
+    /// Seconds to wait for the delivery of the message before failing.
+    #[arg(long, requires = "wait_for_delivery", default_value_t = 600)]
+    timeout: u64,
+    #[command(flatten)]
+    destination_rpc: DestinationArgs,
+}
+
+#[cfg(feature = "evm")]
+#[derive(Args)]
+struct EvmOutbox {
+    #[arg(long)]
+    rpc_url: String,
+    /// The mailbox contract address on the origin chain.
+    #[arg(long)]
+    mailbox: ethers::types::Address,
+    #[arg(long, env = "EVM_PRIVATE_KEY", hide_env_values = true)]
+    private_key: String,
+    /// The destination domain ID or chain name.
+    #[arg(long, short, visible_alias = "destination-chain", default_value_t = DomainArg::Id(ECLIPSE_DOMAIN))]
+    destination: DomainArg,
+    /// The recipient as a hex or base58 address.
+    #[arg(long, short)]
+    recipient: Address,
+    #[command(flatten)]
+    body: BodyArgs,
+    /// Tags the message body with a request ID so replies can be correlated.
+    #[arg(long)]
+    request_id: Option<H256>,
+    /// Dispatch without checking that the recipient is deployed on the
+    /// destination.
+    #[arg(long)]
+    skip_recipient_check: bool,
+    /// Wait for the message to be delivered on the destination after
+    /// dispatching it.
+    #[arg(long)]
+    wait_for_delivery: bool,
+    /// Seconds to wait for the delivery of the message before failing.
+    #[arg(long, requires = "wait_for_delivery", default_value_t = 600)]
+    timeout: u64,
+    #[command(flatten)]
+    destination_rpc: DestinationArgs,
 }
 
 #[derive(Args)]
-struct Query {
+struct FindTagged {
     #[arg(long, short, default_value_t = MAILBOX_PROG_ID)]
     program_id: Pubkey,
+    #[arg(long)]
+    request_id: H256,
+    /// Keep printing tagged messages as they're dispatched, until interrupted.
+    #[arg(long)]
+    watch: bool,
+    /// Websocket URL of the RPC, with `--watch`. Derived from the RPC URL by
+    /// default.
+    #[arg(long, requires = "watch")]
+    ws_url: Option<String>,
+    /// Watch over Geyser gRPC instead of the websocket, with `--watch`.
+    #[cfg(feature = "geyser")]
+    #[command(flatten)]
+    geyser: GeyserArgs,
 }
 
 #[derive(Args)]
-struct SetDefaultIsm {
+struct Broadcast {
+    /// The destination domain IDs or chain names.
+    #[arg(long, num_args = 1.., value_delimiter = ',')]
+    destinations: Vec<DomainArg>,
+    /// The recipient on all destinations, as hex or base58.
     #[arg(long, short)]
+    recipient: Option<Address>,
+    /// Per-destination recipients of the form <domain or chain>=<recipient>.
+    #[arg(long, value_delimiter = ',')]
+    recipient_overrides: Vec<String>,
+    #[command(flatten)]
+    body: BodyArgs,
+    #[arg(long, short, default_value_t = MAILBOX_PROG_ID)]
     program_id: Pubkey,
-    #[arg(long, short)]
-    default_ism: Pubkey,
 }
 
 #[derive(Args)]
-struct Outbox {
-    #[arg(long, short, default_value_t = ECLIPSE_DOMAIN)]
-    destination: u32,
-    #[arg(long, short)]
-    recipient: Pubkey,
-    #[arg(long, short, default_value = "Hello, World!")]
-    message: String,
+struct SendBatch {
+    /// A JSON array of messages, each with a `destination`, a `recipient` and
+    /// a `body` or `bodyHex`.
+    #[arg(long)]
+    file: PathBuf,
+    /// The most dispatches to pack into a single transaction, e.g. to stay
+    /// within the compute budget.
+    #[arg(long)]
+    max_messages_per_transaction: Option<usize>,
+    /// How many transactions to have in flight at once.
+    #[arg(long, default_value_t = 1)]
+    concurrency: usize,
+    /// Send transactions directly to the leaders' TPUs instead of through
+    /// the RPC, when sending concurrently. Transactions aren't simulated
+    /// first.
+    #[arg(long, default_value_t = false)]
+    tpu: bool,
+    /// Websocket URL of the RPC, to track the leader schedule with `--tpu`.
+    /// Derived from the RPC URL by default.
+    #[arg(long, requires = "tpu")]
+    ws_url: Option<String>,
     #[arg(long, short, default_value_t = MAILBOX_PROG_ID)]
     program_id: Pubkey,
 }
@@ -270,22 +1393,92 @@ struct Delivered {
     message_id: H256,
 }
 
+#[derive(Args)]
+struct MailboxCheckpoint {
+    #[arg(long, short, default_value_t = MAILBOX_PROG_ID)]
+    program_id: Pubkey,
+    #[arg(long, default_value_t = VALIDATOR_ANNOUNCE_PROG_ID)]
+    validator_announce_program_id: Pubkey,
+    /// Validators to check for a published signature of the checkpoint.
+    #[arg(long, value_delimiter = ',')]
+    validators: Vec<H160>,
+    /// Print the validators' published signatures of the checkpoint as JSON
+    /// event lines.
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Args)]
+struct MessageStatus {
+    /// The nonce of the message on the origin mailbox.
+    #[arg(long, short)]
+    nonce: u32,
+    #[arg(long, default_value_t = MAILBOX_PROG_ID)]
+    origin_program_id: Pubkey,
+    /// RPC URL of the origin chain. Defaults to the configured URL.
+    #[arg(long)]
+    origin_url: Option<String>,
+    #[arg(long, default_value_t = MAILBOX_PROG_ID)]
+    destination_program_id: Pubkey,
+    /// RPC URL of the destination chain. Defaults to the configured URL.
+    #[arg(long)]
+    destination_url: Option<String>,
+    /// The IGP program on the origin chain, to also look up gas payments.
+    #[arg(long)]
+    igp_program_id: Option<Pubkey>,
+    /// Print the message's events as JSON lines.
+    #[arg(long)]
+    json: bool,
+    /// RPC URL of an EVM origin chain, to read the dispatch from instead.
+    #[cfg(feature = "evm")]
+    #[arg(
+        long,
+        requires = "origin_evm_mailbox",
+        conflicts_with_all = ["origin_url", "igp_program_id"]
+    )]
+    origin_evm_rpc_url: Option<String>,
+    /// The mailbox contract address on the EVM origin chain.
+    #[cfg(feature = "evm")]
+    #[arg(long, requires = "origin_evm_rpc_url")]
+    origin_evm_mailbox: Option<ethers::types::Address>,
+    /// RPC URL of an EVM destination chain.
+    #[cfg(feature = "evm")]
+    #[arg(long, requires = "destination_evm_mailbox")]
+    destination_evm_rpc_url: Option<String>,
+    /// The mailbox contract address on the EVM destination chain.
+    #[cfg(feature = "evm")]
+    #[arg(long)]
+    destination_evm_mailbox: Option<ethers::types::Address>,
+}
+
+#[cfg(feature = "tokens")]
 #[derive(Args)]
 struct TokenCmd {
     #[command(subcommand)]
     cmd: TokenSubCmd,
 }
 
+#[cfg(feature = "tokens")]
 #[derive(Subcommand)]
 enum TokenSubCmd {
+    /// Deploys a warp route, optionally guided by prompts.
+    Deploy(TokenDeploy),
     Query(TokenQuery),
     TransferRemote(TokenTransferRemote),
     EnrollRemoteRouter(TokenEnrollRemoteRouter),
+    /// Enrolls the remote routers in a JSON or YAML file mapping domains to
+    /// routers, batching them into as few transactions as fit them. Routers
+    /// that are null are unenrolled.
+    EnrollRemoteRouters(TokenEnrollRemoteRouters),
+    /// Exports the enrolled remote routers as a JSON file mapping domains to
+    /// routers, as read by `enroll-remote-routers`.
+    ExportRouters(TokenExportRouters),
     TransferOwnership(TransferOwnership),
     SetInterchainSecurityModule(SetInterchainSecurityModule),
     Igp(Igp),
 }
 
+#[cfg(feature = "tokens")]
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum TokenType {
     Native,
@@ -293,14 +1486,68 @@ pub enum TokenType {
     Collateral,
 }
 
+#[cfg(feature = "tokens")]
+#[derive(Args)]
+struct TokenDeploy {
+    /// Prompt for the chains, tokens, ISMs and IGPs of the warp route,
+    /// writing its token config file, and check the deployed routers are
+    /// linked.
+    #[arg(long)]
+    interactive: bool,
+    #[arg(long)]
+    environment: String,
+    #[arg(long)]
+    environments_dir: PathBuf,
+    #[arg(long)]
+    built_so_dir: PathBuf,
+    #[arg(long)]
+    chain_config_file: PathBuf,
+    #[arg(long, required_unless_present = "interactive")]
+    warp_route_name: Option<String>,
+    #[arg(long, required_unless_present_any = ["interactive", "mode"])]
+    token_config_file: Option<PathBuf>,
+    #[arg(long)]
+    ata_payer_funding_amount: Option<u64>,
+    /// Deploy a router of this type to `--chain` without a token config
+    /// file. It's added to the warp route's token config, so it's enrolled
+    /// with the routers already deployed to other chains, and they with it.
+    #[arg(
+        long,
+        value_enum,
+        conflicts_with_all = ["interactive", "token_config_file"],
+        requires = "chain"
+    )]
+    mode: Option<TokenType>,
+    /// The chain to deploy the router to with `--mode`.
+    #[arg(long)]
+    chain: Option<String>,
+    /// The mint of the collateral token, with `--mode collateral`.
+    #[arg(long, required_if_eq("mode", "collateral"))]
+    mint: Option<Pubkey>,
+    /// The decimals of the token, with `--mode`.
+    #[arg(long, default_value_t = 9)]
+    decimals: u8,
+    /// The name of the synthetic token, with `--mode synthetic`.
+    #[arg(long, required_if_eq("mode", "synthetic"))]
+    name: Option<String>,
+    /// The symbol of the synthetic token, with `--mode synthetic`.
+    #[arg(long, required_if_eq("mode", "synthetic"))]
+    symbol: Option<String>,
+}
+
+#[cfg(feature = "tokens")]
 #[derive(Args)]
 struct TokenQuery {
     #[arg(long, short, default_value_t = HYPERLANE_TOKEN_PROG_ID)]
     program_id: Pubkey,
     #[arg(value_enum)]
     token_type: TokenType,
+    /// Also print the router's PDAs and their raw account data.
+    #[arg(long)]
+    raw: bool,
 }
 
+#[cfg(feature = "tokens")]
 #[derive(Args)]
 struct TokenTransferRemote {
     #[arg(long, short, default_value_t = HYPERLANE_TOKEN_PROG_ID)]
@@ -310,11 +1557,12 @@ struct TokenTransferRemote {
     amount: u64,
     // #[arg(long, short, default_value_t = ECLIPSE_DOMAIN)]
     destination_domain: u32,
-    recipient: String,
+    recipient: Address,
     #[arg(value_enum)]
     token_type: TokenType,
 }
 
+#[cfg(feature = "tokens")]
 #[derive(Args)]
 struct TokenEnrollRemoteRouter {
     #[arg(long, short, default_value_t = HYPERLANE_TOKEN_PROG_ID)]
@@ -323,6 +1571,26 @@ struct TokenEnrollRemoteRouter {
     router: H256,
 }
 
+#[cfg(feature = "tokens")]
+#[derive(Args)]
+struct TokenEnrollRemoteRouters {
+    #[arg(long, short, default_value_t = HYPERLANE_TOKEN_PROG_ID)]
+    program_id: Pubkey,
+    #[arg(long)]
+    file: PathBuf,
+}
+
+#[cfg(feature = "tokens")]
+#[derive(Args)]
+struct TokenExportRouters {
+    #[arg(long, short, default_value_t = HYPERLANE_TOKEN_PROG_ID)]
+    program_id: Pubkey,
+    /// The file to write to. Printed if not given.
+    #[arg(long)]
+    file: Option<PathBuf>,
+}
+
+#[cfg(feature = "tokens")]
 #[derive(Args)]
 struct SetInterchainSecurityModule {
     #[arg(long, short)]
@@ -338,8 +1606,13 @@ struct TransferOwnership {
     // To avoid accidentally transferring ownership to None,
     // only support transferring to other Pubkeys for now.
     new_owner: Pubkey,
+    /// Print the instruction as a proposal for the owner multisig instead of
+    /// sending it.
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "squads")]
+    as_multisig_proposal: Option<ProposalFormat>,
 }
 
+#[cfg(feature = "tokens")]
 #[derive(Args)]
 struct Igp {
     #[arg(long, short, default_value_t = HYPERLANE_TOKEN_PROG_ID)]
@@ -354,6 +1627,7 @@ enum GetSetCmd<G: Args, S: Args> {
     Set(S),
 }
 
+#[cfg(feature = "tokens")]
 #[derive(Args)]
 struct SetIgpArgs {
     igp_program: Pubkey,
@@ -362,11 +1636,13 @@ struct SetIgpArgs {
     igp_account: Pubkey,
 }
 
+#[cfg(feature = "tokens")]
 #[derive(Args)]
 struct GetIgpArgs {
     token_type: TokenType,
 }
 
+#[cfg(feature = "tokens")]
 #[derive(ValueEnum, Clone)]
 enum IgpType {
     Igp,
@@ -387,6 +1663,15 @@ enum IgpSubCmd {
     DestinationGasOverhead(DestinationGasOverheadArgs),
     TransferIgpOwnership(TransferIgpOwnership),
     TransferOverheadIgpOwnership(TransferIgpOwnership),
+    /// Computes the payment a relayer enforcing on-chain fee quotes requires
+    /// for a message with the given destination gas limit.
+    Quote(IgpQuoteArgs),
+    /// Shows the gas oracles and gas overheads of the environment's IGP for
+    /// each destination domain.
+    Show(IgpShowArgs),
+    /// Quotes the payment for an amount of destination gas with the IGP
+    /// program, as `pay-for-gas` would charge it.
+    QuoteGasPayment(QuoteGasPaymentArgs),
 }
 
 #[derive(Args)]
@@ -399,6 +1684,52 @@ struct IgpQueryArgs {
     gas_payment_account: Option<Pubkey>,
 }
 
+#[derive(Args)]
+struct IgpQuoteArgs {
+    /// The IGP account to quote with. Not needed if an overhead IGP is given.
+    #[arg(long, required_unless_present = "overhead_igp_account")]
+    igp_account: Option<Pubkey>,
+    /// The overhead IGP account the payment is made to, if any.
+    #[arg(long)]
+    overhead_igp_account: Option<Pubkey>,
+    #[arg(long)]
+    destination_domain: u32,
+    /// The gas limit of the message's handling on the destination.
+    #[arg(long)]
+    gas_limit: u64,
+    /// The fraction of the gas limit the relayer requires to be paid for, as
+    /// `numerator/denominator`.
+    #[arg(long, default_value = "1/2")]
+    gas_fraction: String,
+}
+
+#[derive(Args)]
+struct IgpShowArgs {
+    #[arg(long)]
+    environment: String,
+    #[arg(long)]
+    environments_dir: PathBuf,
+    #[arg(long)]
+    chain_name: String,
+}
+
+#[derive(Args)]
+struct QuoteGasPaymentArgs {
+    #[arg(long)]
+    environment: String,
+    #[arg(long)]
+    environments_dir: PathBuf,
+    #[arg(long)]
+    chain_name: String,
+    #[arg(long)]
+    destination_domain: u32,
+    #[arg(long)]
+    gas: u64,
+    /// Quote with the IGP alone, without the overhead IGP's gas overhead.
+    #[arg(long)]
+    without_overhead: bool,
+}
+
 #[derive(Args)]
 struct TransferIgpOwnership {
     #[arg(long, short)]
@@ -486,6 +1817,7 @@ enum ValidatorAnnounceSubCmd {
     Init(ValidatorAnnounceInit),
     Announce(ValidatorAnnounceAnnounce),
     Query(ValidatorAnnounceQuery),
+    QueryValidators(ValidatorAnnounceQueryValidators),
 }
 
 #[derive(Args)]
@@ -517,6 +1849,84 @@ struct ValidatorAnnounceQuery {
     validator: H160,
 }
 
+#[derive(Args)]
+struct ValidatorAnnounceQueryValidators {
+    #[arg(long, short, default_value_t = VALIDATOR_ANNOUNCE_PROG_ID)]
+    program_id: Pubkey,
+    /// The mailbox whose default ISM is used if no multisig ISM is specified.
+    #[arg(long, short, default_value_t = MAILBOX_PROG_ID)]
+    mailbox: Pubkey,
+    #[arg(long)]
+    multisig_ism: Option<Pubkey>,
+    /// The origin domain of the validator set.
+    #[arg(long, short)]
+    domain: u32,
+}
+
+#[derive(Args)]
+struct IsmCmd {
+    #[command(subcommand)]
+    cmd: IsmSubCmd,
+}
+
+#[derive(Subcommand)]
+enum IsmSubCmd {
+    /// Shows the ISM protecting a recipient, its module type and its
+    /// parameters.
+    Inspect(IsmInspect),
+    Multisig(MultisigIsmMessageIdCmd),
+    /// The test ISM, which accepts or rejects every message as toggled. Not for
+    /// use in production.
+    Test(TestIsmCmd),
+}
+
+#[derive(Args)]
+struct IsmInspect {
+    /// The recipient whose ISM to inspect.
+    recipient: Address,
+    #[arg(long, short, default_value_t = MAILBOX_PROG_ID)]
+    mailbox: Pubkey,
+    /// Origin domain IDs or chain names to show multisig ISM validators and
+    /// thresholds for.
+    #[arg(long, value_delimiter = ',')]
+    origins: Vec<DomainArg>,
+}
+
+#[derive(Args)]
+struct TestIsmCmd {
+    #[command(subcommand)]
+    cmd: TestIsmSubCmd,
+}
+
+#[derive(Subcommand)]
+enum TestIsmSubCmd {
+    Deploy(TestIsmDeploy),
+    /// Makes the test ISM accept every message.
+    SetAccept(TestIsmSetAccept),
+    /// Makes the test ISM reject every message.
+    SetReject(TestIsmSetAccept),
+}
+
+#[derive(Args)]
+struct TestIsmDeploy {
+    #[arg(long)]
+    environment: String,
+    #[arg(long)]
+    environments_dir: PathBuf,
+    #[arg(long)]
+    built_so_dir: PathBuf,
+    #[arg(long)]
+    chain: String,
+    #[arg(long)]
+    context: String,
+}
+
+#[derive(Args)]
+struct TestIsmSetAccept {
+    #[arg(long, short, default_value_t = hyperlane_sealevel_test_ism::id())]
+    program_id: Pubkey,
+}
+
 #[derive(Args)]
 struct MultisigIsmMessageIdCmd {
     #[command(subcommand)]
@@ -527,7 +1937,9 @@ struct MultisigIsmMessageIdCmd {
 enum MultisigIsmMessageIdSubCmd {
     Deploy(MultisigIsmMessageIdDeploy),
     Init(MultisigIsmMessageIdInit),
+    #[command(alias = "set")]
     SetValidatorsAndThreshold(MultisigIsmMessageIdSetValidatorsAndThreshold),
+    #[command(alias = "show")]
     Query(MultisigIsmMessageIdQuery),
     TransferOwnership(TransferOwnership),
     Configure(MultisigIsmMessageIdConfigure),
@@ -593,6 +2005,14 @@ pub(crate) struct HelloWorldCmd {
 pub(crate) enum HelloWorldSubCmd {
     Deploy(HelloWorldDeploy),
     Query(HelloWorldQuery),
+    /// Dispatches a message to the router enrolled for the destination.
+    Send(HelloWorldSend),
+    /// Enrolls the remote routers in a JSON or YAML file mapping domains to
+    /// routers.
+    EnrollRemoteRouters(HelloWorldEnrollRemoteRouters),
+    /// Exports the enrolled remote routers as a JSON file mapping domains to
+    /// routers.
+    ExportRouters(HelloWorldExportRouters),
 }
 
 #[derive(Args)]
@@ -617,21 +2037,249 @@ pub(crate) struct HelloWorldQuery {
     program_id: Pubkey,
 }
 
+#[derive(Args)]
+pub(crate) struct HelloWorldSend {
+    #[arg(long)]
+    program_id: Pubkey,
+    /// The destination domain ID or chain name.
+    #[arg(long)]
+    destination: DomainArg,
+    #[arg(long)]
+    message: String,
+}
+
+#[derive(Args)]
+pub(crate) struct HelloWorldEnrollRemoteRouters {
+    #[arg(long)]
+    program_id: Pubkey,
+    #[arg(long)]
+    file: PathBuf,
+}
+
+#[derive(Args)]
+pub(crate) struct HelloWorldExportRouters {
+    #[arg(long)]
+    program_id: Pubkey,
+    /// The file to write to. Printed if not given.
+    #[arg(long)]
+    file: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub(crate) struct AccountCmd {
+    #[command(subcommand)]
+    cmd: AccountSubCmd,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum AccountSubCmd {
+    /// Prints the balance of an account.
+    Balance(AccountBalance),
+    /// Requests an airdrop from the cluster's faucet, e.g. on devnets.
+    Airdrop(AccountAirdrop),
+    /// Subscribes to accounts and prints every change to them.
+    Watch(AccountWatch),
+}
+
+#[derive(Args)]
+pub(crate) struct AccountBalance {
+    /// The account to query. Defaults to the payer.
+    pubkey: Option<Pubkey>,
+}
+
+#[derive(Args)]
+pub(crate) struct AccountAirdrop {
+    /// The amount of SOL to request.
+    #[arg(long, default_value_t = 1.0)]
+    amount: f64,
+    /// The account to fund. Defaults to the payer.
+    #[arg(long)]
+    recipient: Option<Pubkey>,
+}
+
+#[derive(Args)]
+pub(crate) struct AccountWatch {
+    /// The accounts to watch.
+    #[arg(required = true)]
+    pubkeys: Vec<Pubkey>,
+    /// Decode the account data as this account type.
+    #[arg(long, value_enum)]
+    decode: Option<AccountType>,
+    /// Websocket URL of the RPC. Derived from the RPC URL by default.
+    #[arg(long)]
+    ws_url: Option<String>,
+    #[cfg(feature = "geyser")]
+    #[command(flatten)]
+    geyser: GeyserArgs,
+    /// Every change printed is also POSTed to the webhook.
+    #[command(flatten)]
+    webhook: WebhookArgs,
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub(crate) enum AccountType {
+    Inbox,
+    Outbox,
+    MultisigIsmDomainData,
+    MultisigIsmAccessControl,
+    IgpProgramData,
+    Igp,
+    OverheadIgp,
+    ValidatorStorageLocations,
+}
+
+#[derive(Args)]
+pub(crate) struct KeysCmd {
+    #[command(subcommand)]
+    cmd: KeysSubCmd,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum KeysSubCmd {
+    /// Generates a new keypair.
+    New(KeysNew),
+    /// Prints the public key of a keypair file.
+    Pubkey(KeysPubkey),
+    /// Prints the PDAs a mailbox derives.
+    DerivePdas(KeysDerivePdas),
+}
+
+#[derive(Args)]
+pub(crate) struct KeysNew {
+    /// The file to write the keypair to. The keypair is printed if not given.
+    #[arg(long, short)]
+    outfile: Option<PathBuf>,
+    /// Overwrite the output file if it exists.
+    #[arg(long)]
+    force: bool,
+    /// Grind for a public key starting with this base58 prefix.
+    #[arg(long)]
+    starts_with: Option<String>,
+}
+
+#[derive(Args)]
+pub(crate) struct KeysPubkey {
+    keypair_file: PathBuf,
+}
+
+#[derive(Args)]
+pub(crate) struct KeysDerivePdas {
+    #[arg(long, default_value_t = MAILBOX_PROG_ID)]
+    mailbox: Pubkey,
+    /// A recipient program, to derive its process authority.
+    #[arg(long)]
+    recipient: Option<Pubkey>,
+    /// A sender program, to derive its dispatch authority.
+    #[arg(long)]
+    sender: Option<Pubkey>,
+    /// A unique message account, to derive its dispatched message PDA.
+    #[arg(long)]
+    unique_message_pubkey: Option<Pubkey>,
+    /// A message ID, to derive its processed message PDA.
+    #[arg(long)]
+    message_id: Option<H256>,
+}
+
+#[cfg(all(feature = "self-update", unix))]
+#[derive(Args)]
+pub(crate) struct SelfUpdate {
+    /// URL of the release feed.
+    #[arg(long)]
+    feed_url: String,
+    /// Hex-encoded ed25519 public key that release artifacts are signed with,
+    /// overriding the release key compiled into the binary, e.g. to test a
+    /// feed signed with another key.
+    #[arg(long)]
+    public_key: Option<String>,
+    /// Only check whether an update is available, exiting with a non-zero
+    /// code if so.
+    #[arg(long)]
+    check_only: bool,
+}
+
 fn main() {
     pretty_env_logger::init();
 
-    let cli = Cli::parse();
-    let config = match cli.config.as_ref().or(CONFIG_FILE.as_ref()) {
-        Some(config_file) => Config::load(config_file)
-            .map_err(|e| format!("Failed to load solana config file {}: {}", config_file, e))
-            .unwrap(),
+    print_examples_if_requested();
+    let profile = Profile::load_selected();
+    let matches = profile
+        .apply_program_ids(with_env_vars(Cli::command()))
+        .get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    init_tracing(cli.verbose);
+    if let HyperlaneSealevelCmd::Completions(completions) = &cli.cmd {
+        print_completions(
+            &DomainRegistry::load(cli.chain_metadata_file.as_deref()),
+            completions,
+        );
+        return;
+    }
+    let config_file = cli.config.as_ref().or(CONFIG_FILE.as_ref());
+    let config = match config_file {
+        Some(config_file) => Config::load(config_file).unwrap_or_else(|e| {
+            exit_code::config_error(format!(
+                "Failed to load solana config file {}: {}",
+                config_file, e
+            ))
+        }),
         None => Config::default(),
     };
-    let url = normalize_to_url_if_moniker(cli.url.unwrap_or(config.json_rpc_url));
-    is_url(&url).unwrap();
-    let client = RpcClient::new(url);
+    if let HyperlaneSealevelCmd::Config(ConfigCmd {
+        cmd: ConfigSubCmd::Show(show),
+    }) = &cli.cmd
+    {
+        show_config(
+            show,
+            &matches,
+            &profile,
+            config_file.map(String::as_str),
+            &config,
+        );
+        return;
+    }
+    let url = normalize_to_url_if_moniker(
+        cli.url
+            .or(profile.url.clone())
+            .unwrap_or(config.json_rpc_url),
+    );
+    if let Err(e) = is_url(&url) {
+        exit_code::config_error(e);
+    }
+    let fallback_urls = cli
+        .fallback_url
+        .into_iter()
+        .map(normalize_to_url_if_moniker)
+        .collect::<Vec<_>>();
+    for url in &fallback_urls {
+        if let Err(e) = is_url(url) {
+            exit_code::config_error(e);
+        }
+    }
+    let domains = DomainRegistry::load(cli.chain_metadata_file.as_deref());
+    // Replayed sessions only recorded the genesis hash if the chain was given
+    // explicitly, so it's not inferred for them.
+    let chain = cli.chain.clone().or_else(|| {
+        cli.replay_rpc.is_none().then(|| {
+            profile
+                .chain
+                .clone()
+                .or_else(|| domains.chain_served_at(&url).map(str::to_owned))
+        })?
+    });
+    let client = match &cli.replay_rpc {
+        Some(fixture) => replay_rpc_client(fixture, url.clone(), CommitmentConfig::default()),
+        None => failover_rpc_client(
+            std::iter::once(url.clone()).chain(fallback_urls).collect(),
+            CommitmentConfig::default(),
+            cli.rpc_rate_limit,
+            cli.record_rpc.as_deref(),
+        ),
+    };
 
-    let keypair_path = cli.keypair.unwrap_or(config.keypair_path);
+    let keypair_path = cli
+        .keypair
+        .or(profile.keypair.clone())
+        .unwrap_or(config.keypair_path);
     let (payer_pubkey, payer_keypair) = if let Ok(payer_keypair) = read_keypair_file(&keypair_path)
     {
         (
@@ -646,7 +2294,13 @@ fn main() {
             "Provided key is not a keypair file, treating as a public key {}",
             keypair_path
         );
-        (Pubkey::from_str(&keypair_path).unwrap(), None)
+        let payer_pubkey = Pubkey::from_str(&keypair_path).unwrap_or_else(|e| {
+            exit_code::config_error(format!(
+                "{} is neither a keypair file nor a public key: {}",
+                keypair_path, e
+            ))
+        });
+        (payer_pubkey, None)
     };
 
     let commitment = CommitmentConfig::processed();
@@ -673,6 +2327,30 @@ fn main() {
         );
     }
 
+    match &chain {
+        Some(chain) => check_genesis_hash(&client, &domains, chain, cli.allow_genesis_mismatch),
+        None if cli.replay_rpc.is_none() && !is_local_url(&url) => eprintln!(
+            "WARNING: can't tell which chain {} serves, pass --chain to check its genesis hash",
+            url
+        ),
+        None => {}
+    }
+
+    let (cmd, unsigned_txns_out) = match cli.cmd {
+        HyperlaneSealevelCmd::Tx(TxCmd {
+            cmd: TxSubCmd::Build(build),
+        }) => {
+            let command = profile
+                .apply_program_ids(with_env_vars(TxBuildCommand::command()))
+                .try_get_matches_from(build.command)
+                .and_then(|matches| TxBuildCommand::from_arg_matches(&matches))
+                .unwrap_or_else(|e| e.exit());
+            write_transactions(&build.out, &[]);
+            (command.cmd, Some(build.out))
+        }
+        cmd => (cmd, None),
+    };
+
     let ctx = Context::new(
         client,
         payer_pubkey,
@@ -680,21 +2358,167 @@ fn main() {
         commitment,
         instructions.into(),
         cli.require_tx_approval,
+        cli.simulate_only,
+        ProtocolConfig::load(cli.protocol_config.as_deref())
+            .unwrap_or_else(|e| exit_code::config_error(e)),
+        domains,
+        cli.receipts_dir,
+        cli.nonce_account,
+        cli.strict,
+        unsigned_txns_out,
     );
-    match cli.cmd {
+    #[cfg(feature = "names")]
+    let ctx = ctx.with_names(names::NameResolver::new(cli.ens_rpc_url));
+    let ctx = match cli.metrics_port {
+        Some(port) => {
+            let metrics = Arc::new(metrics::Metrics::new().expect("Failed to register metrics"));
+            metrics.serve(port);
+            ctx.with_metrics(metrics)
+        }
+        None => ctx,
+    };
+    let ctx = ctx.with_confirmation(ConfirmationStrategy {
+        commitment: cli.confirmation_level.into(),
+        timeout: Duration::from_secs(cli.confirmation_timeout),
+        poll_interval: Duration::from_millis(cli.confirmation_poll_interval),
+    });
+    let ctx = if cli.no_progress {
+        ctx.without_progress()
+    } else {
+        ctx
+    };
+    let ctx = if cli.quiet {
+        ctx
+    } else {
+        ctx.with_explorers(Explorers::new(
+            cli.explorer_url,
+            cli.explorer_cluster,
+            cli.hyperlane_explorer_url,
+        ))
+    };
+    match cmd {
         HyperlaneSealevelCmd::Mailbox(cmd) => process_mailbox_cmd(ctx, cmd),
+        #[cfg(feature = "tokens")]
         HyperlaneSealevelCmd::Token(cmd) => process_token_cmd(ctx, cmd),
         HyperlaneSealevelCmd::ValidatorAnnounce(cmd) => process_validator_announce_cmd(ctx, cmd),
         HyperlaneSealevelCmd::MultisigIsmMessageId(cmd) => {
             process_multisig_ism_message_id_cmd(ctx, cmd)
         }
+        HyperlaneSealevelCmd::Ism(cmd) => match cmd.cmd {
+            IsmSubCmd::Inspect(inspect) => inspect_ism(&ctx, inspect),
+            IsmSubCmd::Multisig(cmd) => process_multisig_ism_message_id_cmd(ctx, cmd),
+            IsmSubCmd::Test(cmd) => process_test_ism_cmd(ctx, cmd),
+        },
         HyperlaneSealevelCmd::Core(cmd) => process_core_cmd(ctx, cmd),
+        #[cfg(feature = "tokens")]
         HyperlaneSealevelCmd::WarpRoute(cmd) => process_warp_route_cmd(ctx, cmd),
         HyperlaneSealevelCmd::HelloWorld(cmd) => process_helloworld_cmd(ctx, cmd),
         HyperlaneSealevelCmd::Igp(cmd) => process_igp_cmd(ctx, cmd),
+        HyperlaneSealevelCmd::Account(cmd) => process_account_cmd(ctx, cmd),
+        HyperlaneSealevelCmd::Keys(cmd) => process_keys_cmd(&ctx, cmd),
+        HyperlaneSealevelCmd::Localnet(cmd) => process_localnet_cmd(ctx, cmd),
+        HyperlaneSealevelCmd::Message(cmd) => process_message_cmd(ctx, cmd),
+        HyperlaneSealevelCmd::Tx(cmd) => process_tx_cmd(ctx, cmd),
+        #[cfg(feature = "names")]
+        HyperlaneSealevelCmd::Names(cmd) => match cmd.cmd {
+            NamesSubCmd::Resolve(resolve) => {
+                let address = ctx
+                    .parse_address(&resolve.name)
+                    .unwrap_or_else(|e| panic!("{}", e));
+                println!(
+                    "{}: {:?} ({})",
+                    resolve.name,
+                    address,
+                    Pubkey::new_from_array(address.0)
+                );
+            }
+        },
+        HyperlaneSealevelCmd::Verify(cmd) => process_verify_cmd(ctx, cmd),
+        HyperlaneSealevelCmd::Estimate(estimate) => process_estimate_cmd(ctx, estimate),
+        HyperlaneSealevelCmd::Relay(relay) => process_relay_cmd(ctx, relay),
+        #[cfg(feature = "index")]
+        HyperlaneSealevelCmd::Index(cmd) => process_index_cmd(ctx, cmd),
+        #[cfg(feature = "index")]
+        HyperlaneSealevelCmd::Monitor(cmd) => process_monitor_cmd(ctx, cmd),
+        #[cfg(feature = "index")]
+        HyperlaneSealevelCmd::Report(cmd) => process_report_cmd(ctx, cmd),
+        HyperlaneSealevelCmd::Examples(examples) => {
+            process_examples(&ctx.domains, ctx.payer_pubkey, examples)
+        }
+        HyperlaneSealevelCmd::Schema(schema) => print_schema(schema),
+        HyperlaneSealevelCmd::Completions(_) => {
+            panic!("`completions` is run before connecting to the RPC")
+        }
+        HyperlaneSealevelCmd::Config(ConfigCmd {
+            cmd: ConfigSubCmd::Validate(validate),
+        }) => validate_config(&ctx, validate),
+        HyperlaneSealevelCmd::Config(ConfigCmd {
+            cmd: ConfigSubCmd::Show(_),
+        }) => panic!("`config show` is run before connecting to the RPC"),
+        #[cfg(all(feature = "self-update", unix))]
+        HyperlaneSealevelCmd::SelfUpdate(cmd) => process_self_update(cmd),
+    }
+}
+
+fn init_tracing(verbosity: u8) {
+    use tracing::level_filters::LevelFilter;
+    use tracing_subscriber::{filter::Targets, prelude::*};
+
+    let targets = match verbosity {
+        0 => Targets::new().with_default(LevelFilter::WARN),
+        1 => Targets::new()
+            .with_default(LevelFilter::WARN)
+            .with_target(module_path!(), LevelFilter::DEBUG)
+            .with_target(concat!(module_path!(), "::rpc"), LevelFilter::WARN),
+        _ => Targets::new()
+            .with_default(LevelFilter::WARN)
+            .with_target(module_path!(), LevelFilter::DEBUG),
+    };
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(targets)
+        .init();
+}
+
+/// Guards against operating on the wrong cluster, e.g. dispatching on devnet
+/// with mainnet keys, by comparing the RPC's genesis hash to the chain's.
+fn check_genesis_hash(
+    client: &RpcClient,
+    domains: &DomainRegistry,
+    chain: &str,
+    allow_mismatch: bool,
+) {
+    let Some(expected) = domains.genesis_hash(chain) else {
+        eprintln!(
+            "WARNING: no genesis hash configured for chain {}, add it to the chain metadata file to check the RPC serves it",
+            chain
+        );
+        return;
+    };
+    let Err(e) = check_rpc(client, Some(expected)) else {
+        return;
+    };
+    if allow_mismatch {
+        println!(
+            "WARNING: can't verify the RPC serves chain {}: {}",
+            chain, e
+        );
+    } else {
+        exit_code::config_error(format!(
+            "Can't verify the RPC serves chain {}: {}. Make sure you are connected to the right RPC, or pass --allow-genesis-mismatch.",
+            chain, e
+        ));
     }
 }
 
+/// Whether the URL is of a local test validator, whose chain has no fixed
+/// genesis hash.
+fn is_local_url(url: &str) -> bool {
+    ["http://localhost", "http://127.0.0.1", "http://0.0.0.0"]
+        .iter()
+        .any(|local| url.starts_with(local))
+}
+
 fn process_mailbox_cmd(ctx: Context, cmd: MailboxCmd) {
     match cmd.cmd {
         MailboxSubCmd::Init(init) => {
@@ -709,10 +2533,8 @@ fn process_mailbox_cmd(ctx: Context, cmd: MailboxCmd) {
             ctx.new_txn().add(instruction).send_with_payer();
         }
         MailboxSubCmd::Query(query) => {
-            let (inbox_account, inbox_bump) =
-                Pubkey::find_program_address(mailbox_inbox_pda_seeds!(), &query.program_id);
-            let (outbox_account, outbox_bump) =
-                Pubkey::find_program_address(mailbox_outbox_pda_seeds!(), &query.program_id);
+            let (inbox_account, inbox_bump) = ctx.protocol.inbox_pda(&query.program_id);
+            let (outbox_account, outbox_bump) = ctx.protocol.outbox_pda(&query.program_id);
 
             let accounts = ctx
                 .client
@@ -747,31 +2569,153 @@ fn process_mailbox_cmd(ctx: Context, cmd: MailboxCmd) {
             }
         }
         MailboxSubCmd::Send(outbox) => {
-            let (outbox_account, _outbox_bump) =
-                Pubkey::find_program_address(mailbox_outbox_pda_seeds!(), &outbox.program_id);
-            let ixn = MailboxInstruction::OutboxDispatch(OutboxDispatch {
-                sender: ctx.payer_pubkey,
-                destination_domain: outbox.destination,
-                recipient: H256(outbox.recipient.to_bytes()),
-                message_body: outbox.message.into(),
-            });
-            let outbox_instruction = Instruction {
-                program_id: outbox.program_id,
-                data: ixn.into_instruction_data().unwrap(),
-                accounts: vec![
-                    AccountMeta::new(outbox_account, false),
-                    AccountMeta::new_readonly(ctx.payer_pubkey, true),
-                    AccountMeta::new_readonly(spl_noop::id(), false),
-                ],
+            if let Some(origin) = &outbox.origin {
+                check_origin_domain(&ctx, outbox.program_id, ctx.domains.resolve(origin));
+            }
+            if outbox.wait_for_delivery {
+                outbox.destination_rpc.check_wait_for_delivery();
+            }
+            if !outbox.skip_recipient_check {
+                outbox.destination_rpc.check_recipient(
+                    &ctx,
+                    ctx.domains.resolve(&outbox.destination),
+                    H256(outbox.recipient.to_bytes()),
+                );
+            }
+            let message_body = match outbox.request_id {
+                Some(request_id) => {
+                    println!("Tagging message with request ID {:?}", request_id);
+                    tag_message_body(request_id, &outbox.body.read())
+                }
+                None => outbox.body.read(),
+            };
+            let max_body_len = max_dispatch_body_len(&ctx, outbox.program_id);
+            let message_bodies = if outbox.chunk && message_body.len() > max_body_len {
+                let chunks = chunk_message_body(&message_body, max_body_len);
+                println!(
+                    "Splitting the {} byte message body into {} chunks",
+                    message_body.len(),
+                    chunks.len()
+                );
+                chunks
+            } else {
+                check_dispatch_body(&ctx, outbox.program_id, &message_body);
+                vec![message_body]
             };
-            ctx.new_txn().add(outbox_instruction).send_with_payer();
+            for message_body in message_bodies {
+                let (unique_message_account, submitted) = dispatch_message(
+                    &ctx,
+                    outbox.program_id,
+                    ctx.domains.resolve(&outbox.destination),
+                    H256(outbox.recipient.to_bytes()),
+                    message_body,
+                );
+                if submitted {
+                    if let Some(message) =
+                        get_dispatched_message(&ctx, &outbox.program_id, &unique_message_account)
+                    {
+                        println!("Dispatched message with ID {:?}", message.id());
+                        ctx.print_message_link(message.id());
+                        if outbox.wait_for_delivery {
+                            outbox.destination_rpc.wait_for_delivery(
+                                &ctx,
+                                message.id(),
+                                Duration::from_secs(outbox.timeout),
+                            );
+                        }
+                    }
+                }
+            }
         }
-        MailboxSubCmd::Delivered(delivered) => {
-            let (processed_message_account_key, _processed_message_account_bump) =
-                Pubkey::find_program_address(
-                    mailbox_processed_message_pda_seeds!(delivered.message_id),
-                    &delivered.program_id,
+        #[cfg(feature = "evm")]
+        MailboxSubCmd::SendFromEvm(outbox) => {
+            let recipient = ctx.resolve_address(&outbox.recipient);
+            let destination = ctx.domains.resolve(&outbox.destination);
+            if outbox.wait_for_delivery {
+                outbox.destination_rpc.check_wait_for_delivery();
+            }
+            if !outbox.skip_recipient_check {
+                outbox
+                    .destination_rpc
+                    .check_recipient(&ctx, destination, recipient);
+            }
+            let message_body = match outbox.request_id {
+                Some(request_id) => {
+                    println!("Tagging message with request ID {:?}", request_id);
+                    tag_message_body(request_id, &outbox.body.read())
+                }
+                None => outbox.body.read(),
+            };
+            match evm::dispatch_evm_message(&outbox, destination, recipient, message_body) {
+                Some(message_id) => {
+                    println!("Dispatched message with ID {:?}", message_id);
+                    ctx.print_message_link(message_id);
+                    if outbox.wait_for_delivery {
+                        outbox.destination_rpc.wait_for_delivery(
+                            &ctx,
+                            message_id,
+                            Duration::from_secs(outbox.timeout),
+                        );
+                    }
+                }
+                None => println!("No DispatchId event found in the dispatch transaction"),
+            }
+        }
+        MailboxSubCmd::Broadcast(broadcast) => {
+            process_broadcast(&ctx, broadcast);
+        }
+        MailboxSubCmd::SendBatch(send_batch) => {
+            process_send_batch(&ctx, send_batch);
+        }
+        MailboxSubCmd::FindTagged(find_tagged) => {
+            let print_message = |message: &HyperlaneMessage| {
+                let body = untag_message_body(&message.body)
+                    .map(|(_, body)| body)
+                    .unwrap_or_default();
+                println!(
+                    "message_id={:?} nonce={} destination={} body={}",
+                    message.id(),
+                    message.nonce,
+                    message.destination,
+                    String::from_utf8_lossy(body)
+                );
+            };
+            let print_found = |messages: &[HyperlaneMessage]| {
+                println!(
+                    "Found {} dispatched messages tagged with request ID {:?}",
+                    messages.len(),
+                    find_tagged.request_id
+                );
+                for message in messages {
+                    print_message(message);
+                }
+            };
+            if find_tagged.watch {
+                watch_tagged_dispatched_messages(
+                    &ctx,
+                    &find_tagged.program_id,
+                    find_tagged.request_id,
+                    find_tagged.ws_url,
+                    #[cfg(feature = "geyser")]
+                    find_tagged.geyser.endpoint(),
+                    |messages| {
+                        print_found(messages);
+                        println!("Watching for tagged messages, interrupt to stop");
+                    },
+                    |message| print_message(&message),
                 );
+            } else {
+                print_found(&find_tagged_dispatched_messages(
+                    &ctx,
+                    &find_tagged.program_id,
+                    find_tagged.request_id,
+                ));
+            }
+        }
+        MailboxSubCmd::Delivered(delivered) => {
+            let (processed_message_account_key, _processed_message_account_bump) = ctx
+                .protocol
+                .processed_message_pda(&delivered.program_id, delivered.message_id);
             let account = ctx
                 .client
                 .get_account_with_commitment(&processed_message_account_key, ctx.commitment)
@@ -783,6 +2727,12 @@ fn process_mailbox_cmd(ctx: Context, cmd: MailboxCmd) {
                 println!("Message delivered");
             }
         }
+        MailboxSubCmd::Status(status) => {
+            process_message_status(&ctx, status);
+        }
+        MailboxSubCmd::Checkpoint(checkpoint) => {
+            process_mailbox_checkpoint(&ctx, checkpoint);
+        }
         MailboxSubCmd::TransferOwnership(transfer_ownership) => {
             let instruction =
                 hyperlane_sealevel_mailbox::instruction::transfer_ownership_instruction(
@@ -796,9 +2746,17 @@ fn process_mailbox_cmd(ctx: Context, cmd: MailboxCmd) {
                     instruction,
                     format!("Transfer ownership to {}", transfer_ownership.new_owner),
                 )
-                .send_with_payer();
+                .send_with_payer_or_propose(transfer_ownership.as_multisig_proposal);
         }
         MailboxSubCmd::SetDefaultIsm(set_default_ism) => {
+            let current_default_ism = read_inbox(&ctx, &set_default_ism.program_id)
+                .expect("Inbox account not found. Make sure you are connected to the right RPC.")
+                .default_ism;
+            if current_default_ism == set_default_ism.default_ism {
+                println!("Default ISM already set to {}", current_default_ism);
+                return;
+            }
+            println!("Current default ISM: {}", current_default_ism);
             let instruction = hyperlane_sealevel_mailbox::instruction::set_default_ism_instruction(
                 set_default_ism.program_id,
                 ctx.payer_pubkey,
@@ -810,323 +2768,183 @@ fn process_mailbox_cmd(ctx: Context, cmd: MailboxCmd) {
                     instruction,
                     format!("Setting default ISM to {}", set_default_ism.default_ism),
                 )
-                .send_with_payer();
+                .send_with_payer_or_propose(set_default_ism.as_multisig_proposal);
+        }
+        MailboxSubCmd::GetOwner(query) => {
+            let outbox = read_outbox(&ctx, &query.program_id)
+                .expect("Outbox account not found. Make sure you are connected to the right RPC.");
+            match outbox.owner {
+                Some(owner) => println!("Owner: {}", owner),
+                None => println!("Owner: none (renounced)"),
+            }
+        }
+        MailboxSubCmd::GetDefaultIsm(query) => {
+            let inbox = read_inbox(&ctx, &query.program_id)
+                .expect("Inbox account not found. Make sure you are connected to the right RPC.");
+            println!("Default ISM: {}", inbox.default_ism);
         }
+        MailboxSubCmd::Inbox(inbox_cmd) => match inbox_cmd.cmd {
+            InboxSubCmd::Show(query) => {
+                let inbox = read_inbox(&ctx, &query.program_id).expect(
+                    "Inbox account not found. Make sure you are connected to the right RPC.",
+                );
+                println!("Local domain: {}", inbox.local_domain);
+                println!("Default ISM: {}", inbox.default_ism);
+                println!("Processed count: {}", inbox.processed_count);
+            }
+        },
+        MailboxSubCmd::Outbox(outbox_cmd) => match outbox_cmd.cmd {
+            OutboxSubCmd::Show(query) => {
+                let outbox = read_outbox(&ctx, &query.program_id).expect(
+                    "Outbox account not found. Make sure you are connected to the right RPC.",
+                );
+                println!("Local domain: {}", outbox.local_domain);
+                println!("Count: {}", outbox.tree.count());
+                println!("Latest root: {:?}", outbox.tree.root());
+                match outbox.owner {
+                    Some(owner) => println!("Owner: {}", owner),
+                    None => println!("Owner: none (renounced)"),
+                }
+            }
+        },
     };
 }
 
-fn process_token_cmd(ctx: Context, cmd: TokenCmd) {
-    match cmd.cmd {
-        TokenSubCmd::Query(query) => {
-            let (token_account, token_bump) =
-                Pubkey::find_program_address(hyperlane_token_pda_seeds!(), &query.program_id);
+/// Prints the router's token PDA and its plugin's PDAs, with their raw
+/// account data.
+#[cfg(feature = "tokens")]
+fn print_token_accounts(ctx: &Context, query: &TokenQuery) {
+    let (token_account, token_bump) =
+        Pubkey::find_program_address(hyperlane_token_pda_seeds!(), &query.program_id);
 
-            let mut accounts_to_query = vec![token_account];
+    let mut accounts_to_query = vec![token_account];
 
-            match query.token_type {
-                TokenType::Native => {
-                    let (native_collateral_account, _native_collateral_bump) =
-                        Pubkey::find_program_address(
-                            hyperlane_token_native_collateral_pda_seeds!(),
-                            &query.program_id,
-                        );
-                    accounts_to_query.push(native_collateral_account);
-                }
-                TokenType::Synthetic => {
-                    let (mint_account, _mint_bump) = Pubkey::find_program_address(
-                        hyperlane_token_mint_pda_seeds!(),
-                        &query.program_id,
-                    );
-                    let (ata_payer_account, _ata_payer_bump) = Pubkey::find_program_address(
-                        hyperlane_token_ata_payer_pda_seeds!(),
-                        &query.program_id,
-                    );
-                    accounts_to_query.push(mint_account);
-                    accounts_to_query.push(ata_payer_account);
-                }
-                TokenType::Collateral => {
-                    let (escrow_account, _escrow_bump) = Pubkey::find_program_address(
-                        hyperlane_token_escrow_pda_seeds!(),
-                        &query.program_id,
-                    );
-                    accounts_to_query.push(escrow_account);
-                }
-            }
+    match query.token_type {
+        TokenType::Native => {
+            let (native_collateral_account, _native_collateral_bump) = Pubkey::find_program_address(
+                hyperlane_token_native_collateral_pda_seeds!(),
+                &query.program_id,
+            );
+            accounts_to_query.push(native_collateral_account);
+        }
+        TokenType::Synthetic => {
+            let (mint_account, _mint_bump) =
+                Pubkey::find_program_address(hyperlane_token_mint_pda_seeds!(), &query.program_id);
+            let (ata_payer_account, _ata_payer_bump) = Pubkey::find_program_address(
+                hyperlane_token_ata_payer_pda_seeds!(),
+                &query.program_id,
+            );
+            accounts_to_query.push(mint_account);
+            accounts_to_query.push(ata_payer_account);
+        }
+        TokenType::Collateral => {
+            let (escrow_account, _escrow_bump) = Pubkey::find_program_address(
+                hyperlane_token_escrow_pda_seeds!(),
+                &query.program_id,
+            );
+            accounts_to_query.push(escrow_account);
+        }
+    }
 
-            let accounts = ctx
-                .client
-                .get_multiple_accounts_with_commitment(&accounts_to_query, ctx.commitment)
-                .unwrap()
-                .value;
-            println!("hyperlane-sealevel-token={}", query.program_id);
-            println!("--------------------------------");
+    let accounts = ctx
+        .client
+        .get_multiple_accounts_with_commitment(&accounts_to_query, ctx.commitment)
+        .unwrap()
+        .value;
+    println!("hyperlane-sealevel-token={}", query.program_id);
+    println!("--------------------------------");
+    println!(
+        "Hyperlane Token Storage: {}, bump={}",
+        token_account, token_bump
+    );
+    if let Some(info) = &accounts[0] {
+        println!("{:#?}", info);
+        parse_token_account_data(query.token_type, &mut info.data.as_ref());
+    } else {
+        println!("Not yet created?");
+    }
+    println!("--------------------------------");
+
+    match query.token_type {
+        TokenType::Native => {
+            let (native_collateral_account, native_collateral_bump) = Pubkey::find_program_address(
+                hyperlane_token_native_collateral_pda_seeds!(),
+                &query.program_id,
+            );
             println!(
-                "Hyperlane Token Storage: {}, bump={}",
-                token_account, token_bump
+                "Native Token Collateral: {}, bump={}",
+                native_collateral_account, native_collateral_bump
             );
-            if let Some(info) = &accounts[0] {
+            if let Some(info) = &accounts[1] {
                 println!("{:#?}", info);
-                parse_token_account_data(query.token_type, &mut info.data.as_ref());
             } else {
                 println!("Not yet created?");
             }
             println!("--------------------------------");
-
-            match query.token_type {
-                TokenType::Native => {
-                    let (native_collateral_account, native_collateral_bump) =
-                        Pubkey::find_program_address(
-                            hyperlane_token_native_collateral_pda_seeds!(),
-                            &query.program_id,
-                        );
-                    println!(
-                        "Native Token Collateral: {}, bump={}",
-                        native_collateral_account, native_collateral_bump
-                    );
-                    if let Some(info) = &accounts[1] {
-                        println!("{:#?}", info);
-                    } else {
-                        println!("Not yet created?");
-                    }
-                    println!("--------------------------------");
-                }
-                TokenType::Synthetic => {
-                    let (mint_account, mint_bump) = Pubkey::find_program_address(
-                        hyperlane_token_mint_pda_seeds!(),
-                        &query.program_id,
-                    );
-                    println!(
-                        "Mint / Mint Authority: {}, bump={}",
-                        mint_account, mint_bump
-                    );
-                    if let Some(info) = &accounts[1] {
-                        println!("{:#?}", info);
-                        use solana_program::program_pack::Pack as _;
-                        match spl_token_2022::state::Mint::unpack_from_slice(info.data.as_ref()) {
-                            Ok(mint) => println!("{:#?}", mint),
-                            Err(err) => println!("Failed to deserialize account data: {}", err),
-                        }
-                    } else {
-                        println!("Not yet created?");
-                    }
-
-                    let (ata_payer_account, ata_payer_bump) = Pubkey::find_program_address(
-                        hyperlane_token_ata_payer_pda_seeds!(),
-                        &query.program_id,
-                    );
-                    println!(
-                        "ATA payer account: {}, bump={}",
-                        ata_payer_account, ata_payer_bump,
-                    );
-                }
-                TokenType::Collateral => {
-                    let (escrow_account, escrow_bump) = Pubkey::find_program_address(
-                        hyperlane_token_escrow_pda_seeds!(),
-                        &query.program_id,
-                    );
-
-                    println!(
-                        "escrow_account (key, bump)=({}, {})",
-                        escrow_account, escrow_bump,
-                    );
-
-                    let (ata_payer_account, ata_payer_bump) = Pubkey::find_program_address(
-                        hyperlane_token_ata_payer_pda_seeds!(),
-                        &query.program_id,
-                    );
-
-                    println!(
-                        "ATA payer account: {}, bump={}",
-                        ata_payer_account, ata_payer_bump,
-                    );
-                }
-            }
         }
-        TokenSubCmd::TransferRemote(xfer) => {
-            is_keypair(&xfer.sender).unwrap();
-            let sender = read_keypair_file(xfer.sender).unwrap();
-
-            let recipient = if xfer.recipient.starts_with("0x") {
-                H256::from_str(&xfer.recipient).unwrap()
+        TokenType::Synthetic => {
+            let (mint_account, mint_bump) =
+                Pubkey::find_program_address(hyperlane_token_mint_pda_seeds!(), &query.program_id);
+            println!(
+                "Mint / Mint Authority: {}, bump={}",
+                mint_account, mint_bump
+            );
+            if let Some(info) = &accounts[1] {
+                println!("{:#?}", info);
+                use solana_program::program_pack::Pack as _;
+                match spl_token_2022::state::Mint::unpack_from_slice(info.data.as_ref()) {
+                    Ok(mint) => println!("{:#?}", mint),
+                    Err(err) => println!("Failed to deserialize account data: {}", err),
+                }
             } else {
-                let pubkey = Pubkey::from_str(&xfer.recipient).unwrap();
-                H256::from_slice(&pubkey.to_bytes()[..])
-            };
-
-            let (token_account, _token_bump) =
-                Pubkey::find_program_address(hyperlane_token_pda_seeds!(), &xfer.program_id);
-            let (dispatch_authority_account, _dispatch_authority_bump) =
-                Pubkey::find_program_address(
-                    mailbox_message_dispatch_authority_pda_seeds!(),
-                    &xfer.program_id,
-                );
-
-            let fetched_token_account = ctx
-                .client
-                .get_account_with_commitment(&token_account, ctx.commitment)
-                .unwrap()
-                .value
-                .unwrap();
-            let token = HyperlaneTokenAccount::<()>::fetch(&mut &fetched_token_account.data[..])
-                .unwrap()
-                .into_inner();
-
-            let unique_message_account_keypair = Keypair::new();
-            let (dispatched_message_account, _dispatched_message_bump) =
-                Pubkey::find_program_address(
-                    mailbox_dispatched_message_pda_seeds!(&unique_message_account_keypair.pubkey()),
-                    &token.mailbox,
-                );
+                println!("Not yet created?");
+            }
 
-            let (mailbox_outbox_account, _mailbox_outbox_bump) =
-                Pubkey::find_program_address(mailbox_outbox_pda_seeds!(), &token.mailbox);
+            let (ata_payer_account, ata_payer_bump) = Pubkey::find_program_address(
+                hyperlane_token_ata_payer_pda_seeds!(),
+                &query.program_id,
+            );
+            println!(
+                "ATA payer account: {}, bump={}",
+                ata_payer_account, ata_payer_bump,
+            );
+        }
+        TokenType::Collateral => {
+            let (escrow_account, escrow_bump) = Pubkey::find_program_address(
+                hyperlane_token_escrow_pda_seeds!(),
+                &query.program_id,
+            );
 
-            let ixn = HtInstruction::TransferRemote(HtTransferRemote {
-                destination_domain: xfer.destination_domain,
-                recipient,
-                amount_or_id: xfer.amount.into(),
-            });
+            println!(
+                "escrow_account (key, bump)=({}, {})",
+                escrow_account, escrow_bump,
+            );
 
-            // Transfers tokens to a remote.
-            // Burns the tokens from the sender's associated token account and
-            // then dispatches a message to the remote recipient.
-            //
-            // 0.    [executable] The system program.
-            // 1.    [executable] The spl_noop program.
-            // 2.    [] The token PDA account.
-            // 3.    [executable] The mailbox program.
-            // 4.    [writeable] The mailbox outbox account.
-            // 5.    [] Message dispatch authority.
-            // 6.    [signer] The token sender and mailbox payer.
-            // 7.    [signer] Unique message / gas payment account.
-            // 8.    [writeable] Message storage PDA.
-            //       ---- If using an IGP ----
-            // 9.    [executable] The IGP program.
-            // 10.   [writeable] The IGP program data.
-            // 11.   [writeable] Gas payment PDA.
-            // 12.   [] OPTIONAL - The Overhead IGP program, if the configured IGP is an Overhead IGP.
-            // 13.   [writeable] The IGP account.
-            //       ---- End if ----
-            // 14..N [??..??] Plugin-specific accounts.
-            let mut accounts = vec![
-                AccountMeta::new_readonly(system_program::id(), false),
-                AccountMeta::new_readonly(spl_noop::id(), false),
-                AccountMeta::new_readonly(token_account, false),
-                AccountMeta::new_readonly(token.mailbox, false),
-                AccountMeta::new(mailbox_outbox_account, false),
-                AccountMeta::new_readonly(dispatch_authority_account, false),
-                AccountMeta::new(sender.pubkey(), true),
-                AccountMeta::new_readonly(unique_message_account_keypair.pubkey(), true),
-                AccountMeta::new(dispatched_message_account, false),
-            ];
-
-            if let Some((igp_program_id, igp_account_type)) = token.interchain_gas_paymaster {
-                let (igp_program_data, _bump) =
-                    Pubkey::find_program_address(igp_program_data_pda_seeds!(), &igp_program_id);
-                let (gas_payment_pda, _bump) = Pubkey::find_program_address(
-                    igp_gas_payment_pda_seeds!(&unique_message_account_keypair.pubkey()),
-                    &igp_program_id,
-                );
+            let (ata_payer_account, ata_payer_bump) = Pubkey::find_program_address(
+                hyperlane_token_ata_payer_pda_seeds!(),
+                &query.program_id,
+            );
 
-                accounts.extend([
-                    AccountMeta::new_readonly(igp_program_id, false),
-                    AccountMeta::new(igp_program_data, false),
-                    AccountMeta::new(gas_payment_pda, false),
-                ]);
-
-                match igp_account_type {
-                    InterchainGasPaymasterType::OverheadIgp(overhead_igp_account_id) => {
-                        let overhead_igp_account = ctx
-                            .client
-                            .get_account_with_commitment(&overhead_igp_account_id, ctx.commitment)
-                            .unwrap()
-                            .value
-                            .unwrap();
-                        let overhead_igp_account =
-                            OverheadIgpAccount::fetch(&mut &overhead_igp_account.data[..])
-                                .unwrap()
-                                .into_inner();
-                        accounts.extend([
-                            AccountMeta::new_readonly(overhead_igp_account_id, false),
-                            AccountMeta::new(overhead_igp_account.inner, false),
-                        ]);
-                    }
-                    InterchainGasPaymasterType::Igp(igp_account_id) => {
-                        accounts.push(AccountMeta::new(igp_account_id, false));
-                    }
-                }
-            }
+            println!(
+                "ATA payer account: {}, bump={}",
+                ata_payer_account, ata_payer_bump,
+            );
+        }
+    }
+}
 
-            match xfer.token_type {
-                TokenType::Native => {
-                    // 5. [executable] The system program.
-                    // 6. [writeable] The native token collateral PDA account.
-                    let (native_collateral_account, _native_collateral_bump) =
-                        Pubkey::find_program_address(
-                            hyperlane_token_native_collateral_pda_seeds!(),
-                            &xfer.program_id,
-                        );
-                    accounts.extend([
-                        AccountMeta::new_readonly(system_program::id(), false),
-                        AccountMeta::new(native_collateral_account, false),
-                    ]);
-                }
-                TokenType::Synthetic => {
-                    // 5. [executable] The spl_token_2022 program.
-                    // 6. [writeable] The mint / mint authority PDA account.
-                    // 7. [writeable] The token sender's associated token account, from which tokens will be burned.
-                    let (mint_account, _mint_bump) = Pubkey::find_program_address(
-                        hyperlane_token_mint_pda_seeds!(),
-                        &xfer.program_id,
-                    );
-                    let sender_associated_token_account =
-                        get_associated_token_address_with_program_id(
-                            &sender.pubkey(),
-                            &mint_account,
-                            &spl_token_2022::id(),
-                        );
-                    accounts.extend([
-                        AccountMeta::new_readonly(spl_token_2022::id(), false),
-                        AccountMeta::new(mint_account, false),
-                        AccountMeta::new(sender_associated_token_account, false),
-                    ]);
-                }
-                TokenType::Collateral => {
-                    // 5. [executable] The SPL token program for the mint.
-                    // 6. [writeable] The mint.
-                    // 7. [writeable] The token sender's associated token account, from which tokens will be sent.
-                    // 8. [writeable] The escrow PDA account.
-                    let token = HyperlaneTokenAccount::<CollateralPlugin>::fetch(
-                        &mut &fetched_token_account.data[..],
-                    )
-                    .unwrap()
-                    .into_inner();
-                    let sender_associated_token_account =
-                        get_associated_token_address_with_program_id(
-                            &sender.pubkey(),
-                            &token.plugin_data.mint,
-                            &token.plugin_data.spl_token_program,
-                        );
-                    accounts.extend([
-                        AccountMeta::new_readonly(token.plugin_data.spl_token_program, false),
-                        AccountMeta::new(token.plugin_data.mint, false),
-                        AccountMeta::new(sender_associated_token_account, false),
-                        AccountMeta::new(token.plugin_data.escrow, false),
-                    ]);
-                }
+#[cfg(feature = "tokens")]
+fn process_token_cmd(mut ctx: Context, cmd: TokenCmd) {
+    match cmd.cmd {
+        TokenSubCmd::Deploy(deploy) => process_token_deploy(&mut ctx, deploy),
+        TokenSubCmd::Query(query) => {
+            print_token_summary(&ctx, &query.program_id, query.token_type);
+            if query.raw {
+                print_token_accounts(&ctx, &query);
             }
-
-            eprintln!("accounts={:#?}", accounts); // FIXME remove
-            let xfer_instruction = Instruction {
-                program_id: xfer.program_id,
-                data: ixn.encode().unwrap(),
-                accounts,
-            };
-            let tx_result = ctx.new_txn().add(xfer_instruction).send(&[
-                &*ctx.payer_signer(),
-                &sender,
-                &unique_message_account_keypair,
-            ]);
+        }
+        TokenSubCmd::TransferRemote(xfer) => {
+            let tx_result = transfer_remote(&ctx, &ctx.client, xfer);
             // Print the output so it can be used in e2e tests
             println!("{:?}", tx_result);
         }
@@ -1148,6 +2966,18 @@ fn process_token_cmd(ctx: Context, cmd: TokenCmd) {
             };
             ctx.new_txn().add(instruction).send_with_payer();
         }
+        TokenSubCmd::EnrollRemoteRouters(enroll) => enroll_remote_routers_from_file(
+            &ctx,
+            &WarpRouteDeployer::new(None),
+            &enroll.program_id,
+            &enroll.file,
+        ),
+        TokenSubCmd::ExportRouters(export) => export_remote_routers(
+            &ctx,
+            &WarpRouteDeployer::new(None),
+            &export.program_id,
+            export.file.as_deref(),
+        ),
         TokenSubCmd::TransferOwnership(transfer) => {
             let instruction =
                 hyperlane_sealevel_token_lib::instruction::transfer_ownership_instruction(
@@ -1162,7 +2992,7 @@ fn process_token_cmd(ctx: Context, cmd: TokenCmd) {
                     instruction,
                     format!("Transfer ownership to {}", transfer.new_owner),
                 )
-                .send_with_payer();
+                .send_with_payer_or_propose(transfer.as_multisig_proposal);
         }
         TokenSubCmd::SetInterchainSecurityModule(set_ism) => {
             let instruction =
@@ -1220,6 +3050,46 @@ fn process_token_cmd(ctx: Context, cmd: TokenCmd) {
     }
 }
 
+/// Transfers tokens from the sender to a remote chain, with the transaction
+/// sent via `client`.
+#[cfg(feature = "tokens")]
+pub(crate) fn transfer_remote(
+    ctx: &Context,
+    client: &RpcClient,
+    xfer: TokenTransferRemote,
+) -> Option<solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta> {
+    is_keypair(&xfer.sender).unwrap();
+    let sender = read_keypair_file(xfer.sender).unwrap();
+
+    let recipient = ctx.resolve_address(&xfer.recipient);
+
+    let unique_message_account_keypair = Keypair::new();
+    let xfer_instruction = WarpRouteDeployer::new(None).dispatch_instruction(
+        ctx,
+        client,
+        xfer.program_id,
+        sender.pubkey(),
+        unique_message_account_keypair.pubkey(),
+        TokenTransfer {
+            token_type: xfer.token_type,
+            transfer: HtTransferRemote {
+                destination_domain: xfer.destination_domain,
+                recipient,
+                amount_or_id: xfer.amount.into(),
+            },
+        },
+    );
+    eprintln!("accounts={:#?}", xfer_instruction.accounts); // FIXME remove
+    ctx.new_txn()
+        .add(xfer_instruction)
+        .with_client(client)
+        .send(&[
+            &*ctx.payer_signer(),
+            &sender,
+            &unique_message_account_keypair,
+        ])
+}
+
 fn process_validator_announce_cmd(ctx: Context, cmd: ValidatorAnnounceCmd) {
     match cmd.cmd {
         ValidatorAnnounceSubCmd::Init(init) => {
@@ -1241,50 +3111,26 @@ fn process_validator_announce_cmd(ctx: Context, cmd: ValidatorAnnounceCmd) {
             })
             .unwrap();
 
-            let announce_instruction = ValidatorAnnounceAnnounceInstruction {
-                validator: announce.validator,
-                storage_location: announce.storage_location,
-                signature,
-            };
-
-            let (validator_announce_account, _validator_announce_bump) =
-                Pubkey::find_program_address(validator_announce_pda_seeds!(), &announce.program_id);
-
-            let (validator_storage_locations_key, _validator_storage_locations_bump_seed) =
-                Pubkey::find_program_address(
-                    validator_storage_locations_pda_seeds!(announce.validator),
-                    &announce.program_id,
-                );
-
-            let replay_id = announce_instruction.replay_id();
-            let (replay_protection_pda_key, _replay_protection_bump_seed) =
-                Pubkey::find_program_address(
-                    replay_protection_pda_seeds!(replay_id),
-                    &announce.program_id,
-                );
-
-            let ixn = ValidatorAnnounceInstruction::Announce(announce_instruction);
-
-            // Accounts:
-            // 0. [signer] The payer.
-            // 1. [executable] The system program.
-            // 2. [] The ValidatorAnnounce PDA account.
-            // 3. [writeable] The validator-specific ValidatorStorageLocationsAccount PDA account.
-            // 4. [writeable] The ReplayProtection PDA account specific to the announcement being made.
-            let accounts = vec![
-                AccountMeta::new_readonly(ctx.payer_pubkey, true),
-                AccountMeta::new_readonly(system_program::id(), false),
-                AccountMeta::new_readonly(validator_announce_account, false),
-                AccountMeta::new(validator_storage_locations_key, false),
-                AccountMeta::new(replay_protection_pda_key, false),
-            ];
-
-            let announce_instruction = Instruction {
-                program_id: announce.program_id,
-                data: ixn.into_instruction_data().unwrap(),
-                accounts,
-            };
-            ctx.new_txn().add(announce_instruction).send_with_payer();
+            let announce_instruction =
+                hyperlane_sealevel_validator_announce::instruction::announce_instruction(
+                    announce.program_id,
+                    ctx.payer_pubkey,
+                    ValidatorAnnounceAnnounceInstruction {
+                        validator: announce.validator,
+                        storage_location: announce.storage_location.clone(),
+                        signature,
+                    },
+                )
+                .unwrap();
+            ctx.new_txn()
+                .add_with_description(
+                    announce_instruction,
+                    format!(
+                        "Announce validator {:?} storage location {}",
+                        announce.validator, announce.storage_location
+                    ),
+                )
+                .send_with_payer();
         }
         ValidatorAnnounceSubCmd::Query(query) => {
             let (validator_storage_locations_key, _validator_storage_locations_bump_seed) =
@@ -1311,6 +3157,53 @@ fn process_validator_announce_cmd(ctx: Context, cmd: ValidatorAnnounceCmd) {
                 println!("Validator not yet announced");
             }
         }
+        ValidatorAnnounceSubCmd::QueryValidators(query) => {
+            query_validators(&ctx, query);
+        }
+    }
+}
+
+/// Quotes the payment for the destination gas amount, returning it along with
+/// the gas overhead the overhead IGP adds, if one is given.
+pub(crate) fn quote_igp_payment(
+    ctx: &Context,
+    igp_account: Option<Pubkey>,
+    overhead_igp_account: Option<Pubkey>,
+    destination_domain: u32,
+    gas_amount: u64,
+) -> Result<(u64, u64), hyperlane_sealevel_igp::error::Error> {
+    let overhead_igp = overhead_igp_account.map(|overhead_igp_account| {
+        let account = ctx
+            .client
+            .get_account_with_commitment(&overhead_igp_account, ctx.commitment)
+            .unwrap()
+            .value
+            .expect("Overhead IGP account not found");
+        OverheadIgpAccount::fetch(&mut &account.data[..])
+            .unwrap()
+            .into_inner()
+    });
+    let igp_account = overhead_igp
+        .as_ref()
+        .map(|overhead_igp| overhead_igp.inner)
+        .or(igp_account)
+        .expect("An IGP or overhead IGP account is required");
+    let account = ctx
+        .client
+        .get_account_with_commitment(&igp_account, ctx.commitment)
+        .unwrap()
+        .value
+        .expect("IGP account not found");
+    let igp = IgpAccount::fetch(&mut &account.data[..])
+        .unwrap()
+        .into_inner();
+
+    match &overhead_igp {
+        Some(overhead_igp) => Ok((
+            overhead_igp.quote_gas_payment(destination_domain, gas_amount, &igp.data)?,
+            overhead_igp.gas_overhead(destination_domain),
+        )),
+        None => Ok((igp.quote_gas_payment(destination_domain, gas_amount)?, 0)),
     }
 }
 
@@ -1510,5 +3403,129 @@ fn process_igp_cmd(ctx: Context, cmd: IgpCmd) {
                 )
                 .send_with_payer();
         }
+        IgpSubCmd::Quote(quote) => {
+            let (numerator, denominator) = quote
+                .gas_fraction
+                .split_once('/')
+                .and_then(|(numerator, denominator)| {
+                    Some((
+                        numerator.trim().parse::<u64>().ok()?,
+                        denominator.trim().parse::<u64>().ok()?,
+                    ))
+                })
+                .filter(|(_, denominator)| *denominator != 0)
+                .expect("Invalid gas fraction, expected `numerator / denominator`");
+            // Round up so that the payment always meets the requirement
+            let required_gas = (quote.gas_limit * numerator + denominator - 1) / denominator;
+
+            let (payment, gas_overhead) = quote_igp_payment(
+                &ctx,
+                quote.igp_account,
+                quote.overhead_igp_account,
+                quote.destination_domain,
+                required_gas,
+            )
+            .unwrap_or_else(|e| panic!("Failed to quote gas payment: {}", e));
+
+            if quote.overhead_igp_account.is_some() {
+                println!("Gas overhead: {}", gas_overhead);
+            }
+            println!(
+                "Required gas: {} ({} of the {} gas limit)",
+                required_gas, quote.gas_fraction, quote.gas_limit
+            );
+            println!("Required payment: {} lamports", payment);
+        }
+        IgpSubCmd::Show(args) => {
+            let core_program_ids =
+                read_core_program_ids(&args.environments_dir, &args.environment, &args.chain_name);
+            let accounts = ctx
+                .client
+                .get_multiple_accounts_with_commitment(
+                    &[
+                        core_program_ids.igp_account,
+                        core_program_ids.overhead_igp_account,
+                    ],
+                    ctx.commitment,
+                )
+                .unwrap()
+                .value;
+            let igp = IgpAccount::fetch(
+                &mut &accounts[0]
+                    .as_ref()
+                    .expect("IGP account not found. Make sure you are connected to the right RPC.")
+                    .data[..],
+            )
+            .unwrap()
+            .into_inner();
+            let overhead_igp = accounts[1].as_ref().map(|account| {
+                OverheadIgpAccount::fetch(&mut &account.data[..])
+                    .unwrap()
+                    .into_inner()
+            });
+
+            println!("IGP {}", core_program_ids.igp_account);
+            println!("\tOwner: {:?}", igp.owner);
+            println!("\tBeneficiary: {}", igp.beneficiary);
+            match &overhead_igp {
+                Some(overhead_igp) => {
+                    println!("Overhead IGP {}", core_program_ids.overhead_igp_account);
+                    println!("\tOwner: {:?}", overhead_igp.owner);
+                }
+                None => println!("No overhead IGP"),
+            }
+
+            let mut domains = igp
+                .gas_oracles
+                .keys()
+                .chain(
+                    overhead_igp
+                        .iter()
+                        .flat_map(|overhead_igp| overhead_igp.gas_overheads.keys()),
+                )
+                .copied()
+                .collect::<Vec<_>>();
+            domains.sort_unstable();
+            domains.dedup();
+            for domain in domains {
+                println!("Destination domain {}:", domain);
+                match igp.gas_oracles.get(&domain) {
+                    Some(GasOracle::RemoteGasData(remote_gas_data)) => println!(
+                        "\tToken exchange rate: {}, gas price: {}, token decimals: {}",
+                        remote_gas_data.token_exchange_rate,
+                        remote_gas_data.gas_price,
+                        remote_gas_data.token_decimals
+                    ),
+                    None => println!("\tNo gas oracle"),
+                }
+                if let Some(overhead_igp) = &overhead_igp {
+                    println!("\tGas overhead: {}", overhead_igp.gas_overhead(domain));
+                }
+            }
+        }
+        IgpSubCmd::QuoteGasPayment(args) => {
+            let core_program_ids =
+                read_core_program_ids(&args.environments_dir, &args.environment, &args.chain_name);
+            let instruction = hyperlane_sealevel_igp::instruction::quote_gas_payment_instruction(
+                core_program_ids.igp_program_id,
+                core_program_ids.igp_account,
+                (!args.without_overhead).then_some(core_program_ids.overhead_igp_account),
+                args.destination_domain,
+                args.gas,
+            )
+            .unwrap();
+            let payment = simulate_return_data::<u64>(
+                &ctx.client,
+                ctx.commitment,
+                &ctx.payer_pubkey,
+                instruction,
+            )
+            .unwrap_or_else(|e| panic!("Failed to quote gas payment: {}", e))
+            .expect("The IGP program returned no quote");
+            println!(
+                "Quoted payment for {} gas to domain {}: {} lamports",
+                args.gas, args.destination_domain, payment
+            );
+        }
     }
 }
@@ -0,0 +1,118 @@
+//! Computation of message IDs and outbox nonces, so that the ID of a message
+//! can be predicted before it's dispatched, or checked offline.
+
+use hyperlane_core::{HyperlaneMessage, H256};
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    accounts::{read_outbox, AccountReader},
+    logs::print_messages_from_tx,
+    protocol::MESSAGE_HEADER_BYTES,
+    Context, MessageCmd, MessageId, MessageSubCmd,
+};
+
+/// The ID of a message, the keccak256 hash of its encoding.
+pub(crate) fn compute_message_id(message: &HyperlaneMessage) -> H256 {
+    message.id()
+}
+
+/// The nonce the mailbox will dispatch its next message with, or `None` if
+/// the mailbox isn't initialized.
+pub(crate) fn next_outbox_nonce(reader: &impl AccountReader, mailbox: &Pubkey) -> Option<u32> {
+    read_outbox(reader, mailbox).map(|outbox| outbox.tree.count() as u32)
+}
+
+/// Decodes a hex encoded message, optionally 0x-prefixed.
+fn decode_message(encoded: &str) -> Result<HyperlaneMessage, String> {
+    let bytes = hex::decode(encoded.strip_prefix("0x").unwrap_or(encoded))
+        .map_err(|e| format!("Invalid hex message: {}", e))?;
+    if bytes.len() < MESSAGE_HEADER_BYTES {
+        return Err(format!(
+            "Invalid message: expected at least {} bytes, got {}",
+            MESSAGE_HEADER_BYTES,
+            bytes.len()
+        ));
+    }
+    Ok(HyperlaneMessage::from(&bytes))
+}
+
+pub(crate) fn process_message_cmd(ctx: Context, cmd: MessageCmd) {
+    match cmd.cmd {
+        MessageSubCmd::Id(args) => {
+            let message = match &args.message {
+                Some(encoded) => decode_message(encoded).unwrap_or_else(|e| panic!("{}", e)),
+                None => message_from_args(&ctx, &args),
+            };
+            println!("{:#?}", message);
+            println!("Message ID: {:?}", compute_message_id(&message));
+        }
+        MessageSubCmd::FromTx(args) => print_messages_from_tx(&ctx, args),
+    }
+}
+
+/// The message the payer would dispatch with the given arguments, reading
+/// what isn't given from the mailbox.
+fn message_from_args(ctx: &Context, args: &MessageId) -> HyperlaneMessage {
+    let origin = match &args.origin {
+        Some(origin) => ctx.domains.resolve(origin),
+        None => {
+            read_outbox(ctx, &args.program_id)
+                .expect("Outbox account not found. Pass --origin to compute the ID offline.")
+                .local_domain
+        }
+    };
+    let nonce = args.nonce.unwrap_or_else(|| {
+        next_outbox_nonce(ctx, &args.program_id)
+            .expect("Outbox account not found. Pass --nonce to compute the ID offline.")
+    });
+    let sender = args
+        .sender
+        .as_ref()
+        .map(|sender| ctx.resolve_address(sender))
+        .unwrap_or_else(|| H256(ctx.payer_pubkey.to_bytes()));
+    let destination = args
+        .destination
+        .as_ref()
+        .map(|destination| ctx.domains.resolve(destination))
+        .expect("--destination is required unless --message is given");
+    let recipient = args
+        .recipient
+        .as_ref()
+        .map(|recipient| ctx.resolve_address(recipient))
+        .expect("--recipient is required unless --message is given");
+
+    HyperlaneMessage {
+        version: ctx.protocol.message_version,
+        nonce,
+        origin,
+        sender,
+        destination,
+        recipient,
+        body: args.body.read(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use hyperlane_core::RawHyperlaneMessage;
+
+    use super::*;
+
+    #[test]
+    fn test_decode_message() {
+        let message = HyperlaneMessage {
+            version: 3,
+            nonce: 7,
+            origin: 1234,
+            sender: H256::random(),
+            destination: 4321,
+            recipient: H256::random(),
+            body: b"hi".to_vec(),
+        };
+        let encoded = hex::encode(RawHyperlaneMessage::from(&message));
+
+        let decoded = decode_message(&format!("0x{}", encoded)).unwrap();
+        assert_eq!(compute_message_id(&decoded), compute_message_id(&message));
+        assert!(decode_message(&encoded[..20]).is_err());
+    }
+}
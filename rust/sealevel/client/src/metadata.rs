@@ -0,0 +1,93 @@
+//! Encoding of multisig ISM metadata in the format of the destination ISM's
+//! module type.
+
+use hyperlane_core::{accumulator::merkle::Proof, ModuleType, H256};
+
+/// The parts of multisig ISM metadata, as gathered from the origin's
+/// validators' signed checkpoints.
+pub(crate) struct MultisigMetadata {
+    pub origin_mailbox: H256,
+    /// The index of the signed checkpoint.
+    pub checkpoint_index: u32,
+    /// The root of the signed checkpoint.
+    pub merkle_root: H256,
+    pub message_id: H256,
+    /// The message's proof against the checkpoint's root, needed by merkle
+    /// root multisig ISMs only.
+    pub proof: Option<Proof>,
+    /// The validators' signatures, in the order of the validator set.
+    pub signatures: Vec<[u8; 65]>,
+}
+
+impl MultisigMetadata {
+    /// Encodes the metadata for an ISM of the module type.
+    ///
+    /// Message ID multisig ISM metadata:
+    /// [   0:  32] Origin mailbox address
+    /// [  32:  64] Merkle root
+    /// [  64:????] Validator signatures
+    ///
+    /// Merkle root multisig ISM metadata:
+    /// [   0:  32] Origin mailbox address
+    /// [  32:  36] Checkpoint index
+    /// [  36:  68] Message ID
+    /// [  68:1092] Merkle proof
+    /// [1092:????] Validator signatures
+    pub(crate) fn encode(&self, module_type: ModuleType) -> Result<Vec<u8>, String> {
+        let mut metadata = self.origin_mailbox.as_bytes().to_vec();
+        match module_type {
+            ModuleType::MessageIdMultisig => {
+                metadata.extend_from_slice(self.merkle_root.as_bytes());
+            }
+            ModuleType::MerkleRootMultisig => {
+                let proof = self
+                    .proof
+                    .as_ref()
+                    .ok_or("Merkle root multisig ISM metadata needs a merkle proof")?;
+                metadata.extend_from_slice(&self.checkpoint_index.to_be_bytes());
+                metadata.extend_from_slice(self.message_id.as_bytes());
+                for node in proof.path.iter() {
+                    metadata.extend_from_slice(node.as_bytes());
+                }
+            }
+            other => return Err(format!("{:?} ISMs don't verify multisig metadata", other)),
+        }
+        metadata.extend(self.signatures.concat());
+        Ok(metadata)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use hyperlane_core::accumulator::TREE_DEPTH;
+
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let metadata = MultisigMetadata {
+            origin_mailbox: H256::repeat_byte(1),
+            checkpoint_index: 7,
+            merkle_root: H256::repeat_byte(2),
+            message_id: H256::repeat_byte(3),
+            proof: Some(Proof {
+                leaf: H256::repeat_byte(3),
+                index: 7,
+                path: [H256::repeat_byte(4); TREE_DEPTH],
+            }),
+            signatures: vec![[5; 65], [6; 65]],
+        };
+
+        let message_id = metadata.encode(ModuleType::MessageIdMultisig).unwrap();
+        assert_eq!(message_id.len(), 64 + 2 * 65);
+        assert_eq!(&message_id[32..64], H256::repeat_byte(2).as_bytes());
+
+        let merkle_root = metadata.encode(ModuleType::MerkleRootMultisig).unwrap();
+        assert_eq!(merkle_root.len(), 1092 + 2 * 65);
+        assert_eq!(&merkle_root[32..36], &7u32.to_be_bytes());
+        assert_eq!(&merkle_root[36..68], H256::repeat_byte(3).as_bytes());
+        assert_eq!(&merkle_root[1092..1157], &[5; 65]);
+
+        assert!(metadata.encode(ModuleType::Routing).is_err());
+    }
+}
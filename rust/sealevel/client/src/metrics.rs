@@ -0,0 +1,154 @@
+//! Prometheus metrics for long-running commands such as `account watch` and
+//! `relay --daemon`, named and served on `/metrics` like the agents' metrics.
+
+use std::{
+    io::{BufRead as _, BufReader, Write as _},
+    net::{TcpListener, TcpStream},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+use prometheus::{
+    histogram_opts, labels, opts, register_histogram_with_registry,
+    register_int_counter_vec_with_registry, register_int_counter_with_registry, Encoder as _,
+    Histogram, IntCounter, IntCounterVec, Registry,
+};
+
+/// The metrics namespace prefix, as used by the agents.
+const NAMESPACE: &str = "hyperlane";
+/// The value of the `agent` label of all metrics.
+const AGENT_NAME: &str = "sealevel-client";
+/// How long a scrape may take to send its request or read the response before
+/// it's dropped, so a stalled connection doesn't block the scrapes after it.
+const SCRAPE_TIMEOUT: Duration = Duration::from_secs(5);
+
+macro_rules! namespaced {
+    ($name:expr) => {
+        format!("{}_{}", NAMESPACE, $name)
+    };
+}
+
+pub(crate) struct Metrics {
+    registry: Registry,
+    /// Updates to watched accounts, by account.
+    pub account_updates_observed: IntCounterVec,
+    /// Messages dispatched on an origin and observed by the relayer, by
+    /// origin and destination chain.
+    pub messages_observed: IntCounterVec,
+    pub transactions_sent: IntCounter,
+    pub transaction_failures: IntCounter,
+    /// Seconds from sending a transaction to its confirmation.
+    pub confirmation_latency: Histogram,
+}
+
+impl Metrics {
+    pub(crate) fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new();
+        let const_labels = labels! { "agent".to_owned() => AGENT_NAME.to_owned() };
+        Ok(Self {
+            account_updates_observed: register_int_counter_vec_with_registry!(
+                opts!(
+                    namespaced!("account_updates_observed_count"),
+                    "Number of updates to watched accounts observed",
+                    const_labels.clone()
+                ),
+                &["account"],
+                registry
+            )?,
+            messages_observed: register_int_counter_vec_with_registry!(
+                opts!(
+                    namespaced!("messages_observed_count"),
+                    "Number of dispatched messages observed by the relayer",
+                    const_labels.clone()
+                ),
+                &["origin", "remote"],
+                registry
+            )?,
+            transactions_sent: register_int_counter_with_registry!(
+                opts!(
+                    namespaced!("transactions_sent_count"),
+                    "Number of transactions sent and confirmed",
+                    const_labels.clone()
+                ),
+                registry
+            )?,
+            transaction_failures: register_int_counter_with_registry!(
+                opts!(
+                    namespaced!("transaction_failures_count"),
+                    "Number of transactions that failed to be sent or confirmed",
+                    const_labels.clone()
+                ),
+                registry
+            )?,
+            confirmation_latency: register_histogram_with_registry!(
+                histogram_opts!(
+                    namespaced!("transaction_confirmation_seconds"),
+                    "Seconds from sending a transaction to its confirmation",
+                    vec![0.5, 1.0, 2.0, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0],
+                    const_labels
+                ),
+                registry
+            )?,
+            registry,
+        })
+    }
+
+    /// Gathers the metrics into a text report.
+    pub(crate) fn gather(&self) -> prometheus::Result<Vec<u8>> {
+        let mut out_buf = Vec::with_capacity(1024 * 64);
+        prometheus::TextEncoder::new().encode(&self.registry.gather(), &mut out_buf)?;
+        Ok(out_buf)
+    }
+
+    /// Serves the metrics on `/metrics` from a background thread, for as long
+    /// as the process runs.
+    pub(crate) fn serve(self: &Arc<Self>, port: u16) {
+        let listener = TcpListener::bind(("0.0.0.0", port))
+            .unwrap_or_else(|e| panic!("Failed to serve metrics on port {}: {}", port, e));
+        println!("Serving metrics on 0.0.0.0:{}/metrics", port);
+        let metrics = self.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Err(e) = metrics.respond(stream) {
+                    tracing::warn!(%e, "Failed to respond to a metrics request");
+                }
+            }
+        });
+    }
+
+    fn respond(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        stream.set_read_timeout(Some(SCRAPE_TIMEOUT))?;
+        stream.set_write_timeout(Some(SCRAPE_TIMEOUT))?;
+        let mut request_line = String::new();
+        BufReader::new(&stream).read_line(&mut request_line)?;
+        let (status, body) = if request_line.starts_with("GET /metrics") {
+            ("200 OK", self.gather().expect("failed to encode metrics"))
+        } else {
+            ("404 Not Found", b"go look at /metrics".to_vec())
+        };
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\n\
+             Content-Type: text/plain; charset=utf-8\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\r\n",
+            status,
+            body.len()
+        )?;
+        stream.write_all(&body)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_gather_namespaced() {
+        let metrics = Metrics::new().unwrap();
+        metrics.transactions_sent.inc();
+        let report = String::from_utf8(metrics.gather().unwrap()).unwrap();
+        assert!(report.contains(r#"hyperlane_transactions_sent_count{agent="sealevel-client"} 1"#));
+    }
+}
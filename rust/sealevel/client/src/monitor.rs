@@ -0,0 +1,308 @@
+//! Monitoring of a mailbox's outbox against the local index, for alerting on
+//! messages the index or the relayer missed. The index must be kept synced
+//! with `index sync`, for the destination mailboxes too so that deliveries
+//! are known.
+
+use std::time::Duration;
+
+use hyperlane_sealevel_mailbox::accounts::OutboxAccount;
+use solana_client::{client_error::ClientError, rpc_client::RpcClient};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+
+use crate::{
+    chain_set::ChainSet,
+    core::read_core_program_ids,
+    exit_code,
+    index::{IndexedMessage, MessageFilter, MessageIndex},
+    shutdown::CancellationToken,
+    Context, MonitorChecks, MonitorCmd, MonitorOutbox, MonitorOutboxes, MonitorSubCmd,
+};
+
+pub(crate) fn process_monitor_cmd(ctx: Context, cmd: MonitorCmd) {
+    match cmd.cmd {
+        MonitorSubCmd::Outbox(monitor) => monitor_outbox(&ctx, monitor),
+        MonitorSubCmd::Outboxes(monitor) => monitor_outboxes(&ctx, monitor),
+    }
+}
+
+/// Something wrong with the outbox's messages.
+#[derive(Debug, PartialEq, Eq)]
+enum Anomaly {
+    /// Nonces below the highest indexed one that aren't indexed.
+    Skipped(Vec<u32>),
+    /// The index is this many messages behind the outbox.
+    IndexBehind(u32),
+    /// The index has messages with nonces the outbox hasn't reached, which
+    /// were rolled back.
+    IndexAhead { indexed: u32, count: u32 },
+    /// A message that isn't delivered long after its dispatch.
+    Stuck { nonce: u32, slot: u64 },
+}
+
+/// Checks the outbox against the index once, or every `--watch` seconds,
+/// printing the anomalies found. An outbox that can't be read counts as an
+/// anomaly, so that watching goes on.
+fn monitor_outbox(ctx: &Context, monitor: MonitorOutbox) {
+    let outbox = ctx.protocol.outbox_pda(&monitor.mailbox).0;
+    run_checks(&monitor.checks, |index| {
+        match read_outbox_count(&ctx.client, outbox, ctx.commitment) {
+            Ok(Some((count, slot))) => {
+                let anomalies = check_outbox(index, &monitor.mailbox, count, slot, &monitor.checks);
+                print_anomalies(&monitor.mailbox, &anomalies);
+                anomalies.len()
+            }
+            Ok(None) => {
+                println!("ALERT: mailbox {} isn't initialized", monitor.mailbox);
+                1
+            }
+            Err(err) => {
+                println!(
+                    "ALERT: mailbox {}: failed to read the outbox: {}",
+                    monitor.mailbox, err
+                );
+                1
+            }
+        }
+    });
+}
+
+/// Checks the outboxes of the chains' core deployments against the index,
+/// reading the outboxes and slots of all the chains concurrently. A chain
+/// that can't be read counts as an anomaly without affecting the others.
+fn monitor_outboxes(ctx: &Context, monitor: MonitorOutboxes) {
+    let chain_set = ChainSet::from_chain_config_file(&monitor.chain_config_file, &monitor.chains);
+    let protocol = &ctx.protocol;
+    run_checks(&monitor.checks, |index| {
+        let results = chain_set.query(|chain_config, client| {
+            let mailbox = read_core_program_ids(
+                &monitor.environments_dir,
+                &monitor.environment,
+                chain_config.name(),
+            )
+            .mailbox;
+            read_outbox_count(
+                client,
+                protocol.outbox_pda(&mailbox).0,
+                CommitmentConfig::confirmed(),
+            )
+            .map(|count| (mailbox, count))
+        });
+        let mut anomalies_found = 0;
+        for (chain_name, result) in results {
+            match result {
+                Ok((mailbox, Some((count, slot)))) => {
+                    let anomalies = check_outbox(index, &mailbox, count, slot, &monitor.checks);
+                    print_anomalies(&mailbox, &anomalies);
+                    anomalies_found += anomalies.len();
+                }
+                Ok((mailbox, None)) => {
+                    println!(
+                        "ALERT: {}: mailbox {} isn't initialized",
+                        chain_name, mailbox
+                    );
+                    anomalies_found += 1;
+                }
+                Err(err) => {
+                    println!("ALERT: {}: failed to read the outbox: {}", chain_name, err);
+                    anomalies_found += 1;
+                }
+            }
+        }
+        anomalies_found
+    });
+}
+
+/// Reads the outbox's message count and the slot it was read at, or `None`
+/// if the outbox doesn't exist.
+fn read_outbox_count(
+    client: &RpcClient,
+    outbox: Pubkey,
+    commitment: CommitmentConfig,
+) -> Result<Option<(u32, u64)>, ClientError> {
+    let response = client.get_account_with_commitment(&outbox, commitment)?;
+    Ok(response.value.map(|account| {
+        let outbox = OutboxAccount::fetch(&mut &account.data[..])
+            .unwrap()
+            .into_inner();
+        (outbox.tree.count() as u32, response.context.slot)
+    }))
+}
+
+/// Runs `check`, which prints the anomalies it finds and returns how many
+/// there were, once or every `--watch` seconds. Without `--watch`, exits with
+/// `ANOMALY` if any were found. With `--watch`, runs until SIGINT or SIGTERM.
+fn run_checks(checks: &MonitorChecks, mut check: impl FnMut(&MessageIndex) -> usize) {
+    let index = MessageIndex::open(&checks.db)
+        .unwrap_or_else(|e| panic!("Failed to open index {}: {}", checks.db.display(), e));
+    let shutdown = CancellationToken::on_signals();
+    let (mut checks_run, mut anomalies_found) = (0, 0);
+    loop {
+        let anomalies = check(&index);
+        checks_run += 1;
+        anomalies_found += anomalies;
+        match checks.watch {
+            Some(interval) => {
+                if shutdown.sleep(Duration::from_secs(interval)) {
+                    println!(
+                        "Shut down after {} checks, {} anomalies found",
+                        checks_run, anomalies_found
+                    );
+                    return;
+                }
+            }
+            None if anomalies == 0 => return,
+            None => std::process::exit(exit_code::ANOMALY),
+        }
+    }
+}
+
+/// Checks the index's messages of the mailbox against its outbox's message
+/// count, read at `slot`.
+fn check_outbox(
+    index: &MessageIndex,
+    mailbox: &Pubkey,
+    count: u32,
+    slot: u64,
+    checks: &MonitorChecks,
+) -> Vec<Anomaly> {
+    let nonces = index.nonces(mailbox).unwrap();
+    let undelivered = index
+        .query(&MessageFilter {
+            mailbox: Some(*mailbox),
+            undelivered: true,
+            limit: i64::MAX as usize,
+            ..MessageFilter::default()
+        })
+        .unwrap();
+    find_anomalies(
+        count,
+        &nonces,
+        &undelivered,
+        slot.saturating_sub(checks.stuck_after_slots),
+        checks.max_index_lag,
+    )
+}
+
+fn print_anomalies(mailbox: &Pubkey, anomalies: &[Anomaly]) {
+    if anomalies.is_empty() {
+        println!("Outbox of mailbox {}: ok", mailbox);
+    }
+    for anomaly in anomalies {
+        print_anomaly(mailbox, anomaly);
+    }
+}
+
+/// Finds the anomalies of an outbox of `count` messages, given the nonces of
+/// its indexed messages, in order, and its undelivered indexed messages.
+/// Messages dispatched before `stuck_before` are stuck if undelivered.
+fn find_anomalies(
+    count: u32,
+    nonces: &[u32],
+    undelivered: &[IndexedMessage],
+    stuck_before: u64,
+    max_index_lag: u32,
+) -> Vec<Anomaly> {
+    let mut anomalies = vec![];
+    let mut expected = 0;
+    let mut skipped = vec![];
+    for &nonce in nonces {
+        skipped.extend(expected..nonce);
+        expected = nonce + 1;
+    }
+    if !skipped.is_empty() {
+        anomalies.push(Anomaly::Skipped(skipped));
+    }
+    if expected > count {
+        anomalies.push(Anomaly::IndexAhead {
+            indexed: expected,
+            count,
+        });
+    } else if count - expected > max_index_lag {
+        anomalies.push(Anomaly::IndexBehind(count - expected));
+    }
+    let mut stuck = undelivered
+        .iter()
+        .filter(|message| message.slot < stuck_before)
+        .collect::<Vec<_>>();
+    stuck.sort_by_key(|message| message.message.nonce);
+    anomalies.extend(stuck.into_iter().map(|message| Anomaly::Stuck {
+        nonce: message.message.nonce,
+        slot: message.slot,
+    }));
+    anomalies
+}
+
+fn print_anomaly(mailbox: &Pubkey, anomaly: &Anomaly) {
+    match anomaly {
+        Anomaly::Skipped(nonces) => println!(
+            "ALERT: mailbox {}: nonces {:?} aren't indexed",
+            mailbox, nonces
+        ),
+        Anomaly::IndexBehind(lag) => println!(
+            "ALERT: mailbox {}: the index is {} messages behind the outbox",
+            mailbox, lag
+        ),
+        Anomaly::IndexAhead { indexed, count } => println!(
+            "ALERT: mailbox {}: {} messages are indexed but the outbox has {}, messages were \
+             rolled back",
+            mailbox, indexed, count
+        ),
+        Anomaly::Stuck { nonce, slot } => println!(
+            "ALERT: mailbox {}: message {} dispatched at slot {} isn't delivered",
+            mailbox, nonce, slot
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use hyperlane_core::HyperlaneMessage;
+
+    use super::*;
+
+    fn undelivered(nonce: u32, slot: u64) -> IndexedMessage {
+        let message = HyperlaneMessage {
+            nonce,
+            ..HyperlaneMessage::default()
+        };
+        IndexedMessage {
+            mailbox: Pubkey::new_unique().to_string(),
+            message_id: message.id(),
+            message,
+            slot,
+            signature: None,
+            delivery: None,
+        }
+    }
+
+    #[test]
+    fn test_find_anomalies() {
+        assert_eq!(find_anomalies(3, &[0, 1, 2], &[], 100, 0), vec![]);
+        assert_eq!(
+            find_anomalies(5, &[0, 2, 4], &[], 100, 0),
+            vec![Anomaly::Skipped(vec![1, 3])]
+        );
+        assert_eq!(
+            find_anomalies(5, &[0, 1], &[], 100, 0),
+            vec![Anomaly::IndexBehind(3)]
+        );
+        assert_eq!(find_anomalies(5, &[0, 1], &[], 100, 3), vec![]);
+        assert_eq!(
+            find_anomalies(1, &[0, 1], &[], 100, 0),
+            vec![Anomaly::IndexAhead {
+                indexed: 2,
+                count: 1
+            }]
+        );
+        assert_eq!(
+            find_anomalies(
+                3,
+                &[0, 1, 2],
+                &[undelivered(2, 150), undelivered(1, 50)],
+                100,
+                0
+            ),
+            vec![Anomaly::Stuck { nonce: 1, slot: 50 }]
+        );
+    }
+}
@@ -138,7 +138,7 @@ pub(crate) fn process_multisig_ism_message_id_cmd(mut ctx: Context, cmd: Multisi
                     instruction,
                     format!("Transfer ownership to {}", transfer_ownership.new_owner),
                 )
-                .send_with_payer();
+                .send_with_payer_or_propose(transfer_ownership.as_multisig_proposal);
         }
         MultisigIsmMessageIdSubCmd::Configure(configure) => {
             configure_multisig_ism_message_id(
@@ -0,0 +1,122 @@
+//! Resolution of SNS (`.sol`) names to Solana addresses and ENS names to EVM
+//! addresses. Lookups are cached for the lifetime of the process.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use ethers::providers::{Http, Middleware, Provider};
+use hyperlane_core::{H160, H256};
+use solana_client::rpc_client::RpcClient;
+use solana_program::{hash::hashv, pubkey, pubkey::Pubkey};
+
+/// The SPL Name Service program.
+const NAME_PROGRAM_ID: Pubkey = pubkey!("namesLPneVptA9Z5rqUDD9tMTWEJwofgaYwp8cawRkX");
+/// The name account of the `.sol` top level domain.
+const SOL_TLD_ACCOUNT: Pubkey = pubkey!("58PwtjSDuFHuUkYjH9BYnnQKHfwo9reZhC2zMJv9JPkx");
+/// Prefix of names before they're hashed to derive their name account.
+const NAME_HASH_PREFIX: &str = "SPL Name Service";
+/// Name accounts start with the parent name account, followed by the owner.
+const NAME_OWNER_OFFSET: usize = 32;
+
+/// Whether the string is a name rather than an address. Neither hex nor base58
+/// addresses contain dots.
+pub(crate) fn is_name(s: &str) -> bool {
+    s.contains('.')
+}
+
+/// Resolves names, caching the results.
+#[derive(Default)]
+pub(crate) struct NameResolver {
+    ens_rpc_url: Option<String>,
+    cache: Mutex<HashMap<String, H256>>,
+}
+
+impl NameResolver {
+    pub(crate) fn new(ens_rpc_url: Option<String>) -> Self {
+        Self {
+            ens_rpc_url,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Resolves a `.sol` name with the Solana client and any other name with
+    /// ENS.
+    pub(crate) fn resolve(&self, client: &RpcClient, name: &str) -> Result<H256, String> {
+        let name = name.to_lowercase();
+        if let Some(address) = self.cache.lock().unwrap().get(&name) {
+            return Ok(*address);
+        }
+        let address = match name.strip_suffix(".sol") {
+            Some(sns_name) => resolve_sns(client, sns_name)?,
+            None => {
+                let ens_rpc_url = self
+                    .ens_rpc_url
+                    .as_deref()
+                    .ok_or_else(|| format!("Resolving ENS name {} requires --ens-rpc-url", name))?;
+                resolve_ens(ens_rpc_url, &name)?.into()
+            }
+        };
+        self.cache.lock().unwrap().insert(name, address);
+        Ok(address)
+    }
+}
+
+/// The name account of an SNS name, without the `.sol` suffix. A name with a
+/// subdomain, e.g. `sub.name`, is a child of its parent's name account.
+fn sns_name_account(name: &str) -> Pubkey {
+    let (label, parent) = match name.split_once('.') {
+        Some((sub, parent)) => (format!("\0{}", sub), sns_name_account(parent)),
+        None => (name.to_owned(), SOL_TLD_ACCOUNT),
+    };
+    let hashed_name = hashv(&[NAME_HASH_PREFIX.as_bytes(), label.as_bytes()]);
+    Pubkey::find_program_address(
+        &[
+            hashed_name.as_ref(),
+            Pubkey::default().as_ref(),
+            parent.as_ref(),
+        ],
+        &NAME_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Resolves an SNS name to the owner of its name account.
+fn resolve_sns(client: &RpcClient, name: &str) -> Result<H256, String> {
+    let name_account = sns_name_account(name);
+    let data = client
+        .get_account_data(&name_account)
+        .map_err(|e| format!("SNS name {}.sol not found: {}", name, e))?;
+    let owner = data
+        .get(NAME_OWNER_OFFSET..NAME_OWNER_OFFSET + 32)
+        .ok_or_else(|| format!("Invalid name account {} of {}.sol", name_account, name))?;
+    Ok(H256::from_slice(owner))
+}
+
+/// Resolves an ENS name with the registry of the chain at the RPC URL.
+fn resolve_ens(rpc_url: &str, name: &str) -> Result<H160, String> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(async {
+            let provider = Provider::<Http>::try_from(rpc_url).expect("Invalid ENS RPC URL");
+            provider
+                .resolve_name(name)
+                .await
+                .map(|address| H160(address.0))
+                .map_err(|e| format!("Failed to resolve ENS name {}: {}", name, e))
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sns_subdomain_is_child_of_parent() {
+        assert_ne!(sns_name_account("bonfida"), sns_name_account("sub.bonfida"));
+        assert_eq!(sns_name_account("bonfida"), sns_name_account("bonfida"));
+        assert!(is_name("bonfida.sol"));
+        assert!(is_name("vitalik.eth"));
+        assert!(!is_name(&Pubkey::new_unique().to_string()));
+    }
+}
@@ -0,0 +1,338 @@
+//! Building of mailbox `InboxProcess` instructions. Besides the mailbox's own
+//! accounts, delivering a message needs the accounts of the recipient's ISM
+//! lookup, of the ISM's verification and of the recipient's handler, which are
+//! resolved by simulating the programs' account metas instructions.
+
+use account_utils::SizedData as _;
+use borsh::{BorshDeserialize, BorshSerialize};
+use hyperlane_core::{Encode as _, HyperlaneMessage, ModuleType};
+use num_traits::FromPrimitive as _;
+use solana_client::{rpc_client::RpcClient, rpc_config::RpcSimulateTransactionConfig};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    pubkey::Pubkey,
+    system_program,
+    transaction::Transaction,
+};
+use solana_transaction_status::UiReturnDataEncoding;
+
+use base64::Engine as _;
+use hyperlane_sealevel_interchain_security_module_interface::{
+    InterchainSecurityModuleInstruction, VerifyInstruction, VERIFY_ACCOUNT_METAS_PDA_SEEDS,
+};
+use hyperlane_sealevel_mailbox::{
+    accounts::{ProcessedMessage, ProcessedMessageAccount},
+    instruction::{InboxProcess, Instruction as MailboxInstruction},
+    spl_noop,
+};
+use hyperlane_sealevel_message_recipient_interface::{
+    HandleInstruction, MessageRecipientInstruction, HANDLE_ACCOUNT_METAS_PDA_SEEDS,
+    INTERCHAIN_SECURITY_MODULE_ACCOUNT_METAS_PDA_SEEDS,
+};
+use serializable_account_meta::{SerializableAccountMeta, SimulationReturnData};
+
+use crate::protocol::ProtocolConfig;
+
+/// Simulates the instruction and decodes its return data, if any. The payer
+/// must exist on the chain, but needn't sign.
+pub(crate) fn simulate_return_data<T: BorshDeserialize + BorshSerialize>(
+    client: &RpcClient,
+    commitment: CommitmentConfig,
+    payer: &Pubkey,
+    instruction: Instruction,
+) -> Result<Option<T>, String> {
+    let txn = Transaction::new_unsigned(Message::new(&[instruction], Some(payer)));
+    let result = client
+        .simulate_transaction_with_config(
+            &txn,
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                commitment: Some(commitment),
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )
+        .map_err(|e| format!("Simulation failed: {}", e))?
+        .value;
+    if let Some(err) = result.err {
+        return Err(format!("Simulation failed: {}", err));
+    }
+    let Some(return_data) = result.return_data else {
+        return Ok(None);
+    };
+    let bytes = match return_data.data.1 {
+        UiReturnDataEncoding::Base64 => base64::engine::general_purpose::STANDARD
+            .decode(return_data.data.0)
+            .map_err(|e| format!("Invalid return data: {}", e))?,
+    };
+    SimulationReturnData::<T>::try_from_slice(&bytes)
+        .map(|data| Some(data.return_data))
+        .map_err(|e| format!("Invalid return data: {}", e))
+}
+
+/// Gets the account metas a program requires for an instruction, by
+/// simulating its account metas instruction. Programs that don't return any
+/// require no accounts.
+fn account_metas(
+    client: &RpcClient,
+    commitment: CommitmentConfig,
+    payer: &Pubkey,
+    program_id: Pubkey,
+    instruction_data: Vec<u8>,
+    account_metas_pda_seeds: &[&[u8]],
+) -> Result<Vec<AccountMeta>, String> {
+    let (account_metas_pda, _) = Pubkey::find_program_address(account_metas_pda_seeds, &program_id);
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &instruction_data,
+        vec![AccountMeta::new(account_metas_pda, false)],
+    );
+    Ok(simulate_return_data::<Vec<SerializableAccountMeta>>(
+        client,
+        commitment,
+        payer,
+        instruction,
+    )?
+    .unwrap_or_default()
+    .into_iter()
+    .map(Into::into)
+    .collect())
+}
+
+/// Gets the recipient's ISM as the mailbox resolves it, along with the
+/// accounts the recipient requires to look it up.
+fn recipient_ism_with_account_metas(
+    client: &RpcClient,
+    commitment: CommitmentConfig,
+    protocol: &ProtocolConfig,
+    mailbox: Pubkey,
+    payer: Pubkey,
+    recipient: Pubkey,
+) -> Result<(Pubkey, Vec<AccountMeta>), String> {
+    let (inbox, _) = protocol.inbox_pda(&mailbox);
+    let ism_getter_account_metas = account_metas(
+        client,
+        commitment,
+        &payer,
+        recipient,
+        MessageRecipientInstruction::InterchainSecurityModuleAccountMetas
+            .encode()
+            .unwrap(),
+        INTERCHAIN_SECURITY_MODULE_ACCOUNT_METAS_PDA_SEEDS,
+    )?;
+    let mut get_ism_accounts = vec![
+        AccountMeta::new_readonly(inbox, false),
+        AccountMeta::new_readonly(recipient, false),
+    ];
+    get_ism_accounts.extend(ism_getter_account_metas.iter().cloned());
+    let ism = simulate_return_data::<Pubkey>(
+        client,
+        commitment,
+        &payer,
+        Instruction::new_with_borsh(
+            mailbox,
+            &MailboxInstruction::InboxGetRecipientIsm(recipient),
+            get_ism_accounts,
+        ),
+    )?
+    .ok_or("No ISM returned by the mailbox")?;
+    Ok((ism, ism_getter_account_metas))
+}
+
+/// Gets the ISM that verifies messages to the recipient.
+pub(crate) fn recipient_ism(
+    client: &RpcClient,
+    commitment: CommitmentConfig,
+    protocol: &ProtocolConfig,
+    mailbox: Pubkey,
+    payer: Pubkey,
+    recipient: Pubkey,
+) -> Result<Pubkey, String> {
+    recipient_ism_with_account_metas(client, commitment, protocol, mailbox, payer, recipient)
+        .map(|(ism, _)| ism)
+}
+
+/// Gets the ISM's module type, which determines the format of the metadata
+/// it verifies messages with.
+pub(crate) fn ism_module_type(
+    client: &RpcClient,
+    commitment: CommitmentConfig,
+    payer: Pubkey,
+    ism: Pubkey,
+) -> Result<ModuleType, String> {
+    let module_type = simulate_return_data::<u32>(
+        client,
+        commitment,
+        &payer,
+        Instruction::new_with_bytes(
+            ism,
+            &InterchainSecurityModuleInstruction::Type.encode().unwrap(),
+            vec![],
+        ),
+    )?
+    .ok_or_else(|| format!("No module type returned by ISM {}", ism))?;
+    ModuleType::from_u32(module_type)
+        .ok_or_else(|| format!("Unknown module type {} of ISM {}", module_type, ism))
+}
+
+/// Checks the accounts delivering the message relies on before it's sent: that
+/// the mailbox's inbox exists, the recipient and its ISM are deployed programs,
+/// and the payer can pay the rent of the processed message PDA. Reports every
+/// problem found rather than just the first.
+pub(crate) fn validate_process(
+    client: &RpcClient,
+    commitment: CommitmentConfig,
+    protocol: &ProtocolConfig,
+    mailbox: Pubkey,
+    payer: Pubkey,
+    message: &HyperlaneMessage,
+) -> Result<(), Vec<String>> {
+    let get_account = |pubkey: &Pubkey| {
+        client
+            .get_account_with_commitment(pubkey, commitment)
+            .map(|response| response.value)
+            .map_err(|e| vec![format!("Failed to get account {}: {}", pubkey, e)])
+    };
+    let mut problems = vec![];
+
+    let (inbox, _) = protocol.inbox_pda(&mailbox);
+    match get_account(&inbox)? {
+        Some(account) if account.owner == mailbox => {}
+        Some(account) => problems.push(format!(
+            "Inbox {} is owned by {}, not mailbox {}",
+            inbox, account.owner, mailbox
+        )),
+        None => problems.push(format!(
+            "Inbox {} not found, is mailbox {} initialized?",
+            inbox, mailbox
+        )),
+    }
+
+    let recipient = Pubkey::new_from_array(message.recipient.0);
+    match get_account(&recipient)? {
+        Some(account) if account.executable => {
+            // The mailbox can only look up the ISM of a deployed recipient.
+            match recipient_ism(client, commitment, protocol, mailbox, payer, recipient) {
+                Ok(ism) => match get_account(&ism)? {
+                    Some(account) if account.executable => {}
+                    Some(_) => problems.push(format!("ISM {} isn't a program", ism)),
+                    None => problems.push(format!("ISM {} isn't deployed", ism)),
+                },
+                Err(e) => problems.push(format!(
+                    "Failed to get the ISM of recipient {}: {}",
+                    recipient, e
+                )),
+            }
+        }
+        Some(_) => problems.push(format!("Recipient {} isn't a program", recipient)),
+        None => problems.push(format!("Recipient {} isn't deployed", recipient)),
+    }
+
+    let processed_message_size = ProcessedMessageAccount::from(ProcessedMessage::default()).size();
+    let rent = client
+        .get_minimum_balance_for_rent_exemption(processed_message_size)
+        .map_err(|e| vec![format!("Failed to get the rent exemption minimum: {}", e)])?;
+    let balance = get_account(&payer)?.map_or(0, |account| account.lamports);
+    if balance < rent {
+        problems.push(format!(
+            "Payer {} has {} lamports, less than the processed message PDA's rent of {}",
+            payer, balance, rent
+        ));
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
+
+/// Builds the instruction delivering the message to its recipient through the
+/// mailbox, with the given ISM metadata.
+pub(crate) fn process_instruction(
+    client: &RpcClient,
+    commitment: CommitmentConfig,
+    protocol: &ProtocolConfig,
+    mailbox: Pubkey,
+    payer: Pubkey,
+    message: &HyperlaneMessage,
+    metadata: Vec<u8>,
+) -> Result<Instruction, String> {
+    let recipient = Pubkey::new_from_array(message.recipient.0);
+    let (inbox, _) = protocol.inbox_pda(&mailbox);
+    let (process_authority, _) = protocol.process_authority_pda(&mailbox, &recipient);
+    let (processed_message, _) = protocol.processed_message_pda(&mailbox, message.id());
+    let mut encoded_message = vec![];
+    message.write_to(&mut encoded_message).unwrap();
+
+    let (ism, ism_getter_account_metas) =
+        recipient_ism_with_account_metas(client, commitment, protocol, mailbox, payer, recipient)?;
+
+    let ism_verify_account_metas = account_metas(
+        client,
+        commitment,
+        &payer,
+        ism,
+        InterchainSecurityModuleInstruction::VerifyAccountMetas(VerifyInstruction {
+            metadata: metadata.clone(),
+            message: encoded_message.clone(),
+        })
+        .encode()
+        .unwrap(),
+        VERIFY_ACCOUNT_METAS_PDA_SEEDS,
+    )?;
+    let handle_account_metas = account_metas(
+        client,
+        commitment,
+        &payer,
+        recipient,
+        MessageRecipientInstruction::HandleAccountMetas(HandleInstruction {
+            origin: message.origin,
+            sender: message.sender,
+            message: message.body.clone(),
+        })
+        .encode()
+        .unwrap(),
+        HANDLE_ACCOUNT_METAS_PDA_SEEDS,
+    )?;
+
+    // 0. [signer] Payer.
+    // 1. [executable] System program.
+    // 2. [writeable] Inbox PDA.
+    // 3. [] Process authority of the recipient.
+    // 4. [writeable] Processed message PDA.
+    // 5..N [??] Accounts for getting the recipient's ISM.
+    // N+1. [executable] SPL Noop program.
+    // N+2. [executable] The ISM.
+    // N+3..M [??] Accounts for the ISM's verification.
+    // M+1. [executable] The recipient.
+    // M+2..K [??] Accounts for the recipient's handler.
+    let mut accounts = vec![
+        AccountMeta::new(payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(inbox, false),
+        AccountMeta::new_readonly(process_authority, false),
+        AccountMeta::new(processed_message, false),
+    ];
+    accounts.extend(ism_getter_account_metas);
+    accounts.extend([
+        AccountMeta::new_readonly(spl_noop::id(), false),
+        AccountMeta::new_readonly(ism, false),
+    ]);
+    accounts.extend(ism_verify_account_metas);
+    accounts.push(AccountMeta::new_readonly(recipient, false));
+    accounts.extend(handle_account_metas);
+
+    Ok(Instruction {
+        program_id: mailbox,
+        data: MailboxInstruction::InboxProcess(InboxProcess {
+            metadata,
+            message: encoded_message,
+        })
+        .into_instruction_data()
+        .unwrap(),
+        accounts,
+    })
+}
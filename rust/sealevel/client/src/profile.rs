@@ -0,0 +1,215 @@
+//! Named profiles, e.g. `mainnet`, `testnet` and `local`, each with its own
+//! RPC URL, keypair and program IDs, so switching between deployments is a
+//! matter of `--profile` or `HYPERLANE_PROFILE` rather than of retyping them.
+//!
+//! Profiles are read from a JSON file mapping profile names to profiles.
+//! Flags given on the command line take precedence over the profile, which
+//! takes precedence over the Solana CLI config.
+
+use std::{collections::HashMap, fs::File, path::PathBuf};
+
+use clap::{Command, CommandFactory};
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+
+#[cfg(feature = "tokens")]
+use crate::HYPERLANE_TOKEN_PROG_ID;
+use crate::{
+    exit_code, Cli, MAILBOX_PROG_ID, MULTISIG_ISM_MESSAGE_ID_PROG_ID, VALIDATOR_ANNOUNCE_PROG_ID,
+};
+
+/// The profile used when none is selected.
+const DEFAULT_PROFILE: &str = "local";
+
+/// The profiles file used when none is given, relative to the home directory.
+const DEFAULT_PROFILES_FILE: &str = ".config/hyperlane/sealevel-profiles.json";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub(crate) struct Profile {
+    /// The name of the profile, if one was loaded.
+    #[serde(skip)]
+    pub name: Option<String>,
+    /// The profiles file the profile was loaded from.
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+    pub url: Option<String>,
+    /// The chain the profile's RPC serves, whose genesis hash is checked on
+    /// startup unless `--chain` is given.
+    pub chain: Option<String>,
+    pub keypair: Option<String>,
+    pub program_ids: ProgramIds,
+}
+
+/// Program IDs that arguments defaulting to the built-in program IDs default
+/// to instead.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub(crate) struct ProgramIds {
+    #[serde(with = "crate::serde::serde_option_pubkey")]
+    pub mailbox: Option<Pubkey>,
+    /// The IGP, which `--igp-program-id` arguments default to.
+    #[serde(with = "crate::serde::serde_option_pubkey")]
+    pub igp: Option<Pubkey>,
+    #[serde(with = "crate::serde::serde_option_pubkey")]
+    pub multisig_ism_message_id: Option<Pubkey>,
+    #[serde(with = "crate::serde::serde_option_pubkey")]
+    pub validator_announce: Option<Pubkey>,
+    #[cfg(feature = "tokens")]
+    #[serde(with = "crate::serde::serde_option_pubkey")]
+    pub token: Option<Pubkey>,
+}
+
+impl Profile {
+    /// Loads the profile selected on the command line or by the environment,
+    /// before the arguments are validated, as the profile changes their
+    /// defaults. Without profiles file, or if the default profile isn't in
+    /// it, an empty profile is returned.
+    pub(crate) fn load_selected() -> Self {
+        let matches = Cli::command().ignore_errors(true).get_matches();
+        let name = matches.get_one::<String>("profile");
+        let path = matches
+            .get_one::<PathBuf>("profiles_file")
+            .cloned()
+            .or_else(|| {
+                std::env::var_os("HOME")
+                    .map(|home| PathBuf::from(home).join(DEFAULT_PROFILES_FILE))
+                    .filter(|path| path.exists())
+            });
+        let Some(path) = path else {
+            if let Some(name) = name {
+                exit_code::config_error(format!(
+                    "Profile {} selected but there's no profiles file, pass --profiles-file",
+                    name
+                ));
+            }
+            return Self::default();
+        };
+
+        let file = File::open(&path).unwrap_or_else(|e| {
+            exit_code::config_error(format!(
+                "Failed to open profiles file {}: {}",
+                path.display(),
+                e
+            ))
+        });
+        let mut profiles: HashMap<String, Profile> =
+            serde_json::from_reader(file).unwrap_or_else(|e| {
+                exit_code::config_error(format!("Invalid profiles file {}: {}", path.display(), e))
+            });
+        let profile = match name {
+            Some(name) => profiles.remove(name).unwrap_or_else(|| {
+                exit_code::config_error(format!(
+                    "No profile {} in profiles file {}",
+                    name,
+                    path.display()
+                ))
+            }),
+            None => match profiles.remove(DEFAULT_PROFILE) {
+                Some(profile) => profile,
+                None => return Self::default(),
+            },
+        };
+        Self {
+            name: Some(name.map_or(DEFAULT_PROFILE, String::as_str).to_owned()),
+            path: Some(path),
+            ..profile
+        }
+    }
+
+    /// Makes the arguments of the command and its subcommands that default
+    /// to a built-in program ID default to the profile's program ID instead.
+    pub(crate) fn apply_program_ids(&self, command: Command) -> Command {
+        let ids = &self.program_ids;
+        let mut replacements = vec![
+            (MAILBOX_PROG_ID, ids.mailbox),
+            (MULTISIG_ISM_MESSAGE_ID_PROG_ID, ids.multisig_ism_message_id),
+            (VALIDATOR_ANNOUNCE_PROG_ID, ids.validator_announce),
+        ];
+        #[cfg(feature = "tokens")]
+        replacements.push((HYPERLANE_TOKEN_PROG_ID, ids.token));
+        let replacements = replacements
+            .into_iter()
+            .filter_map(|(default, program_id)| {
+                program_id.map(|program_id| (default.to_string(), leak(program_id)))
+            })
+            .collect::<Vec<_>>();
+        with_program_id_defaults(command, &replacements, ids.igp.map(leak))
+    }
+}
+
+/// Clap only takes static strings as defaults. The handful of program IDs of
+/// a profile are leaked once, on startup.
+fn leak(program_id: Pubkey) -> &'static str {
+    Box::leak(program_id.to_string().into_boxed_str())
+}
+
+fn with_program_id_defaults(
+    mut command: Command,
+    replacements: &[(String, &'static str)],
+    igp: Option<&'static str>,
+) -> Command {
+    let defaults = command
+        .get_arguments()
+        .filter_map(|arg| {
+            let id = arg.get_id().as_str().to_owned();
+            if id == "igp_program_id" {
+                return igp.map(|igp| (id, igp));
+            }
+            let [default] = arg.get_default_values() else {
+                return None;
+            };
+            replacements
+                .iter()
+                .find(|(built_in, _)| default.to_str() == Some(built_in.as_str()))
+                .map(|(_, program_id)| (id, *program_id))
+        })
+        .collect::<Vec<_>>();
+    for (id, program_id) in defaults {
+        command = command.mut_arg(id, |arg| arg.default_value(program_id));
+    }
+
+    let subcommands = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_owned())
+        .collect::<Vec<_>>();
+    for name in subcommands {
+        command = command.mut_subcommand(name, |subcommand| {
+            with_program_id_defaults(subcommand, replacements, igp)
+        });
+    }
+    command
+}
+
+#[cfg(test)]
+mod test {
+    use clap::FromArgMatches as _;
+
+    use super::*;
+    use crate::{HyperlaneSealevelCmd, MailboxCmd, MailboxSubCmd};
+
+    #[test]
+    fn test_apply_program_ids() {
+        let mailbox = Pubkey::new_unique();
+        let profile = Profile {
+            program_ids: ProgramIds {
+                mailbox: Some(mailbox),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let command = profile.apply_program_ids(Cli::command());
+
+        let matches = command
+            .try_get_matches_from(["client", "mailbox", "query"])
+            .unwrap();
+        let cli = Cli::from_arg_matches(&matches).unwrap();
+        let HyperlaneSealevelCmd::Mailbox(MailboxCmd {
+            cmd: MailboxSubCmd::Query(query),
+        }) = cli.cmd
+        else {
+            panic!("Not a mailbox query");
+        };
+        assert_eq!(query.program_id, mailbox);
+    }
+}
@@ -0,0 +1,44 @@
+//! Merkle proofs of dispatched messages against the outbox's merkle tree, as
+//! needed for merkle root multisig ISM metadata.
+
+use hyperlane_core::{
+    accumulator::{
+        merkle::{MerkleTree, Proof},
+        TREE_DEPTH,
+    },
+    H256,
+};
+
+/// Reconstructs the outbox tree with the given leaves, the IDs of the
+/// mailbox's dispatched messages in nonce order, and proves the leaf at
+/// `leaf_index` against its root. The proof is against the root of a
+/// checkpoint if the leaves are the checkpoint's first `index + 1` messages.
+pub(crate) fn outbox_proof(message_ids: &[H256], leaf_index: u32) -> Option<Proof> {
+    if leaf_index as usize >= message_ids.len() {
+        return None;
+    }
+    Some(MerkleTree::create(message_ids, TREE_DEPTH).prove_against_current(leaf_index as usize))
+}
+
+#[cfg(test)]
+mod test {
+    use hyperlane_core::accumulator::incremental::IncrementalMerkle;
+
+    use super::*;
+
+    #[test]
+    fn test_outbox_proof() {
+        let message_ids = (0..5).map(|_| H256::random()).collect::<Vec<_>>();
+        let mut tree = IncrementalMerkle::default();
+        for id in &message_ids {
+            tree.ingest(*id);
+        }
+
+        for leaf_index in 0..5 {
+            let proof = outbox_proof(&message_ids, leaf_index).unwrap();
+            assert_eq!(proof.leaf, message_ids[leaf_index as usize]);
+            assert_eq!(proof.root(), tree.root());
+        }
+        assert!(outbox_proof(&message_ids, 5).is_none());
+    }
+}
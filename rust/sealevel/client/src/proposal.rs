@@ -0,0 +1,156 @@
+//! Payloads for proposing admin instructions to a multisig, such as a Squads
+//! vault or an SPL governance account, which is usually the owner of
+//! production programs.
+
+use base64::Engine as _;
+use clap::ValueEnum;
+use serde::Serialize;
+use solana_sdk::{compute_budget, message::Message, pubkey::Pubkey};
+
+use crate::context::InstructionWithDescription;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ProposalFormat {
+    /// The message for the multisig to execute, bincode serialized and base64
+    /// encoded, as accepted by Squads and SPL governance when importing a
+    /// transaction.
+    Base64,
+    /// A JSON draft of a Squads transaction, listing each instruction with
+    /// its accounts.
+    Squads,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SquadsDraft {
+    /// The multisig's vault, which executes the instructions.
+    authority: String,
+    instructions: Vec<SquadsInstruction>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SquadsInstruction {
+    program_id: String,
+    description: Option<String>,
+    accounts: Vec<SquadsAccountMeta>,
+    /// The instruction data as base64.
+    data: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SquadsAccountMeta {
+    pubkey: String,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+/// Formats the instructions as a proposal for the multisig `authority` to
+/// execute. Compute budget instructions are left out, as they only apply to
+/// the transaction executing the proposal.
+pub(crate) fn format_proposal(
+    format: ProposalFormat,
+    authority: &Pubkey,
+    instructions: &[InstructionWithDescription],
+) -> String {
+    let instructions = instructions
+        .iter()
+        .filter(|ixn| !compute_budget::check_id(&ixn.instruction.program_id))
+        .collect::<Vec<_>>();
+    match format {
+        ProposalFormat::Base64 => {
+            let message = Message::new(
+                &instructions
+                    .iter()
+                    .map(|ixn| ixn.instruction.clone())
+                    .collect::<Vec<_>>(),
+                Some(authority),
+            );
+            base64::engine::general_purpose::STANDARD.encode(bincode::serialize(&message).unwrap())
+        }
+        ProposalFormat::Squads => {
+            let draft = SquadsDraft {
+                authority: authority.to_string(),
+                instructions: instructions
+                    .iter()
+                    .map(
+                        |InstructionWithDescription {
+                             instruction,
+                             description,
+                         }| SquadsInstruction {
+                            program_id: instruction.program_id.to_string(),
+                            description: description.clone(),
+                            accounts: instruction
+                                .accounts
+                                .iter()
+                                .map(|meta| SquadsAccountMeta {
+                                    pubkey: meta.pubkey.to_string(),
+                                    is_signer: meta.is_signer,
+                                    is_writable: meta.is_writable,
+                                })
+                                .collect(),
+                            data: base64::engine::general_purpose::STANDARD
+                                .encode(&instruction.data),
+                        },
+                    )
+                    .collect(),
+            };
+            serde_json::to_string_pretty(&draft).unwrap()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use solana_sdk::{compute_budget::ComputeBudgetInstruction, instruction::AccountMeta};
+
+    use super::*;
+
+    #[test]
+    fn test_format_proposal_skips_compute_budget() {
+        let authority = Pubkey::new_unique();
+        let instructions = vec![
+            (
+                ComputeBudgetInstruction::set_compute_unit_limit(1_400_000),
+                None,
+            )
+                .into(),
+            (
+                solana_sdk::instruction::Instruction {
+                    program_id: Pubkey::new_unique(),
+                    accounts: vec![AccountMeta::new_readonly(authority, true)],
+                    data: vec![1, 2, 3],
+                },
+                Some("Transfer ownership".to_owned()),
+            )
+                .into(),
+        ];
+
+        let draft: serde_json::Value = serde_json::from_str(&format_proposal(
+            ProposalFormat::Squads,
+            &authority,
+            &instructions,
+        ))
+        .unwrap();
+        assert_eq!(draft["instructions"].as_array().unwrap().len(), 1);
+        assert_eq!(draft["instructions"][0]["data"], "AQID");
+        assert_eq!(
+            draft["instructions"][0]["accounts"][0]["pubkey"],
+            authority.to_string()
+        );
+
+        let message: Message = bincode::deserialize(
+            &base64::engine::general_purpose::STANDARD
+                .decode(format_proposal(
+                    ProposalFormat::Base64,
+                    &authority,
+                    &instructions,
+                ))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(message.instructions.len(), 1);
+        assert_eq!(message.account_keys[0], authority);
+    }
+}
@@ -0,0 +1,167 @@
+//! Protocol constants, which can be overridden per deployment so that forks or
+//! testnets with tweaked parameters can be used without a patched build.
+
+use std::{fs::File, path::Path};
+
+use hyperlane_core::H256;
+use serde::Deserialize;
+use solana_program::pubkey::Pubkey;
+
+use hyperlane_sealevel_mailbox::instruction::{MAX_MESSAGE_BODY_BYTES, VERSION};
+
+/// The length of an encoded message header:
+/// version (1) + nonce (4) + origin (4) + sender (32) + destination (4) + recipient (32)
+pub const MESSAGE_HEADER_BYTES: usize = 77;
+
+/// The seed prefix the mailbox derives its PDAs from.
+pub const DEFAULT_SEED_PREFIX: &str = "hyperlane";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ProtocolConfig {
+    /// The message version the mailbox dispatches and accepts.
+    pub message_version: u8,
+    /// The maximum size of a message body accepted by the mailbox.
+    pub max_message_body_bytes: usize,
+    /// The seed prefix the mailbox derives its PDAs from.
+    pub seed_prefix: String,
+}
+
+impl Default for ProtocolConfig {
+    fn default() -> Self {
+        Self {
+            message_version: VERSION,
+            max_message_body_bytes: MAX_MESSAGE_BODY_BYTES,
+            seed_prefix: DEFAULT_SEED_PREFIX.to_owned(),
+        }
+    }
+}
+
+impl ProtocolConfig {
+    /// Loads the protocol config, applying any overrides from the JSON file
+    /// at `path`.
+    pub fn load(path: Option<&Path>) -> Result<Self, String> {
+        let Some(path) = path else {
+            return Ok(Self::default());
+        };
+        let file = File::open(path).map_err(|e| {
+            format!(
+                "Failed to open protocol config file {}: {}",
+                path.display(),
+                e
+            )
+        })?;
+        serde_json::from_reader(file)
+            .map_err(|e| format!("Invalid protocol config file {}: {}", path.display(), e))
+    }
+
+    pub fn inbox_pda(&self, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[self.seed_prefix.as_bytes(), b"-", b"inbox"], program_id)
+    }
+
+    pub fn outbox_pda(&self, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[self.seed_prefix.as_bytes(), b"-", b"outbox"], program_id)
+    }
+
+    pub fn dispatched_message_pda(
+        &self,
+        program_id: &Pubkey,
+        unique_message_pubkey: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                self.seed_prefix.as_bytes(),
+                b"-",
+                b"dispatched_message",
+                b"-",
+                unique_message_pubkey.as_ref(),
+            ],
+            program_id,
+        )
+    }
+
+    pub fn process_authority_pda(&self, program_id: &Pubkey, recipient: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                self.seed_prefix.as_bytes(),
+                b"-",
+                b"process_authority",
+                b"-",
+                recipient.as_ref(),
+            ],
+            program_id,
+        )
+    }
+
+    pub fn processed_message_pda(&self, program_id: &Pubkey, message_id: H256) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                self.seed_prefix.as_bytes(),
+                b"-",
+                b"processed_message",
+                b"-",
+                message_id.as_bytes(),
+            ],
+            program_id,
+        )
+    }
+
+    /// Panics if the message body is too large for the mailbox to accept.
+    pub fn check_message_body(&self, message_body: &[u8]) {
+        assert!(
+            message_body.len() <= self.max_message_body_bytes,
+            "Message body of {} bytes exceeds the maximum of {} bytes",
+            message_body.len(),
+            self.max_message_body_bytes
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use hyperlane_sealevel_mailbox::{
+        mailbox_dispatched_message_pda_seeds, mailbox_inbox_pda_seeds, mailbox_outbox_pda_seeds,
+        mailbox_process_authority_pda_seeds, mailbox_processed_message_pda_seeds,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_default_pdas_match_mailbox_seeds() {
+        let protocol = ProtocolConfig::default();
+        let program_id = Pubkey::new_unique();
+        let unique_message_pubkey = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let message_id = H256::random();
+
+        assert_eq!(
+            protocol.inbox_pda(&program_id),
+            Pubkey::find_program_address(mailbox_inbox_pda_seeds!(), &program_id)
+        );
+        assert_eq!(
+            protocol.outbox_pda(&program_id),
+            Pubkey::find_program_address(mailbox_outbox_pda_seeds!(), &program_id)
+        );
+        assert_eq!(
+            protocol.dispatched_message_pda(&program_id, &unique_message_pubkey),
+            Pubkey::find_program_address(
+                mailbox_dispatched_message_pda_seeds!(unique_message_pubkey),
+                &program_id
+            )
+        );
+        assert_eq!(
+            protocol.process_authority_pda(&program_id, &recipient),
+            Pubkey::find_program_address(
+                mailbox_process_authority_pda_seeds!(recipient),
+                &program_id
+            )
+        );
+        assert_eq!(
+            protocol.processed_message_pda(&program_id, message_id),
+            Pubkey::find_program_address(
+                mailbox_processed_message_pda_seeds!(message_id),
+                &program_id
+            )
+        );
+    }
+}
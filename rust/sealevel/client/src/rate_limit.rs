@@ -0,0 +1,220 @@
+//! Rate limiting of RPC requests, so that long-running or request-heavy
+//! commands like `index sync` and `mailbox send-batch` don't trip the rate
+//! limits of public RPCs. Requests are spaced out by a token bucket, and
+//! requests rejected with a 429 anyway are retried with exponential backoff.
+
+use std::{
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use serde_json::Value;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind, Result as ClientResult},
+    rpc_request::{RpcError, RpcRequest},
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use solana_sdk::{
+    account::Account, pubkey::Pubkey, signature::Signature, transaction::Transaction,
+};
+use tracing::debug;
+
+use crate::{
+    accounts::AccountReader,
+    protocol::ProtocolConfig,
+    sender::{ConfirmationStrategy, TransactionSender},
+};
+
+const TOO_MANY_REQUESTS: u16 = 429;
+/// How many times a rate limited request is retried.
+const MAX_RATE_LIMITED_RETRIES: u32 = 6;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// A token bucket refilled at a fixed rate, holding up to a second's worth of
+/// tokens so that short bursts aren't slowed down.
+struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(requests_per_second: f64) -> Self {
+        let capacity = requests_per_second.max(1.0);
+        Self {
+            rate: requests_per_second,
+            capacity,
+            state: Mutex::new(BucketState {
+                tokens: capacity,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Blocks until a token is available, and takes it.
+    fn acquire(&self) {
+        while let Some(wait) = self.try_take(Instant::now()) {
+            thread::sleep(wait);
+        }
+    }
+
+    /// Takes a token if one is available at `now`, otherwise returns how long
+    /// until one is.
+    fn try_take(&self, now: Instant) -> Option<Duration> {
+        let mut state = self.state.lock().unwrap();
+        let elapsed = now.saturating_duration_since(state.refilled_at);
+        state.tokens = (state.tokens + elapsed.as_secs_f64() * self.rate).min(self.capacity);
+        state.refilled_at = now;
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - state.tokens) / self.rate))
+        }
+    }
+}
+
+/// Whether the request was rejected for exceeding the RPC's rate limit.
+fn is_rate_limited(err: &ClientError) -> bool {
+    match err.kind() {
+        ClientErrorKind::Reqwest(err) => {
+            err.status().map(|status| status.as_u16()) == Some(TOO_MANY_REQUESTS)
+        }
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => {
+            *code == i64::from(TOO_MANY_REQUESTS)
+        }
+        _ => false,
+    }
+}
+
+/// Limits the requests made through the wrapped sender, account reader or
+/// RPC transport to a number per second, backing off when rate limited
+/// anyway.
+pub(crate) struct RateLimited<T> {
+    inner: T,
+    bucket: TokenBucket,
+}
+
+impl<T> RateLimited<T> {
+    pub(crate) fn new(inner: T, requests_per_second: f64) -> Self {
+        assert!(requests_per_second > 0.0, "The rate limit must be positive");
+        Self {
+            inner,
+            bucket: TokenBucket::new(requests_per_second),
+        }
+    }
+
+    /// Makes the request once a token is available, retrying it with
+    /// exponential backoff while it's rate limited.
+    fn limit<R>(&self, mut request: impl FnMut(&T) -> ClientResult<R>) -> ClientResult<R> {
+        let mut backoff = INITIAL_BACKOFF;
+        let mut retries = 0;
+        loop {
+            self.bucket.acquire();
+            match request(&self.inner) {
+                Err(err) if is_rate_limited(&err) && retries < MAX_RATE_LIMITED_RETRIES => {
+                    debug!(?backoff, "Rate limited, backing off");
+                    thread::sleep(backoff);
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                    retries += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl<T: TransactionSender> TransactionSender for RateLimited<T> {
+    fn send(&self, txn: &Transaction) -> Result<Signature, ClientError> {
+        self.limit(|inner| inner.send(txn))
+    }
+
+    fn confirm(
+        &self,
+        signature: &Signature,
+        strategy: &ConfirmationStrategy,
+    ) -> Result<(), ClientError> {
+        self.limit(|inner| inner.confirm(signature, strategy))
+    }
+}
+
+impl<T: AccountReader> AccountReader for RateLimited<T> {
+    fn protocol(&self) -> &ProtocolConfig {
+        self.inner.protocol()
+    }
+
+    fn read_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, ClientError> {
+        self.bucket.acquire();
+        self.inner.read_account(pubkey)
+    }
+}
+
+// The blocking `RpcClient` drives each request to completion on the calling
+// thread, so waiting for a token blocks only the caller.
+#[async_trait]
+impl<T: RpcSender + Send + Sync + 'static> RpcSender for RateLimited<T> {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        let mut backoff = INITIAL_BACKOFF;
+        let mut retries = 0;
+        loop {
+            self.bucket.acquire();
+            match self.inner.send(request, params.clone()).await {
+                Err(err) if is_rate_limited(&err) && retries < MAX_RATE_LIMITED_RETRIES => {
+                    debug!(%request, ?backoff, "Rate limited, backing off");
+                    thread::sleep(backoff);
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                    retries += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.inner.get_transport_stats()
+    }
+
+    fn url(&self) -> String {
+        self.inner.url()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let bucket = TokenBucket::new(2.0);
+        let start = bucket.state.lock().unwrap().refilled_at;
+        // A second's worth of requests goes through at once.
+        assert_eq!(bucket.try_take(start), None);
+        assert_eq!(bucket.try_take(start), None);
+        assert_eq!(bucket.try_take(start), Some(Duration::from_millis(500)));
+        // Then they're spaced out at the rate.
+        let later = start + Duration::from_millis(500);
+        assert_eq!(bucket.try_take(later), None);
+        assert_eq!(bucket.try_take(later), Some(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_is_rate_limited() {
+        let rate_limited = ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            code: 429,
+            message: "Too many requests".to_owned(),
+            data: solana_client::rpc_request::RpcResponseErrorData::Empty,
+        });
+        assert!(is_rate_limited(&rate_limited.into()));
+        assert!(!is_rate_limited(
+            &ClientErrorKind::Custom("failed".to_owned()).into()
+        ));
+    }
+}
@@ -0,0 +1,192 @@
+//! Receipts of sent transactions, so that operational changes can be archived
+//! along with evidence of what was changed.
+
+use std::{fs, path::Path};
+
+use base64::Engine as _;
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    account::Account, commitment_config::CommitmentConfig, signature::Signature,
+    transaction::Transaction,
+};
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+
+use crate::{artifacts::write_json, context::InstructionWithDescription};
+
+/// The outcome of a sent transaction, as read back from the chain.
+#[derive(Debug, Clone)]
+pub(crate) struct TxOutcome {
+    pub(crate) signature: Signature,
+    pub(crate) slot: u64,
+    /// The fee paid in lamports, if the transaction's status was returned.
+    pub(crate) fee: Option<u64>,
+    pub(crate) compute_units_consumed: Option<u64>,
+    pub(crate) logs: Vec<String>,
+}
+
+impl TxOutcome {
+    /// Reads the outcome from the confirmed transaction, or `None` if its
+    /// signature can't be decoded.
+    pub(crate) fn new(confirmed_txn: &EncodedConfirmedTransactionWithStatusMeta) -> Option<Self> {
+        let signature = *confirmed_txn
+            .transaction
+            .transaction
+            .decode()?
+            .signatures
+            .first()?;
+        let meta = confirmed_txn.transaction.meta.as_ref();
+        Some(Self {
+            signature,
+            slot: confirmed_txn.slot,
+            fee: meta.map(|meta| meta.fee),
+            compute_units_consumed: meta
+                .and_then(|meta| Option::from(meta.compute_units_consumed.clone())),
+            logs: meta
+                .and_then(|meta| Option::from(meta.log_messages.clone()))
+                .unwrap_or_default(),
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Receipt {
+    signature: String,
+    /// The slot the transaction was confirmed in, if it could be read back.
+    slot: Option<u64>,
+    /// The fee paid in lamports, if the transaction could be read back.
+    fee: Option<u64>,
+    compute_units_consumed: Option<u64>,
+    /// The program logs, if the transaction could be read back.
+    logs: Vec<String>,
+    /// The signed transaction, bincode serialized and base64 encoded.
+    transaction: String,
+    instructions: Vec<ReceiptInstruction>,
+    /// The states of the transaction's writable accounts after it landed.
+    accounts: Vec<ReceiptAccount>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReceiptInstruction {
+    program_id: String,
+    description: Option<String>,
+    accounts: Vec<ReceiptAccountMeta>,
+    /// The instruction data as hex.
+    data: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReceiptAccountMeta {
+    pubkey: String,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReceiptAccount {
+    pubkey: String,
+    /// The account's state, or `None` if it doesn't exist.
+    state: Option<ReceiptAccountState>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReceiptAccountState {
+    lamports: u64,
+    owner: String,
+    executable: bool,
+    /// The account data as base64.
+    data: String,
+}
+
+impl From<Account> for ReceiptAccountState {
+    fn from(account: Account) -> Self {
+        Self {
+            lamports: account.lamports,
+            owner: account.owner.to_string(),
+            executable: account.executable,
+            data: base64::engine::general_purpose::STANDARD.encode(account.data),
+        }
+    }
+}
+
+/// Writes a receipt for a confirmed transaction to `<receipts_dir>/<signature>.json`.
+pub(crate) fn write_receipt(
+    receipts_dir: &Path,
+    client: &RpcClient,
+    commitment: CommitmentConfig,
+    txn: &Transaction,
+    instructions: &[InstructionWithDescription],
+    outcome: Option<&TxOutcome>,
+) {
+    let writable_accounts = txn
+        .message
+        .account_keys
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| txn.message.is_writable(*i))
+        .map(|(_, key)| *key)
+        .collect::<Vec<_>>();
+    let account_states = client
+        .get_multiple_accounts_with_commitment(&writable_accounts, commitment)
+        .unwrap()
+        .value;
+
+    let receipt = Receipt {
+        signature: txn.signatures[0].to_string(),
+        slot: outcome.map(|outcome| outcome.slot),
+        fee: outcome.and_then(|outcome| outcome.fee),
+        compute_units_consumed: outcome.and_then(|outcome| outcome.compute_units_consumed),
+        logs: outcome
+            .map(|outcome| outcome.logs.clone())
+            .unwrap_or_default(),
+        transaction: base64::engine::general_purpose::STANDARD
+            .encode(bincode::serialize(txn).unwrap()),
+        instructions: instructions
+            .iter()
+            .map(
+                |InstructionWithDescription {
+                     instruction,
+                     description,
+                 }| ReceiptInstruction {
+                    program_id: instruction.program_id.to_string(),
+                    description: description.clone(),
+                    accounts: instruction
+                        .accounts
+                        .iter()
+                        .map(|meta| ReceiptAccountMeta {
+                            pubkey: meta.pubkey.to_string(),
+                            is_signer: meta.is_signer,
+                            is_writable: meta.is_writable,
+                        })
+                        .collect(),
+                    data: hex::encode(&instruction.data),
+                },
+            )
+            .collect(),
+        accounts: writable_accounts
+            .iter()
+            .zip(account_states)
+            .map(|(pubkey, state)| ReceiptAccount {
+                pubkey: pubkey.to_string(),
+                state: state.map(ReceiptAccountState::from),
+            })
+            .collect(),
+    };
+
+    fs::create_dir_all(receipts_dir).unwrap_or_else(|e| {
+        panic!(
+            "Failed to create receipts directory {}: {}",
+            receipts_dir.display(),
+            e
+        )
+    });
+    write_json(
+        &receipts_dir.join(format!("{}.json", receipt.signature)),
+        receipt,
+    );
+}
@@ -0,0 +1,686 @@
+//! A minimal relayer for a single route between two Sealevel chains of an
+//! environment. Messages dispatched on the origin are delivered to the
+//! destination in nonce order, with metadata for the recipient's multisig ISM
+//! built from the checkpoints the origin's validators publish, in the message
+//! ID or merkle root format depending on the ISM's module type.
+//! Delivery is paid by the payer, no IGP payment is required. During
+//! congestion, deliveries can be submitted as Jito bundles with a tip instead.
+//!
+//! The last processed nonce is recorded in a checkpoint file, so a restarted
+//! relayer resumes where it left off, re-checking the deliveries of the last
+//! few messages in case they were rolled back. On SIGINT or SIGTERM, the
+//! relayer stops after the message being delivered, if any.
+
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    path::Path,
+    time::Duration,
+};
+
+use hyperlane_core::{accumulator::merkle::Proof, Decode as _, HyperlaneMessage, ModuleType, H256};
+use serde::{Deserialize, Serialize};
+use solana_client::client_error::ClientError;
+use solana_sdk::{commitment_config::CommitmentConfig, instruction::Instruction, pubkey::Pubkey};
+
+use hyperlane_sealevel::is_method_unavailable;
+use hyperlane_sealevel_mailbox::accounts::DispatchedMessage;
+use hyperlane_sealevel_multisig_ism_message_id::{
+    accounts::DomainData, domain_data_pda_seeds, instruction::ValidatorsAndThreshold,
+};
+
+use crate::{
+    accounts::{
+        read_program_account, AccountError, AccountReader, MailboxReader, RpcAccountReader,
+    },
+    artifacts::read_json,
+    checkpoint::{announced_storage_locations, fetch_signed_checkpoint},
+    events::{DeliveryEvent, DeliveryFailedEvent, HyperlaneEvent},
+    jito::{tip_instruction, JitoBundleSender},
+    metadata::MultisigMetadata,
+    process::{ism_module_type, process_instruction, recipient_ism, validate_process},
+    proof::outbox_proof,
+    resume::first_nonce_at_slot,
+    router::ChainMetadata,
+    sender::{ChainError, Client, SolanaRpcSender, TransactionSender},
+    shutdown::CancellationToken,
+    status::{find_dispatched_message_by_nonce, find_dispatched_messages},
+    webhook::Webhook,
+    Context, CoreProgramIds, Relay,
+};
+
+/// Progress of the relayer, persisted between runs.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct RelayCheckpoint {
+    /// The nonce of the last message processed, whether it was delivered or
+    /// skipped.
+    last_processed_nonce: Option<u32>,
+    /// Nonces of messages that couldn't be delivered within the retries.
+    #[serde(default)]
+    failed_nonces: Vec<u32>,
+    /// Nonces of messages that weren't ready to deliver, e.g. for lack of
+    /// validator signatures, which are tried again on every poll.
+    #[serde(default)]
+    pending_nonces: Vec<u32>,
+}
+
+impl RelayCheckpoint {
+    fn load(path: &Path) -> Option<Self> {
+        let file = File::open(path).ok()?;
+        Some(serde_json::from_reader(file).expect("Invalid relay checkpoint file"))
+    }
+
+    fn store(&self, path: &Path) {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        // Write to a temporary file first so a crash can't leave a truncated
+        // checkpoint behind.
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self).unwrap()).unwrap();
+        fs::rename(&tmp, path).unwrap();
+    }
+}
+
+enum Relayed {
+    Delivered,
+    AlreadyDelivered,
+    /// The message is to another destination.
+    OtherDestination(u32),
+}
+
+enum RelayError {
+    /// The message can't be delivered yet, e.g. because too few validators
+    /// have signed it. Tried again on the next poll.
+    NotReady(String),
+    /// Delivering the message failed. Retried up to the max retries.
+    Failed(String),
+}
+
+impl From<AccountError> for RelayError {
+    fn from(err: AccountError) -> Self {
+        match err {
+            // RPC failures are usually transient, so rather than giving up on
+            // the message, it's tried again on the next poll.
+            AccountError::Rpc { .. } => RelayError::NotReady(err.to_string()),
+            _ => RelayError::Failed(err.to_string()),
+        }
+    }
+}
+
+/// Whether the mailbox has processed the message, i.e. its processed message
+/// PDA exists.
+fn is_processed(
+    reader: &impl AccountReader,
+    mailbox: Pubkey,
+    message_id: H256,
+) -> Result<bool, AccountError> {
+    MailboxReader::new(reader, mailbox)
+        .processed_message(message_id)
+        .map(|processed_message| processed_message.is_some())
+}
+
+/// Delivers the message by sending a transaction through `send`, which is
+/// given the destination to send through. A delivery that fails because
+/// another relayer has delivered the message since it was checked, failing
+/// the transaction on the processed message account's creation, counts as
+/// already delivered.
+fn send_delivery<C: Client>(
+    destination: &C,
+    mailbox: Pubkey,
+    message_id: H256,
+    send: impl FnOnce(&dyn TransactionSender) -> Result<(), ClientError>,
+) -> Result<Relayed, ChainError> {
+    match send(destination) {
+        Ok(()) => Ok(Relayed::Delivered),
+        Err(_) if is_processed(destination, mailbox, message_id)? => Ok(Relayed::AlreadyDelivered),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// A route's chains and programs.
+struct Route<'a> {
+    ctx: &'a Context,
+    origin_name: &'a str,
+    origin: RpcAccountReader<'a>,
+    origin_domain: u32,
+    origin_mailbox: Pubkey,
+    validator_announce: Pubkey,
+    destination_name: &'a str,
+    destination: SolanaRpcSender,
+    destination_domain: u32,
+    destination_mailbox: Pubkey,
+    strict: bool,
+    /// Submits deliveries as Jito bundles paying the tip, if set.
+    jito: Option<JitoBundleSender>,
+    tip_lamports: u64,
+    /// Notified of the messages observed and of their deliveries, if set.
+    webhook: Option<Webhook>,
+    /// The nonces of the messages observed, so that messages retried while
+    /// not ready are only counted and notified once.
+    observed_nonces: RefCell<HashSet<u32>>,
+    /// The IDs of the origin's messages by nonce, fetched as merkle proofs
+    /// need them.
+    message_ids: RefCell<Vec<H256>>,
+}
+
+pub(crate) fn process_relay_cmd(ctx: Context, relay: Relay) {
+    let chain_configs: HashMap<String, ChainMetadata> =
+        serde_json::from_reader(File::open(&relay.chain_config_file).unwrap())
+            .expect("Invalid chain config file");
+    let chain_config = |chain: &str| {
+        chain_configs
+            .get(chain)
+            .unwrap_or_else(|| panic!("Chain {} isn't in the chain config file", chain))
+    };
+    let (origin_config, destination_config) = (
+        chain_config(&relay.origin),
+        chain_config(&relay.destination),
+    );
+    let environment_dir = relay.environments_dir.join(&relay.environment);
+    let core_program_ids = |chain: &str| -> CoreProgramIds {
+        read_json(
+            &environment_dir
+                .join(chain)
+                .join("core")
+                .join("program-ids.json"),
+        )
+    };
+    let (origin_program_ids, destination_program_ids) = (
+        core_program_ids(&relay.origin),
+        core_program_ids(&relay.destination),
+    );
+
+    let shutdown = CancellationToken::on_signals();
+    let route = Route {
+        ctx: &ctx,
+        origin_name: &relay.origin,
+        origin: RpcAccountReader {
+            client: origin_config.client(),
+            commitment: CommitmentConfig::confirmed(),
+            protocol: &ctx.protocol,
+        },
+        origin_domain: origin_config.domain_id(),
+        origin_mailbox: origin_program_ids.mailbox,
+        validator_announce: origin_program_ids.validator_announce,
+        destination_name: &relay.destination,
+        destination: ctx
+            .rpc_sender(destination_config.rpc_url())
+            .commitment(CommitmentConfig::confirmed())
+            .skip_preflight(relay.skip_preflight),
+        destination_domain: destination_config.domain_id(),
+        destination_mailbox: destination_program_ids.mailbox,
+        strict: ctx.is_strict(relay.daemon),
+        jito: relay.jito_url.as_deref().map(|url| ctx.jito_sender(url)),
+        tip_lamports: relay.tip_lamports,
+        webhook: relay.webhook.webhook(&shutdown),
+        observed_nonces: RefCell::default(),
+        message_ids: RefCell::default(),
+    };
+
+    let checkpoint_path = relay.checkpoint_file.clone().unwrap_or_else(|| {
+        environment_dir
+            .join(&relay.origin)
+            .join(format!("relay-{}.json", relay.destination))
+    });
+    let mut checkpoint = RelayCheckpoint::load(&checkpoint_path).unwrap_or_default();
+    let mut nonce = relay
+        .resume
+        .start_nonce(checkpoint.last_processed_nonce, |slot| {
+            first_nonce_at_slot(
+                &route.origin.client,
+                &ctx.protocol,
+                &route.origin_mailbox,
+                route.origin.commitment,
+                route.strict,
+                route
+                    .dispatched_count()
+                    .unwrap_or_else(|e| panic!("Failed to read the origin outbox: {}", e)),
+                slot,
+            )
+        });
+    println!(
+        "Relaying from {} to {}, starting at nonce {}",
+        relay.origin, relay.destination, nonce
+    );
+
+    // Compute budget instructions are added to every delivery, rather than
+    // only the first transaction.
+    let initial_instructions = ctx
+        .initial_instructions
+        .borrow_mut()
+        .drain(..)
+        .map(|i| i.instruction)
+        .collect::<Vec<_>>();
+    let poll_interval = Duration::from_secs(relay.poll_interval);
+    let mut delivered = 0;
+    // Polls since the last one that left no message waiting, which are
+    // bounded by the max retries unless running as a daemon.
+    let mut waiting_polls = 0;
+    'relay: loop {
+        // Messages that weren't ready are tried again on every poll, along
+        // with the messages dispatched since the last one, so a message
+        // waiting for validator signatures doesn't hold up the ones after it.
+        let pending = checkpoint.pending_nonces.clone();
+        // An outbox that can't be read only holds up the new messages until
+        // the next poll.
+        let count = route.dispatched_count().unwrap_or_else(|e| {
+            println!("Failed to read the origin outbox: {}", e);
+            nonce
+        });
+        let new_nonces = (nonce..count).filter(|nonce| !pending.contains(nonce));
+        for next in pending.iter().copied().chain(new_nonces) {
+            // Only stop between messages, so a delivery that was submitted is
+            // confirmed and checkpointed.
+            if shutdown.is_cancelled() {
+                break 'relay;
+            }
+            let mut retries = 0;
+            let outcome = loop {
+                match route.relay(next, &initial_instructions) {
+                    Ok(relayed) => break Some(Ok(relayed)),
+                    Err(RelayError::NotReady(reason)) => {
+                        println!("Message {} not ready: {}", next, reason);
+                        break None;
+                    }
+                    Err(RelayError::Failed(reason)) if retries < relay.max_retries => {
+                        retries += 1;
+                        println!(
+                            "Message {} failed, retry {} of {}: {}",
+                            next, retries, relay.max_retries, reason
+                        );
+                    }
+                    Err(RelayError::Failed(reason)) => break Some(Err(reason)),
+                }
+                // Back off exponentially on failures, up to a minute. A message
+                // still retried when shutting down is relayed on restart.
+                if shutdown.sleep(
+                    poll_interval
+                        .saturating_mul(2u32.saturating_pow(retries))
+                        .min(Duration::from_secs(60)),
+                ) {
+                    break 'relay;
+                }
+            };
+            match outcome {
+                None => {
+                    if !checkpoint.pending_nonces.contains(&next) {
+                        checkpoint.pending_nonces.push(next);
+                    }
+                }
+                Some(outcome) => {
+                    checkpoint.pending_nonces.retain(|pending| *pending != next);
+                    if outcome.is_ok() {
+                        checkpoint.failed_nonces.retain(|failed| *failed != next);
+                    }
+                    route.notify_outcome(next, &outcome);
+                    match outcome {
+                        Ok(Relayed::Delivered) => {
+                            delivered += 1;
+                            println!("Message {} delivered", next)
+                        }
+                        Ok(Relayed::AlreadyDelivered) => {
+                            println!("Message {} already delivered, nothing to submit", next)
+                        }
+                        Ok(Relayed::OtherDestination(destination)) => {
+                            println!("Message {} skipped, it's to domain {}", next, destination)
+                        }
+                        Err(reason) => {
+                            println!("Giving up on message {}: {}", next, reason);
+                            if !checkpoint.failed_nonces.contains(&next) {
+                                checkpoint.failed_nonces.push(next);
+                            }
+                        }
+                    }
+                }
+            }
+            if next >= nonce {
+                // Re-scanned nonces don't move the checkpoint back.
+                checkpoint.last_processed_nonce = checkpoint.last_processed_nonce.max(Some(next));
+                nonce = next + 1;
+            }
+            checkpoint.store(&checkpoint_path);
+        }
+
+        if checkpoint.pending_nonces.is_empty() {
+            waiting_polls = 0;
+        } else {
+            waiting_polls += 1;
+        }
+        let waiting = !checkpoint.pending_nonces.is_empty() && waiting_polls <= relay.max_retries;
+        if !(relay.daemon || waiting) || shutdown.sleep(poll_interval) {
+            break;
+        }
+    }
+    if shutdown.is_cancelled() {
+        println!(
+            "Shut down before message {}, checkpoint written to {}",
+            nonce,
+            checkpoint_path.display()
+        );
+    } else {
+        println!(
+            "Relayed all {} dispatched messages, checkpoint written to {}",
+            nonce,
+            checkpoint_path.display()
+        );
+    }
+    println!("Delivered {} messages", delivered);
+    if !checkpoint.pending_nonces.is_empty() {
+        println!(
+            "Messages not ready to deliver, tried again on the next run: {:?}",
+            checkpoint.pending_nonces
+        );
+    }
+    if !checkpoint.failed_nonces.is_empty() {
+        println!(
+            "Messages that couldn't be delivered: {:?}",
+            checkpoint.failed_nonces
+        );
+    }
+}
+
+impl Route<'_> {
+    /// Notifies the webhook, if any, of the delivery or the failure to
+    /// deliver the message.
+    fn notify_outcome(&self, nonce: u32, outcome: &Result<Relayed, String>) {
+        let Some(webhook) = &self.webhook else {
+            return;
+        };
+        let event = match outcome {
+            Ok(Relayed::Delivered) => HyperlaneEvent::DeliverySucceeded(DeliveryEvent {
+                origin: self.origin_domain,
+                destination: self.destination_domain,
+                nonce,
+            }),
+            Err(reason) => HyperlaneEvent::DeliveryFailed(DeliveryFailedEvent {
+                origin: self.origin_domain,
+                destination: self.destination_domain,
+                nonce,
+                reason: reason.clone(),
+            }),
+            Ok(Relayed::AlreadyDelivered | Relayed::OtherDestination(_)) => return,
+        };
+        webhook.post(event);
+    }
+
+    /// The number of messages dispatched on the origin.
+    fn dispatched_count(&self) -> Result<u32, AccountError> {
+        let outbox = MailboxReader::new(&self.origin, self.origin_mailbox)
+            .outbox()?
+            .expect("Origin mailbox not initialized");
+        Ok(outbox.tree.count() as u32)
+    }
+
+    fn relay(
+        &self,
+        nonce: u32,
+        initial_instructions: &[Instruction],
+    ) -> Result<Relayed, RelayError> {
+        let dispatched_message = find_dispatched_message_by_nonce(
+            &self.origin.client,
+            &self.ctx.protocol,
+            &self.origin_mailbox,
+            nonce,
+            self.origin.commitment,
+            self.strict,
+        )
+        .map_err(|e| RelayError::NotReady(format!("failed to get dispatched message: {}", e)))?
+        .ok_or_else(|| RelayError::NotReady("dispatched message not found".to_owned()))?;
+        let message = HyperlaneMessage::read_from(&mut &dispatched_message.encoded_message[..])
+            .map_err(|e| RelayError::Failed(format!("invalid dispatched message: {}", e)))?;
+        if self.observed_nonces.borrow_mut().insert(nonce) {
+            if let Some(metrics) = self.ctx.metrics() {
+                metrics
+                    .messages_observed
+                    .with_label_values(&[self.origin_name, self.destination_name])
+                    .inc();
+            }
+            if let Some(webhook) = &self.webhook {
+                webhook.post(HyperlaneEvent::dispatched(&dispatched_message));
+            }
+        }
+        if message.destination != self.destination_domain {
+            return Ok(Relayed::OtherDestination(message.destination));
+        }
+
+        let message_id = message.id();
+        if self.delivered(message_id)? {
+            return Ok(Relayed::AlreadyDelivered);
+        }
+        // Catch misconfigurations before waiting on validator signatures.
+        validate_process(
+            self.destination.client(),
+            self.destination.commitment_config(),
+            &self.ctx.protocol,
+            self.destination_mailbox,
+            self.ctx.payer_pubkey,
+            &message,
+        )
+        .map_err(|problems| RelayError::Failed(problems.join("; ")))?;
+
+        let ism = recipient_ism(
+            self.destination.client(),
+            self.destination.commitment_config(),
+            &self.ctx.protocol,
+            self.destination_mailbox,
+            self.ctx.payer_pubkey,
+            Pubkey::new_from_array(message.recipient.0),
+        )
+        .map_err(RelayError::Failed)?;
+        let module_type = ism_module_type(
+            self.destination.client(),
+            self.destination.commitment_config(),
+            self.ctx.payer_pubkey,
+            ism,
+        )
+        .map_err(RelayError::Failed)?;
+        let metadata = match module_type {
+            ModuleType::MessageIdMultisig | ModuleType::MerkleRootMultisig => self
+                .metadata(ism, &message, message_id, module_type)?
+                .encode(module_type)
+                .map_err(RelayError::Failed)?,
+            // The test ISM reports an unused module type, and like null ISMs
+            // verifies messages without metadata.
+            ModuleType::Null | ModuleType::Unused => vec![],
+            other => {
+                return Err(RelayError::Failed(format!(
+                    "{:?} ISM {} isn't supported",
+                    other, ism
+                )))
+            }
+        };
+        let instruction = process_instruction(
+            self.destination.client(),
+            self.destination.commitment_config(),
+            &self.ctx.protocol,
+            self.destination_mailbox,
+            self.ctx.payer_pubkey,
+            &message,
+            metadata,
+        )
+        .map_err(RelayError::Failed)?;
+        send_delivery(
+            &self.destination,
+            self.destination_mailbox,
+            message_id,
+            |sender| {
+                let mut txn = initial_instructions
+                    .iter()
+                    .cloned()
+                    .fold(self.ctx.new_txn(), |txn, instruction| txn.add(instruction))
+                    .with_client(self.destination.client())
+                    .with_sender(sender)
+                    .add_with_description(
+                        instruction,
+                        format!("Deliver message {:?} (nonce {})", message_id, nonce),
+                    );
+                if let Some(jito) = &self.jito {
+                    txn = txn.with_sender(jito).add_with_description(
+                        tip_instruction(&self.ctx.payer_pubkey, self.tip_lamports),
+                        format!("Tip {} lamports for the Jito bundle", self.tip_lamports),
+                    );
+                }
+                txn.try_send_with_payer().map(|_| ())
+            },
+        )
+        .map_err(|e| RelayError::Failed(e.to_string()))
+    }
+
+    fn delivered(&self, message_id: H256) -> Result<bool, RelayError> {
+        is_processed(&self.destination, self.destination_mailbox, message_id)
+            .map_err(RelayError::from)
+    }
+
+    /// Gathers the multisig ISM metadata for the message: the origin
+    /// mailbox, the checkpoint at the message's nonce, the signatures of a
+    /// threshold of the origin's validators set on the recipient's ISM, in
+    /// the order of the validator set, and for merkle root multisig ISMs, the
+    /// message's proof.
+    fn metadata(
+        &self,
+        ism: Pubkey,
+        message: &HyperlaneMessage,
+        message_id: H256,
+        module_type: ModuleType,
+    ) -> Result<MultisigMetadata, RelayError> {
+        let (domain_data_key, _) =
+            Pubkey::find_program_address(domain_data_pda_seeds!(self.origin_domain), &ism);
+        let ValidatorsAndThreshold {
+            validators,
+            threshold,
+        } = read_program_account::<DomainData>(&self.destination, &ism, &domain_data_key)
+            .map_err(RelayError::from)?
+            .ok_or_else(|| {
+                RelayError::Failed(format!(
+                    "no validators set for domain {} on ISM {}",
+                    self.origin_domain, ism
+                ))
+            })?
+            .validators_and_threshold;
+
+        let storage_locations = announced_storage_locations(
+            &self.origin.client,
+            self.origin.commitment,
+            &self.validator_announce,
+            &validators,
+        );
+        let origin_mailbox = H256(self.origin_mailbox.to_bytes());
+        let mut merkle_root = None;
+        let mut signatures = vec![];
+        for storage_locations in storage_locations {
+            let signed = storage_locations.iter().find_map(|location| {
+                fetch_signed_checkpoint(location, message.nonce, self.strict).filter(|signed| {
+                    signed.value.message_id == message_id
+                        && signed.value.mailbox_address == origin_mailbox
+                        && signed.value.mailbox_domain == self.origin_domain
+                        && (merkle_root.is_none() || merkle_root == Some(signed.value.root))
+                })
+            });
+            if let Some(signed) = signed {
+                merkle_root = Some(signed.value.root);
+                signatures.push(<[u8; 65]>::from(signed.signature));
+                if signatures.len() == usize::from(threshold) {
+                    break;
+                }
+            }
+        }
+        if signatures.len() < usize::from(threshold) {
+            return Err(RelayError::NotReady(format!(
+                "{} of {} validator signatures",
+                signatures.len(),
+                threshold
+            )));
+        }
+
+        let merkle_root = merkle_root.unwrap_or_default();
+        let proof = match module_type {
+            ModuleType::MerkleRootMultisig => Some(self.proof(message.nonce, merkle_root)?),
+            _ => None,
+        };
+        Ok(MultisigMetadata {
+            origin_mailbox,
+            checkpoint_index: message.nonce,
+            merkle_root,
+            message_id,
+            proof,
+            signatures,
+        })
+    }
+
+    /// Proves the message with the nonce against the root of the checkpoint
+    /// at its nonce, from the IDs of the origin's messages up to it. Missing
+    /// IDs are filled from a single scan of the origin's dispatched messages,
+    /// and only those the scan didn't return, e.g. because the RPC disabled
+    /// `getProgramAccounts`, are looked up by nonce.
+    fn proof(&self, nonce: u32, merkle_root: H256) -> Result<Proof, RelayError> {
+        let mut message_ids = self.message_ids.borrow_mut();
+        if message_ids.len() <= nonce as usize {
+            match find_dispatched_messages(
+                &self.origin.client,
+                &self.origin_mailbox,
+                self.origin.commitment,
+                self.strict,
+            ) {
+                Ok(mut dispatched_messages) => {
+                    dispatched_messages.sort_by_key(|dispatched_message| dispatched_message.nonce);
+                    for dispatched_message in dispatched_messages {
+                        match dispatched_message.nonce.cmp(&(message_ids.len() as u32)) {
+                            Ordering::Less => {}
+                            Ordering::Equal => {
+                                message_ids.push(dispatched_message_id(&dispatched_message)?)
+                            }
+                            Ordering::Greater => break,
+                        }
+                    }
+                }
+                Err(e) if is_method_unavailable(&e) => {}
+                Err(e) => {
+                    return Err(RelayError::NotReady(format!(
+                        "failed to get dispatched messages: {}",
+                        e
+                    )))
+                }
+            }
+        }
+        while message_ids.len() <= nonce as usize {
+            let next_nonce = message_ids.len() as u32;
+            let dispatched_message = find_dispatched_message_by_nonce(
+                &self.origin.client,
+                &self.ctx.protocol,
+                &self.origin_mailbox,
+                next_nonce,
+                self.origin.commitment,
+                self.strict,
+            )
+            .map_err(|e| {
+                RelayError::NotReady(format!(
+                    "failed to get dispatched message {}: {}",
+                    next_nonce, e
+                ))
+            })?
+            .ok_or_else(|| {
+                RelayError::NotReady(format!("dispatched message {} not found", next_nonce))
+            })?;
+            message_ids.push(dispatched_message_id(&dispatched_message)?);
+        }
+        let proof = outbox_proof(&message_ids[..=nonce as usize], nonce).unwrap();
+        if proof.root() != merkle_root {
+            return Err(RelayError::Failed(format!(
+                "the proof of message {} doesn't match the checkpoint root {:?}",
+                nonce, merkle_root
+            )));
+        }
+        Ok(proof)
+    }
+}
+
+/// The ID of the dispatched message.
+fn dispatched_message_id(dispatched_message: &DispatchedMessage) -> Result<H256, RelayError> {
+    HyperlaneMessage::read_from(&mut &dispatched_message.encoded_message[..])
+        .map(|message| message.id())
+        .map_err(|e| RelayError::Failed(format!("invalid dispatched message: {}", e)))
+}
@@ -0,0 +1,165 @@
+//! Reports on the indexed messages, for operators evaluating relayers.
+
+use std::{collections::HashMap, str::FromStr};
+
+use solana_client::rpc_client::RpcClient;
+
+use crate::{
+    domain::DomainArg,
+    index::{MessageFilter, MessageIndex},
+    rpc::rpc_client,
+    Context, ReportCmd, ReportLatency, ReportSubCmd,
+};
+
+pub(crate) fn process_report_cmd(ctx: Context, cmd: ReportCmd) {
+    match cmd.cmd {
+        ReportSubCmd::Latency(latency) => report_latency(&ctx, latency),
+    }
+}
+
+/// A route messages take, given as `<origin>-><destination>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Route {
+    pub origin: DomainArg,
+    pub destination: DomainArg,
+}
+
+impl FromStr for Route {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (origin, destination) = s
+            .split_once("->")
+            .ok_or_else(|| format!("Route {} isn't of the form <origin>-><destination>", s))?;
+        Ok(Self {
+            origin: origin.trim().parse()?,
+            destination: destination.trim().parse()?,
+        })
+    }
+}
+
+/// Prints the percentiles of the times from dispatch to delivery of the
+/// route's most recent delivered messages, from the block times of their
+/// dispatch and delivery slots.
+fn report_latency(ctx: &Context, latency: ReportLatency) {
+    let index = MessageIndex::open(&latency.db)
+        .unwrap_or_else(|e| panic!("Failed to open index {}: {}", latency.db.display(), e));
+    let origin = ctx.domains.resolve(&latency.route.origin);
+    let destination = ctx.domains.resolve(&latency.route.destination);
+    let messages = index
+        .query(&MessageFilter {
+            destination: Some(destination),
+            limit: i64::MAX as usize,
+            ..MessageFilter::default()
+        })
+        .unwrap();
+    let deliveries = messages
+        .iter()
+        .filter(|message| message.message.origin == origin)
+        .filter_map(|message| {
+            message
+                .delivery
+                .as_ref()
+                .map(|(delivery_slot, _)| (message.slot, *delivery_slot))
+        })
+        .take(latency.last)
+        .collect::<Vec<_>>();
+    if deliveries.is_empty() {
+        println!(
+            "No delivered messages from {} to {} indexed",
+            latency.route.origin, latency.route.destination
+        );
+        return;
+    }
+
+    let destination_client = latency
+        .destination_url
+        .as_ref()
+        .map(|url| rpc_client(url.clone(), ctx.commitment));
+    let destination_client = destination_client.as_ref().unwrap_or(&ctx.client);
+    let mut origin_block_times = BlockTimes::new(&ctx.client);
+    let mut destination_block_times = BlockTimes::new(destination_client);
+    let mut latencies = deliveries
+        .iter()
+        .filter_map(|(dispatch_slot, delivery_slot)| {
+            Some(
+                destination_block_times.get(*delivery_slot)?
+                    - origin_block_times.get(*dispatch_slot)?,
+            )
+        })
+        .collect::<Vec<_>>();
+    latencies.sort();
+
+    println!(
+        "Latency from dispatch to delivery of the last {} delivered messages from {} to {}:",
+        latencies.len(),
+        latency.route.origin,
+        latency.route.destination
+    );
+    if latencies.len() < deliveries.len() {
+        println!(
+            "\t{} messages skipped, as the block times of their slots aren't available",
+            deliveries.len() - latencies.len()
+        );
+    }
+    for percentile in [50, 90, 99] {
+        if let Some(latency) = latency_percentile(&latencies, percentile) {
+            println!("\tp{}: {}s", percentile, latency);
+        }
+    }
+}
+
+/// The block times of slots, fetched once per slot.
+struct BlockTimes<'a> {
+    client: &'a RpcClient,
+    block_times: HashMap<u64, Option<i64>>,
+}
+
+impl<'a> BlockTimes<'a> {
+    fn new(client: &'a RpcClient) -> Self {
+        Self {
+            client,
+            block_times: HashMap::new(),
+        }
+    }
+
+    fn get(&mut self, slot: u64) -> Option<i64> {
+        let client = self.client;
+        *self
+            .block_times
+            .entry(slot)
+            .or_insert_with(|| client.get_block_time(slot).ok())
+    }
+}
+
+/// The latency at the percentile of the sorted latencies.
+fn latency_percentile(latencies: &[i64], percentile: usize) -> Option<i64> {
+    let last = latencies.len().checked_sub(1)?;
+    Some(latencies[last * percentile / 100])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_route() {
+        assert_eq!(
+            "solanamainnet->1".parse(),
+            Ok(Route {
+                origin: DomainArg::Name("solanamainnet".to_owned()),
+                destination: DomainArg::Id(1),
+            })
+        );
+        assert!("solanamainnet".parse::<Route>().is_err());
+        assert!("->1".parse::<Route>().is_err());
+    }
+
+    #[test]
+    fn test_latency_percentile() {
+        let latencies = (1..=100).collect::<Vec<_>>();
+        assert_eq!(latency_percentile(&latencies, 50), Some(50));
+        assert_eq!(latency_percentile(&latencies, 99), Some(99));
+        assert_eq!(latency_percentile(&[], 50), None);
+    }
+}
@@ -0,0 +1,115 @@
+//! Where scans of a mailbox's messages, like the relayer's and the message
+//! index's, resume after a restart. The last processed nonce is recorded, and
+//! a window of nonces before it is scanned again on resuming, so that
+//! messages whose dispatch or delivery was rolled back by a reorg are picked
+//! up again.
+
+use clap::Args;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+
+use crate::{protocol::ProtocolConfig, status::find_dispatched_message_by_nonce};
+
+/// How many of the last processed nonces are scanned again by default.
+const DEFAULT_RESCAN_WINDOW: u32 = 8;
+
+#[derive(Args)]
+pub(crate) struct ResumeArgs {
+    /// Start from this nonce instead of resuming from the checkpoint.
+    #[arg(long, conflicts_with = "from_slot")]
+    pub from_nonce: Option<u32>,
+    /// Start from the first message dispatched at or after this slot instead
+    /// of resuming from the checkpoint.
+    #[arg(long)]
+    pub from_slot: Option<u64>,
+    /// How many of the last processed nonces to scan again when resuming from
+    /// the checkpoint, in case they were rolled back.
+    #[arg(long, default_value_t = DEFAULT_RESCAN_WINDOW)]
+    pub rescan_window: u32,
+}
+
+impl ResumeArgs {
+    /// Whether the start was given explicitly rather than resumed.
+    pub(crate) fn is_override(&self) -> bool {
+        self.from_nonce.is_some() || self.from_slot.is_some()
+    }
+
+    /// The nonce to start scanning at, given the last processed nonce, if
+    /// any. `nonce_at_slot` finds the first nonce dispatched at or after a
+    /// slot, and is only called if `--from-slot` is given.
+    pub(crate) fn start_nonce(
+        &self,
+        last_processed_nonce: Option<u32>,
+        nonce_at_slot: impl FnOnce(u64) -> u32,
+    ) -> u32 {
+        if let Some(nonce) = self.from_nonce {
+            return nonce;
+        }
+        if let Some(slot) = self.from_slot {
+            return nonce_at_slot(slot);
+        }
+        last_processed_nonce.map_or(0, |nonce| (nonce + 1).saturating_sub(self.rescan_window))
+    }
+}
+
+/// The nonce of the first message dispatched at or after the slot, or `count`
+/// if there's none. Nonces are dispatched in slot order, so this is a binary
+/// search over the `count` dispatched messages.
+pub(crate) fn first_nonce_at_slot(
+    client: &RpcClient,
+    protocol: &ProtocolConfig,
+    mailbox: &Pubkey,
+    commitment: CommitmentConfig,
+    strict: bool,
+    count: u32,
+    slot: u64,
+) -> u32 {
+    let (mut low, mut high) = (0, count);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        // A message that can't be found, e.g. because the RPC is lagging or
+        // the request failed, is treated as dispatched after the slot.
+        let mid_slot =
+            find_dispatched_message_by_nonce(client, protocol, mailbox, mid, commitment, strict)
+                .ok()
+                .flatten()
+                .map_or(u64::MAX, |dispatched_message| dispatched_message.slot);
+        if mid_slot < slot {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn resume_args(from_nonce: Option<u32>, from_slot: Option<u64>) -> ResumeArgs {
+        ResumeArgs {
+            from_nonce,
+            from_slot,
+            rescan_window: 8,
+        }
+    }
+
+    #[test]
+    fn test_start_nonce() {
+        fn no_slot(_: u64) -> u32 {
+            unreachable!()
+        }
+        // Fresh starts scan everything.
+        assert_eq!(resume_args(None, None).start_nonce(None, no_slot), 0);
+        // Resuming re-scans the window before the last processed nonce.
+        assert_eq!(resume_args(None, None).start_nonce(Some(20), no_slot), 13);
+        assert_eq!(resume_args(None, None).start_nonce(Some(3), no_slot), 0);
+        // Overrides ignore the checkpoint.
+        assert_eq!(resume_args(Some(5), None).start_nonce(Some(20), no_slot), 5);
+        assert_eq!(
+            resume_args(None, Some(100)).start_nonce(Some(20), |slot| slot as u32 / 10),
+            10
+        );
+    }
+}
@@ -1,23 +1,28 @@
 use hyperlane_core::{utils::hex_or_base58_to_h256, H256};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fs::File,
     path::{Path, PathBuf},
 };
 
 use solana_client::rpc_client::RpcClient;
-use solana_program::instruction::Instruction;
+use solana_program::instruction::{AccountMeta, Instruction};
 use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signer};
 
 use account_utils::DiscriminatorData;
 use hyperlane_sealevel_connection_client::router::RemoteRouterConfig;
-use hyperlane_sealevel_igp::accounts::{Igp, InterchainGasPaymasterType, OverheadIgp};
+use hyperlane_sealevel_igp::{
+    accounts::{Igp, InterchainGasPaymasterType, OverheadIgp, OverheadIgpAccount},
+    igp_gas_payment_pda_seeds, igp_program_data_pda_seeds,
+};
 
 use crate::{
-    artifacts::{write_json, HexAndBase58ProgramIdArtifact},
+    artifacts::{read_json, read_json_or_yaml, write_json, HexAndBase58ProgramIdArtifact},
     cmd_utils::{create_and_write_keypair, create_new_directory, deploy_program_idempotent},
-    read_core_program_ids, Context, CoreProgramIds,
+    read_core_program_ids,
+    rpc::rpc_client,
+    Context, CoreProgramIds,
 };
 
 /// Optional connection client configuration.
@@ -116,16 +121,33 @@ pub struct ChainMetadata {
     name: String,
     /// Collection of RPC endpoints
     rpc_urls: Vec<RpcUrlConfig>,
+    /// The base58 hash of the chain's genesis block, used to check that RPCs
+    /// serve the expected cluster
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    genesis_hash: Option<String>,
 }
 
 impl ChainMetadata {
     pub fn client(&self) -> RpcClient {
-        RpcClient::new_with_commitment(self.rpc_urls[0].http.clone(), CommitmentConfig::confirmed())
+        rpc_client(self.rpc_url().to_owned(), CommitmentConfig::confirmed())
+    }
+
+    /// The chain's first RPC URL.
+    pub fn rpc_url(&self) -> &str {
+        &self.rpc_urls[0].http
     }
 
     pub fn domain_id(&self) -> u32 {
         self.domain_id.unwrap_or(self.chain_id)
     }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn genesis_hash(&self) -> Option<&str> {
+        self.genesis_hash.as_deref()
+    }
 }
 
 pub trait RouterConfigGetter {
@@ -133,7 +155,7 @@ pub trait RouterConfigGetter {
 }
 
 pub(crate) trait RouterDeployer<Config: RouterConfigGetter + std::fmt::Debug>:
-    ConnectionClient
+    ConnectionClient + Router
 {
     #[allow(clippy::too_many_arguments)]
     fn deploy(
@@ -229,7 +251,16 @@ pub(crate) trait RouterDeployer<Config: RouterConfigGetter + std::fmt::Debug>:
     /// The program's name, i.e. the name of the program's .so file (without the .so suffix)
     /// and the name that will be used to create the keypair file
     fn program_name(&self, config: &Config) -> &str;
+}
+
+/// A program implementing the router pattern, i.e. the library's
+/// `HyperlaneRouter`, so that router commands work with any such program.
+pub(crate) trait Router {
+    /// The program-specific instruction data of a message dispatched via the
+    /// router, e.g. a token transfer.
+    type Payload;
 
+    /// Gets an instruction to enroll or unenroll remote routers.
     fn enroll_remote_routers_instruction(
         &self,
         program_id: Pubkey,
@@ -237,7 +268,69 @@ pub(crate) trait RouterDeployer<Config: RouterConfigGetter + std::fmt::Debug>:
         router_configs: Vec<RemoteRouterConfig>,
     ) -> Instruction;
 
+    /// Gets the remote routers enrolled on-chain.
     fn get_routers(&self, rpc_client: &RpcClient, program_id: &Pubkey) -> HashMap<u32, H256>;
+
+    /// Gets an instruction to dispatch a message via the program to the
+    /// router enrolled for the payload's destination, with accounts read via
+    /// `client`. The sender pays for the message and signs the instruction
+    /// along with the unique message account.
+    fn dispatch_instruction(
+        &self,
+        ctx: &Context,
+        client: &RpcClient,
+        program_id: Pubkey,
+        sender: Pubkey,
+        unique_message_account: Pubkey,
+        payload: Self::Payload,
+    ) -> Instruction;
+}
+
+/// Gets the accounts a router program requires to pay the IGP, if it has one,
+/// for a message dispatched with the unique message account: the IGP program,
+/// its program data, the gas payment PDA and the IGP account, or the overhead
+/// IGP account followed by its inner IGP account.
+pub(crate) fn igp_payment_account_metas(
+    ctx: &Context,
+    client: &RpcClient,
+    igp: Option<(Pubkey, InterchainGasPaymasterType)>,
+    unique_message_account: &Pubkey,
+) -> Vec<AccountMeta> {
+    let Some((igp_program_id, igp_account_type)) = igp else {
+        return vec![];
+    };
+    let (igp_program_data, _bump) =
+        Pubkey::find_program_address(igp_program_data_pda_seeds!(), &igp_program_id);
+    let (gas_payment_pda, _bump) = Pubkey::find_program_address(
+        igp_gas_payment_pda_seeds!(unique_message_account),
+        &igp_program_id,
+    );
+    let mut accounts = vec![
+        AccountMeta::new_readonly(igp_program_id, false),
+        AccountMeta::new(igp_program_data, false),
+        AccountMeta::new(gas_payment_pda, false),
+    ];
+    match igp_account_type {
+        InterchainGasPaymasterType::OverheadIgp(overhead_igp_account_id) => {
+            let overhead_igp_account = client
+                .get_account_with_commitment(&overhead_igp_account_id, ctx.commitment)
+                .unwrap()
+                .value
+                .unwrap();
+            let overhead_igp_account =
+                OverheadIgpAccount::fetch(&mut &overhead_igp_account.data[..])
+                    .unwrap()
+                    .into_inner();
+            accounts.extend([
+                AccountMeta::new_readonly(overhead_igp_account_id, false),
+                AccountMeta::new(overhead_igp_account.inner, false),
+            ]);
+        }
+        InterchainGasPaymasterType::Igp(igp_account_id) => {
+            accounts.push(AccountMeta::new(igp_account_id, false));
+        }
+    }
+    accounts
 }
 
 pub(crate) trait Ownable {
@@ -563,6 +656,66 @@ fn enroll_all_remote_routers<
     }
 }
 
+/// How many remote routers are enrolled per instruction, keeping the
+/// transaction within the packet size limit.
+const MAX_ROUTERS_PER_ENROLLMENT: usize = 20;
+
+/// Enrolls the routers in the file, a JSON or, if its extension is `yaml` or
+/// `yml`, YAML map of domains to routers or null to unenroll, skipping those
+/// already enrolled.
+pub(crate) fn enroll_remote_routers_from_file(
+    ctx: &Context,
+    router: &impl Router,
+    program_id: &Pubkey,
+    file: &Path,
+) {
+    let routers: BTreeMap<u32, Option<H256>> = read_json_or_yaml(file);
+    let enrolled = router.get_routers(&ctx.client, program_id);
+    let router_configs = routers
+        .into_iter()
+        .filter(|(domain, router)| enrolled.get(domain) != router.as_ref())
+        .map(|(domain, router)| RemoteRouterConfig { domain, router })
+        .collect::<Vec<_>>();
+    if router_configs.is_empty() {
+        println!("All routers are already enrolled");
+        return;
+    }
+    for router_configs in router_configs.chunks(MAX_ROUTERS_PER_ENROLLMENT) {
+        let domains = router_configs
+            .iter()
+            .map(|config| config.domain.to_string())
+            .collect::<Vec<_>>();
+        ctx.new_txn()
+            .add_with_description(
+                router.enroll_remote_routers_instruction(
+                    *program_id,
+                    ctx.payer_pubkey,
+                    router_configs.to_vec(),
+                ),
+                format!("Enroll remote routers for domains {}", domains.join(", ")),
+            )
+            .send_with_payer();
+    }
+}
+
+/// Writes the enrolled routers as a JSON map of domains to routers, to the
+/// file if given, otherwise to stdout.
+pub(crate) fn export_remote_routers(
+    ctx: &Context,
+    router: &impl Router,
+    program_id: &Pubkey,
+    file: Option<&Path>,
+) {
+    let routers = router
+        .get_routers(&ctx.client, program_id)
+        .into_iter()
+        .collect::<BTreeMap<_, _>>();
+    match file {
+        Some(file) => write_json(file, routers),
+        None => println!("{}", serde_json::to_string_pretty(&routers).unwrap()),
+    }
+}
+
 // Writes router program IDs as hex and base58.
 fn write_router_program_ids(deploy_dir: &Path, routers: &HashMap<String, H256>) {
     let serialized_program_ids = routers
@@ -574,7 +727,7 @@ fn write_router_program_ids(deploy_dir: &Path, routers: &HashMap<String, H256>)
     write_json(&program_ids_file, serialized_program_ids);
 }
 
-fn read_router_program_ids(deploy_dir: &Path) -> Option<HashMap<String, Pubkey>> {
+pub(crate) fn read_router_program_ids(deploy_dir: &Path) -> Option<HashMap<String, Pubkey>> {
     let program_ids_file = deploy_dir.join("program-ids.json");
 
     if !program_ids_file.exists() {
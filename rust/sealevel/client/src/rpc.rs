@@ -0,0 +1,115 @@
+//! Construction of RPC clients. Requests are sent through a sender that logs
+//! each of them as a tracing event, so that `-vv` shows every RPC request,
+//! including confirmation polling, and its outcome. The client of the chain
+//! the CLI is connected to can also fail over to fallback RPCs, limit its
+//! rate of requests and record them, or replay a recorded session instead.
+
+use std::path::Path;
+
+use async_trait::async_trait;
+use serde_json::Value;
+use solana_client::{
+    client_error::Result as ClientResult,
+    http_sender::HttpSender,
+    rpc_client::{RpcClient, RpcClientConfig},
+    rpc_request::RpcRequest,
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use solana_sdk::commitment_config::CommitmentConfig;
+use tracing::{debug, debug_span, Instrument as _};
+
+use crate::{
+    failover::FailoverClient,
+    rate_limit::RateLimited,
+    rpc_fixture::{RecordingSender, ReplaySender},
+};
+
+struct TracingSender {
+    inner: HttpSender,
+}
+
+#[async_trait]
+impl RpcSender for TracingSender {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        async {
+            debug!(%params, "Request");
+            let result = self.inner.send(request, params).await;
+            match &result {
+                Ok(_) => debug!("Response received"),
+                Err(err) => debug!(%err, "Request failed"),
+            }
+            result
+        }
+        .instrument(debug_span!("rpc", method = %request))
+        .await
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.inner.get_transport_stats()
+    }
+
+    fn url(&self) -> String {
+        self.inner.url()
+    }
+}
+
+/// Creates a client for the RPC at `url` that logs its requests.
+pub(crate) fn rpc_client(url: String, commitment: CommitmentConfig) -> RpcClient {
+    RpcClient::new_sender(
+        TracingSender {
+            inner: HttpSender::new(url),
+        },
+        RpcClientConfig::with_commitment(commitment),
+    )
+}
+
+/// Like `rpc_client`, but failing over from the first of the RPCs at `urls`
+/// to the others, limiting requests to the rate if given, and recording them
+/// to a fixture if given.
+pub(crate) fn failover_rpc_client(
+    urls: Vec<String>,
+    commitment: CommitmentConfig,
+    requests_per_second: Option<f64>,
+    record: Option<&Path>,
+) -> RpcClient {
+    let failover = FailoverClient::new(
+        urls.into_iter()
+            .map(|url| TracingSender {
+                inner: HttpSender::new(url),
+            })
+            .collect(),
+    );
+    match requests_per_second {
+        Some(requests_per_second) => recording_rpc_client(
+            RateLimited::new(failover, requests_per_second),
+            commitment,
+            record,
+        ),
+        None => recording_rpc_client(failover, commitment, record),
+    }
+}
+
+fn recording_rpc_client(
+    sender: impl RpcSender + Send + Sync + 'static,
+    commitment: CommitmentConfig,
+    record: Option<&Path>,
+) -> RpcClient {
+    let config = RpcClientConfig::with_commitment(commitment);
+    match record {
+        Some(path) => RpcClient::new_sender(RecordingSender::new(sender, path), config),
+        None => RpcClient::new_sender(sender, config),
+    }
+}
+
+/// Creates a client replaying the RPC session recorded in the fixture at
+/// `path`, posing as the RPC at `url`.
+pub(crate) fn replay_rpc_client(
+    path: &Path,
+    url: String,
+    commitment: CommitmentConfig,
+) -> RpcClient {
+    RpcClient::new_sender(
+        ReplaySender::load(path, url),
+        RpcClientConfig::with_commitment(commitment),
+    )
+}
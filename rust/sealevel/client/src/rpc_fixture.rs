@@ -0,0 +1,250 @@
+//! Recording of RPC sessions to fixtures, and their replay. A command run with
+//! `--record-rpc` writes every RPC request and its response to a fixture, one
+//! JSON line per request. Run again with `--replay-rpc`, the command gets the
+//! recorded responses instead of talking to an RPC, so it can be regression
+//! tested quickly and deterministically without a validator.
+
+use std::{
+    fs::File,
+    io::{BufRead as _, BufReader, Write as _},
+    path::Path,
+    sync::Mutex,
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind, Result as ClientResult},
+    rpc_request::{RpcError, RpcRequest, RpcResponseErrorData},
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+
+/// A recorded request and its outcome.
+#[derive(Serialize, Deserialize)]
+struct Exchange {
+    method: String,
+    params: Value,
+    #[serde(flatten)]
+    outcome: Outcome,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum Outcome {
+    Result(Value),
+    /// A JSON-RPC error response.
+    RpcError {
+        code: i64,
+        message: String,
+    },
+    /// Any other failure, e.g. a connection error.
+    Error(String),
+}
+
+impl Outcome {
+    fn new(result: &ClientResult<Value>) -> Self {
+        match result {
+            Ok(value) => Self::Result(value.clone()),
+            Err(err) => match err.kind() {
+                ClientErrorKind::RpcError(RpcError::RpcResponseError { code, message, .. }) => {
+                    Self::RpcError {
+                        code: *code,
+                        message: message.clone(),
+                    }
+                }
+                _ => Self::Error(err.to_string()),
+            },
+        }
+    }
+
+    fn into_result(self) -> ClientResult<Value> {
+        match self {
+            Self::Result(value) => Ok(value),
+            Self::RpcError { code, message } => Err(RpcError::RpcResponseError {
+                code,
+                message,
+                data: RpcResponseErrorData::Empty,
+            }
+            .into()),
+            Self::Error(message) => Err(ClientErrorKind::Custom(message).into()),
+        }
+    }
+}
+
+/// Records the requests made through the wrapped sender to a fixture.
+pub(crate) struct RecordingSender<T> {
+    inner: T,
+    fixture: Mutex<File>,
+}
+
+impl<T> RecordingSender<T> {
+    /// Records to the fixture at `path`, replacing it if it exists.
+    pub(crate) fn new(inner: T, path: &Path) -> Self {
+        let fixture = File::create(path)
+            .unwrap_or_else(|e| panic!("Failed to create RPC fixture {}: {}", path.display(), e));
+        Self {
+            inner,
+            fixture: Mutex::new(fixture),
+        }
+    }
+}
+
+#[async_trait]
+impl<T: RpcSender + Send + Sync + 'static> RpcSender for RecordingSender<T> {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        let result = self.inner.send(request, params.clone()).await;
+        let exchange = Exchange {
+            method: request.to_string(),
+            params,
+            outcome: Outcome::new(&result),
+        };
+        let mut fixture = self.fixture.lock().unwrap();
+        writeln!(fixture, "{}", serde_json::to_string(&exchange).unwrap())
+            .expect("Failed to write RPC fixture");
+        result
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.inner.get_transport_stats()
+    }
+
+    fn url(&self) -> String {
+        self.inner.url()
+    }
+}
+
+/// Answers requests with the responses recorded in a fixture. A request gets
+/// the response to the first unanswered recorded request with the same method
+/// and params, or failing that, with the same method, since params like
+/// freshly generated keys can differ between runs.
+pub(crate) struct ReplaySender {
+    url: String,
+    exchanges: Mutex<Vec<Option<Exchange>>>,
+}
+
+impl ReplaySender {
+    /// Replays the fixture at `path`, posing as the RPC at `url`.
+    pub(crate) fn load(path: &Path, url: String) -> Self {
+        let file = File::open(path)
+            .unwrap_or_else(|e| panic!("Failed to open RPC fixture {}: {}", path.display(), e));
+        let exchanges = BufReader::new(file)
+            .lines()
+            .map(|line| {
+                let line = line.expect("Failed to read RPC fixture");
+                Some(serde_json::from_str(&line).expect("Invalid RPC fixture"))
+            })
+            .collect();
+        Self {
+            url,
+            exchanges: Mutex::new(exchanges),
+        }
+    }
+
+    fn answer(&self, method: &str, params: &Value) -> Option<Exchange> {
+        let mut exchanges = self.exchanges.lock().unwrap();
+        let index = exchanges
+            .iter()
+            .position(|exchange| {
+                exchange.as_ref().map_or(false, |exchange| {
+                    exchange.method == method && exchange.params == *params
+                })
+            })
+            .or_else(|| {
+                exchanges.iter().position(|exchange| {
+                    exchange
+                        .as_ref()
+                        .map_or(false, |exchange| exchange.method == method)
+                })
+            })?;
+        exchanges[index].take()
+    }
+}
+
+#[async_trait]
+impl RpcSender for ReplaySender {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        let method = request.to_string();
+        match self.answer(&method, &params) {
+            Some(exchange) => exchange.outcome.into_result(),
+            None => Err(ClientError::from(ClientErrorKind::Custom(format!(
+                "No recorded response left for {} {}",
+                method, params
+            )))),
+        }
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        RpcTransportStats::default()
+    }
+
+    fn url(&self) -> String {
+        self.url.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::{self, OpenOptions};
+
+    use serde_json::json;
+    use solana_client::rpc_client::{RpcClient, RpcClientConfig};
+    use solana_sdk::{commitment_config::CommitmentConfig, system_program};
+
+    use super::*;
+
+    fn append_exchange(path: &Path, method: &str, params: Value, outcome: Outcome) {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        let exchange = Exchange {
+            method: method.to_owned(),
+            params,
+            outcome,
+        };
+        writeln!(file, "{}", serde_json::to_string(&exchange).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_replay() {
+        let path = std::env::temp_dir().join(format!("rpc-fixture-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        // The client checks the node's version before its first request.
+        append_exchange(
+            &path,
+            "getVersion",
+            json!(null),
+            Outcome::Result(json!({ "solana-core": "1.14.13", "feature-set": 0 })),
+        );
+        let commitment = json!([{ "commitment": "processed" }]);
+        append_exchange(
+            &path,
+            "getSlot",
+            commitment.clone(),
+            Outcome::Result(json!(42)),
+        );
+        append_exchange(&path, "getSlot", commitment, Outcome::Result(json!(43)));
+        append_exchange(
+            &path,
+            "getBalance",
+            json!(["11111111111111111111111111111111"]),
+            Outcome::RpcError {
+                code: -32602,
+                message: "Invalid param".to_owned(),
+            },
+        );
+
+        let client = RpcClient::new_sender(
+            ReplaySender::load(&path, "http://localhost:8899".to_owned()),
+            RpcClientConfig::with_commitment(CommitmentConfig::processed()),
+        );
+        fs::remove_file(&path).unwrap();
+        // Recorded responses are replayed in order.
+        assert_eq!(client.get_slot().unwrap(), 42);
+        assert_eq!(client.get_slot().unwrap(), 43);
+        assert!(client.get_slot().is_err());
+        assert!(client.get_balance(&system_program::id()).is_err());
+    }
+}
@@ -0,0 +1,669 @@
+//! Machine readable descriptions of the instructions of the Hyperlane
+//! programs, in the shape of Anchor IDLs, for external tooling like SDKs in
+//! other languages and explorers to encode and decode them like the Rust
+//! builders do.
+//!
+//! The programs aren't Anchor programs: an instruction is a Borsh enum, whose
+//! variant is identified by its `u8` index rather than by a sighash, and
+//! which some programs prefix with a constant 8 byte discriminator. Each
+//! instruction's `discriminant` and the IDL's `metadata.instructionPrefix`
+//! describe that encoding.
+
+use clap::ValueEnum as _;
+use serde_json::{json, Value};
+
+use account_utils::PROGRAM_INSTRUCTION_DISCRIMINATOR;
+
+use crate::{Schema, SchemaProgram};
+
+fn account(name: &str, is_mut: bool, is_signer: bool) -> Value {
+    json!({ "name": name, "isMut": is_mut, "isSigner": is_signer })
+}
+
+fn optional_account(name: &str, is_mut: bool, is_signer: bool) -> Value {
+    json!({ "name": name, "isMut": is_mut, "isSigner": is_signer, "isOptional": true })
+}
+
+fn arg(name: &str, ty: Value) -> Value {
+    json!({ "name": name, "type": ty })
+}
+
+fn h256() -> Value {
+    json!({ "array": ["u8", 32] })
+}
+
+fn h160() -> Value {
+    json!({ "array": ["u8", 20] })
+}
+
+fn defined(name: &str) -> Value {
+    json!({ "defined": name })
+}
+
+fn struct_type(name: &str, fields: Vec<Value>) -> Value {
+    json!({ "name": name, "type": { "kind": "struct", "fields": fields } })
+}
+
+/// An enum type whose variants each hold a single value of the given type.
+fn enum_type(name: &str, variants: &[(&str, Value)]) -> Value {
+    let variants = variants
+        .iter()
+        .map(|(name, ty)| json!({ "name": name, "fields": [ty] }))
+        .collect::<Vec<_>>();
+    json!({ "name": name, "type": { "kind": "enum", "variants": variants } })
+}
+
+/// The IDL of a program, whose instructions are given in the order of the
+/// variants of its instruction enum, each with its accounts, arguments and
+/// documentation.
+fn idl(
+    name: &str,
+    instruction_prefix: Option<&[u8]>,
+    instructions: Vec<(&str, Vec<Value>, Vec<Value>, &str)>,
+    types: Vec<Value>,
+) -> Value {
+    let instructions = instructions
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, accounts, args, docs))| {
+            json!({
+                "name": name,
+                "docs": [docs],
+                "discriminant": { "type": "u8", "value": index },
+                "accounts": accounts,
+                "args": args,
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "name": name,
+        "instructions": instructions,
+        "types": types,
+        "metadata": {
+            "encoding": "borsh",
+            "instructionPrefix": instruction_prefix,
+        },
+    })
+}
+
+fn mailbox_idl() -> Value {
+    idl(
+        "hyperlane_sealevel_mailbox",
+        None,
+        vec![
+            (
+                "init",
+                vec![
+                    account("systemProgram", false, false),
+                    account("payer", true, true),
+                    account("inbox", true, false),
+                    account("outbox", true, false),
+                ],
+                vec![
+                    arg("localDomain", json!("u32")),
+                    arg("defaultIsm", json!("publicKey")),
+                ],
+                "Initializes the mailbox.",
+            ),
+            (
+                "inboxProcess",
+                vec![
+                    account("payer", true, true),
+                    account("systemProgram", false, false),
+                    account("inbox", true, false),
+                    account("processAuthority", false, false),
+                    account("processedMessage", true, false),
+                ],
+                vec![
+                    arg("metadata", json!("bytes")),
+                    arg("message", json!("bytes")),
+                ],
+                "Processes a message. Followed by the accounts of the recipient's ISM \
+                 instruction, the SPL noop program, the ISM, the accounts of its Verify \
+                 instruction, the recipient and the accounts of its Handle instruction.",
+            ),
+            (
+                "inboxSetDefaultIsm",
+                vec![
+                    account("inbox", true, false),
+                    account("outbox", false, false),
+                    account("owner", false, true),
+                ],
+                vec![arg("defaultIsm", json!("publicKey"))],
+                "Sets the default ISM.",
+            ),
+            (
+                "inboxGetRecipientIsm",
+                vec![
+                    account("inbox", false, false),
+                    account("recipient", false, false),
+                ],
+                vec![arg("recipient", json!("publicKey"))],
+                "Gets the ISM of a recipient as return data. Followed by the accounts of \
+                 the recipient's ISM instruction.",
+            ),
+            (
+                "outboxDispatch",
+                vec![
+                    account("outbox", true, false),
+                    account("sender", false, true),
+                    account("systemProgram", false, false),
+                    account("splNoop", false, false),
+                    account("payer", true, true),
+                    account("uniqueMessage", false, true),
+                    account("dispatchedMessage", true, false),
+                ],
+                vec![
+                    arg("sender", json!("publicKey")),
+                    arg("destinationDomain", json!("u32")),
+                    arg("recipient", h256()),
+                    arg("messageBody", json!("bytes")),
+                ],
+                "Dispatches a message.",
+            ),
+            (
+                "outboxGetCount",
+                vec![account("outbox", false, false)],
+                vec![],
+                "Gets the number of dispatched messages as return data.",
+            ),
+            (
+                "outboxGetLatestCheckpoint",
+                vec![account("outbox", false, false)],
+                vec![],
+                "Gets the latest checkpoint as return data.",
+            ),
+            (
+                "outboxGetRoot",
+                vec![account("outbox", false, false)],
+                vec![],
+                "Gets the merkle root as return data.",
+            ),
+            (
+                "getOwner",
+                vec![account("outbox", false, false)],
+                vec![],
+                "Gets the owner as return data.",
+            ),
+            (
+                "transferOwnership",
+                vec![
+                    account("outbox", true, false),
+                    account("owner", false, true),
+                ],
+                vec![arg("newOwner", json!({ "option": "publicKey" }))],
+                "Transfers ownership of the mailbox.",
+            ),
+        ],
+        vec![],
+    )
+}
+
+fn igp_idl() -> Value {
+    let owner_accounts = |igp: &str| vec![account(igp, true, false), account("owner", false, true)];
+    idl(
+        "hyperlane_sealevel_igp",
+        None,
+        vec![
+            (
+                "init",
+                vec![
+                    account("systemProgram", false, false),
+                    account("payer", true, true),
+                    account("programData", true, false),
+                ],
+                vec![],
+                "Initializes the program.",
+            ),
+            (
+                "initIgp",
+                vec![
+                    account("systemProgram", false, false),
+                    account("payer", true, true),
+                    account("igp", true, false),
+                ],
+                vec![
+                    arg("salt", h256()),
+                    arg("owner", json!({ "option": "publicKey" })),
+                    arg("beneficiary", json!("publicKey")),
+                ],
+                "Initializes an IGP.",
+            ),
+            (
+                "initOverheadIgp",
+                vec![
+                    account("systemProgram", false, false),
+                    account("payer", true, true),
+                    account("overheadIgp", true, false),
+                ],
+                vec![
+                    arg("salt", h256()),
+                    arg("owner", json!({ "option": "publicKey" })),
+                    arg("inner", json!("publicKey")),
+                ],
+                "Initializes an overhead IGP.",
+            ),
+            (
+                "payForGas",
+                vec![
+                    account("systemProgram", false, false),
+                    account("payer", true, true),
+                    account("programData", true, false),
+                    account("uniqueGasPayment", false, true),
+                    account("gasPayment", true, false),
+                    account("igp", true, false),
+                    optional_account("overheadIgp", false, false),
+                ],
+                vec![
+                    arg("messageId", h256()),
+                    arg("destinationDomain", json!("u32")),
+                    arg("gasAmount", json!("u64")),
+                ],
+                "Pays for gas.",
+            ),
+            (
+                "quoteGasPayment",
+                vec![
+                    account("systemProgram", false, false),
+                    account("igp", false, false),
+                    optional_account("overheadIgp", false, false),
+                ],
+                vec![
+                    arg("destinationDomain", json!("u32")),
+                    arg("gasAmount", json!("u64")),
+                ],
+                "Quotes a gas payment as return data.",
+            ),
+            (
+                "transferIgpOwnership",
+                owner_accounts("igp"),
+                vec![arg("newOwner", json!({ "option": "publicKey" }))],
+                "Transfers ownership of an IGP.",
+            ),
+            (
+                "transferOverheadIgpOwnership",
+                owner_accounts("overheadIgp"),
+                vec![arg("newOwner", json!({ "option": "publicKey" }))],
+                "Transfers ownership of an overhead IGP.",
+            ),
+            (
+                "setIgpBeneficiary",
+                owner_accounts("igp"),
+                vec![arg("beneficiary", json!("publicKey"))],
+                "Sets the beneficiary of an IGP.",
+            ),
+            (
+                "setDestinationGasOverheads",
+                vec![
+                    account("systemProgram", false, false),
+                    account("overheadIgp", true, false),
+                    account("owner", true, true),
+                ],
+                vec![arg(
+                    "configs",
+                    json!({ "vec": defined("GasOverheadConfig") }),
+                )],
+                "Sets destination gas overheads on an overhead IGP.",
+            ),
+            (
+                "setGasOracleConfigs",
+                vec![
+                    account("systemProgram", false, false),
+                    account("igp", true, false),
+                    account("owner", true, true),
+                ],
+                vec![arg("configs", json!({ "vec": defined("GasOracleConfig") }))],
+                "Sets gas oracles on an IGP.",
+            ),
+            (
+                "claim",
+                vec![
+                    account("systemProgram", false, false),
+                    account("igp", true, false),
+                    account("beneficiary", true, false),
+                ],
+                vec![],
+                "Claims lamports from an IGP, sending them to its beneficiary.",
+            ),
+        ],
+        vec![
+            struct_type(
+                "GasOverheadConfig",
+                vec![
+                    arg("destinationDomain", json!("u32")),
+                    arg("gasOverhead", json!({ "option": "u64" })),
+                ],
+            ),
+            struct_type(
+                "GasOracleConfig",
+                vec![
+                    arg("domain", json!("u32")),
+                    arg("gasOracle", json!({ "option": defined("GasOracle") })),
+                ],
+            ),
+            enum_type("GasOracle", &[("RemoteGasData", defined("RemoteGasData"))]),
+            struct_type(
+                "RemoteGasData",
+                vec![
+                    arg("tokenExchangeRate", json!("u128")),
+                    arg("gasPrice", json!("u128")),
+                    arg("tokenDecimals", json!("u8")),
+                ],
+            ),
+        ],
+    )
+}
+
+fn multisig_ism_message_id_idl() -> Value {
+    idl(
+        "hyperlane_sealevel_multisig_ism_message_id",
+        Some(&PROGRAM_INSTRUCTION_DISCRIMINATOR[..]),
+        vec![
+            (
+                "initialize",
+                vec![
+                    account("owner", true, true),
+                    account("accessControl", true, false),
+                    account("systemProgram", false, false),
+                ],
+                vec![],
+                "Initializes the program.",
+            ),
+            (
+                "setValidatorsAndThreshold",
+                vec![
+                    account("owner", true, true),
+                    account("accessControl", false, false),
+                    account("domainData", true, false),
+                    account("systemProgram", false, false),
+                ],
+                vec![
+                    arg("domain", json!("u32")),
+                    arg("data", defined("ValidatorsAndThreshold")),
+                ],
+                "Sets the validators and threshold of an origin domain.",
+            ),
+            (
+                "getOwner",
+                vec![account("accessControl", false, false)],
+                vec![],
+                "Gets the owner as return data.",
+            ),
+            (
+                "transferOwnership",
+                vec![
+                    account("owner", true, true),
+                    account("accessControl", true, false),
+                ],
+                vec![arg("newOwner", json!({ "option": "publicKey" }))],
+                "Transfers ownership of the ISM.",
+            ),
+        ],
+        vec![struct_type(
+            "ValidatorsAndThreshold",
+            vec![
+                arg("validators", json!({ "vec": h160() })),
+                arg("threshold", json!("u8")),
+            ],
+        )],
+    )
+}
+
+fn validator_announce_idl() -> Value {
+    idl(
+        "hyperlane_sealevel_validator_announce",
+        None,
+        vec![
+            (
+                "init",
+                vec![
+                    account("payer", true, true),
+                    account("systemProgram", false, false),
+                    account("validatorAnnounce", true, false),
+                ],
+                vec![
+                    arg("mailbox", json!("publicKey")),
+                    arg("localDomain", json!("u32")),
+                ],
+                "Initializes the program.",
+            ),
+            (
+                "announce",
+                vec![
+                    account("payer", true, true),
+                    account("systemProgram", false, false),
+                    account("validatorAnnounce", false, false),
+                    account("validatorStorageLocations", true, false),
+                    account("replayProtection", true, false),
+                ],
+                vec![
+                    arg("validator", h160()),
+                    arg("storageLocation", json!("string")),
+                    arg("signature", json!("bytes")),
+                ],
+                "Announces a validator's storage location.",
+            ),
+        ],
+        vec![],
+    )
+}
+
+/// The instructions shared by the warp route programs, whichever their kind
+/// of token. Each kind of token adds its own accounts after those listed.
+#[cfg(feature = "tokens")]
+fn token_idl() -> Value {
+    let owner_accounts = || {
+        vec![
+            account("systemProgram", false, false),
+            account("token", true, false),
+            account("owner", true, true),
+        ]
+    };
+    idl(
+        "hyperlane_sealevel_token",
+        Some(&PROGRAM_INSTRUCTION_DISCRIMINATOR[..]),
+        vec![
+            (
+                "init",
+                vec![
+                    account("systemProgram", false, false),
+                    account("token", true, false),
+                    account("dispatchAuthority", true, false),
+                    account("payer", true, true),
+                ],
+                vec![
+                    arg("mailbox", json!("publicKey")),
+                    arg("interchainSecurityModule", json!({ "option": "publicKey" })),
+                    arg(
+                        "interchainGasPaymaster",
+                        json!({ "option": defined("InterchainGasPaymaster") }),
+                    ),
+                    arg("decimals", json!("u8")),
+                    arg("remoteDecimals", json!("u8")),
+                ],
+                "Initializes the program. Followed by the accounts of the kind of token.",
+            ),
+            (
+                "transferRemote",
+                vec![
+                    account("systemProgram", false, false),
+                    account("splNoop", false, false),
+                    account("token", false, false),
+                    account("mailbox", false, false),
+                    account("outbox", true, false),
+                    account("dispatchAuthority", false, false),
+                    account("sender", true, true),
+                    account("uniqueMessage", false, true),
+                    account("dispatchedMessage", true, false),
+                ],
+                vec![
+                    arg("destinationDomain", json!("u32")),
+                    arg("recipient", h256()),
+                    arg("amountOrId", json!("u256")),
+                ],
+                "Transfers tokens to a remote recipient. Followed, with an IGP, by the IGP \
+                 program, its program data, the gas payment PDA, the overhead IGP if any \
+                 and the IGP, then by the accounts of the kind of token.",
+            ),
+            (
+                "enrollRemoteRouter",
+                owner_accounts(),
+                vec![arg("config", defined("RemoteRouterConfig"))],
+                "Enrolls a remote router.",
+            ),
+            (
+                "enrollRemoteRouters",
+                owner_accounts(),
+                vec![arg(
+                    "configs",
+                    json!({ "vec": defined("RemoteRouterConfig") }),
+                )],
+                "Enrolls remote routers.",
+            ),
+            (
+                "setDestinationGasConfigs",
+                owner_accounts(),
+                vec![arg("configs", json!({ "vec": defined("GasRouterConfig") }))],
+                "Sets the gas amounts to pay for on destination domains.",
+            ),
+            (
+                "setInterchainSecurityModule",
+                vec![account("token", true, false), account("owner", false, true)],
+                vec![arg("ism", json!({ "option": "publicKey" }))],
+                "Sets the ISM.",
+            ),
+            (
+                "setInterchainGasPaymaster",
+                vec![account("token", true, false), account("owner", false, true)],
+                vec![arg(
+                    "igp",
+                    json!({ "option": defined("InterchainGasPaymaster") }),
+                )],
+                "Sets the IGP program and account.",
+            ),
+            (
+                "transferOwnership",
+                vec![account("token", true, false), account("owner", false, true)],
+                vec![arg("newOwner", json!({ "option": "publicKey" }))],
+                "Transfers ownership of the program.",
+            ),
+        ],
+        vec![
+            // A `(Pubkey, InterchainGasPaymasterType)` tuple, which Borsh
+            // encodes like a struct.
+            struct_type(
+                "InterchainGasPaymaster",
+                vec![
+                    arg("program", json!("publicKey")),
+                    arg("type", defined("InterchainGasPaymasterType")),
+                ],
+            ),
+            enum_type(
+                "InterchainGasPaymasterType",
+                &[
+                    ("Igp", json!("publicKey")),
+                    ("OverheadIgp", json!("publicKey")),
+                ],
+            ),
+            struct_type(
+                "RemoteRouterConfig",
+                vec![
+                    arg("domain", json!("u32")),
+                    arg("router", json!({ "option": h256() })),
+                ],
+            ),
+            struct_type(
+                "GasRouterConfig",
+                vec![
+                    arg("domain", json!("u32")),
+                    arg("gas", json!({ "option": "u64" })),
+                ],
+            ),
+        ],
+    )
+}
+
+fn program_idl(program: SchemaProgram) -> Value {
+    match program {
+        SchemaProgram::Mailbox => mailbox_idl(),
+        SchemaProgram::Igp => igp_idl(),
+        SchemaProgram::MultisigIsmMessageId => multisig_ism_message_id_idl(),
+        SchemaProgram::ValidatorAnnounce => validator_announce_idl(),
+        #[cfg(feature = "tokens")]
+        SchemaProgram::Token => token_idl(),
+    }
+}
+
+/// Prints the IDL of the given program, or an array of the IDLs of all
+/// programs.
+pub(crate) fn print_schema(schema: Schema) {
+    let value = match schema.program {
+        Some(program) => program_idl(program),
+        None => Value::Array(
+            SchemaProgram::value_variants()
+                .iter()
+                .map(|program| program_idl(*program))
+                .collect(),
+        ),
+    };
+    println!("{}", serde_json::to_string_pretty(&value).unwrap());
+}
+
+#[cfg(test)]
+mod test {
+    use borsh::BorshSerialize as _;
+    use hyperlane_core::H256;
+    use solana_program::pubkey::Pubkey;
+
+    use hyperlane_sealevel_igp::instruction::{Instruction as IgpInstruction, PayForGas};
+    use hyperlane_sealevel_mailbox::instruction::{
+        Instruction as MailboxInstruction, OutboxDispatch,
+    };
+
+    use super::*;
+
+    fn discriminant(idl: &Value, name: &str) -> u64 {
+        idl["instructions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|instruction| instruction["name"] == name)
+            .unwrap_or_else(|| panic!("No instruction {}", name))["discriminant"]["value"]
+            .as_u64()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_discriminants_match_builders() {
+        let dispatch = MailboxInstruction::OutboxDispatch(OutboxDispatch {
+            sender: Pubkey::new_unique(),
+            destination_domain: 1234,
+            recipient: H256::random(),
+            message_body: vec![],
+        })
+        .into_instruction_data()
+        .unwrap();
+        assert_eq!(
+            discriminant(&mailbox_idl(), "outboxDispatch"),
+            dispatch[0] as u64
+        );
+
+        let transfer_ownership = MailboxInstruction::TransferOwnership(None)
+            .into_instruction_data()
+            .unwrap();
+        assert_eq!(
+            discriminant(&mailbox_idl(), "transferOwnership"),
+            transfer_ownership[0] as u64
+        );
+
+        let pay_for_gas = IgpInstruction::PayForGas(PayForGas {
+            message_id: H256::random(),
+            destination_domain: 1234,
+            gas_amount: 100,
+        })
+        .try_to_vec()
+        .unwrap();
+        assert_eq!(discriminant(&igp_idl(), "payForGas"), pay_for_gas[0] as u64);
+    }
+}
@@ -0,0 +1,220 @@
+//! Updating the client binary from a release feed.
+//!
+//! The release feed is a JSON document of the form:
+//!
+//! ```json
+//! {
+//!   "version": "0.2.0",
+//!   "artifacts": {
+//!     "x86_64-unknown-linux-gnu": {
+//!       "url": "https://...",
+//!       "sha256": "<hex>",
+//!       "signature": "<hex ed25519 signature of the artifact's manifest>"
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! The signed manifest of an artifact binds its checksum to the version and
+//! target triple, so that a mirror can't serve a validly signed binary as
+//! another version, e.g. an older one with known bugs:
+//!
+//! ```text
+//! hyperlane-sealevel-client
+//! version: 0.2.0
+//! target: x86_64-unknown-linux-gnu
+//! sha256: <lowercase hex>
+//! ```
+//!
+//! Manifests are verified against the release key compiled into the binary,
+//! from `HYPERLANE_RELEASE_PUBLIC_KEY` at build time, so that whoever picks the
+//! feed can't also pick the key its artifacts are trusted with.
+
+use std::{
+    collections::HashMap, env, fs, io::Read as _, os::unix::fs::PermissionsExt as _, path::Path,
+};
+
+use ed25519_dalek::{PublicKey, Signature, Verifier as _};
+use semver::Version;
+use serde::Deserialize;
+use sha2::{Digest as _, Sha256};
+
+use crate::{exit_code, SelfUpdate};
+
+/// The exit code used by `--check-only` when an update is available.
+const UPDATE_AVAILABLE_EXIT_CODE: i32 = 10;
+
+/// The target triple the client was built for.
+const TARGET: &str = env!("TARGET");
+
+/// The hex-encoded ed25519 public key release artifacts are signed with, if
+/// the client was built with one.
+const RELEASE_PUBLIC_KEY: Option<&str> = option_env!("HYPERLANE_RELEASE_PUBLIC_KEY");
+
+#[derive(Debug, Deserialize)]
+struct ReleaseFeed {
+    version: Version,
+    artifacts: HashMap<String, ReleaseArtifact>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReleaseArtifact {
+    url: String,
+    sha256: String,
+    signature: String,
+}
+
+pub(crate) fn process_self_update(self_update: SelfUpdate) {
+    let public_key = self_update
+        .public_key
+        .as_deref()
+        .or(RELEASE_PUBLIC_KEY)
+        .unwrap_or_else(|| {
+            exit_code::config_error(
+                "This build has no release key compiled in, pass the key to verify releases \
+                 against with --public-key",
+            )
+        });
+    let current_version = Version::parse(env!("CARGO_PKG_VERSION")).unwrap();
+    let feed: ReleaseFeed = ureq::get(&self_update.feed_url)
+        .call()
+        .expect("Failed to fetch release feed")
+        .into_json()
+        .expect("Invalid release feed");
+
+    let artifact = feed
+        .artifacts
+        .get(TARGET)
+        .unwrap_or_else(|| panic!("No release artifact for target {}", TARGET));
+    // The feed's version is only trusted once the manifest is verified.
+    verify_manifest(public_key, &feed.version, TARGET, artifact);
+    if feed.version < current_version {
+        println!(
+            "Refusing to downgrade from version {} to {}",
+            current_version, feed.version
+        );
+        return;
+    }
+    if feed.version == current_version {
+        println!("Already up to date (version {})", current_version);
+        return;
+    }
+    println!("Update available: {} -> {}", current_version, feed.version);
+    if self_update.check_only {
+        std::process::exit(UPDATE_AVAILABLE_EXIT_CODE);
+    }
+
+    let mut binary = vec![];
+    ureq::get(&artifact.url)
+        .call()
+        .expect("Failed to download release artifact")
+        .into_reader()
+        .read_to_end(&mut binary)
+        .unwrap();
+    verify_checksum(artifact, &binary);
+
+    let current_exe = env::current_exe().unwrap();
+    replace_binary(&current_exe, &binary);
+    println!(
+        "Updated {} to version {}",
+        current_exe.display(),
+        feed.version
+    );
+}
+
+/// The manifest the release key signs for an artifact.
+fn manifest(version: &Version, target: &str, sha256: &str) -> String {
+    format!(
+        "hyperlane-sealevel-client\nversion: {}\ntarget: {}\nsha256: {}\n",
+        version,
+        target,
+        sha256.to_lowercase()
+    )
+}
+
+/// Verifies that the artifact's manifest, for the version and target, was
+/// signed by the release key.
+fn verify_manifest(public_key: &str, version: &Version, target: &str, artifact: &ReleaseArtifact) {
+    let public_key = PublicKey::from_bytes(&hex::decode(public_key).expect("Invalid public key"))
+        .expect("Invalid public key");
+    let signature = Signature::try_from(
+        &hex::decode(&artifact.signature).expect("Invalid artifact signature")[..],
+    )
+    .expect("Invalid artifact signature");
+    public_key
+        .verify(
+            manifest(version, target, &artifact.sha256).as_bytes(),
+            &signature,
+        )
+        .expect("Artifact signature verification failed");
+}
+
+/// Verifies the downloaded binary against the checksum in the verified
+/// manifest.
+fn verify_checksum(artifact: &ReleaseArtifact, binary: &[u8]) {
+    let digest = Sha256::digest(binary);
+    let expected_digest = hex::decode(&artifact.sha256).expect("Invalid artifact checksum");
+    if digest.as_slice() != expected_digest.as_slice() {
+        panic!(
+            "Checksum mismatch: expected {}, got {}",
+            artifact.sha256,
+            hex::encode(digest)
+        );
+    }
+}
+
+/// Writes the new binary next to the current one and renames it into place so
+/// the swap is atomic. Only unix allows renaming over a running binary.
+fn replace_binary(current_exe: &Path, binary: &[u8]) {
+    let staged = current_exe.with_extension("update");
+    fs::write(&staged, binary).unwrap();
+    fs::set_permissions(&staged, fs::Permissions::from_mode(0o755)).unwrap();
+    fs::rename(&staged, current_exe).unwrap();
+}
+
+#[cfg(test)]
+mod test {
+    use ed25519_dalek::{Keypair, SecretKey, Signer as _};
+
+    use super::*;
+
+    fn keypair() -> Keypair {
+        let secret = SecretKey::from_bytes(&[7; 32]).unwrap();
+        Keypair {
+            public: (&secret).into(),
+            secret,
+        }
+    }
+
+    fn signed_artifact(keypair: &Keypair, version: &str, target: &str) -> ReleaseArtifact {
+        let sha256 = hex::encode(Sha256::digest(b"binary"));
+        let signature =
+            keypair.sign(manifest(&version.parse().unwrap(), target, &sha256).as_bytes());
+        ReleaseArtifact {
+            url: String::new(),
+            sha256,
+            signature: hex::encode(signature.to_bytes()),
+        }
+    }
+
+    #[test]
+    fn test_verify_manifest() {
+        let keypair = keypair();
+        let public_key = hex::encode(keypair.public.as_bytes());
+        let artifact = signed_artifact(&keypair, "0.2.0", TARGET);
+
+        verify_manifest(&public_key, &"0.2.0".parse().unwrap(), TARGET, &artifact);
+        verify_checksum(&artifact, b"binary");
+    }
+
+    #[test]
+    #[should_panic(expected = "Artifact signature verification failed")]
+    fn test_verify_manifest_of_other_version() {
+        let keypair = keypair();
+        let public_key = hex::encode(keypair.public.as_bytes());
+        // An old release served as the latest one.
+        let artifact = signed_artifact(&keypair, "0.1.0", TARGET);
+
+        verify_manifest(&public_key, &"0.2.0".parse().unwrap(), TARGET, &artifact);
+    }
+}
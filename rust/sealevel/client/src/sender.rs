@@ -0,0 +1,604 @@
+//! Submission of transactions: sending a transaction and waiting for it to
+//! reach a confirmation level, and concurrent submission of many
+//! transactions.
+
+use std::{
+    fmt,
+    io::{self, Write as _},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use clap::ValueEnum;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_client::RpcClient,
+    rpc_config::RpcSendTransactionConfig,
+    tpu_client::{TpuClient, TpuClientConfig},
+};
+use solana_sdk::{
+    account::Account,
+    commitment_config::{CommitmentConfig, CommitmentLevel},
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::Transaction,
+};
+use tracing::debug;
+
+use crate::{
+    accounts::{AccountError, AccountReader},
+    blockhash::CachedBlockhash,
+    protocol::ProtocolConfig,
+    rpc::rpc_client,
+};
+
+/// How often the status of an in-flight transaction is polled.
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// How long to wait for a transaction to be confirmed by default. Blockhashes
+/// are valid for about a minute, so a transaction that isn't confirmed by
+/// then won't be.
+const DEFAULT_CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// The commitment level a sent transaction is waited for.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ConfirmationLevel {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+impl From<ConfirmationLevel> for CommitmentConfig {
+    fn from(level: ConfirmationLevel) -> Self {
+        match level {
+            ConfirmationLevel::Processed => CommitmentConfig::processed(),
+            ConfirmationLevel::Confirmed => CommitmentConfig::confirmed(),
+            ConfirmationLevel::Finalized => CommitmentConfig::finalized(),
+        }
+    }
+}
+
+/// When a sent transaction counts as confirmed: once it reaches the
+/// commitment level, polling its status at the poll interval, or failed once
+/// the timeout passes. A transaction confirmed at one level can be waited for
+/// at a higher one later, e.g. to return at `processed` and only later await
+/// `finalized`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ConfirmationStrategy {
+    pub commitment: CommitmentConfig,
+    pub timeout: Duration,
+    pub poll_interval: Duration,
+}
+
+impl ConfirmationStrategy {
+    pub(crate) fn new(commitment: CommitmentConfig) -> Self {
+        Self {
+            commitment,
+            timeout: DEFAULT_CONFIRMATION_TIMEOUT,
+            poll_interval: CONFIRMATION_POLL_INTERVAL,
+        }
+    }
+
+    /// The same strategy waiting for a different commitment level.
+    pub(crate) fn with_commitment(self, commitment: CommitmentConfig) -> Self {
+        Self { commitment, ..self }
+    }
+}
+
+/// Sends signed transactions and waits for their confirmation. Senders are
+/// shared between threads, e.g. to have many transactions in flight at once.
+pub(crate) trait TransactionSender: Send + Sync {
+    /// Sends the transaction without waiting for it to be confirmed.
+    fn send(&self, txn: &Transaction) -> Result<Signature, ClientError>;
+
+    /// Waits for the sent transaction to be confirmed as the strategy
+    /// requires, failing if the transaction failed or the wait timed out.
+    fn confirm(
+        &self,
+        signature: &Signature,
+        strategy: &ConfirmationStrategy,
+    ) -> Result<(), ClientError>;
+
+    fn send_and_confirm(
+        &self,
+        txn: &Transaction,
+        strategy: &ConfirmationStrategy,
+    ) -> Result<Signature, ClientError> {
+        let signature = self.send(txn)?;
+        self.confirm(&signature, strategy)?;
+        Ok(signature)
+    }
+}
+
+/// A connection to a chain that both reads accounts and sends transactions,
+/// as most commands need.
+pub(crate) trait Client: TransactionSender + AccountReader {}
+
+impl<T: TransactionSender + AccountReader + ?Sized> Client for T {}
+
+/// Why reading from or sending to a chain failed.
+#[derive(Debug)]
+pub(crate) enum ChainError {
+    Rpc(ClientError),
+    Account(AccountError),
+}
+
+impl fmt::Display for ChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainError::Rpc(err) => err.fmt(f),
+            ChainError::Account(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ChainError {}
+
+impl From<ClientError> for ChainError {
+    fn from(err: ClientError) -> Self {
+        ChainError::Rpc(err)
+    }
+}
+
+impl From<AccountError> for ChainError {
+    fn from(err: AccountError) -> Self {
+        ChainError::Account(err)
+    }
+}
+
+/// A shared sender, which threads spawned with their own handle to it can
+/// send through.
+impl<T: TransactionSender + ?Sized> TransactionSender for Arc<T> {
+    fn send(&self, txn: &Transaction) -> Result<Signature, ClientError> {
+        (**self).send(txn)
+    }
+
+    fn confirm(
+        &self,
+        signature: &Signature,
+        strategy: &ConfirmationStrategy,
+    ) -> Result<(), ClientError> {
+        (**self).confirm(signature, strategy)
+    }
+}
+
+/// Sends transactions through an RPC's `sendTransaction` and polls their
+/// signature statuses, printing the wait on stderr unless progress is turned
+/// off.
+pub(crate) struct RpcTransactionSender<'a> {
+    client: &'a RpcClient,
+    /// The commitment level transactions are simulated at before sending.
+    preflight_commitment: CommitmentLevel,
+    progress: bool,
+}
+
+impl<'a> RpcTransactionSender<'a> {
+    pub(crate) fn new(
+        client: &'a RpcClient,
+        preflight_commitment: CommitmentLevel,
+        progress: bool,
+    ) -> Self {
+        Self {
+            client,
+            preflight_commitment,
+            progress,
+        }
+    }
+}
+
+impl TransactionSender for RpcTransactionSender<'_> {
+    fn send(&self, txn: &Transaction) -> Result<Signature, ClientError> {
+        self.client.send_transaction_with_config(
+            txn,
+            RpcSendTransactionConfig {
+                preflight_commitment: Some(self.preflight_commitment),
+                ..RpcSendTransactionConfig::default()
+            },
+        )
+    }
+
+    fn confirm(
+        &self,
+        signature: &Signature,
+        strategy: &ConfirmationStrategy,
+    ) -> Result<(), ClientError> {
+        await_confirmation(self.client, signature, strategy, self.progress)
+    }
+}
+
+/// A connection to an RPC that both reads accounts and sends transactions,
+/// configured with a builder, e.g.
+/// `SolanaRpcSender::new(url).commitment(CommitmentConfig::confirmed())`.
+pub(crate) struct SolanaRpcSender {
+    client: RpcClient,
+    commitment: CommitmentConfig,
+    skip_preflight: bool,
+    progress: bool,
+    protocol: ProtocolConfig,
+}
+
+impl SolanaRpcSender {
+    /// Connects to the RPC at `url`, reading and simulating at the `confirmed`
+    /// commitment level, without printing progress.
+    pub(crate) fn new(url: impl Into<String>) -> Self {
+        let commitment = CommitmentConfig::confirmed();
+        Self {
+            client: rpc_client(url.into(), commitment),
+            commitment,
+            skip_preflight: false,
+            progress: false,
+            protocol: ProtocolConfig::default(),
+        }
+    }
+
+    /// The commitment level accounts are read and transactions simulated at.
+    pub(crate) fn commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.client = rpc_client(self.client.url(), commitment);
+        self.commitment = commitment;
+        self
+    }
+
+    /// Sends transactions without simulating them first, so that failing
+    /// transactions still land and pay their fee.
+    pub(crate) fn skip_preflight(mut self, skip_preflight: bool) -> Self {
+        self.skip_preflight = skip_preflight;
+        self
+    }
+
+    /// Prints the wait for confirmations on stderr.
+    pub(crate) fn progress(mut self, progress: bool) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// The protocol config the addresses of accounts are derived with.
+    pub(crate) fn protocol(mut self, protocol: ProtocolConfig) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
+    pub(crate) fn client(&self) -> &RpcClient {
+        &self.client
+    }
+
+    pub(crate) fn commitment_config(&self) -> CommitmentConfig {
+        self.commitment
+    }
+}
+
+impl TransactionSender for SolanaRpcSender {
+    fn send(&self, txn: &Transaction) -> Result<Signature, ClientError> {
+        self.client.send_transaction_with_config(
+            txn,
+            RpcSendTransactionConfig {
+                skip_preflight: self.skip_preflight,
+                preflight_commitment: Some(self.commitment.commitment),
+                ..RpcSendTransactionConfig::default()
+            },
+        )
+    }
+
+    fn confirm(
+        &self,
+        signature: &Signature,
+        strategy: &ConfirmationStrategy,
+    ) -> Result<(), ClientError> {
+        await_confirmation(&self.client, signature, strategy, self.progress)
+    }
+}
+
+impl AccountReader for SolanaRpcSender {
+    fn protocol(&self) -> &ProtocolConfig {
+        &self.protocol
+    }
+
+    fn read_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, ClientError> {
+        self.client
+            .get_account_with_commitment(pubkey, self.commitment)
+            .map(|response| response.value)
+    }
+}
+
+/// Sends transactions directly to the TPUs of the upcoming leaders rather than
+/// through an RPC's `sendTransaction`, for when the RPC is the bottleneck, e.g.
+/// when load testing. Transactions aren't simulated before they're sent, and
+/// their signature statuses are still polled through the RPC.
+pub(crate) struct TpuTransactionSender {
+    tpu_client: TpuClient,
+    progress: bool,
+}
+
+impl TpuTransactionSender {
+    /// Connects to the leaders' TPUs, tracking the leader schedule through
+    /// the RPC and its websocket.
+    pub(crate) fn new(
+        rpc_client: Arc<RpcClient>,
+        websocket_url: &str,
+        progress: bool,
+    ) -> Result<Self, ClientError> {
+        let tpu_client = TpuClient::new(rpc_client, websocket_url, TpuClientConfig::default())
+            .map_err(|e| ClientErrorKind::Custom(format!("Failed to connect to TPUs: {}", e)))?;
+        Ok(Self {
+            tpu_client,
+            progress,
+        })
+    }
+}
+
+impl TransactionSender for TpuTransactionSender {
+    fn send(&self, txn: &Transaction) -> Result<Signature, ClientError> {
+        self.tpu_client
+            .try_send_transaction(txn)
+            .map_err(ClientErrorKind::from)?;
+        Ok(txn.signatures[0])
+    }
+
+    fn confirm(
+        &self,
+        signature: &Signature,
+        strategy: &ConfirmationStrategy,
+    ) -> Result<(), ClientError> {
+        await_confirmation(
+            self.tpu_client.rpc_client(),
+            signature,
+            strategy,
+            self.progress,
+        )
+    }
+}
+
+/// Polls the signature status of a sent transaction until it's confirmed as
+/// the strategy requires, printing the wait on stderr if `progress` is set.
+fn await_confirmation(
+    client: &RpcClient,
+    signature: &Signature,
+    strategy: &ConfirmationStrategy,
+    progress: bool,
+) -> Result<(), ClientError> {
+    let started_at = Instant::now();
+    let mut waited = false;
+    let result = loop {
+        let status = client.get_signature_status_with_commitment(signature, strategy.commitment);
+        debug!(%signature, ?status, "Polled signature status");
+        match status {
+            Ok(Some(Ok(()))) => break Ok(()),
+            Ok(Some(Err(err))) => break Err(err.into()),
+            Err(err) => break Err(err),
+            Ok(None) if started_at.elapsed() >= strategy.timeout => {
+                break Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!(
+                        "Transaction {} wasn't {:?} within {}s",
+                        signature,
+                        strategy.commitment.commitment,
+                        strategy.timeout.as_secs()
+                    ),
+                )
+                .into())
+            }
+            Ok(None) => {}
+        }
+        if progress {
+            eprint!(
+                "\rWaiting for transaction to be {:?}... {}s",
+                strategy.commitment.commitment,
+                started_at.elapsed().as_secs()
+            );
+            io::stderr().flush().ok();
+            waited = true;
+        }
+        thread::sleep(strategy.poll_interval);
+    };
+    if waited {
+        eprintln!();
+    }
+    result
+}
+
+/// The outcome of sending one of the transactions.
+pub(crate) struct SendOutcome {
+    /// The index of the transaction, in the order they were given.
+    pub index: usize,
+    /// The signature and confirmation latency, or why the transaction failed.
+    pub result: Result<(Signature, Duration), String>,
+}
+
+/// Throughput and latency of a batch of sends.
+pub(crate) struct SendStats {
+    pub confirmed: usize,
+    pub failed: usize,
+    pub elapsed: Duration,
+    /// Confirmation latencies of the confirmed transactions, sorted.
+    pub latencies: Vec<Duration>,
+}
+
+impl SendStats {
+    fn new(outcomes: &[SendOutcome], elapsed: Duration) -> Self {
+        let mut latencies = outcomes
+            .iter()
+            .filter_map(|outcome| outcome.result.as_ref().ok().map(|(_, latency)| *latency))
+            .collect::<Vec<_>>();
+        latencies.sort();
+        Self {
+            confirmed: latencies.len(),
+            failed: outcomes.len() - latencies.len(),
+            elapsed,
+            latencies,
+        }
+    }
+
+    fn latency_percentile(&self, percentile: usize) -> Option<Duration> {
+        let last = self.latencies.len().checked_sub(1)?;
+        Some(self.latencies[last * percentile / 100])
+    }
+}
+
+impl fmt::Display for SendStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} confirmed, {} failed in {:.1}s ({:.1} tx/s)",
+            self.confirmed,
+            self.failed,
+            self.elapsed.as_secs_f64(),
+            self.confirmed as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+        )?;
+        if let (Some(p50), Some(p90), Some(max)) = (
+            self.latency_percentile(50),
+            self.latency_percentile(90),
+            self.latencies.last(),
+        ) {
+            write!(
+                f,
+                ", confirmation latency p50 {:.1}s p90 {:.1}s max {:.1}s",
+                p50.as_secs_f64(),
+                p90.as_secs_f64(),
+                max.as_secs_f64()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Sends transactions through the sender with up to `concurrency` of them in
+/// flight at once, tracking the confirmation of each through the client.
+pub(crate) struct ConcurrentSender<'a> {
+    client: &'a RpcClient,
+    sender: &'a dyn TransactionSender,
+    commitment: CommitmentConfig,
+    concurrency: usize,
+    blockhash: CachedBlockhash,
+}
+
+impl<'a> ConcurrentSender<'a> {
+    pub(crate) fn new(
+        client: &'a RpcClient,
+        sender: &'a dyn TransactionSender,
+        commitment: CommitmentConfig,
+        concurrency: usize,
+    ) -> Self {
+        Self {
+            client,
+            sender,
+            commitment,
+            concurrency: concurrency.max(1),
+            blockhash: CachedBlockhash::default(),
+        }
+    }
+
+    /// Sends `count` transactions, the `i`th of which is built and signed by
+    /// `sign(i, recent_blockhash)`. Returns the outcome of each, in order.
+    pub(crate) fn send_all<F>(&self, count: usize, sign: F) -> (Vec<SendOutcome>, SendStats)
+    where
+        F: Fn(usize, Hash) -> Transaction + Sync,
+    {
+        let start = Instant::now();
+        let next = AtomicUsize::new(0);
+        let outcomes = Mutex::new(Vec::with_capacity(count));
+        thread::scope(|scope| {
+            for _ in 0..self.concurrency.min(count) {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    if index >= count {
+                        break;
+                    }
+                    let result = self.send_one(index, &sign);
+                    match &result {
+                        Ok((signature, latency)) => println!(
+                            "Transaction {} confirmed in {:.1}s: {}",
+                            index,
+                            latency.as_secs_f64(),
+                            signature
+                        ),
+                        Err(err) => println!("Transaction {} failed: {}", index, err),
+                    }
+                    outcomes.lock().unwrap().push(SendOutcome { index, result });
+                });
+            }
+        });
+
+        let mut outcomes = outcomes.into_inner().unwrap();
+        outcomes.sort_by_key(|outcome| outcome.index);
+        let stats = SendStats::new(&outcomes, start.elapsed());
+        (outcomes, stats)
+    }
+
+    fn send_one<F>(&self, index: usize, sign: &F) -> Result<(Signature, Duration), String>
+    where
+        F: Fn(usize, Hash) -> Transaction,
+    {
+        let blockhash = self
+            .blockhash
+            .get(self.client)
+            .map_err(|e| format!("Failed to get latest blockhash: {}", e))?;
+        let txn = sign(index, blockhash);
+        let sent_at = Instant::now();
+        let signature = self.sender.send(&txn).map_err(|e| e.to_string())?;
+
+        loop {
+            let status = self
+                .client
+                .get_signature_status_with_commitment(&signature, self.commitment)
+                .map_err(|e| e.to_string())?;
+            match status {
+                Some(Ok(())) => return Ok((signature, sent_at.elapsed())),
+                Some(Err(err)) => return Err(format!("{}: {}", signature, err)),
+                None => {
+                    let blockhash_valid = self
+                        .client
+                        .is_blockhash_valid(&blockhash, self.commitment)
+                        .map_err(|e| e.to_string())?;
+                    if !blockhash_valid {
+                        self.blockhash.invalidate();
+                        return Err(format!(
+                            "{}: blockhash expired before confirmation",
+                            signature
+                        ));
+                    }
+                }
+            }
+            thread::sleep(CONFIRMATION_POLL_INTERVAL);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_senders_are_thread_safe() {
+        fn assert_thread_safe<T: Send + Sync + ?Sized>() {}
+        fn assert_sender<T: TransactionSender + ?Sized>() {}
+        assert_thread_safe::<dyn TransactionSender>();
+        assert_sender::<Arc<dyn TransactionSender>>();
+    }
+
+    #[test]
+    fn test_send_stats() {
+        let outcomes = [3, 1, 2]
+            .into_iter()
+            .enumerate()
+            .map(|(index, secs)| SendOutcome {
+                index,
+                result: Ok((Signature::default(), Duration::from_secs(secs))),
+            })
+            .chain([SendOutcome {
+                index: 3,
+                result: Err("failed".to_owned()),
+            }])
+            .collect::<Vec<_>>();
+        let stats = SendStats::new(&outcomes, Duration::from_secs(2));
+        assert_eq!(stats.confirmed, 3);
+        assert_eq!(stats.failed, 1);
+        assert_eq!(stats.latency_percentile(50), Some(Duration::from_secs(2)));
+        assert_eq!(
+            stats.to_string(),
+            "3 confirmed, 1 failed in 2.0s (1.5 tx/s), confirmation latency p50 2.0s p90 2.0s max 3.0s"
+        );
+    }
+}
@@ -0,0 +1,84 @@
+//! Graceful shutdown of the long-running commands, e.g. `relay --daemon`, on
+//! SIGINT or SIGTERM. The first signal cancels a token the command checks
+//! between units of work, so that an in-flight transaction is confirmed and
+//! progress is recorded before the command stops. A second signal exits
+//! immediately.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How often waits check whether they're cancelled.
+pub(crate) const CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Exit code of a process killed by SIGINT, for the second signal.
+const INTERRUPTED: i32 = 130;
+
+#[derive(Clone, Default)]
+pub(crate) struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// A token cancelled on SIGINT or SIGTERM. Only one command runs per
+    /// process, so the handler is only installed once.
+    pub(crate) fn on_signals() -> Self {
+        let token = Self::default();
+        let handler_token = token.clone();
+        ctrlc::set_handler(move || {
+            if handler_token.is_cancelled() {
+                std::process::exit(INTERRUPTED);
+            }
+            eprintln!("Shutting down, signal again to exit immediately");
+            handler_token.cancel();
+        })
+        .expect("Failed to install the signal handler");
+        token
+    }
+
+    pub(crate) fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Sleeps for the duration, waking up early if the token is cancelled.
+    /// Returns whether it was.
+    pub(crate) fn sleep(&self, duration: Duration) -> bool {
+        let deadline = Instant::now() + duration;
+        while !self.is_cancelled() {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            thread::sleep(CHECK_INTERVAL.min(deadline - now));
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sleep_wakes_up_when_cancelled() {
+        let token = CancellationToken::default();
+        assert!(!token.sleep(Duration::from_millis(10)));
+
+        let cancelling = token.clone();
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            cancelling.cancel();
+        });
+        let start = Instant::now();
+        assert!(token.sleep(Duration::from_secs(60)));
+        assert!(start.elapsed() < Duration::from_secs(10));
+        canceller.join().unwrap();
+    }
+}
@@ -0,0 +1,424 @@
+use hyperlane_core::{Decode as _, HyperlaneMessage, H256};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_program::pubkey::Pubkey;
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
+
+use hyperlane_sealevel::is_method_unavailable;
+use hyperlane_sealevel_igp::accounts::{
+    GasPaymentAccount, GasPaymentData, GAS_PAYMENT_DISCRIMINATOR,
+};
+use hyperlane_sealevel_mailbox::accounts::{
+    DispatchedMessage, DispatchedMessageAccount, OutboxAccount, ProcessedMessageAccount,
+    DISPATCHED_MESSAGE_DISCRIMINATOR,
+};
+
+use crate::{
+    events::HyperlaneEvent,
+    exit_code,
+    logs::{dispatched_messages, get_transaction},
+    protocol::ProtocolConfig,
+    rpc::rpc_client,
+    strict::skip_or_fail,
+    Context, MessageStatus,
+};
+
+/// The most signatures `getSignaturesForAddress` returns per request.
+const MAX_SIGNATURES_PER_PAGE: usize = 1_000;
+
+/// Prints a timeline of a message's dispatch on the origin chain and its
+/// delivery on the destination chain.
+pub(crate) fn process_message_status(ctx: &Context, status: MessageStatus) {
+    #[cfg(feature = "evm")]
+    if let (Some(rpc_url), Some(mailbox)) = (&status.origin_evm_rpc_url, status.origin_evm_mailbox)
+    {
+        let Some(dispatched_message) =
+            crate::evm::find_evm_dispatched_message(rpc_url, mailbox, status.nonce)
+        else {
+            println!(
+                "No message with nonce {} dispatched on origin",
+                status.nonce
+            );
+            return;
+        };
+        let message = &dispatched_message.message;
+        if status.json {
+            println!(
+                "{}",
+                HyperlaneEvent::dispatched_message(
+                    message,
+                    dispatched_message.block_number,
+                    Some(format!("{:?}", dispatched_message.transaction_hash)),
+                )
+                .to_json_line()
+            );
+        } else {
+            println!("Message ID: {:?}", message.id());
+            println!(
+                "Origin: {}, destination: {}",
+                message.origin, message.destination
+            );
+            println!(
+                "Dispatched: block {}, transaction {:?}",
+                dispatched_message.block_number, dispatched_message.transaction_hash
+            );
+        }
+        print_delivery(ctx, &status, message.id());
+        return;
+    }
+
+    let origin_client = status
+        .origin_url
+        .as_ref()
+        .map(|url| rpc_client(url.clone(), ctx.commitment));
+    let origin_client = origin_client.as_ref().unwrap_or(&ctx.client);
+
+    let Some(dispatched_message) = find_dispatched_message_by_nonce(
+        origin_client,
+        &ctx.protocol,
+        &status.origin_program_id,
+        status.nonce,
+        ctx.commitment,
+        ctx.is_strict(false),
+    )
+    .unwrap_or_else(|e| {
+        exit_code::config_error(format!(
+            "Failed to get the message with nonce {} from the origin: {}",
+            status.nonce, e
+        ))
+    }) else {
+        println!(
+            "No message with nonce {} dispatched on origin",
+            status.nonce
+        );
+        return;
+    };
+    let message = HyperlaneMessage::read_from(&mut &dispatched_message.encoded_message[..])
+        .expect("Invalid dispatched message");
+    let message_id = message.id();
+    if message.version != ctx.protocol.message_version {
+        println!(
+            "Warning: message version {} differs from the expected version {}",
+            message.version, ctx.protocol.message_version
+        );
+    }
+
+    if status.json {
+        println!(
+            "{}",
+            HyperlaneEvent::dispatched(&dispatched_message).to_json_line()
+        );
+    } else {
+        println!("Message ID: {:?}", message_id);
+        println!(
+            "Origin: {}, destination: {}",
+            message.origin, message.destination
+        );
+        println!(
+            "Dispatched: slot {}{}",
+            dispatched_message.slot,
+            fmt_block_time(origin_client, dispatched_message.slot)
+        );
+    }
+
+    if let Some(igp_program_id) = status.igp_program_id.as_ref() {
+        for gas_payment in find_gas_payments_by_message_id(
+            origin_client,
+            igp_program_id,
+            message_id,
+            ctx.commitment,
+            ctx.is_strict(false),
+        ) {
+            if status.json {
+                println!("{}", HyperlaneEvent::gas_paid(&gas_payment).to_json_line());
+            } else {
+                println!(
+                    "Gas paid: {} gas for {} lamports, slot {}",
+                    gas_payment.gas_amount, gas_payment.payment, gas_payment.slot
+                );
+            }
+        }
+    }
+
+    print_delivery(ctx, &status, message_id);
+}
+
+/// Prints whether the message was delivered on the destination chain.
+fn print_delivery(ctx: &Context, status: &MessageStatus, message_id: H256) {
+    #[cfg(feature = "evm")]
+    if let (Some(rpc_url), Some(mailbox)) = (
+        &status.destination_evm_rpc_url,
+        status.destination_evm_mailbox,
+    ) {
+        let delivered = crate::evm::evm_message_delivered(rpc_url, mailbox, message_id).unwrap();
+        if !status.json {
+            println!("Delivered: {}", if delivered { "yes" } else { "not yet" });
+        }
+        return;
+    }
+
+    let destination_client = status
+        .destination_url
+        .as_ref()
+        .map(|url| rpc_client(url.clone(), ctx.commitment));
+    let destination_client = destination_client.as_ref().unwrap_or(&ctx.client);
+    let (processed_message_key, _processed_message_bump) = ctx
+        .protocol
+        .processed_message_pda(&status.destination_program_id, message_id);
+    let processed_message = destination_client
+        .get_account_with_commitment(&processed_message_key, ctx.commitment)
+        .unwrap()
+        .value
+        .map(|account| {
+            ProcessedMessageAccount::fetch(&mut &account.data[..])
+                .unwrap()
+                .into_inner()
+        });
+    match processed_message {
+        Some(processed_message) if status.json => println!(
+            "{}",
+            HyperlaneEvent::processed(&processed_message).to_json_line()
+        ),
+        Some(processed_message) => println!(
+            "Delivered: slot {}{}, sequence {}",
+            processed_message.slot,
+            fmt_block_time(destination_client, processed_message.slot),
+            processed_message.sequence
+        ),
+        None if status.json => {}
+        None => println!("Delivered: not yet"),
+    }
+}
+
+/// Finds the dispatched message with the given nonce by filtering the mailbox
+/// program's accounts or, if the RPC disabled `getProgramAccounts`, by
+/// searching the outbox's transaction history. RPC failures are returned
+/// rather than treated as the message not being found.
+pub(crate) fn find_dispatched_message_by_nonce(
+    client: &RpcClient,
+    protocol: &ProtocolConfig,
+    program_id: &Pubkey,
+    nonce: u32,
+    commitment: CommitmentConfig,
+    strict: bool,
+) -> Result<Option<DispatchedMessage>, ClientError> {
+    // The initialized flag precedes the discriminator, which precedes the nonce.
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                1,
+                DISPATCHED_MESSAGE_DISCRIMINATOR.to_vec(),
+            )),
+            RpcFilterType::Memcmp(Memcmp::new_raw_bytes(1 + 8, nonce.to_le_bytes().to_vec())),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(commitment),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    let accounts = match client.get_program_accounts_with_config(program_id, config) {
+        Ok(accounts) => accounts,
+        Err(err) if is_method_unavailable(&err) => {
+            let outbox = protocol.outbox_pda(program_id).0;
+            return find_dispatched_message_in_outbox_history(client, &outbox, nonce, commitment);
+        }
+        Err(err) => return Err(err),
+    };
+    Ok(accounts.into_iter().find_map(|(pubkey, account)| {
+        skip_or_fail(
+            strict,
+            DispatchedMessageAccount::fetch(&mut &account.data[..]),
+            || format!("dispatched message account {}", pubkey),
+        )
+        .map(|account| *account.into_inner())
+    }))
+}
+
+/// Finds all the messages the mailbox program dispatched in a single scan of
+/// its dispatched message accounts, in no particular order.
+pub(crate) fn find_dispatched_messages(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    commitment: CommitmentConfig,
+    strict: bool,
+) -> Result<Vec<DispatchedMessage>, ClientError> {
+    // The initialized flag precedes the discriminator.
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+            1,
+            DISPATCHED_MESSAGE_DISCRIMINATOR.to_vec(),
+        ))]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(commitment),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    let accounts = client.get_program_accounts_with_config(program_id, config)?;
+    Ok(accounts
+        .into_iter()
+        .filter_map(|(pubkey, account)| {
+            skip_or_fail(
+                strict,
+                DispatchedMessageAccount::fetch(&mut &account.data[..]),
+                || format!("dispatched message account {}", pubkey),
+            )
+            .map(|account| *account.into_inner())
+        })
+        .collect())
+}
+
+/// Finds the dispatched message with the given nonce in the logs of the
+/// outbox's transactions, newest first. Every dispatch writes to the outbox,
+/// so its count tells how many dispatches are newer than the message, which
+/// sizes the first page of signatures.
+fn find_dispatched_message_in_outbox_history(
+    client: &RpcClient,
+    outbox: &Pubkey,
+    nonce: u32,
+    commitment: CommitmentConfig,
+) -> Result<Option<DispatchedMessage>, ClientError> {
+    let Some(outbox_account) = client
+        .get_account_with_commitment(outbox, commitment)?
+        .value
+        .and_then(|account| OutboxAccount::fetch(&mut &account.data[..]).ok())
+    else {
+        return Ok(None);
+    };
+    let count = outbox_account.into_inner().tree.count() as u32;
+    if nonce >= count {
+        return Ok(None);
+    }
+    let mut limit = ((count - nonce) as usize).min(MAX_SIGNATURES_PER_PAGE);
+    let mut before = None;
+    loop {
+        let statuses = client.get_signatures_for_address_with_config(
+            outbox,
+            GetConfirmedSignaturesForAddress2Config {
+                before,
+                until: None,
+                limit: Some(limit),
+                commitment: Some(commitment),
+            },
+        )?;
+        let Some(oldest) = statuses.last() else {
+            return Ok(None);
+        };
+        before = Some(parse_signature(&oldest.signature)?);
+        // Failed dispatches and other transactions writing to the outbox take
+        // up room in the first page, so the next ones are full.
+        limit = MAX_SIGNATURES_PER_PAGE;
+        for status in statuses.iter().filter(|status| status.err.is_none()) {
+            let signature = parse_signature(&status.signature)?;
+            let txn = get_transaction(client, &signature, commitment)?;
+            let dispatched_messages = dispatched_messages(&txn);
+            // Walking back past the nonce means it was rolled back.
+            let passed = dispatched_messages
+                .iter()
+                .any(|dispatched_message| dispatched_message.nonce < nonce);
+            if let Some(dispatched_message) = dispatched_messages
+                .into_iter()
+                .find(|dispatched_message| dispatched_message.nonce == nonce)
+            {
+                return Ok(Some(dispatched_message));
+            }
+            if passed {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+fn parse_signature(signature: &str) -> Result<Signature, ClientError> {
+    signature.parse().map_err(|e| {
+        ClientErrorKind::Custom(format!("Invalid signature {}: {}", signature, e)).into()
+    })
+}
+
+/// Finds the gas payments for a message by filtering the IGP program's accounts.
+pub(crate) fn find_gas_payments_by_message_id(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    message_id: H256,
+    commitment: CommitmentConfig,
+    strict: bool,
+) -> Vec<GasPaymentData> {
+    // The initialized flag and discriminator are followed by the sequence
+    // number, the IGP and the destination domain, then the message ID.
+    let message_id_filter = RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+        1 + 8 + 8 + 32 + 4,
+        message_id.as_bytes().to_vec(),
+    ));
+    get_gas_payments(
+        client,
+        program_id,
+        vec![message_id_filter],
+        commitment,
+        strict,
+    )
+}
+
+/// Finds all the gas payments of the IGP program, for looking up the gas
+/// payments of many messages with a single request.
+pub(crate) fn find_gas_payments(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    commitment: CommitmentConfig,
+    strict: bool,
+) -> Vec<GasPaymentData> {
+    get_gas_payments(client, program_id, vec![], commitment, strict)
+}
+
+/// Gets the IGP program's gas payment accounts matching the filters.
+fn get_gas_payments(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    filters: Vec<RpcFilterType>,
+    commitment: CommitmentConfig,
+    strict: bool,
+) -> Vec<GasPaymentData> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(
+            std::iter::once(RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                1,
+                GAS_PAYMENT_DISCRIMINATOR.to_vec(),
+            )))
+            .chain(filters)
+            .collect(),
+        ),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(commitment),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    client
+        .get_program_accounts_with_config(program_id, config)
+        .unwrap()
+        .into_iter()
+        .filter_map(|(pubkey, account)| {
+            skip_or_fail(
+                strict,
+                GasPaymentAccount::fetch(&mut &account.data[..]),
+                || format!("gas payment account {}", pubkey),
+            )
+            .map(|account| account.into_inner().data)
+        })
+        .collect()
+}
+
+fn fmt_block_time(client: &RpcClient, slot: u64) -> String {
+    client
+        .get_block_time(slot)
+        .map(|block_time| format!(" (unix time {})", block_time))
+        .unwrap_or_default()
+}
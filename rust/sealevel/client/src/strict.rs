@@ -0,0 +1,46 @@
+//! Handling of data that can't be decoded, e.g. program accounts with
+//! unexpected layouts or malformed published checkpoints. By default such
+//! data is skipped with a warning, in strict mode it's a hard error.
+
+use std::fmt::Display;
+
+/// Returns the value if decoding succeeded. Otherwise, in strict mode, exits
+/// with an error describing what failed to decode, and if not strict warns and
+/// returns `None`.
+///
+/// This exits rather than panicking so that it also stops the process when
+/// called from a spawned thread.
+pub(crate) fn skip_or_fail<T, E: Display>(
+    strict: bool,
+    result: Result<T, E>,
+    what: impl FnOnce() -> String,
+) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(err) if strict => {
+            eprintln!("Error: failed to decode {}: {}", what(), err);
+            std::process::exit(1);
+        }
+        Err(err) => {
+            eprintln!("Warning: skipping {}, failed to decode: {}", what(), err);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_skip_or_fail_not_strict() {
+        assert_eq!(
+            skip_or_fail(false, Ok::<_, String>(1), || "value".into()),
+            Some(1)
+        );
+        assert_eq!(
+            skip_or_fail(false, Err::<u32, _>("invalid"), || "value".into()),
+            None
+        );
+    }
+}
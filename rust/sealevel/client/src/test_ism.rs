@@ -0,0 +1,89 @@
+//! Management of the test ISM, which accepts or rejects every message as
+//! toggled, so that delivery flows can be exercised on localnet both ways.
+
+use std::path::Path;
+
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+
+use hyperlane_sealevel_test_ism::instruction::{init_instruction, set_accept_instruction};
+
+use crate::{
+    artifacts::{write_json, SingularProgramIdArtifact},
+    cmd_utils::{create_and_write_keypair, create_new_directory, deploy_program},
+    Context, TestIsmCmd, TestIsmSubCmd,
+};
+
+pub(crate) fn process_test_ism_cmd(mut ctx: Context, cmd: TestIsmCmd) {
+    match cmd.cmd {
+        TestIsmSubCmd::Deploy(deploy) => {
+            let environments_dir =
+                create_new_directory(&deploy.environments_dir, &deploy.environment);
+            let ism_dir = create_new_directory(&environments_dir, "test-ism");
+            let chain_dir = create_new_directory(&ism_dir, &deploy.chain);
+            let context_dir = create_new_directory(&chain_dir, &deploy.context);
+            let key_dir = create_new_directory(&context_dir, "keys");
+
+            let ism_program_id = deploy_test_ism(&mut ctx, &deploy.built_so_dir, true, &key_dir);
+
+            write_json::<SingularProgramIdArtifact>(
+                &context_dir.join("program-ids.json"),
+                ism_program_id.into(),
+            );
+        }
+        TestIsmSubCmd::SetAccept(set) => set_accept(&mut ctx, set.program_id, true),
+        TestIsmSubCmd::SetReject(set) => set_accept(&mut ctx, set.program_id, false),
+    }
+}
+
+/// Deploys and initializes the test ISM, which starts out accepting every
+/// message.
+pub(crate) fn deploy_test_ism(
+    ctx: &mut Context,
+    built_so_dir: &Path,
+    use_existing_keys: bool,
+    key_dir: &Path,
+) -> Pubkey {
+    let (keypair, keypair_path) = create_and_write_keypair(
+        key_dir,
+        "hyperlane_sealevel_test_ism-keypair.json",
+        use_existing_keys,
+    );
+    let program_id = keypair.pubkey();
+
+    deploy_program(
+        ctx.payer_keypair_path(),
+        keypair_path.to_str().unwrap(),
+        built_so_dir
+            .join("hyperlane_sealevel_test_ism.so")
+            .to_str()
+            .unwrap(),
+        &ctx.client.url(),
+    );
+
+    println!("Deployed Test ISM at program ID {}", program_id);
+
+    let instruction = init_instruction(program_id, ctx.payer_pubkey).unwrap();
+    ctx.new_txn()
+        .add_with_description(
+            instruction,
+            format!("Initializing Test ISM with payer {}", ctx.payer_pubkey),
+        )
+        .send_with_payer();
+
+    program_id
+}
+
+fn set_accept(ctx: &mut Context, program_id: Pubkey, accept: bool) {
+    let instruction = set_accept_instruction(program_id, accept).unwrap();
+    ctx.new_txn()
+        .add_with_description(
+            instruction,
+            format!(
+                "Setting Test ISM {} to {} messages",
+                program_id,
+                if accept { "accept" } else { "reject" }
+            ),
+        )
+        .send_with_payer();
+}
@@ -0,0 +1,191 @@
+//! Offline signing: transactions built with `tx build` are written to a file,
+//! one base64 encoded transaction per line, signed on another machine with
+//! `tx sign` and sent later with `tx broadcast`. Unless a durable nonce is
+//! used, a transaction expires about a minute after it's built.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::Write as _,
+    path::Path,
+};
+
+use base64::Engine as _;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+
+use crate::{
+    exit_code, sender::TransactionSender as _, tx_inspect::inspect_transaction,
+    tx_logs::print_client_error, Context, TxBroadcast, TxCmd, TxSign, TxSubCmd,
+};
+
+pub(crate) fn process_tx_cmd(ctx: Context, cmd: TxCmd) {
+    match cmd.cmd {
+        TxSubCmd::Build(_) => panic!("`tx build` can't build transactions of another `tx build`"),
+        TxSubCmd::Sign(sign) => sign_transactions(&ctx, sign),
+        TxSubCmd::Broadcast(broadcast) => broadcast_transactions(&ctx, broadcast),
+        TxSubCmd::Inspect(inspect) => inspect_transaction(&ctx, inspect),
+    }
+}
+
+fn encode_transaction(txn: &Transaction) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bincode::serialize(txn).unwrap())
+}
+
+fn decode_transaction(line: &str) -> Transaction {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(line.trim())
+        .unwrap_or_else(|e| panic!("Invalid base64 transaction: {}", e));
+    bincode::deserialize(&bytes).unwrap_or_else(|e| panic!("Invalid transaction: {}", e))
+}
+
+/// Reads the transactions in a file written by `tx build`.
+pub(crate) fn read_transactions(path: &Path) -> Vec<Transaction> {
+    fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("Failed to read transactions {}: {}", path.display(), e))
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(decode_transaction)
+        .collect()
+}
+
+/// Writes transactions to a file, replacing its contents.
+pub(crate) fn write_transactions(path: &Path, txns: &[Transaction]) {
+    let contents = txns
+        .iter()
+        .map(|txn| format!("{}\n", encode_transaction(txn)))
+        .collect::<String>();
+    fs::write(path, contents)
+        .unwrap_or_else(|e| panic!("Failed to write transactions {}: {}", path.display(), e));
+}
+
+/// Appends a transaction to a file, creating it if needed.
+pub(crate) fn append_transaction(path: &Path, txn: &Transaction) {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .unwrap_or_else(|e| panic!("Failed to open transactions {}: {}", path.display(), e));
+    writeln!(file, "{}", encode_transaction(txn))
+        .unwrap_or_else(|e| panic!("Failed to write transactions {}: {}", path.display(), e));
+}
+
+/// The signers whose signatures the transaction is still missing.
+fn missing_signers(txn: &Transaction) -> Vec<Pubkey> {
+    txn.message
+        .account_keys
+        .iter()
+        .zip(&txn.signatures)
+        .filter(|(_, signature)| **signature == Signature::default())
+        .map(|(pubkey, _)| *pubkey)
+        .collect()
+}
+
+/// Signs the transactions in place with the payer and the other given
+/// signers, each only signing the transactions it's a signer of.
+fn sign_transactions(ctx: &Context, sign: TxSign) {
+    let keypairs = sign
+        .signers
+        .iter()
+        .map(|path| {
+            read_keypair_file(path)
+                .unwrap_or_else(|e| panic!("Failed to read keypair {}: {}", path, e))
+        })
+        .collect::<Vec<Keypair>>();
+    let signers = keypairs
+        .iter()
+        .chain(ctx.payer_keypair())
+        .collect::<Vec<_>>();
+    assert!(!signers.is_empty(), "No keypairs to sign with");
+
+    let mut txns = read_transactions(&sign.transactions);
+    for (i, txn) in txns.iter_mut().enumerate() {
+        let required_signers =
+            &txn.message.account_keys[..txn.message.header.num_required_signatures as usize];
+        let signers = signers
+            .iter()
+            .filter(|signer| required_signers.contains(&signer.pubkey()))
+            .map(|signer| *signer as &dyn Signer)
+            .collect::<Vec<_>>();
+        let blockhash = txn.message.recent_blockhash;
+        txn.partial_sign(&signers, blockhash);
+
+        let missing = missing_signers(txn);
+        if missing.is_empty() {
+            println!("Transaction {}: fully signed", i);
+        } else {
+            println!("Transaction {}: missing signatures of {:?}", i, missing);
+        }
+    }
+    write_transactions(&sign.transactions, &txns);
+}
+
+/// Sends the signed transactions in a file, in order.
+fn broadcast_transactions(ctx: &Context, broadcast: TxBroadcast) {
+    let txns = read_transactions(&broadcast.transactions);
+    let sender = ctx.sender(&ctx.client);
+    let mut signatures = vec![];
+    for (i, txn) in txns.iter().enumerate() {
+        let missing = missing_signers(txn);
+        assert!(
+            missing.is_empty(),
+            "Transaction {} is missing signatures of {:?}",
+            i,
+            missing
+        );
+        let signature = sender
+            .send_and_confirm(txn, ctx.confirmation())
+            .unwrap_or_else(|err| {
+                print_client_error(&err, &[]);
+                exit_code::exit_on_transaction_error(err)
+            });
+        println!("Transaction {}: confirmed, signature {}", i, signature);
+        ctx.print_transaction_link(&ctx.client, &signature);
+        signatures.push(signature);
+    }
+
+    if broadcast.finalize {
+        // The transactions were confirmed as they were sent, only their
+        // finalization is waited for here so it overlaps for all of them.
+        let finalized = ctx
+            .confirmation()
+            .with_commitment(CommitmentConfig::finalized());
+        for (i, signature) in signatures.iter().enumerate() {
+            sender.confirm(signature, &finalized).unwrap_or_else(|err| {
+                print_client_error(&err, &[]);
+                exit_code::exit_on_transaction_error(err)
+            });
+            println!("Transaction {}: finalized", i);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use solana_sdk::{hash::Hash, message::Message, system_instruction};
+
+    use super::*;
+
+    #[test]
+    fn test_transaction_roundtrip_and_missing_signers() {
+        let payer = Keypair::new();
+        let other = Keypair::new();
+        let message = Message::new_with_blockhash(
+            &[
+                system_instruction::transfer(&payer.pubkey(), &other.pubkey(), 1),
+                system_instruction::transfer(&other.pubkey(), &payer.pubkey(), 1),
+            ],
+            Some(&payer.pubkey()),
+            &Hash::new_unique(),
+        );
+        let mut txn = Transaction::new_unsigned(message);
+        txn.partial_sign(&[&other], txn.message.recent_blockhash);
+
+        let decoded = decode_transaction(&encode_transaction(&txn));
+        assert_eq!(decoded, txn);
+        assert_eq!(missing_signers(&decoded), vec![payer.pubkey()]);
+    }
+}
@@ -0,0 +1,264 @@
+//! Inspection of a sent transaction for debugging: the instructions of the
+//! Hyperlane programs, including those invoked through CPIs, are decoded and
+//! the PDAs they involve are named.
+
+use hyperlane_core::{Decode as _, HyperlaneMessage};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use solana_transaction_status::{UiInnerInstructions, UiInstruction};
+
+use hyperlane_sealevel_igp::{
+    igp_gas_payment_pda_seeds, igp_program_data_pda_seeds,
+    instruction::Instruction as IgpInstruction,
+};
+use hyperlane_sealevel_mailbox::{instruction::Instruction as MailboxInstruction, spl_noop};
+
+use crate::{
+    decode::{DecodedInstruction, HyperlanePrograms},
+    logs::{account_keys, get_transaction},
+    Context, TxInspect,
+};
+
+/// Resolves a compiled instruction's program and accounts from the
+/// transaction's account keys. Only the keys of the accounts are needed to
+/// decode it, so they're all given as read only and not signing.
+fn resolve_instruction(
+    account_keys: &[Pubkey],
+    program_id_index: u8,
+    accounts: &[u8],
+    data: Vec<u8>,
+) -> Option<Instruction> {
+    Some(Instruction {
+        program_id: *account_keys.get(program_id_index as usize)?,
+        accounts: accounts
+            .iter()
+            .map(|index| {
+                account_keys
+                    .get(*index as usize)
+                    .map(|key| AccountMeta::new_readonly(*key, false))
+            })
+            .collect::<Option<_>>()?,
+        data,
+    })
+}
+
+/// Prints the transaction's instructions, decoding those of the Hyperlane
+/// programs.
+pub(crate) fn inspect_transaction(ctx: &Context, inspect: TxInspect) {
+    let txn = get_transaction(&ctx.client, &inspect.signature, ctx.commitment)
+        .unwrap_or_else(|e| panic!("Failed to get transaction {}: {}", inspect.signature, e));
+    let (Some(meta), Some(versioned)) = (
+        txn.transaction.meta.as_ref(),
+        txn.transaction.transaction.decode(),
+    ) else {
+        panic!("Transaction {} can't be decoded", inspect.signature);
+    };
+    let account_keys = account_keys(&versioned, meta);
+    let inner_instructions: Vec<UiInnerInstructions> =
+        Option::from(meta.inner_instructions.clone()).unwrap_or_default();
+    let programs = HyperlanePrograms {
+        mailbox: inspect.mailbox,
+        igp: inspect.igp_program_id,
+        multisig_ism_message_id: inspect.multisig_ism_program_id,
+        validator_announce: inspect.validator_announce_program_id,
+        #[cfg(feature = "tokens")]
+        warp_routes: inspect.warp_routes,
+    };
+
+    match &meta.err {
+        Some(err) => println!(
+            "Transaction {} at slot {} failed: {}",
+            inspect.signature, txn.slot, err
+        ),
+        None => println!("Transaction {} at slot {}", inspect.signature, txn.slot),
+    }
+    for (index, instruction) in versioned.message.instructions().iter().enumerate() {
+        let position = (index + 1).to_string();
+        print_instruction(
+            ctx,
+            &programs,
+            &position,
+            resolve_instruction(
+                &account_keys,
+                instruction.program_id_index,
+                &instruction.accounts,
+                instruction.data.clone(),
+            ),
+        );
+        let cpis = inner_instructions
+            .iter()
+            .filter(|inner| inner.index as usize == index)
+            .flat_map(|inner| &inner.instructions);
+        for (inner_index, instruction) in cpis.enumerate() {
+            let position = format!("{}.{}", position, inner_index + 1);
+            let instruction = match instruction {
+                UiInstruction::Compiled(compiled) => bs58::decode(&compiled.data)
+                    .into_vec()
+                    .ok()
+                    .and_then(|data| {
+                        resolve_instruction(
+                            &account_keys,
+                            compiled.program_id_index,
+                            &compiled.accounts,
+                            data,
+                        )
+                    }),
+                UiInstruction::Parsed(_) => None,
+            };
+            print_instruction(ctx, &programs, &position, instruction);
+        }
+    }
+}
+
+fn print_instruction(
+    ctx: &Context,
+    programs: &HyperlanePrograms,
+    position: &str,
+    instruction: Option<Instruction>,
+) {
+    let Some(instruction) = instruction else {
+        println!("Instruction {}: can't be resolved", position);
+        return;
+    };
+    let pdas = match programs.decode(&instruction) {
+        Some(Ok(DecodedInstruction::Mailbox(decoded))) => {
+            println!(
+                "Instruction {}: mailbox {}",
+                position, instruction.program_id
+            );
+            mailbox_instruction_pdas(ctx, &instruction, decoded)
+        }
+        Some(Ok(DecodedInstruction::Igp(decoded))) => {
+            println!("Instruction {}: IGP {}", position, instruction.program_id);
+            igp_instruction_pdas(&instruction, decoded)
+        }
+        Some(Ok(decoded)) => {
+            println!(
+                "Instruction {}: program {}",
+                position, instruction.program_id
+            );
+            println!("\t{:#?}", decoded);
+            vec![]
+        }
+        Some(Err(_)) => {
+            println!(
+                "Instruction {}: program {}, data doesn't decode: 0x{}",
+                position,
+                instruction.program_id,
+                hex::encode(&instruction.data)
+            );
+            vec![]
+        }
+        None if instruction.program_id == spl_noop::id() => {
+            println!("Instruction {}: SPL noop log", position);
+            return;
+        }
+        None => {
+            println!(
+                "Instruction {}: program {}",
+                position, instruction.program_id
+            );
+            return;
+        }
+    };
+
+    println!("\tAccounts:");
+    for (index, account) in instruction.accounts.iter().enumerate() {
+        match pdas.iter().find(|(pda, _)| *pda == account.pubkey) {
+            Some((_, name)) => println!("\t{}. {} ({})", index, account.pubkey, name),
+            None => println!("\t{}. {}", index, account.pubkey),
+        }
+    }
+}
+
+/// Prints the decoded mailbox instruction, returning the PDAs it involves.
+fn mailbox_instruction_pdas(
+    ctx: &Context,
+    instruction: &Instruction,
+    decoded: MailboxInstruction,
+) -> Vec<(Pubkey, String)> {
+    let mailbox = instruction.program_id;
+    let mut pdas = vec![
+        (ctx.protocol.inbox_pda(&mailbox).0, "inbox PDA".to_owned()),
+        (ctx.protocol.outbox_pda(&mailbox).0, "outbox PDA".to_owned()),
+    ];
+    match decoded {
+        MailboxInstruction::InboxProcess(process) => {
+            println!("\tInboxProcess");
+            println!("\tMetadata: 0x{}", hex::encode(&process.metadata));
+            match HyperlaneMessage::read_from(&mut &process.message[..]) {
+                Ok(message) => {
+                    let recipient = Pubkey::new_from_array(message.recipient.0);
+                    pdas.push((
+                        ctx.protocol.processed_message_pda(&mailbox, message.id()).0,
+                        "processed message PDA".to_owned(),
+                    ));
+                    pdas.push((
+                        ctx.protocol.process_authority_pda(&mailbox, &recipient).0,
+                        "process authority PDA of the recipient".to_owned(),
+                    ));
+                    println!("\tMessage {:?}:", message.id());
+                    println!("{:#?}", message);
+                }
+                Err(e) => println!(
+                    "\tMessage doesn't decode: {}, 0x{}",
+                    e,
+                    hex::encode(&process.message)
+                ),
+            }
+        }
+        MailboxInstruction::OutboxDispatch(dispatch) => {
+            println!("\tOutboxDispatch");
+            println!("\tSender: {}", dispatch.sender);
+            println!("\tDestination domain: {}", dispatch.destination_domain);
+            println!("\tRecipient: {:?}", dispatch.recipient);
+            println!("\tBody: 0x{}", hex::encode(&dispatch.message_body));
+            // The dispatched message PDA is derived from the unique message
+            // account, the 6th account.
+            if let Some(unique_message) = instruction.accounts.get(5) {
+                pdas.push((
+                    ctx.protocol
+                        .dispatched_message_pda(&mailbox, &unique_message.pubkey)
+                        .0,
+                    "dispatched message PDA".to_owned(),
+                ));
+            }
+        }
+        decoded => println!("\t{:#?}", decoded),
+    }
+    pdas
+}
+
+/// Prints the decoded IGP instruction, returning the PDAs it involves.
+fn igp_instruction_pdas(
+    instruction: &Instruction,
+    decoded: IgpInstruction,
+) -> Vec<(Pubkey, String)> {
+    let mut pdas = vec![(
+        Pubkey::find_program_address(igp_program_data_pda_seeds!(), &instruction.program_id).0,
+        "IGP program data PDA".to_owned(),
+    )];
+    if let IgpInstruction::PayForGas(payment) = &decoded {
+        // The gas payment PDA is derived from the unique gas payment account,
+        // the 4th account.
+        if let Some(unique_gas_payment) = instruction.accounts.get(3) {
+            pdas.push((
+                Pubkey::find_program_address(
+                    igp_gas_payment_pda_seeds!(unique_gas_payment.pubkey),
+                    &instruction.program_id,
+                )
+                .0,
+                "gas payment PDA".to_owned(),
+            ));
+        }
+        println!("\tPayForGas");
+        println!("\tMessage ID: {:?}", payment.message_id);
+        println!("\tDestination domain: {}", payment.destination_domain);
+        println!("\tGas amount: {}", payment.gas_amount);
+    } else {
+        println!("\t{:#?}", decoded);
+    }
+    pdas
+}
@@ -0,0 +1,189 @@
+//! Pretty printing of transaction failures and program logs.
+
+use std::str::FromStr;
+
+use num_traits::FromPrimitive;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_request::{RpcError, RpcResponseErrorData},
+};
+use solana_sdk::{
+    compute_budget, instruction::InstructionError, pubkey::Pubkey, system_program,
+    transaction::TransactionError,
+};
+
+use hyperlane_sealevel_mailbox::spl_noop;
+
+use crate::{MAILBOX_PROG_ID, MULTISIG_ISM_MESSAGE_ID_PROG_ID, VALIDATOR_ANNOUNCE_PROG_ID};
+
+/// Prints why a transaction failed, along with its program logs if the error
+/// includes them.
+pub(crate) fn print_client_error(err: &ClientError, descriptions: &[Option<String>]) {
+    match err.kind() {
+        ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+            ..
+        }) => print_transaction_failure(
+            result.err.as_ref(),
+            result.logs.as_deref().unwrap_or_default(),
+            descriptions,
+        ),
+        _ => match err.get_transaction_error() {
+            Some(tx_err) => print_transaction_failure(Some(&tx_err), &[], descriptions),
+            None => eprintln!("{:#?}", err),
+        },
+    }
+}
+
+/// Prints the failing instruction, if any, followed by the program logs.
+pub(crate) fn print_transaction_failure(
+    err: Option<&TransactionError>,
+    logs: &[String],
+    descriptions: &[Option<String>],
+) {
+    match err {
+        Some(TransactionError::InstructionError(index, instruction_err)) => eprintln!(
+            "\t==== Instruction {} failed: {} ====\n\t{}",
+            index,
+            describe_instruction_error(instruction_err),
+            descriptions
+                .get(*index as usize)
+                .cloned()
+                .flatten()
+                .unwrap_or_else(|| "No description provided".to_owned())
+        ),
+        Some(err) => eprintln!("\t==== Transaction failed: {} ====", err),
+        None => {}
+    }
+    if !logs.is_empty() {
+        eprintln!("\t==== Program logs: ====");
+        for line in format_transaction_logs(logs) {
+            eprintln!("\t{}", line);
+        }
+    }
+}
+
+/// Formats program logs, naming known programs, indenting by invocation depth,
+/// decoding Hyperlane error codes and marking the failing invocation.
+pub(crate) fn format_transaction_logs(logs: &[String]) -> Vec<String> {
+    let mut depth = 0usize;
+    let mut lines = vec![];
+    for log in logs {
+        let program_log = log
+            .strip_prefix("Program ")
+            .and_then(|rest| rest.split_once(' '))
+            .and_then(|(program_id, rest)| {
+                Pubkey::from_str(program_id)
+                    .ok()
+                    .map(|program_id| (program_id, rest))
+            });
+        let Some((program_id, rest)) = program_log else {
+            lines.push(format!("{}{}", "  ".repeat(depth), log));
+            continue;
+        };
+        let name = program_name(&program_id);
+        if let Some(invoke_depth) = rest
+            .strip_prefix("invoke [")
+            .and_then(|rest| rest.strip_suffix(']'))
+            .and_then(|invoke_depth| invoke_depth.parse::<usize>().ok())
+        {
+            depth = invoke_depth;
+            lines.push(format!("{}{} invoke", "  ".repeat(depth - 1), name));
+        } else if let Some(failure) = rest.strip_prefix("failed: ") {
+            let decoded = failure
+                .strip_prefix("custom program error: 0x")
+                .and_then(|code| u32::from_str_radix(code, 16).ok())
+                .and_then(|code| decode_custom_error(&program_id, code))
+                .map(|decoded| format!(" ({})", decoded))
+                .unwrap_or_default();
+            lines.push(format!(
+                ">>> {}{} failed: {}{}",
+                "  ".repeat(depth.saturating_sub(1)),
+                name,
+                failure,
+                decoded
+            ));
+            depth = depth.saturating_sub(1);
+        } else {
+            lines.push(format!(
+                "{}{} {}",
+                "  ".repeat(depth.saturating_sub(1)),
+                name,
+                rest
+            ));
+            if rest == "success" {
+                depth = depth.saturating_sub(1);
+            }
+        }
+    }
+    lines
+}
+
+fn program_name(program_id: &Pubkey) -> String {
+    let name = if *program_id == MAILBOX_PROG_ID {
+        "mailbox"
+    } else if *program_id == MULTISIG_ISM_MESSAGE_ID_PROG_ID {
+        "multisig ISM"
+    } else if *program_id == VALIDATOR_ANNOUNCE_PROG_ID {
+        "validator announce"
+    } else if *program_id == spl_noop::id() {
+        "noop"
+    } else if *program_id == system_program::id() {
+        "system program"
+    } else if *program_id == compute_budget::id() {
+        "compute budget"
+    } else {
+        return program_id.to_string();
+    };
+    format!("{} ({})", name, program_id)
+}
+
+fn decode_custom_error(program_id: &Pubkey, code: u32) -> Option<String> {
+    if *program_id == MAILBOX_PROG_ID {
+        hyperlane_sealevel_mailbox::error::Error::from_u32(code).map(|err| err.to_string())
+    } else if *program_id == MULTISIG_ISM_MESSAGE_ID_PROG_ID {
+        hyperlane_sealevel_multisig_ism_message_id::error::Error::from_u32(code)
+            .map(|err| err.to_string())
+    } else {
+        None
+    }
+}
+
+fn describe_instruction_error(err: &InstructionError) -> String {
+    match err {
+        InstructionError::Custom(code) => format!("custom program error: {:#x}", code),
+        err => err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_format_transaction_logs() {
+        let logs = [
+            format!("Program {} invoke [1]", MAILBOX_PROG_ID),
+            "Program log: Processing message".to_owned(),
+            format!("Program {} invoke [2]", spl_noop::id()),
+            format!("Program {} success", spl_noop::id()),
+            format!(
+                "Program {} failed: custom program error: 0x5",
+                MAILBOX_PROG_ID
+            ),
+        ];
+        assert_eq!(
+            format_transaction_logs(&logs),
+            vec![
+                format!("mailbox ({}) invoke", MAILBOX_PROG_ID),
+                "  Program log: Processing message".to_owned(),
+                format!("  noop ({}) invoke", spl_noop::id()),
+                format!("  noop ({}) success", spl_noop::id()),
+                format!(
+                    ">>> mailbox ({}) failed: custom program error: 0x5 (Message has already been processed)",
+                    MAILBOX_PROG_ID
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use hyperlane_core::H160;
+use solana_program::pubkey::Pubkey;
+
+use hyperlane_sealevel_multisig_ism_message_id::{
+    accounts::DomainDataAccount, domain_data_pda_seeds,
+};
+use hyperlane_sealevel_validator_announce::{
+    accounts::ValidatorStorageLocationsAccount, validator_storage_locations_pda_seeds,
+};
+
+use crate::{accounts::read_inbox, Context, ValidatorAnnounceQueryValidators};
+
+/// Lists the validators in the multisig ISM's validator set for an origin domain
+/// alongside their announced storage locations, indicating whether a quorum of
+/// signatures is obtainable.
+pub(crate) fn query_validators(ctx: &Context, query: ValidatorAnnounceQueryValidators) {
+    let multisig_ism = query.multisig_ism.unwrap_or_else(|| {
+        read_inbox(ctx, &query.mailbox)
+            .expect("Inbox account not found. Make sure you are connected to the right RPC.")
+            .default_ism
+    });
+
+    let (domain_data_key, _domain_data_bump) =
+        Pubkey::find_program_address(domain_data_pda_seeds!(query.domain), &multisig_ism);
+    let domain_data = ctx
+        .client
+        .get_account_with_commitment(&domain_data_key, ctx.commitment)
+        .unwrap()
+        .value
+        .map(|account| {
+            DomainDataAccount::fetch(&mut &account.data[..])
+                .unwrap()
+                .into_inner()
+        })
+        .unwrap_or_else(|| {
+            panic!(
+                "No validators configured for domain {} on multisig ISM {}",
+                query.domain, multisig_ism
+            )
+        });
+    let validators_and_threshold = domain_data.validators_and_threshold;
+
+    let storage_location_keys = validators_and_threshold
+        .validators
+        .iter()
+        .map(|validator| {
+            Pubkey::find_program_address(
+                validator_storage_locations_pda_seeds!(validator),
+                &query.program_id,
+            )
+            .0
+        })
+        .collect::<Vec<_>>();
+    let accounts = ctx
+        .client
+        .get_multiple_accounts_with_commitment(&storage_location_keys, ctx.commitment)
+        .unwrap()
+        .value;
+
+    println!(
+        "Multisig ISM {} validators for origin domain {}:",
+        multisig_ism, query.domain
+    );
+    let mut announced_count = 0;
+    for (validator, account) in validators_and_threshold.validators.iter().zip(accounts) {
+        let storage_locations = account.map(|account| {
+            ValidatorStorageLocationsAccount::fetch(&mut &account.data[..])
+                .unwrap()
+                .into_inner()
+                .storage_locations
+        });
+        print_validator(validator, storage_locations.as_deref());
+        if storage_locations.map_or(false, |locations| !locations.is_empty()) {
+            announced_count += 1;
+        }
+    }
+
+    println!(
+        "{} of {} validators announced, threshold {}: signatures are {}obtainable",
+        announced_count,
+        validators_and_threshold.validators.len(),
+        validators_and_threshold.threshold,
+        if announced_count >= validators_and_threshold.threshold as usize {
+            ""
+        } else {
+            "NOT "
+        }
+    );
+}
+
+fn print_validator(validator: &H160, storage_locations: Option<&[String]>) {
+    match storage_locations {
+        Some(storage_locations) if !storage_locations.is_empty() => {
+            println!("\t{:?}: {}", validator, storage_locations.join(", "))
+        }
+        _ => println!("\t{:?}: not announced", validator),
+    }
+}
@@ -0,0 +1,413 @@
+//! Checks of a deployment's cross-chain invariants, e.g. for gating CI: chain
+//! domains match the registry, mailboxes are initialized with the right
+//! domain, default ISMs have validator sets for every remote chain, IGPs have
+//! gas oracles for every remote chain, and warp route routers are enrolled
+//! with each other in both directions.
+//!
+//! Every check is printed as it runs and the process exits with an error if
+//! any failed.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use hyperlane_sealevel_igp::accounts::{GasOracle, Igp, RemoteGasData};
+use hyperlane_sealevel_multisig_ism_message_id::{
+    accounts::DomainData, domain_data_pda_seeds, instruction::ValidatorsAndThreshold,
+};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+
+use account_utils::DiscriminatorPrefixed;
+
+use crate::{
+    accounts::{read_program_account, MailboxReader, RpcAccountReader},
+    artifacts::read_json,
+    router::ChainMetadata,
+    Context, CoreProgramIds, VerifyCmd, VerifyDeployment, VerifySubCmd,
+};
+
+pub(crate) fn process_verify_cmd(ctx: Context, cmd: VerifyCmd) {
+    match cmd.cmd {
+        VerifySubCmd::Deployment(deployment) => verify_deployment(&ctx, deployment),
+    }
+}
+
+enum Outcome {
+    Pass,
+    Fail(String),
+    /// The check couldn't be done, e.g. because the chain isn't in the
+    /// registry. Doesn't fail the report.
+    Skip(String),
+}
+
+#[derive(Default)]
+struct Report {
+    passed: usize,
+    failed: usize,
+    skipped: usize,
+}
+
+impl Report {
+    fn record(&mut self, check: &str, outcome: Outcome) {
+        match outcome {
+            Outcome::Pass => {
+                self.passed += 1;
+                println!("PASS {}", check);
+            }
+            Outcome::Fail(reason) => {
+                self.failed += 1;
+                println!("FAIL {}: {}", check, reason);
+            }
+            Outcome::Skip(reason) => {
+                self.skipped += 1;
+                println!("SKIP {}: {}", check, reason);
+            }
+        }
+    }
+
+    fn check(&mut self, check: &str, result: Result<(), String>) {
+        self.record(
+            check,
+            match result {
+                Ok(()) => Outcome::Pass,
+                Err(reason) => Outcome::Fail(reason),
+            },
+        );
+    }
+}
+
+fn verify_deployment(ctx: &Context, deployment: VerifyDeployment) {
+    let chain_configs: HashMap<String, ChainMetadata> =
+        serde_json::from_reader(File::open(&deployment.chain_config_file).unwrap())
+            .expect("Invalid chain config file");
+    let environment_dir = deployment.environments_dir.join(&deployment.environment);
+
+    // The Sealevel chains of the deployment are those with core program IDs.
+    let mut chains = if deployment.chains.is_empty() {
+        chain_configs
+            .keys()
+            .filter(|chain| core_program_ids_path(&environment_dir, chain).exists())
+            .cloned()
+            .collect::<Vec<_>>()
+    } else {
+        deployment.chains
+    };
+    chains.sort();
+    assert!(
+        !chains.is_empty(),
+        "No core deployments found in {}",
+        environment_dir.display()
+    );
+    for chain in &chains {
+        assert!(
+            chain_configs.contains_key(chain),
+            "Chain {} isn't in the chain config file",
+            chain
+        );
+    }
+
+    let mut report = Report::default();
+    for chain in &chains {
+        let chain_config = &chain_configs[chain];
+        let remote_domains = chains
+            .iter()
+            .filter(|remote| *remote != chain)
+            .map(|remote| (remote.as_str(), chain_configs[remote].domain_id()))
+            .collect::<Vec<_>>();
+        let reader = RpcAccountReader {
+            client: chain_config.client(),
+            commitment: CommitmentConfig::confirmed(),
+            protocol: &ctx.protocol,
+        };
+        verify_core(
+            ctx,
+            &mut report,
+            &environment_dir,
+            chain,
+            chain_config,
+            &remote_domains,
+            &reader,
+        );
+    }
+
+    #[cfg(feature = "tokens")]
+    verify_warp_routes(
+        ctx,
+        &mut report,
+        &environment_dir,
+        &deployment.warp_routes,
+        &chains,
+        &chain_configs,
+    );
+
+    println!(
+        "{} passed, {} failed, {} skipped",
+        report.passed, report.failed, report.skipped
+    );
+    if report.failed > 0 {
+        std::process::exit(1);
+    }
+}
+
+fn core_program_ids_path(environment_dir: &Path, chain: &str) -> PathBuf {
+    environment_dir
+        .join(chain)
+        .join("core")
+        .join("program-ids.json")
+}
+
+fn verify_core(
+    ctx: &Context,
+    report: &mut Report,
+    environment_dir: &Path,
+    chain: &str,
+    chain_config: &ChainMetadata,
+    remote_domains: &[(&str, u32)],
+    reader: &RpcAccountReader,
+) {
+    let domain = chain_config.domain_id();
+    let check = format!("{}: domain matches the registry", chain);
+    match ctx.domains.lookup(chain) {
+        Some(registry_domain) if registry_domain == domain => report.record(&check, Outcome::Pass),
+        Some(registry_domain) => report.record(
+            &check,
+            Outcome::Fail(format!(
+                "configured as {}, registered as {}",
+                domain, registry_domain
+            )),
+        ),
+        None => report.record(&check, Outcome::Skip("not in the registry".to_owned())),
+    }
+
+    let core_program_ids: CoreProgramIds =
+        read_json(&core_program_ids_path(environment_dir, chain));
+    let mailbox = MailboxReader::new(reader, core_program_ids.mailbox);
+
+    let inbox = mailbox.inbox();
+    report.check(
+        &format!("{}: inbox domain", chain),
+        match &inbox {
+            Ok(Some(inbox)) => expect_domain(inbox.local_domain, domain),
+            Ok(None) => Err("mailbox not initialized".to_owned()),
+            Err(e) => Err(e.to_string()),
+        },
+    );
+    report.check(
+        &format!("{}: outbox domain", chain),
+        match mailbox.outbox() {
+            Ok(Some(outbox)) => expect_domain(outbox.local_domain, domain),
+            Ok(None) => Err("mailbox not initialized".to_owned()),
+            Err(e) => Err(e.to_string()),
+        },
+    );
+
+    let igp = read_program_account::<DiscriminatorPrefixed<Igp>>(
+        reader,
+        &core_program_ids.igp_program_id,
+        &core_program_ids.igp_account,
+    );
+    for (remote, remote_domain) in remote_domains {
+        if let Ok(Some(inbox)) = &inbox {
+            report.check(
+                &format!("{}: default ISM validators for {}", chain, remote),
+                verify_validator_set(reader, &inbox.default_ism, *remote_domain),
+            );
+        }
+        report.check(
+            &format!("{}: IGP gas oracle for {}", chain, remote),
+            match &igp {
+                Ok(Some(igp)) => verify_gas_oracle(igp.gas_oracles.get(remote_domain)),
+                Ok(None) => Err(format!(
+                    "IGP account {} not initialized",
+                    core_program_ids.igp_account
+                )),
+                Err(e) => Err(e.to_string()),
+            },
+        );
+    }
+}
+
+fn expect_domain(actual: u32, expected: u32) -> Result<(), String> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!("domain is {}, expected {}", actual, expected))
+    }
+}
+
+/// Checks that the multisig ISM has a validator set for the domain that can
+/// reach its threshold.
+fn verify_validator_set(
+    reader: &RpcAccountReader,
+    ism: &Pubkey,
+    domain: u32,
+) -> Result<(), String> {
+    let (domain_data_key, _) = Pubkey::find_program_address(domain_data_pda_seeds!(domain), ism);
+    let domain_data = read_program_account::<DomainData>(reader, ism, &domain_data_key)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("no validators set on ISM {}", ism))?;
+    let ValidatorsAndThreshold {
+        validators,
+        threshold,
+    } = domain_data.validators_and_threshold;
+    if threshold == 0 || usize::from(threshold) > validators.len() {
+        return Err(format!(
+            "threshold {} of {} validators",
+            threshold,
+            validators.len()
+        ));
+    }
+    Ok(())
+}
+
+fn verify_gas_oracle(gas_oracle: Option<&GasOracle>) -> Result<(), String> {
+    match gas_oracle {
+        None => Err("no gas oracle".to_owned()),
+        Some(GasOracle::RemoteGasData(RemoteGasData {
+            token_exchange_rate,
+            gas_price,
+            ..
+        })) => {
+            if *token_exchange_rate == 0 || *gas_price == 0 {
+                Err(format!(
+                    "token exchange rate {}, gas price {}",
+                    token_exchange_rate, gas_price
+                ))
+            } else {
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Checks that every Sealevel router of each warp route has exactly the
+/// routers of the route's other chains enrolled. As each Sealevel router is
+/// checked, this covers both directions of every Sealevel to Sealevel link.
+#[cfg(feature = "tokens")]
+fn verify_warp_routes(
+    ctx: &Context,
+    report: &mut Report,
+    environment_dir: &Path,
+    warp_routes: &[String],
+    chains: &[String],
+    chain_configs: &HashMap<String, ChainMetadata>,
+) {
+    use hyperlane_core::H256;
+    use hyperlane_sealevel_token_lib::{accounts::HyperlaneToken, hyperlane_token_pda_seeds};
+
+    use crate::router::read_router_program_ids;
+
+    let warp_routes_dir = environment_dir.join("warp-routes");
+    let mut warp_routes = if warp_routes.is_empty() {
+        match std::fs::read_dir(&warp_routes_dir) {
+            Ok(entries) => entries
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+                .collect(),
+            Err(_) => vec![],
+        }
+    } else {
+        warp_routes.to_vec()
+    };
+    warp_routes.sort();
+
+    for warp_route in warp_routes {
+        let Some(routers) = read_router_program_ids(&warp_routes_dir.join(&warp_route)) else {
+            report.record(
+                &format!("{}: routers", warp_route),
+                Outcome::Skip("no program IDs".to_owned()),
+            );
+            continue;
+        };
+
+        // The token accounts of the route's Sealevel routers, by chain.
+        let tokens = routers
+            .iter()
+            .filter(|(chain, _)| chains.contains(chain))
+            .map(|(chain, program_id)| {
+                let reader = RpcAccountReader {
+                    client: chain_configs[chain].client(),
+                    commitment: CommitmentConfig::confirmed(),
+                    protocol: &ctx.protocol,
+                };
+                let (token_key, _) =
+                    Pubkey::find_program_address(hyperlane_token_pda_seeds!(), program_id);
+                let token =
+                    read_program_account::<HyperlaneToken<()>>(&reader, program_id, &token_key)
+                        .map_err(|e| e.to_string())
+                        .and_then(|token| {
+                            token.ok_or_else(|| {
+                                format!("token account {} not initialized", token_key)
+                            })
+                        });
+                (chain.as_str(), token)
+            })
+            .collect::<HashMap<_, _>>();
+
+        let mut token_chains = tokens.keys().copied().collect::<Vec<_>>();
+        token_chains.sort();
+        for chain in token_chains {
+            let token = match &tokens[chain] {
+                Ok(token) => token,
+                Err(e) => {
+                    report.check(&format!("{}: {} router", warp_route, chain), Err(e.clone()));
+                    continue;
+                }
+            };
+            let mut remotes = routers
+                .keys()
+                .filter(|remote| *remote != chain)
+                .collect::<Vec<_>>();
+            remotes.sort();
+            for remote in remotes {
+                let check = format!("{}: {} enrolls {}", warp_route, chain, remote);
+                let Some(remote_config) = chain_configs.get(remote) else {
+                    report.record(
+                        &check,
+                        Outcome::Skip("chain isn't in the chain config file".to_owned()),
+                    );
+                    continue;
+                };
+                let expected = H256(routers[remote].to_bytes());
+                report.check(
+                    &check,
+                    verify_enrolled(&token.remote_routers, remote_config.domain_id(), expected),
+                );
+            }
+
+            let route_domains = routers
+                .keys()
+                .filter_map(|remote| chain_configs.get(remote))
+                .map(ChainMetadata::domain_id)
+                .collect::<Vec<_>>();
+            let mut unexpected = token
+                .remote_routers
+                .keys()
+                .filter(|domain| !route_domains.contains(domain))
+                .collect::<Vec<_>>();
+            unexpected.sort();
+            report.check(
+                &format!("{}: {} enrolls only the route's routers", warp_route, chain),
+                if unexpected.is_empty() {
+                    Ok(())
+                } else {
+                    Err(format!("routers enrolled for domains {:?}", unexpected))
+                },
+            );
+        }
+    }
+}
+
+#[cfg(feature = "tokens")]
+fn verify_enrolled(
+    remote_routers: &HashMap<u32, hyperlane_core::H256>,
+    domain: u32,
+    expected: hyperlane_core::H256,
+) -> Result<(), String> {
+    match remote_routers.get(&domain) {
+        Some(router) if *router == expected => Ok(()),
+        Some(router) => Err(format!("enrolled {:?}, expected {:?}", router, expected)),
+        None => Err(format!("no router enrolled for domain {}", domain)),
+    }
+}
@@ -1,20 +1,31 @@
+use account_utils::DiscriminatorEncode;
 use borsh::{BorshDeserialize, BorshSerialize};
 use hyperlane_core::H256;
 use hyperlane_sealevel_token_collateral::plugin::CollateralPlugin;
-use hyperlane_sealevel_token_native::plugin::NativePlugin;
+use hyperlane_sealevel_token_native::{
+    hyperlane_token_native_collateral_pda_seeds, plugin::NativePlugin,
+};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, fmt::Debug};
 
 use solana_client::{client_error::ClientError, rpc_client::RpcClient};
 
-use solana_sdk::{instruction::Instruction, program_error::ProgramError, pubkey::Pubkey};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
 
 use hyperlane_sealevel_connection_client::{
     gas_router::GasRouterConfig, router::RemoteRouterConfig,
 };
 use hyperlane_sealevel_igp::accounts::InterchainGasPaymasterType;
+use hyperlane_sealevel_mailbox::{mailbox_message_dispatch_authority_pda_seeds, spl_noop};
 use hyperlane_sealevel_token::{
-    hyperlane_token_mint_pda_seeds, plugin::SyntheticPlugin, spl_token, spl_token_2022,
+    hyperlane_token_mint_pda_seeds, plugin::SyntheticPlugin,
+    spl_associated_token_account::get_associated_token_address_with_program_id, spl_token,
+    spl_token_2022,
 };
 use hyperlane_sealevel_token_lib::{
     accounts::{HyperlaneToken, HyperlaneTokenAccount},
@@ -22,17 +33,21 @@ use hyperlane_sealevel_token_lib::{
     instruction::{
         enroll_remote_routers_instruction, set_destination_gas_configs,
         set_interchain_security_module_instruction, transfer_ownership_instruction, Init,
+        Instruction as TokenInstruction, TransferRemote,
     },
 };
 
 use crate::{
+    artifacts::read_json,
+    chain_set::ChainSet,
     cmd_utils::account_exists,
     core::CoreProgramIds,
     router::{
-        deploy_routers, ChainMetadata, ConnectionClient, Ownable, RouterConfig, RouterConfigGetter,
-        RouterDeployer,
+        deploy_routers, igp_payment_account_metas, read_router_program_ids, ChainMetadata,
+        ConnectionClient, Ownable, Router, RouterConfig, RouterConfigGetter, RouterDeployer,
     },
-    Context, TokenType as FlatTokenType, WarpRouteCmd, WarpRouteSubCmd,
+    Context, TokenType as FlatTokenType, WarpRouteBalances, WarpRouteCmd, WarpRouteDeploy,
+    WarpRouteSubCmd,
 };
 
 /// Configuration relating to decimals.
@@ -117,19 +132,7 @@ struct TokenConfig {
 
 pub(crate) fn process_warp_route_cmd(mut ctx: Context, cmd: WarpRouteCmd) {
     match cmd.cmd {
-        WarpRouteSubCmd::Deploy(deploy) => {
-            deploy_routers(
-                &mut ctx,
-                WarpRouteDeployer::new(deploy.ata_payer_funding_amount),
-                "warp-routes",
-                &deploy.warp_route_name,
-                deploy.token_config_file,
-                deploy.chain_config_file,
-                deploy.environments_dir,
-                &deploy.environment,
-                deploy.built_so_dir,
-            );
-        }
+        WarpRouteSubCmd::Deploy(deploy) => deploy_warp_route(&mut ctx, deploy),
         WarpRouteSubCmd::DestinationGas(args) => {
             let destination_gas = get_destination_gas(&ctx.client, &args.program_id).unwrap();
             println!(
@@ -137,15 +140,103 @@ pub(crate) fn process_warp_route_cmd(mut ctx: Context, cmd: WarpRouteCmd) {
                 destination_gas[&args.destination_domain]
             );
         }
+        WarpRouteSubCmd::Balances(balances) => print_warp_route_balances(balances),
+    }
+}
+
+/// Prints the collateral or synthetic supply of the routers of the warp
+/// route's chains that aren't foreign deployments, querying the chains
+/// concurrently.
+fn print_warp_route_balances(balances: WarpRouteBalances) {
+    let token_configs: HashMap<String, TokenConfig> = read_json(&balances.token_config_file);
+    let deploy_dir = balances
+        .environments_dir
+        .join(&balances.environment)
+        .join("warp-routes")
+        .join(&balances.warp_route_name);
+    let program_ids = read_router_program_ids(&deploy_dir)
+        .unwrap_or_else(|| panic!("No routers deployed in {}", deploy_dir.display()));
+    let mut chains = token_configs
+        .iter()
+        .filter(|(_, token_config)| token_config.router_config.foreign_deployment.is_none())
+        .map(|(chain_name, _)| chain_name.clone())
+        .collect::<Vec<_>>();
+    chains.sort();
+
+    let chain_set = ChainSet::from_chain_config_file(&balances.chain_config_file, &chains);
+    let results = chain_set.query(|chain_config, client| {
+        let program_id = program_ids
+            .get(chain_config.name())
+            .ok_or_else(|| "Router not deployed".to_owned())?;
+        token_balance(
+            client,
+            program_id,
+            &token_configs[chain_config.name()].token_type,
+        )
+    });
+    for (chain_name, result) in results {
+        match result {
+            Ok(balance) => println!("{}: {}", chain_name, balance),
+            Err(err) => println!("{}: error: {}", chain_name, err),
+        }
+    }
+}
+
+/// The total collateral the router holds, or the supply of its synthetic
+/// token.
+fn token_balance(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    token_type: &TokenType,
+) -> Result<String, String> {
+    match token_type {
+        TokenType::Native => {
+            let (native_collateral, _native_collateral_bump) = Pubkey::find_program_address(
+                hyperlane_token_native_collateral_pda_seeds!(),
+                program_id,
+            );
+            client
+                .get_balance(&native_collateral)
+                .map(|balance| format!("{} lamports of collateral", balance))
+                .map_err(|e| e.to_string())
+        }
+        TokenType::Synthetic(_) => {
+            let token = try_get_token_data::<SyntheticPlugin>(client, program_id)?;
+            client
+                .get_token_supply(&token.plugin_data.mint)
+                .map(|supply| format!("{} total supply", supply.ui_amount_string))
+                .map_err(|e| e.to_string())
+        }
+        TokenType::Collateral(_) => {
+            let token = try_get_token_data::<CollateralPlugin>(client, program_id)?;
+            client
+                .get_token_account_balance(&token.plugin_data.escrow)
+                .map(|balance| format!("{} of collateral", balance.ui_amount_string))
+                .map_err(|e| e.to_string())
+        }
     }
 }
 
-struct WarpRouteDeployer {
+pub(crate) fn deploy_warp_route(ctx: &mut Context, deploy: WarpRouteDeploy) {
+    deploy_routers(
+        ctx,
+        WarpRouteDeployer::new(deploy.ata_payer_funding_amount),
+        "warp-routes",
+        &deploy.warp_route_name,
+        deploy.token_config_file,
+        deploy.chain_config_file,
+        deploy.environments_dir,
+        &deploy.environment,
+        deploy.built_so_dir,
+    );
+}
+
+pub(crate) struct WarpRouteDeployer {
     ata_payer_funding_amount: Option<u64>,
 }
 
 impl WarpRouteDeployer {
-    fn new(ata_payer_funding_amount: Option<u64>) -> Self {
+    pub(crate) fn new(ata_payer_funding_amount: Option<u64>) -> Self {
         Self {
             ata_payer_funding_amount,
         }
@@ -154,14 +245,14 @@ impl WarpRouteDeployer {
 
 impl WarpRouteDeployer {}
 
-impl RouterDeployer<TokenConfig> for WarpRouteDeployer {
-    fn program_name(&self, config: &TokenConfig) -> &str {
-        match config.token_type {
-            TokenType::Native => "hyperlane_sealevel_token_native",
-            TokenType::Synthetic(_) => "hyperlane_sealevel_token",
-            TokenType::Collateral(_) => "hyperlane_sealevel_token_collateral",
-        }
-    }
+/// A transfer of tokens to a remote router, of the type the program holds.
+pub(crate) struct TokenTransfer {
+    pub token_type: FlatTokenType,
+    pub transfer: TransferRemote,
+}
+
+impl Router for WarpRouteDeployer {
+    type Payload = TokenTransfer;
 
     fn enroll_remote_routers_instruction(
         &self,
@@ -178,6 +269,151 @@ impl RouterDeployer<TokenConfig> for WarpRouteDeployer {
         token_data.remote_routers
     }
 
+    fn dispatch_instruction(
+        &self,
+        ctx: &Context,
+        client: &RpcClient,
+        program_id: Pubkey,
+        sender: Pubkey,
+        unique_message_account: Pubkey,
+        payload: TokenTransfer,
+    ) -> Instruction {
+        let (token_account, _token_bump) =
+            Pubkey::find_program_address(hyperlane_token_pda_seeds!(), &program_id);
+        let (dispatch_authority_account, _dispatch_authority_bump) = Pubkey::find_program_address(
+            mailbox_message_dispatch_authority_pda_seeds!(),
+            &program_id,
+        );
+
+        let fetched_token_account = client
+            .get_account_with_commitment(&token_account, ctx.commitment)
+            .unwrap()
+            .value
+            .unwrap();
+        let token = HyperlaneTokenAccount::<()>::fetch(&mut &fetched_token_account.data[..])
+            .unwrap()
+            .into_inner();
+
+        let (dispatched_message_account, _dispatched_message_bump) = ctx
+            .protocol
+            .dispatched_message_pda(&token.mailbox, &unique_message_account);
+
+        let (mailbox_outbox_account, _mailbox_outbox_bump) =
+            ctx.protocol.outbox_pda(&token.mailbox);
+
+        // Transfers tokens to a remote.
+        // Burns the tokens from the sender's associated token account and
+        // then dispatches a message to the remote recipient.
+        //
+        // 0.    [executable] The system program.
+        // 1.    [executable] The spl_noop program.
+        // 2.    [] The token PDA account.
+        // 3.    [executable] The mailbox program.
+        // 4.    [writeable] The mailbox outbox account.
+        // 5.    [] Message dispatch authority.
+        // 6.    [signer] The token sender and mailbox payer.
+        // 7.    [signer] Unique message / gas payment account.
+        // 8.    [writeable] Message storage PDA.
+        //       ---- If using an IGP ----
+        // 9.    [executable] The IGP program.
+        // 10.   [writeable] The IGP program data.
+        // 11.   [writeable] Gas payment PDA.
+        // 12.   [] OPTIONAL - The Overhead IGP program, if the configured IGP is an Overhead IGP.
+        // 13.   [writeable] The IGP account.
+        //       ---- End if ----
+        // 14..N [??..??] Plugin-specific accounts.
+        let mut accounts = vec![
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_noop::id(), false),
+            AccountMeta::new_readonly(token_account, false),
+            AccountMeta::new_readonly(token.mailbox, false),
+            AccountMeta::new(mailbox_outbox_account, false),
+            AccountMeta::new_readonly(dispatch_authority_account, false),
+            AccountMeta::new(sender, true),
+            AccountMeta::new_readonly(unique_message_account, true),
+            AccountMeta::new(dispatched_message_account, false),
+        ];
+        accounts.extend(igp_payment_account_metas(
+            ctx,
+            client,
+            token.interchain_gas_paymaster,
+            &unique_message_account,
+        ));
+
+        match payload.token_type {
+            FlatTokenType::Native => {
+                // 5. [executable] The system program.
+                // 6. [writeable] The native token collateral PDA account.
+                let (native_collateral_account, _native_collateral_bump) =
+                    Pubkey::find_program_address(
+                        hyperlane_token_native_collateral_pda_seeds!(),
+                        &program_id,
+                    );
+                accounts.extend([
+                    AccountMeta::new_readonly(system_program::id(), false),
+                    AccountMeta::new(native_collateral_account, false),
+                ]);
+            }
+            FlatTokenType::Synthetic => {
+                // 5. [executable] The spl_token_2022 program.
+                // 6. [writeable] The mint / mint authority PDA account.
+                // 7. [writeable] The token sender's associated token account, from which tokens will be burned.
+                let (mint_account, _mint_bump) =
+                    Pubkey::find_program_address(hyperlane_token_mint_pda_seeds!(), &program_id);
+                let sender_associated_token_account = get_associated_token_address_with_program_id(
+                    &sender,
+                    &mint_account,
+                    &spl_token_2022::id(),
+                );
+                accounts.extend([
+                    AccountMeta::new_readonly(spl_token_2022::id(), false),
+                    AccountMeta::new(mint_account, false),
+                    AccountMeta::new(sender_associated_token_account, false),
+                ]);
+            }
+            FlatTokenType::Collateral => {
+                // 5. [executable] The SPL token program for the mint.
+                // 6. [writeable] The mint.
+                // 7. [writeable] The token sender's associated token account, from which tokens will be sent.
+                // 8. [writeable] The escrow PDA account.
+                let token = HyperlaneTokenAccount::<CollateralPlugin>::fetch(
+                    &mut &fetched_token_account.data[..],
+                )
+                .unwrap()
+                .into_inner();
+                let sender_associated_token_account = get_associated_token_address_with_program_id(
+                    &sender,
+                    &token.plugin_data.mint,
+                    &token.plugin_data.spl_token_program,
+                );
+                accounts.extend([
+                    AccountMeta::new_readonly(token.plugin_data.spl_token_program, false),
+                    AccountMeta::new(token.plugin_data.mint, false),
+                    AccountMeta::new(sender_associated_token_account, false),
+                    AccountMeta::new(token.plugin_data.escrow, false),
+                ]);
+            }
+        }
+
+        Instruction {
+            program_id,
+            data: TokenInstruction::TransferRemote(payload.transfer)
+                .encode()
+                .unwrap(),
+            accounts,
+        }
+    }
+}
+
+impl RouterDeployer<TokenConfig> for WarpRouteDeployer {
+    fn program_name(&self, config: &TokenConfig) -> &str {
+        match config.token_type {
+            TokenType::Native => "hyperlane_sealevel_token_native",
+            TokenType::Synthetic(_) => "hyperlane_sealevel_token",
+            TokenType::Collateral(_) => "hyperlane_sealevel_token_collateral",
+        }
+    }
+
     fn init_program_idempotent(
         &self,
         ctx: &mut Context,
@@ -436,17 +672,28 @@ impl ConnectionClient for WarpRouteDeployer {
     }
 }
 
-fn get_token_data<T>(client: &RpcClient, program_id: &Pubkey) -> HyperlaneToken<T>
+pub(crate) fn get_token_data<T>(client: &RpcClient, program_id: &Pubkey) -> HyperlaneToken<T>
+where
+    T: BorshDeserialize + BorshSerialize + Default + account_utils::Data,
+{
+    try_get_token_data(client, program_id).unwrap()
+}
+
+fn try_get_token_data<T>(
+    client: &RpcClient,
+    program_id: &Pubkey,
+) -> Result<HyperlaneToken<T>, String>
 where
     T: BorshDeserialize + BorshSerialize + Default + account_utils::Data,
 {
     let (token_pda, _token_bump) =
         Pubkey::find_program_address(hyperlane_token_pda_seeds!(), program_id);
 
-    let account = client.get_account(&token_pda).unwrap();
-    *HyperlaneTokenAccount::<T>::fetch(&mut &account.data[..])
-        .unwrap()
-        .into_inner()
+    let account = client.get_account(&token_pda).map_err(|e| e.to_string())?;
+    let token = HyperlaneTokenAccount::<T>::fetch(&mut &account.data[..])
+        .map_err(|e| e.to_string())?
+        .into_inner();
+    Ok(*token)
 }
 
 fn get_destination_gas(
@@ -495,6 +742,83 @@ fn fund_ata_payer_up_to(
         .send_with_payer();
 }
 
+/// Prints a summary of the router's token: its mode, mint, decimals, ISM and
+/// enrolled remote routers, and the total collateral it holds or the supply
+/// of its synthetic token.
+pub(crate) fn print_token_summary(ctx: &Context, program_id: &Pubkey, token_type: FlatTokenType) {
+    let client = &ctx.client;
+    println!("Router {}", program_id);
+    match token_type {
+        FlatTokenType::Native => {
+            let token = get_token_data::<NativePlugin>(client, program_id);
+            print_router_summary(&token, "native");
+            let (native_collateral, _native_collateral_bump) = Pubkey::find_program_address(
+                hyperlane_token_native_collateral_pda_seeds!(),
+                program_id,
+            );
+            let balance = client
+                .get_balance_with_commitment(&native_collateral, ctx.commitment)
+                .unwrap()
+                .value;
+            println!(
+                "Total collateral: {} lamports in {}",
+                balance, native_collateral
+            );
+        }
+        FlatTokenType::Synthetic => {
+            let token = get_token_data::<SyntheticPlugin>(client, program_id);
+            print_router_summary(&token, "synthetic");
+            let mint = token.plugin_data.mint;
+            println!("Mint: {}", mint);
+            let supply = client
+                .get_token_supply_with_commitment(&mint, ctx.commitment)
+                .unwrap()
+                .value;
+            println!("Total supply: {}", supply.ui_amount_string);
+        }
+        FlatTokenType::Collateral => {
+            let token = get_token_data::<CollateralPlugin>(client, program_id);
+            print_router_summary(&token, "collateral");
+            let plugin = &token.plugin_data;
+            println!(
+                "Mint: {} (token program {})",
+                plugin.mint, plugin.spl_token_program
+            );
+            let balance = client
+                .get_token_account_balance_with_commitment(&plugin.escrow, ctx.commitment)
+                .unwrap()
+                .value;
+            println!(
+                "Total collateral: {} in escrow {}",
+                balance.ui_amount_string, plugin.escrow
+            );
+        }
+    }
+}
+
+fn print_router_summary<T>(token: &HyperlaneToken<T>, mode: &str) {
+    println!("Mode: {}", mode);
+    println!(
+        "Decimals: {} (remote {})",
+        token.decimals, token.remote_decimals
+    );
+    println!("Mailbox: {}", token.mailbox);
+    println!("Owner: {:?}", token.owner);
+    match token.interchain_security_module {
+        Some(ism) => println!("ISM: {}", ism),
+        None => println!("ISM: the mailbox's default"),
+    }
+    let mut remote_routers = token.remote_routers.iter().collect::<Vec<_>>();
+    remote_routers.sort();
+    println!("Remote routers:");
+    if remote_routers.is_empty() {
+        println!("\tNone");
+    }
+    for (domain, router) in remote_routers {
+        println!("\t{}: {:?}", domain, router);
+    }
+}
+
 pub fn parse_token_account_data(token_type: FlatTokenType, data: &mut &[u8]) {
     fn print_data_or_err<T: Debug>(data: Result<T, ProgramError>) {
         match data {
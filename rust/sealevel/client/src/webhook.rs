@@ -0,0 +1,169 @@
+//! Notifications of the events long-running commands observe, POSTed as JSON
+//! to a webhook, e.g. to alert on failed deliveries without extra glue.
+//! Events are POSTed from a background thread, so that a slow or unreachable
+//! webhook doesn't hold up the command.
+
+use std::{
+    sync::mpsc::{self, SyncSender, TrySendError},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use clap::Args;
+use tracing::debug;
+
+use crate::{events::HyperlaneEvent, shutdown::CancellationToken};
+
+/// The wait before the first retry of a failed POST, doubled for each retry
+/// up to `MAX_RETRY_DELAY`.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// How long a POST can take, so that a webhook that accepts the connection
+/// but never responds doesn't stall the queue.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How many events can wait to be POSTed. Events observed while the queue is
+/// full are dropped.
+const QUEUE_SIZE: usize = 1024;
+
+#[derive(Args)]
+pub(crate) struct WebhookArgs {
+    /// URL to POST every event to as JSON.
+    #[arg(long)]
+    webhook_url: Option<String>,
+    /// How many times to retry POSTing an event before dropping it.
+    #[arg(long, default_value_t = 3)]
+    webhook_max_retries: u32,
+}
+
+impl WebhookArgs {
+    /// The webhook, if a URL is given. Retries of failed POSTs stop once the
+    /// shutdown token is cancelled.
+    pub(crate) fn webhook(&self, shutdown: &CancellationToken) -> Option<Webhook> {
+        self.webhook_url
+            .as_ref()
+            .map(|url| Webhook::spawn(url.clone(), self.webhook_max_retries, shutdown.clone()))
+    }
+}
+
+/// A queue of events to POST, drained by a background thread. Dropping it
+/// waits for the queued events to be POSTed.
+pub(crate) struct Webhook {
+    url: String,
+    sender: Option<SyncSender<HyperlaneEvent>>,
+    poster: Option<JoinHandle<()>>,
+}
+
+impl Webhook {
+    fn spawn(url: String, max_retries: u32, shutdown: CancellationToken) -> Self {
+        let (sender, receiver) = mpsc::sync_channel::<HyperlaneEvent>(QUEUE_SIZE);
+        let poster_url = url.clone();
+        let poster = thread::spawn(move || {
+            let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+            for event in receiver {
+                post(&agent, &poster_url, max_retries, &shutdown, &event);
+            }
+        });
+        Self {
+            url,
+            sender: Some(sender),
+            poster: Some(poster),
+        }
+    }
+
+    /// Queues the event to be POSTed, dropping it with a warning if the queue
+    /// is full.
+    pub(crate) fn post(&self, event: HyperlaneEvent) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+        match sender.try_send(event) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => eprintln!(
+                "WARNING: dropping event, {} events are already waiting to be POSTed to \
+                 webhook {}",
+                QUEUE_SIZE, self.url
+            ),
+            Err(TrySendError::Disconnected(_)) => eprintln!(
+                "WARNING: dropping event, POSTing events to webhook {} stopped",
+                self.url
+            ),
+        }
+    }
+}
+
+impl Drop for Webhook {
+    fn drop(&mut self) {
+        // Closing the queue stops the poster once it's drained.
+        self.sender.take();
+        if let Some(poster) = self.poster.take() {
+            let _ = poster.join();
+        }
+    }
+}
+
+/// POSTs the event, retrying with exponential backoff. An event that can't be
+/// POSTed within the retries, or before shutdown, is dropped with a warning
+/// rather than stopping the command.
+fn post(
+    agent: &ureq::Agent,
+    url: &str,
+    max_retries: u32,
+    shutdown: &CancellationToken,
+    event: &HyperlaneEvent,
+) {
+    let mut retries = 0;
+    let mut retry_delay = INITIAL_RETRY_DELAY;
+    loop {
+        debug!(url = %url, event = %event.to_json_line(), "Webhook request");
+        match agent.post(url).send_json(event) {
+            Ok(_) => return,
+            Err(e) if retries < max_retries && !shutdown.is_cancelled() => {
+                retries += 1;
+                debug!(url = %url, error = %e, retries, "Webhook request failed");
+                if shutdown.sleep(retry_delay) {
+                    eprintln!(
+                        "WARNING: dropping event, shutting down before POSTing it to webhook \
+                         {}: {}",
+                        url, e
+                    );
+                    return;
+                }
+                retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+            }
+            Err(e) => {
+                eprintln!(
+                    "WARNING: dropping event, POSTing it to webhook {} failed: {}",
+                    url, e
+                );
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::events::DeliveryEvent;
+
+    #[test]
+    fn test_drop_doesnt_wait_for_retries_after_shutdown() {
+        let shutdown = CancellationToken::default();
+        shutdown.cancel();
+        let webhook = Webhook::spawn("http://127.0.0.1:1".to_owned(), 10, shutdown);
+        let start = Instant::now();
+        for nonce in 0..3 {
+            webhook.post(HyperlaneEvent::DeliverySucceeded(DeliveryEvent {
+                origin: 1,
+                destination: 2,
+                nonce,
+            }));
+        }
+        drop(webhook);
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,360 @@
+//! A guided warp route deployment: prompts for the chains and tokens of the
+//! route, writes its token config into the environment's deployment artifacts,
+//! deploys and links the routers, and checks the result. Routers can also be
+//! deployed one chain at a time from command line flags.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufRead as _, Write as _},
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Map, Value};
+use solana_program::pubkey::Pubkey;
+
+use hyperlane_sealevel_token::{
+    hyperlane_token_ata_payer_pda_seeds, hyperlane_token_mint_pda_seeds,
+};
+use hyperlane_sealevel_token_collateral::hyperlane_token_escrow_pda_seeds;
+use hyperlane_sealevel_token_lib::hyperlane_token_pda_seeds;
+use hyperlane_sealevel_token_native::hyperlane_token_native_collateral_pda_seeds;
+
+use crate::{
+    address::Address,
+    artifacts::write_json,
+    cmd_utils::create_new_directory,
+    router::{read_router_program_ids, ChainMetadata},
+    transfer_remote,
+    warp_route::{deploy_warp_route, get_token_data},
+    Context, TokenDeploy, TokenTransferRemote, TokenType, WarpRouteDeploy,
+};
+
+/// Prints the question and reads an answer, falling back to the default if
+/// the answer is empty. An empty default makes the answer optional.
+fn prompt(question: &str, default: Option<&str>) -> String {
+    loop {
+        match default {
+            Some(default) if !default.is_empty() => print!("{} [{}]: ", question, default),
+            _ => print!("{}: ", question),
+        }
+        io::stdout().flush().unwrap();
+        let mut answer = String::new();
+        io::stdin().lock().read_line(&mut answer).unwrap();
+        let answer = answer.trim();
+        match (answer.is_empty(), default) {
+            (false, _) => return answer.to_owned(),
+            (true, Some(default)) => return default.to_owned(),
+            (true, None) => println!("An answer is required"),
+        }
+    }
+}
+
+/// Prompts for an optional pubkey, re-prompting until the answer is empty or
+/// a valid pubkey.
+fn prompt_optional_pubkey(question: &str) -> Option<Pubkey> {
+    loop {
+        let answer = prompt(question, Some(""));
+        if answer.is_empty() {
+            return None;
+        }
+        match answer.parse() {
+            Ok(pubkey) => return Some(pubkey),
+            Err(e) => println!("Invalid pubkey {}: {}", answer, e),
+        }
+    }
+}
+
+fn confirm(question: &str) -> bool {
+    prompt(&format!("{} [y/N]", question), Some("")).eq_ignore_ascii_case("y")
+}
+
+/// The directory of the warp route's deployment artifacts.
+fn warp_route_dir(environments_dir: &Path, environment: &str, warp_route_name: &str) -> PathBuf {
+    create_new_directory(
+        &create_new_directory(
+            &create_new_directory(environments_dir, environment),
+            "warp-routes",
+        ),
+        warp_route_name,
+    )
+}
+
+pub(crate) fn process_token_deploy(ctx: &mut Context, deploy: TokenDeploy) {
+    if let Some(mode) = deploy.mode {
+        deploy_router(ctx, deploy, mode);
+        return;
+    }
+    if !deploy.interactive {
+        deploy_warp_route(
+            ctx,
+            WarpRouteDeploy {
+                environment: deploy.environment,
+                environments_dir: deploy.environments_dir,
+                built_so_dir: deploy.built_so_dir,
+                warp_route_name: deploy.warp_route_name.unwrap(),
+                token_config_file: deploy.token_config_file.unwrap(),
+                chain_config_file: deploy.chain_config_file,
+                ata_payer_funding_amount: deploy.ata_payer_funding_amount,
+            },
+        );
+        return;
+    }
+
+    let chain_configs: HashMap<String, ChainMetadata> =
+        serde_json::from_reader(File::open(&deploy.chain_config_file).unwrap())
+            .expect("Invalid chain config file");
+    let mut chain_names = chain_configs.keys().cloned().collect::<Vec<_>>();
+    chain_names.sort();
+    println!("Configured chains: {}", chain_names.join(", "));
+
+    let warp_route_name = deploy
+        .warp_route_name
+        .unwrap_or_else(|| prompt("Warp route name", None));
+    let chains = loop {
+        let chains = prompt(
+            "Chains to deploy to, comma separated, starting with the collateral chain",
+            None,
+        )
+        .split(',')
+        .map(|chain| chain.trim().to_owned())
+        .filter(|chain| !chain.is_empty())
+        .collect::<Vec<_>>();
+        if let Some(unknown) = chains
+            .iter()
+            .find(|chain| !chain_configs.contains_key(*chain))
+        {
+            println!("Unknown chain {}", unknown);
+        } else if chains.len() < 2 {
+            println!("A warp route needs at least two chains");
+        } else {
+            break chains;
+        }
+    };
+
+    let collateral_chain = &chains[0];
+    let collateral_type = loop {
+        match prompt(
+            &format!("Token type on {} (native or collateral)", collateral_chain),
+            Some("native"),
+        )
+        .as_str()
+        {
+            "native" => break TokenType::Native,
+            "collateral" => break TokenType::Collateral,
+            other => println!("Unknown token type {}", other),
+        }
+    };
+    let decimals = prompt("Decimals", Some("9"))
+        .parse::<u8>()
+        .expect("Invalid decimals");
+
+    let mut token_configs = Map::new();
+    let mut collateral_config = json!({ "decimals": decimals });
+    match collateral_type {
+        TokenType::Native => collateral_config["type"] = json!("native"),
+        _ => {
+            collateral_config["type"] = json!("collateral");
+            collateral_config["token"] = json!(prompt("Collateral mint", None));
+        }
+    }
+    token_configs.insert(collateral_chain.clone(), collateral_config);
+
+    let name = prompt("Synthetic token name", None);
+    let symbol = prompt("Synthetic token symbol", None);
+    for chain in &chains[1..] {
+        token_configs.insert(
+            chain.clone(),
+            json!({
+                "type": "synthetic",
+                "decimals": decimals,
+                "name": name,
+                "symbol": symbol,
+            }),
+        );
+    }
+
+    for chain in &chains {
+        let token_config = token_configs.get_mut(chain).unwrap();
+        if let Some(ism) = prompt_optional_pubkey(&format!(
+            "Interchain security module on {}, blank for the mailbox's default",
+            chain
+        )) {
+            token_config["interchainSecurityModule"] = json!(ism.to_string());
+        }
+        if let Some(igp) = prompt_optional_pubkey(&format!(
+            "IGP or overhead IGP account on {}, blank for none",
+            chain
+        )) {
+            token_config["interchainGasPaymaster"] = json!(igp.to_string());
+        }
+    }
+
+    let warp_route_dir = warp_route_dir(
+        &deploy.environments_dir,
+        &deploy.environment,
+        &warp_route_name,
+    );
+    let token_config_file = warp_route_dir.join("token-config.json");
+    write_json(&token_config_file, Value::Object(token_configs));
+    println!("Wrote token config to {}", token_config_file.display());
+
+    if !confirm("Deploy the warp route?") {
+        return;
+    }
+    deploy_warp_route(
+        ctx,
+        WarpRouteDeploy {
+            environment: deploy.environment,
+            environments_dir: deploy.environments_dir,
+            built_so_dir: deploy.built_so_dir,
+            warp_route_name,
+            token_config_file,
+            chain_config_file: deploy.chain_config_file,
+            ata_payer_funding_amount: deploy.ata_payer_funding_amount,
+        },
+    );
+
+    let program_ids =
+        read_router_program_ids(&warp_route_dir).expect("No program IDs written by the deploy");
+    if !check_linkage(&chains, &chain_configs, &program_ids) {
+        panic!("Warp route routers aren't all enrolled with each other");
+    }
+    println!("All routers are enrolled with each other");
+
+    let destination_chain = &chains[1];
+    if confirm(&format!(
+        "Send a test transfer of 1 base unit from {} to the payer on {}?",
+        collateral_chain, destination_chain
+    )) {
+        let tx_result = transfer_remote(
+            ctx,
+            &chain_configs[collateral_chain].client(),
+            TokenTransferRemote {
+                program_id: program_ids[collateral_chain],
+                sender: ctx.payer_keypair_path().clone(),
+                amount: 1,
+                destination_domain: chain_configs[destination_chain].domain_id(),
+                recipient: Address::Resolved(ctx.payer_pubkey.to_bytes().into()),
+                token_type: collateral_type,
+            },
+        );
+        match tx_result {
+            Some(tx_result) => println!("Test transfer sent in slot {}", tx_result.slot),
+            None => println!("Test transfer not sent"),
+        }
+    }
+}
+
+/// Deploys a router of the mode to the chain given by the flags, after
+/// adding it to the warp route's token config, and prints its PDAs.
+fn deploy_router(ctx: &mut Context, deploy: TokenDeploy, mode: TokenType) {
+    let chain = deploy.chain.unwrap();
+    let warp_route_name = deploy.warp_route_name.unwrap();
+    let warp_route_dir = warp_route_dir(
+        &deploy.environments_dir,
+        &deploy.environment,
+        &warp_route_name,
+    );
+
+    let token_config_file = warp_route_dir.join("token-config.json");
+    let mut token_configs: Map<String, Value> = if token_config_file.exists() {
+        serde_json::from_reader(File::open(&token_config_file).unwrap())
+            .expect("Invalid token config file")
+    } else {
+        Map::new()
+    };
+    let mut token_config = json!({ "decimals": deploy.decimals });
+    match mode {
+        TokenType::Native => token_config["type"] = json!("native"),
+        TokenType::Synthetic => {
+            token_config["type"] = json!("synthetic");
+            token_config["name"] = json!(deploy.name.unwrap());
+            token_config["symbol"] = json!(deploy.symbol.unwrap());
+        }
+        TokenType::Collateral => {
+            token_config["type"] = json!("collateral");
+            token_config["token"] = json!(deploy.mint.unwrap().to_string());
+        }
+    }
+    token_configs.insert(chain.clone(), token_config);
+    write_json(&token_config_file, Value::Object(token_configs));
+    println!("Wrote token config to {}", token_config_file.display());
+
+    deploy_warp_route(
+        ctx,
+        WarpRouteDeploy {
+            environment: deploy.environment,
+            environments_dir: deploy.environments_dir,
+            built_so_dir: deploy.built_so_dir,
+            warp_route_name,
+            token_config_file,
+            chain_config_file: deploy.chain_config_file,
+            ata_payer_funding_amount: deploy.ata_payer_funding_amount,
+        },
+    );
+
+    let program_id = read_router_program_ids(&warp_route_dir)
+        .and_then(|program_ids| program_ids.get(&chain).copied())
+        .expect("No program ID written by the deploy");
+    print_router_pdas(program_id, mode);
+}
+
+/// Prints the PDAs the router's program derives for its token type.
+fn print_router_pdas(program_id: Pubkey, mode: TokenType) {
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id).0;
+    println!("Router {}", program_id);
+    println!("\tToken: {}", pda(hyperlane_token_pda_seeds!()));
+    match mode {
+        TokenType::Native => println!(
+            "\tNative collateral: {}",
+            pda(hyperlane_token_native_collateral_pda_seeds!())
+        ),
+        TokenType::Synthetic => {
+            println!("\tMint: {}", pda(hyperlane_token_mint_pda_seeds!()));
+            println!(
+                "\tATA payer: {}",
+                pda(hyperlane_token_ata_payer_pda_seeds!())
+            );
+        }
+        TokenType::Collateral => {
+            println!("\tEscrow: {}", pda(hyperlane_token_escrow_pda_seeds!()));
+            println!(
+                "\tATA payer: {}",
+                pda(hyperlane_token_ata_payer_pda_seeds!())
+            );
+        }
+    }
+}
+
+/// Checks that every router has every other router enrolled, printing any
+/// missing or mismatched enrollments.
+fn check_linkage(
+    chains: &[String],
+    chain_configs: &HashMap<String, ChainMetadata>,
+    program_ids: &HashMap<String, Pubkey>,
+) -> bool {
+    let mut linked = true;
+    for chain in chains {
+        let token = get_token_data::<()>(&chain_configs[chain].client(), &program_ids[chain]);
+        for remote in chains.iter().filter(|remote| *remote != chain) {
+            let expected = program_ids[remote].to_bytes().into();
+            let domain = chain_configs[remote].domain_id();
+            match token.remote_routers.get(&domain) {
+                Some(router) if *router == expected => {}
+                Some(router) => {
+                    println!(
+                        "{} has router {:?} enrolled for {}, expected {:?}",
+                        chain, router, remote, expected
+                    );
+                    linked = false;
+                }
+                None => {
+                    println!("{} has no router enrolled for {}", chain, remote);
+                    linked = false;
+                }
+            }
+        }
+    }
+    linked
+}
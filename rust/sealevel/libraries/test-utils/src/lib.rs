@@ -34,6 +34,8 @@ use serializable_account_meta::{SerializableAccountMeta, SimulationReturnData};
 
 pub mod igp;
 pub use igp::*;
+pub mod test_env;
+pub use test_env::*;
 
 // ========= Mailbox =========
 
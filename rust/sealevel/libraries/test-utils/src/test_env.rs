@@ -0,0 +1,134 @@
+use solana_program_test::*;
+use solana_sdk::{
+    hash::hashv,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{keypair_from_seed, Signer},
+    signer::keypair::Keypair,
+};
+
+use crate::{initialize_mailbox, mailbox_id, transfer_lamports, MailboxAccounts};
+
+/// The balance seeded payers are topped up to whenever they run low.
+pub const SEEDED_PAYER_LAMPORTS: u64 = 10 * LAMPORTS_PER_SOL;
+
+/// A program test environment with an initialized Mailbox, for crates to
+/// exercise their programs against in their own tests.
+pub struct TestEnv {
+    pub banks_client: BanksClient,
+    pub payer: Keypair,
+    pub mailbox_accounts: MailboxAccounts,
+}
+
+impl TestEnv {
+    /// A `ProgramTest` with the Mailbox, the spl_noop program it logs
+    /// dispatches with, and the test ISM serving as its default ISM. Programs
+    /// under test can be added to it before passing it to `TestEnv::start`.
+    pub fn program_test() -> ProgramTest {
+        let mut program_test = ProgramTest::new(
+            "hyperlane_sealevel_mailbox",
+            mailbox_id(),
+            processor!(hyperlane_sealevel_mailbox::processor::process_instruction),
+        );
+
+        program_test.add_program("spl_noop", spl_noop::id(), processor!(spl_noop::noop));
+
+        program_test.add_program(
+            "hyperlane_sealevel_test_ism",
+            hyperlane_sealevel_test_ism::id(),
+            processor!(hyperlane_sealevel_test_ism::program::process_instruction),
+        );
+
+        program_test
+    }
+
+    /// Starts the program test and initializes the Mailbox for the local
+    /// domain, with the test ISM as its default ISM.
+    pub async fn start(
+        program_test: ProgramTest,
+        local_domain: u32,
+    ) -> Result<Self, BanksClientError> {
+        let (mut banks_client, payer, _recent_blockhash) = program_test.start().await;
+
+        let mailbox_accounts =
+            initialize_mailbox(&mut banks_client, &mailbox_id(), &payer, local_domain).await?;
+
+        Ok(Self {
+            banks_client,
+            payer,
+            mailbox_accounts,
+        })
+    }
+
+    /// Starts an environment with only the Mailbox and its dependencies.
+    pub async fn new(local_domain: u32) -> Result<Self, BanksClientError> {
+        Self::start(Self::program_test(), local_domain).await
+    }
+
+    /// A keypair derived from the seed, the same on every run, funded with
+    /// `SEEDED_PAYER_LAMPORTS` if its balance is below half of that.
+    pub async fn payer_from_seed(&mut self, seed: u64) -> Keypair {
+        let keypair =
+            keypair_from_seed(hashv(&[b"hyperlane-test-env", &seed.to_le_bytes()]).as_ref())
+                .unwrap();
+        self.ensure_funded(&keypair.pubkey(), SEEDED_PAYER_LAMPORTS)
+            .await;
+        keypair
+    }
+
+    /// Tops the account up to `lamports` from the environment's payer if its
+    /// balance is below half of that, so that accounts reused across runs
+    /// don't run dry.
+    pub async fn ensure_funded(&mut self, account: &Pubkey, lamports: u64) {
+        let balance = self.banks_client.get_balance(*account).await.unwrap();
+        if balance < lamports / 2 {
+            transfer_lamports(
+                &mut self.banks_client,
+                &self.payer,
+                account,
+                lamports - balance,
+            )
+            .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const LOCAL_DOMAIN: u32 = 13775;
+
+    async fn balance(env: &mut TestEnv, account: &Pubkey) -> u64 {
+        env.banks_client.get_balance(*account).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_seeded_payers_are_reused_and_topped_up() {
+        let mut env = TestEnv::new(LOCAL_DOMAIN).await.unwrap();
+        let outbox = env.mailbox_accounts.outbox;
+        assert!(env
+            .banks_client
+            .get_account(outbox)
+            .await
+            .unwrap()
+            .is_some());
+
+        let payer = env.payer_from_seed(1).await.pubkey();
+        assert_eq!(balance(&mut env, &payer).await, SEEDED_PAYER_LAMPORTS);
+        // The same seed gives the same payer, which isn't funded again.
+        assert_eq!(env.payer_from_seed(1).await.pubkey(), payer);
+        assert_eq!(balance(&mut env, &payer).await, SEEDED_PAYER_LAMPORTS);
+        assert_ne!(env.payer_from_seed(2).await.pubkey(), payer);
+
+        let account = Pubkey::new_unique();
+        env.ensure_funded(&account, LAMPORTS_PER_SOL).await;
+        assert_eq!(balance(&mut env, &account).await, LAMPORTS_PER_SOL);
+        // At least half of the target balance is left alone.
+        env.ensure_funded(&account, 2 * LAMPORTS_PER_SOL).await;
+        assert_eq!(balance(&mut env, &account).await, LAMPORTS_PER_SOL);
+        // Below half of it is topped up to the target.
+        env.ensure_funded(&account, 3 * LAMPORTS_PER_SOL).await;
+        assert_eq!(balance(&mut env, &account).await, 3 * LAMPORTS_PER_SOL);
+    }
+}
@@ -317,6 +317,41 @@ pub fn pay_for_gas_instruction(
     Ok((instruction, gas_payment_account))
 }
 
+/// Gets an instruction to quote a gas payment, whose required payment is
+/// returned as simulation return data.
+pub fn quote_gas_payment_instruction(
+    program_id: Pubkey,
+    igp: Pubkey,
+    overhead_igp: Option<Pubkey>,
+    destination_domain: u32,
+    gas_amount: u64,
+) -> Result<SolanaInstruction, ProgramError> {
+    let ixn = Instruction::QuoteGasPayment(QuoteGasPayment {
+        destination_domain,
+        gas_amount,
+    });
+
+    // Accounts:
+    // 0. [executable] The system program.
+    // 1. [] The IGP account.
+    // 2. [] Overhead IGP account (optional).
+    let mut accounts = vec![
+        AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        AccountMeta::new_readonly(igp, false),
+    ];
+    if let Some(overhead_igp) = overhead_igp {
+        accounts.push(AccountMeta::new_readonly(overhead_igp, false));
+    }
+
+    let instruction = SolanaInstruction {
+        program_id,
+        data: ixn.try_to_vec()?,
+        accounts,
+    };
+
+    Ok(instruction)
+}
+
 /// Gets an instruction to change an IGP or Overhead IGP
 /// account's owner.
 pub fn transfer_igp_account_ownership_instruction(
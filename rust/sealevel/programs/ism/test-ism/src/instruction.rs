@@ -0,0 +1,61 @@
+//! Instruction builders for the Test ISM program.
+
+use borsh::BorshSerialize;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+
+use crate::{program::TestIsmInstruction, test_ism_storage_pda_seeds};
+
+/// Gets the storage PDA key of the Test ISM program.
+pub fn storage_pda_key(program_id: &Pubkey) -> Pubkey {
+    let (storage_pda_key, _storage_pda_bump) =
+        Pubkey::find_program_address(test_ism_storage_pda_seeds!(), program_id);
+    storage_pda_key
+}
+
+/// Gets an instruction to initialize the Test ISM program.
+pub fn init_instruction(program_id: Pubkey, payer: Pubkey) -> Result<Instruction, ProgramError> {
+    let data = TestIsmInstruction::Init
+        .try_to_vec()
+        .map_err(|err| ProgramError::BorshIoError(err.to_string()))?;
+
+    // Accounts:
+    // 0. [executable] System program.
+    // 1. [signer] Payer.
+    // 2. [writeable] Storage PDA.
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(payer, true),
+        AccountMeta::new(storage_pda_key(&program_id), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Gets an instruction to set whether the Test ISM accepts messages.
+pub fn set_accept_instruction(
+    program_id: Pubkey,
+    accept: bool,
+) -> Result<Instruction, ProgramError> {
+    let data = TestIsmInstruction::SetAccept(accept)
+        .try_to_vec()
+        .map_err(|err| ProgramError::BorshIoError(err.to_string()))?;
+
+    // Accounts:
+    // 0. [writeable] Storage PDA.
+    let accounts = vec![AccountMeta::new(storage_pda_key(&program_id), false)];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
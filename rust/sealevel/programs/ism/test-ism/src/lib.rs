@@ -5,6 +5,7 @@
 #![deny(missing_docs)]
 #![deny(unsafe_code)]
 
+pub mod instruction;
 pub mod program;
 #[cfg(feature = "test-client")]
 pub mod test_client;
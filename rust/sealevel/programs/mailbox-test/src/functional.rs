@@ -16,7 +16,7 @@ use hyperlane_sealevel_test_send_receiver::{
 use hyperlane_test_utils::{
     assert_transaction_error, clone_keypair, get_process_account_metas, get_recipient_ism,
     initialize_mailbox, mailbox_id, new_funded_keypair, process, process_instruction,
-    process_with_accounts,
+    process_with_accounts, TestEnv,
 };
 use solana_program::{
     instruction::{AccountMeta, Instruction},
@@ -46,27 +46,7 @@ async fn setup_client() -> (
     TestSendReceiverTestClient,
     TestIsmTestClient,
 ) {
-    let program_id = mailbox_id();
-    let mut program_test = ProgramTest::new(
-        "hyperlane_sealevel_mailbox",
-        program_id,
-        processor!(hyperlane_sealevel_mailbox::processor::process_instruction),
-    );
-
-    program_test.add_program("spl_noop", spl_noop::id(), processor!(spl_noop::noop));
-
-    let mailbox_program_id = mailbox_id();
-    program_test.add_program(
-        "hyperlane_sealevel_mailbox",
-        mailbox_program_id,
-        processor!(hyperlane_sealevel_mailbox::processor::process_instruction),
-    );
-
-    program_test.add_program(
-        "hyperlane_sealevel_test_ism",
-        hyperlane_sealevel_test_ism::id(),
-        processor!(hyperlane_sealevel_test_ism::program::process_instruction),
-    );
+    let mut program_test = TestEnv::program_test();
 
     program_test.add_program(
         "hyperlane_sealevel_test_send_receiver",
@@ -9,7 +9,10 @@ use solana_program::{
     pubkey::Pubkey,
 };
 
-use crate::validator_announce_pda_seeds;
+use crate::{
+    replay_protection_pda_seeds, validator_announce_pda_seeds,
+    validator_storage_locations_pda_seeds,
+};
 
 /// Instructions for the ValidatorAnnounce program.
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
@@ -97,3 +100,50 @@ pub fn init_instruction(
 
     Ok(instruction)
 }
+
+/// Gets an instruction to announce a validator's storage location.
+pub fn announce_instruction(
+    program_id: Pubkey,
+    payer: Pubkey,
+    announcement: AnnounceInstruction,
+) -> Result<SolanaInstruction, ProgramError> {
+    let (validator_announce_account, _validator_announce_bump) =
+        Pubkey::try_find_program_address(validator_announce_pda_seeds!(), &program_id)
+            .ok_or(ProgramError::InvalidSeeds)?;
+
+    let (validator_storage_locations_key, _validator_storage_locations_bump_seed) =
+        Pubkey::try_find_program_address(
+            validator_storage_locations_pda_seeds!(announcement.validator),
+            &program_id,
+        )
+        .ok_or(ProgramError::InvalidSeeds)?;
+
+    let replay_id = announcement.replay_id();
+    let (replay_protection_pda_key, _replay_protection_bump_seed) =
+        Pubkey::try_find_program_address(replay_protection_pda_seeds!(replay_id), &program_id)
+            .ok_or(ProgramError::InvalidSeeds)?;
+
+    let ixn = Instruction::Announce(announcement);
+
+    // Accounts:
+    // 0. [signer] The payer.
+    // 1. [executable] The system program.
+    // 2. [] The ValidatorAnnounce PDA account.
+    // 3. [writeable] The validator-specific ValidatorStorageLocationsAccount PDA account.
+    // 4. [writeable] The ReplayProtection PDA account specific to the announcement being made.
+    let accounts = vec![
+        AccountMeta::new_readonly(payer, true),
+        AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        AccountMeta::new_readonly(validator_announce_account, false),
+        AccountMeta::new(validator_storage_locations_key, false),
+        AccountMeta::new(replay_protection_pda_key, false),
+    ];
+
+    let instruction = SolanaInstruction {
+        program_id,
+        data: ixn.into_instruction_data()?,
+        accounts,
+    };
+
+    Ok(instruction)
+}
@@ -288,6 +288,12 @@ export const ValidatorAgentConfigSchema = AgentConfigSchema.extend({
   interval: ZUint.optional().describe(
     'How long to wait between checking for new checkpoints in seconds.',
   ),
+  signingBlacklist: z
+    .union([MatchingListSchema, z.string().nonempty()])
+    .optional()
+    .describe(
+      'Checkpoints covering messages on the signing blacklist will not be signed.',
+    ),
 });
 
 export type ValidatorConfig = z.infer<typeof ValidatorAgentConfigSchema>;